@@ -0,0 +1,430 @@
+//! Key/value store application, storing the `key=value` transactions of every block and
+//! answering queries for a key with its value, along with a Merkle proof of it against the
+//! app hash.
+//!
+//! The state is kept in memory, or persisted to a sled database when given its path:
+//!
+//! ```text
+//! cargo run --example kvstore -- [address] [database]
+//! ```
+//!
+//! The app hash is the root of the simple Merkle tree of the key/values, sorted by key, so
+//! that it only depends on the state and not on the order transactions were executed in.
+//! Queries with `prove` set return `simple:v` proofs, as verified by
+//! `tendermint::merkle::proof::Proof::verify_value`.
+
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::sync::{Arc, Mutex};
+
+use prost::Message;
+use sha2::{Digest, Sha256};
+use tendermint_abci::{middleware::CatchPanic, Application, Server};
+use tendermint_proto::abci::{
+    RequestCheckTx, RequestDeliverTx, RequestInfo, RequestQuery, ResponseCheckTx, ResponseCommit,
+    ResponseDeliverTx, ResponseInfo, ResponseQuery,
+};
+use tendermint_proto::crypto::{ProofOp, ProofOps};
+
+/// Prefix of the keys of the values in the database
+const VALUE_PREFIX: &[u8] = b"value/";
+
+/// Key of the height of the last committed block in the database
+const HEIGHT_KEY: &[u8] = b"height";
+
+/// Key/value store application
+#[derive(Clone, Default)]
+struct KvStore {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Key/values as of the last committed block
+    values: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// Key/values set by the block being executed
+    pending: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// Height of the last committed block
+    height: i64,
+    /// App hash as of the last committed block
+    app_hash: Vec<u8>,
+    /// Database the committed state is persisted to, if any
+    db: Option<sled::Db>,
+}
+
+impl KvStore {
+    /// Key/value store persisted to the given database, restoring the state committed to it
+    fn with_db(db: sled::Db) -> sled::Result<Self> {
+        let mut values = BTreeMap::new();
+        for entry in db.scan_prefix(VALUE_PREFIX) {
+            let (key, value) = entry?;
+            values.insert(key[VALUE_PREFIX.len()..].to_vec(), value.to_vec());
+        }
+
+        let height = match db.get(HEIGHT_KEY)? {
+            Some(height) => i64::from_be_bytes(height.as_ref().try_into().expect("invalid height")),
+            None => 0,
+        };
+
+        let state = State {
+            app_hash: app_hash(&values),
+            values,
+            pending: BTreeMap::new(),
+            height,
+            db: Some(db),
+        };
+
+        Ok(Self {
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+}
+
+impl Application for KvStore {
+    fn info(&self, _request: RequestInfo) -> ResponseInfo {
+        let state = self.state.lock().unwrap();
+        ResponseInfo {
+            data: "kvstore".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            last_block_height: state.height,
+            last_block_app_hash: state.app_hash.clone(),
+            ..Default::default()
+        }
+    }
+
+    fn query(&self, request: RequestQuery) -> ResponseQuery {
+        let state = self.state.lock().unwrap();
+        let key = request.data;
+
+        let value = match state.values.get(&key) {
+            Some(value) => value.clone(),
+            None => {
+                return ResponseQuery {
+                    log: "does not exist".to_string(),
+                    key,
+                    height: state.height,
+                    ..Default::default()
+                }
+            }
+        };
+
+        let proof_ops = if request.prove {
+            Some(ProofOps {
+                ops: vec![prove(&state.values, &key)],
+            })
+        } else {
+            None
+        };
+
+        ResponseQuery {
+            log: "exists".to_string(),
+            key,
+            value,
+            proof_ops,
+            height: state.height,
+            ..Default::default()
+        }
+    }
+
+    fn check_tx(&self, request: RequestCheckTx) -> ResponseCheckTx {
+        match parse_tx(&request.tx) {
+            Some(_) => ResponseCheckTx::default(),
+            None => ResponseCheckTx {
+                code: 1,
+                log: "empty transaction".to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn deliver_tx(&self, request: RequestDeliverTx) -> ResponseDeliverTx {
+        let (key, value) = match parse_tx(&request.tx) {
+            Some(key_value) => key_value,
+            None => {
+                return ResponseDeliverTx {
+                    code: 1,
+                    log: "empty transaction".to_string(),
+                    ..Default::default()
+                }
+            }
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state.pending.insert(key.to_vec(), value.to_vec());
+        ResponseDeliverTx::default()
+    }
+
+    fn commit(&self) -> ResponseCommit {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+
+        let pending = std::mem::take(&mut state.pending);
+        state.height += 1;
+
+        if let Some(db) = &state.db {
+            let mut batch = sled::Batch::default();
+            for (key, value) in &pending {
+                batch.insert([VALUE_PREFIX, key.as_slice()].concat(), value.as_slice());
+            }
+            batch.insert(HEIGHT_KEY, &state.height.to_be_bytes()[..]);
+
+            db.apply_batch(batch).expect("failed to persist the block");
+            db.flush().expect("failed to persist the block");
+        }
+
+        state.values.extend(pending);
+        state.app_hash = app_hash(&state.values);
+
+        ResponseCommit {
+            data: state.app_hash.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Key and value of a `key=value` transaction, or of a `key` transaction setting the key to
+/// itself
+fn parse_tx(tx: &[u8]) -> Option<(&[u8], &[u8])> {
+    if tx.is_empty() {
+        return None;
+    }
+
+    match tx.iter().position(|&byte| byte == b'=') {
+        Some(split) => Some((&tx[..split], &tx[split + 1..])),
+        None => Some((tx, tx)),
+    }
+}
+
+/// Root of the simple Merkle tree of the given key/values, or nothing if there are none
+fn app_hash(values: &BTreeMap<Vec<u8>, Vec<u8>>) -> Vec<u8> {
+    if values.is_empty() {
+        return Vec::new();
+    }
+
+    merkle_root(&leaves(values), None, &mut Vec::new()).to_vec()
+}
+
+/// `simple:v` proof of the value of the given key, which must be in the given key/values
+fn prove(values: &BTreeMap<Vec<u8>, Vec<u8>>, key: &[u8]) -> ProofOp {
+    let leaves = leaves(values);
+    let index = values.keys().position(|k| k.as_slice() == key).unwrap();
+
+    let mut aunts = Vec::new();
+    merkle_root(&leaves, Some(index), &mut aunts);
+
+    let op = SimpleValueOp {
+        proof: Some(SimpleProof {
+            total: leaves.len() as i64,
+            index: index as i64,
+            leaf_hash: leaf_hash(&leaves[index]).to_vec(),
+            aunts,
+        }),
+    };
+
+    let mut data = Vec::new();
+    op.encode_length_delimited(&mut data).unwrap();
+
+    ProofOp {
+        r#type: "simple:v".to_string(),
+        key: key.to_vec(),
+        data,
+    }
+}
+
+/// `simple:v` proof operation, as amino encoded by Tendermint
+#[derive(Clone, PartialEq, Message)]
+struct SimpleValueOp {
+    #[prost(message, optional, tag = "1")]
+    proof: Option<SimpleProof>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct SimpleProof {
+    #[prost(int64, tag = "1")]
+    total: i64,
+    #[prost(int64, tag = "2")]
+    index: i64,
+    #[prost(bytes, tag = "3")]
+    leaf_hash: Vec<u8>,
+    #[prost(bytes, repeated, tag = "4")]
+    aunts: Vec<Vec<u8>>,
+}
+
+/// Leaves of the simple Merkle map of the given key/values: the length-prefixed key followed
+/// by the length-prefixed hash of the value
+fn leaves(values: &BTreeMap<Vec<u8>, Vec<u8>>) -> Vec<Vec<u8>> {
+    values
+        .iter()
+        .map(|(key, value)| {
+            let mut leaf = Vec::new();
+            prost::encoding::encode_varint(key.len() as u64, &mut leaf);
+            leaf.extend_from_slice(key);
+            prost::encoding::encode_varint(32, &mut leaf);
+            leaf.extend_from_slice(&Sha256::digest(value));
+            leaf
+        })
+        .collect()
+}
+
+/// Root of the simple Merkle tree of the given leaves, pushing the aunts of the leaf at the
+/// given index, if any, from the bottom up
+fn merkle_root(leaves: &[Vec<u8>], index: Option<usize>, aunts: &mut Vec<Vec<u8>>) -> [u8; 32] {
+    if leaves.len() == 1 {
+        return leaf_hash(&leaves[0]);
+    }
+
+    let split = leaves.len().next_power_of_two() / 2;
+    let (left_index, right_index) = match index {
+        Some(index) if index < split => (Some(index), None),
+        Some(index) => (None, Some(index - split)),
+        None => (None, None),
+    };
+
+    let left = merkle_root(&leaves[..split], left_index, aunts);
+    let right = merkle_root(&leaves[split..], right_index, aunts);
+
+    if left_index.is_some() {
+        aunts.push(right.to_vec());
+    } else if right_index.is_some() {
+        aunts.push(left.to_vec());
+    }
+
+    hash(&[&[0x01], &left[..], &right[..]])
+}
+
+fn leaf_hash(leaf: &[u8]) -> [u8; 32] {
+    hash(&[&[0x00], leaf])
+}
+
+fn hash(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&hasher.finalize());
+    hash
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:26658".to_string());
+
+    let app = match args.next() {
+        Some(path) => KvStore::with_db(sled::open(path)?)?,
+        None => KvStore::default(),
+    };
+
+    let server = Server::bind(addr, app)?.middleware(CatchPanic);
+    println!("kvstore listening on {}", server.local_addr()?);
+    server.listen()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tendermint::merkle::{
+        proof::{Proof, ProofOp as TendermintProofOp},
+        simple_hash_from_byte_vectors,
+    };
+
+    fn execute(app: &KvStore, txs: &[&[u8]]) -> Vec<u8> {
+        for tx in txs {
+            let response = app.deliver_tx(RequestDeliverTx { tx: tx.to_vec() });
+            assert_eq!(response.code, 0);
+        }
+        app.commit().data
+    }
+
+    fn query(app: &KvStore, key: &[u8]) -> ResponseQuery {
+        app.query(RequestQuery {
+            data: key.to_vec(),
+            prove: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn app_hash_is_merkle_root_of_sorted_values() {
+        let (a, b) = (KvStore::default(), KvStore::default());
+        let app_hash = execute(&a, &[b"b=2", b"a=1", b"c=3"]);
+        assert_eq!(app_hash, execute(&b, &[b"c=3", b"a=1", b"b=2"]));
+
+        let values = a.state.lock().unwrap().values.clone();
+        assert_eq!(
+            app_hash,
+            simple_hash_from_byte_vectors(leaves(&values)).to_vec()
+        );
+        assert_eq!(a.info(RequestInfo::default()).last_block_app_hash, app_hash);
+    }
+
+    #[test]
+    fn query_proves_values_against_app_hash() {
+        let app = KvStore::default();
+        execute(&app, &[b"a=1", b"b=2", b"c=3", b"d=4"]);
+        let app_hash = execute(&app, &[b"e=5", b"b=two"]);
+
+        for (key, value) in &[
+            (b"a", b"1".to_vec()),
+            (b"b", b"two".to_vec()),
+            (b"e", b"5".to_vec()),
+        ] {
+            let response = query(&app, &key[..]);
+            assert_eq!(&response.value, value);
+            assert_eq!(response.height, 2);
+
+            let proof = Proof {
+                ops: response
+                    .proof_ops
+                    .unwrap()
+                    .ops
+                    .into_iter()
+                    .map(|op| TendermintProofOp {
+                        field_type: op.r#type,
+                        key: op.key,
+                        data: op.data,
+                    })
+                    .collect(),
+            };
+            assert!(proof.verify_value(&app_hash, &[&key[..]], value).is_ok());
+            assert!(proof
+                .verify_value(&app_hash, &[&key[..]], b"other")
+                .is_err());
+        }
+
+        let missing = query(&app, b"f");
+        assert!(missing.value.is_empty());
+        assert!(missing.proof_ops.is_none());
+    }
+
+    #[test]
+    fn uncommitted_values_are_not_queried() {
+        let app = KvStore::default();
+        app.deliver_tx(RequestDeliverTx {
+            tx: b"a=1".to_vec(),
+        });
+        assert!(query(&app, b"a").value.is_empty());
+
+        app.commit();
+        assert_eq!(query(&app, b"a").value, b"1".to_vec());
+    }
+
+    #[test]
+    fn state_persists_across_restarts() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+
+        let app = KvStore::with_db(db.clone()).unwrap();
+        execute(&app, &[b"a=1"]);
+        let app_hash = execute(&app, &[b"b=2"]);
+        drop(app);
+
+        let app = KvStore::with_db(db).unwrap();
+        let info = app.info(RequestInfo::default());
+        assert_eq!(info.last_block_height, 2);
+        assert_eq!(info.last_block_app_hash, app_hash);
+        assert_eq!(query(&app, b"b").value, b"2".to_vec());
+    }
+}
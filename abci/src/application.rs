@@ -0,0 +1,148 @@
+//! Applications replicated by Tendermint, served over ABCI by the [`Server`](crate::Server).
+
+use tendermint_proto::abci::{
+    request, response, response_apply_snapshot_chunk, response_offer_snapshot,
+    RequestApplySnapshotChunk, RequestBeginBlock, RequestCheckTx, RequestDeliverTx, RequestEcho,
+    RequestEndBlock, RequestInfo, RequestInitChain, RequestLoadSnapshotChunk, RequestOfferSnapshot,
+    RequestQuery, RequestSetOption, ResponseApplySnapshotChunk, ResponseBeginBlock,
+    ResponseCheckTx, ResponseCommit, ResponseDeliverTx, ResponseEcho, ResponseEndBlock,
+    ResponseException, ResponseFlush, ResponseInfo, ResponseInitChain, ResponseListSnapshots,
+    ResponseLoadSnapshotChunk, ResponseOfferSnapshot, ResponseQuery, ResponseSetOption,
+};
+
+/// Application driven by Tendermint through ABCI.
+///
+/// Every method has a default implementation accepting the request without doing anything,
+/// so that applications only implement the methods they need. Applications not implementing
+/// the state sync methods neither offer snapshots to their peers nor restore any, aborting
+/// state sync so that Tendermint replays the chain instead.
+///
+/// The server clones the application for each connection Tendermint opens, and may call
+/// its methods from several threads at once, except for those of the consensus connection,
+/// which are called one at a time, see [`ConnectionRole`](crate::server::ConnectionRole).
+/// Applications thus keep the state they share between connections behind `Arc`s.
+pub trait Application: Clone + Send + 'static {
+    /// Echo a message back
+    fn echo(&self, request: RequestEcho) -> ResponseEcho {
+        ResponseEcho {
+            message: request.message,
+        }
+    }
+
+    /// Information about the application state
+    fn info(&self, _request: RequestInfo) -> ResponseInfo {
+        Default::default()
+    }
+
+    /// Set a non-consensus critical option of the application
+    fn set_option(&self, _request: RequestSetOption) -> ResponseSetOption {
+        Default::default()
+    }
+
+    /// Initialize the blockchain with the genesis validators and application state
+    fn init_chain(&self, _request: RequestInitChain) -> ResponseInitChain {
+        Default::default()
+    }
+
+    /// Query the application state
+    fn query(&self, _request: RequestQuery) -> ResponseQuery {
+        Default::default()
+    }
+
+    /// Check whether a transaction may enter the mempool
+    fn check_tx(&self, _request: RequestCheckTx) -> ResponseCheckTx {
+        Default::default()
+    }
+
+    /// Signal the beginning of a block
+    fn begin_block(&self, _request: RequestBeginBlock) -> ResponseBeginBlock {
+        Default::default()
+    }
+
+    /// Execute a transaction of the current block
+    fn deliver_tx(&self, _request: RequestDeliverTx) -> ResponseDeliverTx {
+        Default::default()
+    }
+
+    /// Signal the end of a block, returning validator and consensus parameter updates
+    fn end_block(&self, _request: RequestEndBlock) -> ResponseEndBlock {
+        Default::default()
+    }
+
+    /// Commit the application state, returning the resulting app hash
+    fn commit(&self) -> ResponseCommit {
+        Default::default()
+    }
+
+    /// Snapshots of the application state available to peers restoring theirs
+    fn list_snapshots(&self) -> ResponseListSnapshots {
+        Default::default()
+    }
+
+    /// Offer a snapshot discovered on the network to restore the application state from
+    fn offer_snapshot(&self, _request: RequestOfferSnapshot) -> ResponseOfferSnapshot {
+        ResponseOfferSnapshot {
+            result: response_offer_snapshot::Result::Abort as i32,
+        }
+    }
+
+    /// Load a chunk of one of the snapshots listed by `list_snapshots`
+    fn load_snapshot_chunk(&self, _request: RequestLoadSnapshotChunk) -> ResponseLoadSnapshotChunk {
+        Default::default()
+    }
+
+    /// Apply a chunk of the snapshot accepted by `offer_snapshot`
+    fn apply_snapshot_chunk(
+        &self,
+        _request: RequestApplySnapshotChunk,
+    ) -> ResponseApplySnapshotChunk {
+        ResponseApplySnapshotChunk {
+            result: response_apply_snapshot_chunk::Result::Abort as i32,
+            ..Default::default()
+        }
+    }
+
+    /// Dispatch the given request to the method handling it
+    fn handle(&self, request: request::Value) -> response::Value {
+        match request {
+            request::Value::Echo(request) => response::Value::Echo(self.echo(request)),
+            request::Value::Flush(_) => response::Value::Flush(ResponseFlush {}),
+            request::Value::Info(request) => response::Value::Info(self.info(request)),
+            request::Value::SetOption(request) => {
+                response::Value::SetOption(self.set_option(request))
+            }
+            request::Value::InitChain(request) => {
+                response::Value::InitChain(self.init_chain(request))
+            }
+            request::Value::Query(request) => response::Value::Query(self.query(request)),
+            request::Value::BeginBlock(request) => {
+                response::Value::BeginBlock(self.begin_block(request))
+            }
+            request::Value::CheckTx(request) => response::Value::CheckTx(self.check_tx(request)),
+            request::Value::DeliverTx(request) => {
+                response::Value::DeliverTx(self.deliver_tx(request))
+            }
+            request::Value::EndBlock(request) => response::Value::EndBlock(self.end_block(request)),
+            request::Value::Commit(_) => response::Value::Commit(self.commit()),
+            request::Value::ListSnapshots(_) => {
+                response::Value::ListSnapshots(self.list_snapshots())
+            }
+            request::Value::OfferSnapshot(request) => {
+                response::Value::OfferSnapshot(self.offer_snapshot(request))
+            }
+            request::Value::LoadSnapshotChunk(request) => {
+                response::Value::LoadSnapshotChunk(self.load_snapshot_chunk(request))
+            }
+            request::Value::ApplySnapshotChunk(request) => {
+                response::Value::ApplySnapshotChunk(self.apply_snapshot_chunk(request))
+            }
+        }
+    }
+}
+
+/// Exception answering a request with the given error
+pub(crate) fn exception(error: impl Into<String>) -> response::Value {
+    response::Value::Exception(ResponseException {
+        error: error.into(),
+    })
+}
@@ -0,0 +1,269 @@
+//! Client driving an ABCI application over a socket connection, the way Tendermint does.
+
+use std::net::{TcpStream, ToSocketAddrs};
+
+use prost::Message as _;
+use tendermint_proto::abci::{
+    request, response, Request, RequestApplySnapshotChunk, RequestBeginBlock, RequestCheckTx,
+    RequestCommit, RequestDeliverTx, RequestEcho, RequestEndBlock, RequestFlush, RequestInfo,
+    RequestInitChain, RequestListSnapshots, RequestLoadSnapshotChunk, RequestOfferSnapshot,
+    RequestQuery, RequestSetOption, Response, ResponseApplySnapshotChunk, ResponseBeginBlock,
+    ResponseCheckTx, ResponseCommit, ResponseDeliverTx, ResponseEcho, ResponseEndBlock,
+    ResponseInfo, ResponseInitChain, ResponseListSnapshots, ResponseLoadSnapshotChunk,
+    ResponseOfferSnapshot, ResponseQuery, ResponseSetOption,
+};
+
+use crate::{Codec, Error};
+
+/// Maximum number of requests pipelined before a `Flush` request
+pub const MAX_PIPELINED_REQUESTS: usize = 256;
+
+/// Number of bytes of pipelined requests after which a `Flush` request is sent, well within
+/// the buffers of the sockets, so that sending them never waits for the application to read
+pub const MAX_PIPELINED_BYTES: usize = 65_536;
+
+/// Sends a request and returns the response of the matching type.
+macro_rules! perform {
+    ($client:expr, $variant:ident, $request:expr) => {
+        match $client.perform(request::Value::$variant($request))? {
+            response::Value::$variant(response) => Ok(response),
+            other => Err(Error::UnexpectedResponse {
+                request: stringify!($variant),
+                response: format!("{:?}", other),
+            }),
+        }
+    };
+}
+
+/// Client connected to an ABCI application.
+///
+/// Like Tendermint, the client follows every request with a `Flush` request, upon which the
+/// application writes out its buffered responses, and waits for the response before sending
+/// the next request.
+///
+/// Transactions of a block may also be pipelined with [`Client::deliver_txs`], which sends
+/// them by windows of up to [`MAX_PIPELINED_REQUESTS`] requests or [`MAX_PIPELINED_BYTES`]
+/// bytes, each followed by a single `Flush` request.
+///
+/// Tendermint opens one connection per role (consensus, mempool, info and snapshot), so
+/// exercising an application the way Tendermint does may take one client per role.
+#[derive(Debug)]
+pub struct Client<S = TcpStream>
+where
+    S: std::io::Read + std::io::Write,
+{
+    codec: Codec<S>,
+}
+
+impl Client<TcpStream> {
+    /// Connect to the ABCI application listening on the given TCP address
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self, Error> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        Ok(Self::new(stream.try_clone()?, stream))
+    }
+}
+
+impl<S> Client<S>
+where
+    S: std::io::Read + std::io::Write,
+{
+    /// Drive the ABCI application reading from the first stream and writing to the second
+    /// one, which are usually handles to the same connection, eg. a unix socket.
+    pub fn new(read: S, write: S) -> Self {
+        Self {
+            codec: Codec::new(read, write),
+        }
+    }
+
+    /// Echo a message back
+    pub fn echo(&mut self, request: RequestEcho) -> Result<ResponseEcho, Error> {
+        perform!(self, Echo, request)
+    }
+
+    /// Make the application write out its buffered responses
+    pub fn flush(&mut self) -> Result<(), Error> {
+        perform!(self, Flush, RequestFlush {}).map(|_| ())
+    }
+
+    /// Information about the application state
+    pub fn info(&mut self, request: RequestInfo) -> Result<ResponseInfo, Error> {
+        perform!(self, Info, request)
+    }
+
+    /// Set a non-consensus critical option of the application
+    pub fn set_option(&mut self, request: RequestSetOption) -> Result<ResponseSetOption, Error> {
+        perform!(self, SetOption, request)
+    }
+
+    /// Initialize the blockchain with the genesis validators and application state
+    pub fn init_chain(&mut self, request: RequestInitChain) -> Result<ResponseInitChain, Error> {
+        perform!(self, InitChain, request)
+    }
+
+    /// Query the application state
+    pub fn query(&mut self, request: RequestQuery) -> Result<ResponseQuery, Error> {
+        perform!(self, Query, request)
+    }
+
+    /// Signal the beginning of a block
+    pub fn begin_block(&mut self, request: RequestBeginBlock) -> Result<ResponseBeginBlock, Error> {
+        perform!(self, BeginBlock, request)
+    }
+
+    /// Check whether a transaction may enter the mempool
+    pub fn check_tx(&mut self, request: RequestCheckTx) -> Result<ResponseCheckTx, Error> {
+        perform!(self, CheckTx, request)
+    }
+
+    /// Execute a transaction of the current block
+    pub fn deliver_tx(&mut self, request: RequestDeliverTx) -> Result<ResponseDeliverTx, Error> {
+        perform!(self, DeliverTx, request)
+    }
+
+    /// Execute the given transactions of the current block, sending them by windows each
+    /// followed by a single `Flush` request rather than waiting for each response in turn
+    pub fn deliver_txs(
+        &mut self,
+        requests: impl IntoIterator<Item = RequestDeliverTx>,
+    ) -> Result<Vec<ResponseDeliverTx>, Error> {
+        let values = requests
+            .into_iter()
+            .map(request::Value::DeliverTx)
+            .collect();
+
+        self.perform_batch(values)?
+            .into_iter()
+            .map(|value| match value {
+                response::Value::DeliverTx(response) => Ok(response),
+                other => Err(Error::UnexpectedResponse {
+                    request: "DeliverTx",
+                    response: format!("{:?}", other),
+                }),
+            })
+            .collect()
+    }
+
+    /// Signal the end of a block, returning validator and consensus parameter updates
+    pub fn end_block(&mut self, request: RequestEndBlock) -> Result<ResponseEndBlock, Error> {
+        perform!(self, EndBlock, request)
+    }
+
+    /// Commit the application state, returning the resulting app hash
+    pub fn commit(&mut self) -> Result<ResponseCommit, Error> {
+        perform!(self, Commit, RequestCommit {})
+    }
+
+    /// List the snapshots available for state sync
+    pub fn list_snapshots(&mut self) -> Result<ResponseListSnapshots, Error> {
+        perform!(self, ListSnapshots, RequestListSnapshots {})
+    }
+
+    /// Offer a snapshot to restore the application state from
+    pub fn offer_snapshot(
+        &mut self,
+        request: RequestOfferSnapshot,
+    ) -> Result<ResponseOfferSnapshot, Error> {
+        perform!(self, OfferSnapshot, request)
+    }
+
+    /// Load a chunk of a local snapshot
+    pub fn load_snapshot_chunk(
+        &mut self,
+        request: RequestLoadSnapshotChunk,
+    ) -> Result<ResponseLoadSnapshotChunk, Error> {
+        perform!(self, LoadSnapshotChunk, request)
+    }
+
+    /// Apply a chunk of the snapshot being restored
+    pub fn apply_snapshot_chunk(
+        &mut self,
+        request: RequestApplySnapshotChunk,
+    ) -> Result<ResponseApplySnapshotChunk, Error> {
+        perform!(self, ApplySnapshotChunk, request)
+    }
+
+    /// Send the given request followed by a `Flush` request, and receive the response to it
+    fn perform(&mut self, value: request::Value) -> Result<response::Value, Error> {
+        if let request::Value::Flush(_) = value {
+            self.codec.send(&Request { value: Some(value) })?;
+            self.codec.flush()?;
+            return self.receive().and_then(into_result);
+        }
+
+        let mut responses = self.perform_batch(vec![value])?;
+        Ok(responses.remove(0))
+    }
+
+    /// Send the given requests by windows, each followed by a `Flush` request, and receive
+    /// the responses to them, failing with the first exception raised by the application if
+    /// any
+    ///
+    /// Responses are drained after every window, lest the application block writing its
+    /// responses to us while we block writing more requests to it.
+    fn perform_batch(
+        &mut self,
+        values: Vec<request::Value>,
+    ) -> Result<Vec<response::Value>, Error> {
+        let mut responses = Vec::with_capacity(values.len());
+        let (mut pending, mut pending_bytes) = (0, 0);
+
+        for value in values {
+            let request = Request { value: Some(value) };
+            pending_bytes += request.encoded_len();
+            self.codec.send(&request)?;
+            pending += 1;
+
+            if pending >= MAX_PIPELINED_REQUESTS || pending_bytes >= MAX_PIPELINED_BYTES {
+                self.drain(pending, &mut responses)?;
+                pending = 0;
+                pending_bytes = 0;
+            }
+        }
+
+        if pending > 0 {
+            self.drain(pending, &mut responses)?;
+        }
+
+        responses.into_iter().map(into_result).collect()
+    }
+
+    /// Send a `Flush` request after the given number of pending requests, and receive their
+    /// responses
+    fn drain(&mut self, pending: usize, responses: &mut Vec<response::Value>) -> Result<(), Error> {
+        self.codec.send(&Request {
+            value: Some(request::Value::Flush(RequestFlush {})),
+        })?;
+        self.codec.flush()?;
+
+        for _ in 0..pending {
+            responses.push(self.receive()?);
+        }
+
+        match self.receive()? {
+            response::Value::Flush(_) => Ok(()),
+            other => Err(Error::UnexpectedResponse {
+                request: "Flush",
+                response: format!("{:?}", other),
+            }),
+        }
+    }
+
+    fn receive(&mut self) -> Result<response::Value, Error> {
+        self.codec
+            .receive::<Response>()?
+            .ok_or(Error::ConnectionClosed)?
+            .value
+            .ok_or_else(|| Error::UnexpectedResponse {
+                request: "any",
+                response: "empty response".to_string(),
+            })
+    }
+}
+
+fn into_result(value: response::Value) -> Result<response::Value, Error> {
+    match value {
+        response::Value::Exception(exception) => Err(Error::Exception(exception.error)),
+        value => Ok(value),
+    }
+}
@@ -0,0 +1,178 @@
+//! Length-prefixed encoding of the ABCI messages exchanged over a stream.
+//!
+//! As in Tendermint, each message is the protobuf encoding of a `Request` or `Response`,
+//! prefixed with its length as an unsigned varint.
+
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use prost::Message;
+
+use crate::Error;
+
+/// Maximum size of a message accepted by the codec, matching the one of Tendermint
+pub const MAX_MESSAGE_SIZE: usize = 104_857_600;
+
+/// Default capacity of the buffer of sent messages, in bytes
+pub const DEFAULT_BUFFER_CAPACITY: usize = 65_536;
+
+/// Maximum length of a varint encoding a `u64`
+const MAX_VARINT_LENGTH: usize = 10;
+
+/// Sends and receives length-prefixed messages over a stream.
+///
+/// As in Tendermint, sent messages are buffered and only written out to the stream on
+/// [`Codec::flush`], typically upon a `Flush` request, or when the buffer fills up. Pipelining
+/// a batch of messages thus takes a handful of writes rather than one per message.
+#[derive(Debug)]
+pub struct Codec<S: Read + Write> {
+    reader: BufReader<S>,
+    writer: BufWriter<S>,
+}
+
+impl<S: Read + Write> Codec<S> {
+    /// Receive messages from the first stream and send them to the second one, which are
+    /// usually handles to the same connection, eg. obtained through `TcpStream::try_clone`.
+    pub fn new(read: S, write: S) -> Self {
+        Self::with_capacity(DEFAULT_BUFFER_CAPACITY, read, write)
+    }
+
+    /// Like [`Codec::new`], buffering up to the given number of bytes of sent messages
+    pub fn with_capacity(capacity: usize, read: S, write: S) -> Self {
+        Self {
+            reader: BufReader::new(read),
+            writer: BufWriter::with_capacity(capacity, write),
+        }
+    }
+
+    /// Buffer the given message to be sent on the next flush
+    pub fn send<M: Message>(&mut self, message: &M) -> Result<(), Error> {
+        let mut buf = Vec::with_capacity(message.encoded_len() + MAX_VARINT_LENGTH);
+        message.encode_length_delimited(&mut buf)?;
+        self.writer.write_all(&buf)?;
+        Ok(())
+    }
+
+    /// Send the buffered messages
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Receive the next message, or `None` if the stream was closed in between messages
+    pub fn receive<M: Message + Default>(&mut self) -> Result<Option<M>, Error> {
+        let size = match read_varint(&mut self.reader)? {
+            Some(size) => size,
+            None => return Ok(None),
+        };
+
+        if size > MAX_MESSAGE_SIZE as u64 {
+            return Err(Error::MessageTooLarge {
+                size,
+                max: MAX_MESSAGE_SIZE,
+            });
+        }
+
+        let mut buf = vec![0; size as usize];
+        self.reader.read_exact(&mut buf).map_err(closed_on_eof)?;
+
+        Ok(Some(M::decode(buf.as_slice())?))
+    }
+}
+
+/// Read an unsigned varint, or `None` if the stream ends before its first byte
+fn read_varint(reader: &mut impl Read) -> Result<Option<u64>, Error> {
+    let mut value = 0_u64;
+
+    for i in 0..MAX_VARINT_LENGTH {
+        let mut byte = [0_u8];
+        if let Err(e) = reader.read_exact(&mut byte) {
+            return match e.kind() {
+                io::ErrorKind::UnexpectedEof if i == 0 => Ok(None),
+                _ => Err(closed_on_eof(e)),
+            };
+        }
+
+        value |= u64::from(byte[0] & 0x7f) << (7 * i);
+        if byte[0] < 0x80 {
+            return Ok(Some(value));
+        }
+    }
+
+    Err(prost::DecodeError::new("invalid varint").into())
+}
+
+fn closed_on_eof(e: io::Error) -> Error {
+    match e.kind() {
+        io::ErrorKind::UnexpectedEof => Error::ConnectionClosed,
+        _ => e.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use tendermint_proto::abci::{request, Request, RequestEcho, RequestFlush};
+
+    fn echo(message: &str) -> Request {
+        Request {
+            value: Some(request::Value::Echo(RequestEcho {
+                message: message.to_string(),
+            })),
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let long = "a".repeat(300);
+        let flush = Request {
+            value: Some(request::Value::Flush(RequestFlush {})),
+        };
+
+        let mut codec = Codec::new(Cursor::new(Vec::new()), Cursor::new(Vec::new()));
+        codec.send(&echo("hello")).unwrap();
+        codec.send(&echo(&long)).unwrap();
+        codec.send(&flush).unwrap();
+        codec.flush().unwrap();
+
+        let sent = codec.writer.get_ref().get_ref().clone();
+        let mut codec = Codec::new(Cursor::new(sent), Cursor::new(Vec::new()));
+        assert_eq!(codec.receive::<Request>().unwrap(), Some(echo("hello")));
+        assert_eq!(codec.receive::<Request>().unwrap(), Some(echo(&long)));
+        assert_eq!(codec.receive::<Request>().unwrap(), Some(flush));
+        assert_eq!(codec.receive::<Request>().unwrap(), None);
+    }
+
+    #[test]
+    fn writes_on_flush_or_full_buffer() {
+        let message = echo("hello");
+        let size = message.encoded_len() + 1;
+
+        let mut codec =
+            Codec::with_capacity(2 * size, Cursor::new(Vec::new()), Cursor::new(Vec::new()));
+        let written = |codec: &Codec<Cursor<Vec<u8>>>| codec.writer.get_ref().get_ref().len();
+
+        codec.send(&message).unwrap();
+        codec.send(&message).unwrap();
+        assert_eq!(written(&codec), 0);
+
+        codec.send(&message).unwrap();
+        assert_eq!(written(&codec), 2 * size);
+
+        codec.flush().unwrap();
+        assert_eq!(written(&codec), 3 * size);
+    }
+
+    #[test]
+    fn truncated_message() {
+        let mut buf = Vec::new();
+        echo("hello").encode_length_delimited(&mut buf).unwrap();
+        buf.pop();
+
+        let mut codec = Codec::new(Cursor::new(buf), Cursor::new(Vec::new()));
+        assert!(matches!(
+            codec.receive::<Request>(),
+            Err(Error::ConnectionClosed)
+        ));
+    }
+}
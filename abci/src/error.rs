@@ -0,0 +1,45 @@
+//! ABCI errors
+
+use thiserror::Error;
+
+/// Errors of the ABCI codec and client
+#[derive(Debug, Error)]
+pub enum Error {
+    /// I/O error on the underlying stream
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Message could not be encoded
+    #[error("failed to encode message: {0}")]
+    Encode(#[from] prost::EncodeError),
+
+    /// Message could not be decoded
+    #[error("failed to decode message: {0}")]
+    Decode(#[from] prost::DecodeError),
+
+    /// Length prefix announcing a message larger than the codec accepts
+    #[error("message of {size} bytes exceeds the maximum of {max} bytes")]
+    MessageTooLarge {
+        /// Announced size of the message
+        size: u64,
+        /// Maximum size accepted by the codec
+        max: usize,
+    },
+
+    /// Stream closed by the peer while a message was expected
+    #[error("connection closed by peer")]
+    ConnectionClosed,
+
+    /// Application answered the request with an exception
+    #[error("application raised an exception: {0}")]
+    Exception(String),
+
+    /// Application answered the request with a response of another type
+    #[error("unexpected response to {request} request: {response}")]
+    UnexpectedResponse {
+        /// Type of the request
+        request: &'static str,
+        /// Response received instead
+        response: String,
+    },
+}
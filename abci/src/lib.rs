@@ -0,0 +1,36 @@
+#![forbid(unsafe_code)]
+#![deny(
+    warnings,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications,
+    rust_2018_idioms,
+    nonstandard_style
+)]
+
+//! Implementation of the Application BlockChain Interface (ABCI) socket protocol of
+//! Tendermint, through which Tendermint drives the application replicated by the chain.
+//!
+//! Requests and responses are the protobuf messages of [`tendermint_proto::abci`],
+//! exchanged over a stream as length-prefixed messages by the [`Codec`].
+//!
+//! The [`Client`] sends requests to an ABCI application the same way Tendermint does,
+//! so that integration tests and tooling can exercise it without running a full node.
+//!
+//! The [`Server`] serves an [`Application`] to Tendermint, handling the requests of the
+//! mempool, info and snapshot connections concurrently while serializing those of the
+//! consensus connection. Requests are dispatched to the application through
+//! [`middleware`], eg. to log them or to catch the panics of the application.
+
+pub mod application;
+pub mod client;
+pub mod codec;
+pub mod error;
+pub mod middleware;
+pub mod server;
+
+pub use self::{
+    application::Application, client::Client, codec::Codec, error::Error, server::Server,
+};
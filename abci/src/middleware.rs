@@ -0,0 +1,84 @@
+//! Middleware intercepting the requests the [`Server`](crate::Server) dispatches to the
+//! [`Application`](crate::Application), eg. for logging, metrics or timing.
+
+use std::panic::{self, AssertUnwindSafe};
+
+use tendermint_proto::abci::{request, response};
+
+use crate::application::exception;
+
+/// Layer around the dispatch of requests to the application.
+///
+/// Layers are called in the order they were added to the server, the first one being the
+/// outermost. By default, `around` calls `before` with the request, dispatches it to the next
+/// layer, and calls `after` with the response, so that most middleware only implement these
+/// hooks. Middleware needing to keep state across the dispatch, such as timers, or to
+/// short-circuit it, implement `around` instead.
+pub trait Middleware: Send + Sync + 'static {
+    /// Hook called with a request before it is dispatched
+    fn before(&self, _request: &request::Value) {}
+
+    /// Hook called with the response to a request of the given method, as named by
+    /// [`method_name`], before it is sent
+    fn after(&self, _method: &'static str, _response: &mut response::Value) {}
+
+    /// Dispatch the given request to the next layer
+    fn around(
+        &self,
+        request: request::Value,
+        next: &dyn Fn(request::Value) -> response::Value,
+    ) -> response::Value {
+        let method = method_name(&request);
+        self.before(&request);
+        let mut response = next(request);
+        self.after(method, &mut response);
+        response
+    }
+}
+
+/// Middleware answering the requests the application panics on with an exception, instead of
+/// closing the connection.
+///
+/// Tendermint halts upon exceptions on the consensus connection just as it would upon the
+/// connection closing, but keeps on serving the other connections.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CatchPanic;
+
+impl Middleware for CatchPanic {
+    fn around(
+        &self,
+        request: request::Value,
+        next: &dyn Fn(request::Value) -> response::Value,
+    ) -> response::Value {
+        let method = method_name(&request);
+        panic::catch_unwind(AssertUnwindSafe(|| next(request))).unwrap_or_else(|panic| {
+            let cause = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown cause");
+            exception(format!("application panicked on {}: {}", method, cause))
+        })
+    }
+}
+
+/// Name of the ABCI method of the given request, eg. `check_tx`
+pub fn method_name(request: &request::Value) -> &'static str {
+    match request {
+        request::Value::Echo(_) => "echo",
+        request::Value::Flush(_) => "flush",
+        request::Value::Info(_) => "info",
+        request::Value::SetOption(_) => "set_option",
+        request::Value::InitChain(_) => "init_chain",
+        request::Value::Query(_) => "query",
+        request::Value::BeginBlock(_) => "begin_block",
+        request::Value::CheckTx(_) => "check_tx",
+        request::Value::DeliverTx(_) => "deliver_tx",
+        request::Value::EndBlock(_) => "end_block",
+        request::Value::Commit(_) => "commit",
+        request::Value::ListSnapshots(_) => "list_snapshots",
+        request::Value::OfferSnapshot(_) => "offer_snapshot",
+        request::Value::LoadSnapshotChunk(_) => "load_snapshot_chunk",
+        request::Value::ApplySnapshotChunk(_) => "apply_snapshot_chunk",
+    }
+}
@@ -0,0 +1,206 @@
+//! Server exposing an [`Application`] to Tendermint over the ABCI socket protocol.
+
+use std::fmt;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+
+use tendermint_proto::abci::{request, response, Request, Response};
+
+use crate::application::exception;
+use crate::middleware::Middleware;
+use crate::{Application, Codec, Error};
+
+/// Role of a connection opened by Tendermint, which opens one connection per role.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ConnectionRole {
+    /// Executes blocks: `InitChain`, `BeginBlock`, `DeliverTx`, `EndBlock` and `Commit`
+    Consensus,
+    /// Checks the transactions entering the mempool: `CheckTx`
+    Mempool,
+    /// Queries the application state: `Info`, `SetOption` and `Query`
+    Info,
+    /// Serves and restores state sync snapshots: `ListSnapshots`, `OfferSnapshot`,
+    /// `LoadSnapshotChunk` and `ApplySnapshotChunk`
+    Snapshot,
+}
+
+impl ConnectionRole {
+    /// Role of the connections carrying the given request, or `None` for `Echo` and `Flush`
+    /// requests, which connections of every role carry
+    pub fn of(request: &request::Value) -> Option<Self> {
+        match request {
+            request::Value::Echo(_) | request::Value::Flush(_) => None,
+            request::Value::InitChain(_)
+            | request::Value::BeginBlock(_)
+            | request::Value::DeliverTx(_)
+            | request::Value::EndBlock(_)
+            | request::Value::Commit(_) => Some(Self::Consensus),
+            request::Value::CheckTx(_) => Some(Self::Mempool),
+            request::Value::Info(_) | request::Value::SetOption(_) | request::Value::Query(_) => {
+                Some(Self::Info)
+            }
+            request::Value::ListSnapshots(_)
+            | request::Value::OfferSnapshot(_)
+            | request::Value::LoadSnapshotChunk(_)
+            | request::Value::ApplySnapshotChunk(_) => Some(Self::Snapshot),
+        }
+    }
+
+    /// Whether the requests of connections of this role may be handled concurrently with
+    /// other requests, which all but those of the consensus connection may
+    pub fn is_concurrent(self) -> bool {
+        self != Self::Consensus
+    }
+}
+
+/// Server exposing an application to Tendermint over TCP.
+///
+/// Each connection is served on its own thread by a clone of the application. Its first
+/// request sets the role of a connection, after which requests of other roles are answered
+/// with an exception, as Tendermint never mixes them up. Requests of the consensus connection
+/// are handled one at a time, even across connections, whereas those of the mempool, info and
+/// snapshot connections are handled concurrently with them and with each other.
+///
+/// Requests are dispatched to the application through the [`Middleware`] added with
+/// [`Server::middleware`].
+///
+/// As in Tendermint, responses are buffered and only written out upon a `Flush` request, or
+/// once the buffer fills up.
+pub struct Server<A: Application> {
+    listener: TcpListener,
+    app: A,
+    middleware: Vec<Arc<dyn Middleware>>,
+    consensus: Arc<Mutex<()>>,
+}
+
+impl<A: Application> Server<A> {
+    /// Serve the given application on the given TCP address
+    pub fn bind(addr: impl ToSocketAddrs, app: A) -> Result<Self, Error> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            app,
+            middleware: Vec::new(),
+            consensus: Arc::new(Mutex::new(())),
+        })
+    }
+
+    /// Dispatch requests through the given middleware, within the middleware added before
+    pub fn middleware(mut self, middleware: impl Middleware) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Address the server listens on, eg. to find out the port it was bound to
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serve incoming connections, until accepting one fails.
+    ///
+    /// A connection is closed once the peer closes it or an I/O error occurs on it.
+    pub fn listen(self) -> Result<(), Error> {
+        let middleware: Arc<[Arc<dyn Middleware>]> = self.middleware.into();
+        loop {
+            let (stream, _) = self.listener.accept()?;
+            let connection = Connection {
+                app: self.app.clone(),
+                middleware: middleware.clone(),
+                consensus: self.consensus.clone(),
+                role: None,
+            };
+
+            // Errors only close the connection, on which they cannot be reported
+            thread::spawn(move || connection.serve(stream));
+        }
+    }
+}
+
+impl<A: Application> fmt::Debug for Server<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Server")
+            .field("listener", &self.listener)
+            .field("middleware", &self.middleware.len())
+            .finish()
+    }
+}
+
+/// Connection opened by Tendermint
+struct Connection<A> {
+    app: A,
+    /// Layers the requests are dispatched through, outermost first
+    middleware: Arc<[Arc<dyn Middleware>]>,
+    /// Lock serializing the requests of the consensus connection
+    consensus: Arc<Mutex<()>>,
+    /// Role of the connection, once set by its first request
+    role: Option<ConnectionRole>,
+}
+
+impl<A: Application> Connection<A> {
+    fn serve(mut self, stream: TcpStream) -> Result<(), Error> {
+        stream.set_nodelay(true)?;
+        let mut codec = Codec::new(stream.try_clone()?, stream);
+
+        while let Some(request) = codec.receive::<Request>()? {
+            let response = match request.value {
+                Some(value) => self.handle(value),
+                None => exception("empty request"),
+            };
+
+            let is_flush = matches!(response, response::Value::Flush(_));
+            codec.send(&Response {
+                value: Some(response),
+            })?;
+            if is_flush {
+                codec.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle(&mut self, request: request::Value) -> response::Value {
+        let role = match ConnectionRole::of(&request) {
+            Some(role) => role,
+            None => return self.dispatch(request),
+        };
+
+        match self.role {
+            Some(current) if current != role => {
+                return exception(format!(
+                    "{:?} connection cannot carry {:?} requests",
+                    current, role
+                ))
+            }
+            _ => self.role = Some(role),
+        }
+
+        if role.is_concurrent() {
+            return self.dispatch(request);
+        }
+
+        // A panic of the application while handling a consensus request only aborts the
+        // connection it came from
+        let _serialized = self
+            .consensus
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.dispatch(request)
+    }
+
+    fn dispatch(&self, request: request::Value) -> response::Value {
+        dispatch(&self.app, &self.middleware, request)
+    }
+}
+
+/// Dispatch the given request to the application through the given middleware
+fn dispatch<A: Application>(
+    app: &A,
+    middleware: &[Arc<dyn Middleware>],
+    request: request::Value,
+) -> response::Value {
+    match middleware.split_first() {
+        Some((layer, inner)) => layer.around(request, &|request| dispatch(app, inner, request)),
+        None => app.handle(request),
+    }
+}
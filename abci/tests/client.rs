@@ -0,0 +1,108 @@
+use std::net::TcpListener;
+use std::thread;
+
+use tendermint_abci::{Client, Codec, Error};
+use tendermint_proto::abci::{
+    request, response, Request, RequestDeliverTx, RequestEcho, RequestInfo, Response,
+    ResponseDeliverTx, ResponseEcho, ResponseException, ResponseFlush,
+};
+
+/// Serve a single connection, echoing messages and transactions back and failing any other
+/// request
+fn serve_echo() -> std::net::SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut codec = Codec::new(stream.try_clone().unwrap(), stream);
+
+        while let Some(request) = codec.receive::<Request>().unwrap() {
+            let value = match request.value.unwrap() {
+                request::Value::Echo(echo) => response::Value::Echo(ResponseEcho {
+                    message: echo.message,
+                }),
+                request::Value::DeliverTx(deliver_tx) => {
+                    response::Value::DeliverTx(ResponseDeliverTx {
+                        data: deliver_tx.tx,
+                        ..Default::default()
+                    })
+                }
+                request::Value::Flush(_) => response::Value::Flush(ResponseFlush {}),
+                _ => response::Value::Exception(ResponseException {
+                    error: "not implemented".to_string(),
+                }),
+            };
+
+            let is_flush = matches!(value, response::Value::Flush(_));
+            codec.send(&Response { value: Some(value) }).unwrap();
+            if is_flush {
+                codec.flush().unwrap();
+            }
+        }
+    });
+
+    addr
+}
+
+#[test]
+fn client_receives_typed_responses() {
+    let mut client = Client::connect(serve_echo()).unwrap();
+
+    let echo = client
+        .echo(RequestEcho {
+            message: "hello".to_string(),
+        })
+        .unwrap();
+    assert_eq!(echo.message, "hello");
+
+    client.flush().unwrap();
+
+    match client.info(RequestInfo::default()) {
+        Err(Error::Exception(error)) => assert_eq!(error, "not implemented"),
+        other => panic!("expected an exception, got {:?}", other),
+    }
+
+    let echo = client
+        .echo(RequestEcho {
+            message: "again".to_string(),
+        })
+        .unwrap();
+    assert_eq!(echo.message, "again");
+}
+
+#[test]
+fn client_pipelines_transactions() {
+    let mut client = Client::connect(serve_echo()).unwrap();
+
+    let txs = (0..100_u8).map(|i| vec![i; 1000]).collect::<Vec<_>>();
+    let responses = client
+        .deliver_txs(txs.iter().map(|tx| RequestDeliverTx { tx: tx.clone() }))
+        .unwrap();
+
+    let data = responses.into_iter().map(|r| r.data).collect::<Vec<_>>();
+    assert_eq!(data, txs);
+
+    let echo = client
+        .echo(RequestEcho {
+            message: "done".to_string(),
+        })
+        .unwrap();
+    assert_eq!(echo.message, "done");
+}
+
+#[test]
+fn client_pipelines_batches_larger_than_socket_buffers() {
+    let mut client = Client::connect(serve_echo()).unwrap();
+
+    // Echoed back, the transactions fill up the socket buffers both ways
+    let txs = (0..1_000_u32)
+        .map(|i| vec![i as u8; 16_384])
+        .collect::<Vec<_>>();
+    let responses = client
+        .deliver_txs(txs.iter().map(|tx| RequestDeliverTx { tx: tx.clone() }))
+        .unwrap();
+
+    let data = responses.into_iter().map(|r| r.data).collect::<Vec<_>>();
+    assert_eq!(data, txs);
+}
@@ -0,0 +1,255 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use tendermint_abci::middleware::{CatchPanic, Middleware};
+use tendermint_abci::{Application, Client, Error, Server};
+use tendermint_proto::abci::{
+    request, response, RequestBeginBlock, RequestCheckTx, RequestEcho, RequestInfo, RequestQuery,
+    ResponseBeginBlock, ResponseCheckTx, ResponseInfo, ResponseQuery,
+};
+
+/// Application tracking how many blocks it begins at once, and optionally waiting for a
+/// signal before beginning any
+#[derive(Clone, Default)]
+struct Tracker {
+    running: Arc<AtomicUsize>,
+    max_running: Arc<AtomicUsize>,
+    release: Option<Arc<Mutex<mpsc::Receiver<()>>>>,
+}
+
+impl Application for Tracker {
+    fn info(&self, _request: RequestInfo) -> ResponseInfo {
+        ResponseInfo {
+            data: "tracker".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn check_tx(&self, request: RequestCheckTx) -> ResponseCheckTx {
+        ResponseCheckTx {
+            data: request.tx,
+            ..Default::default()
+        }
+    }
+
+    fn query(&self, _request: RequestQuery) -> ResponseQuery {
+        panic!("query is not supported")
+    }
+
+    fn begin_block(&self, _request: RequestBeginBlock) -> ResponseBeginBlock {
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_running.fetch_max(running, Ordering::SeqCst);
+
+        match &self.release {
+            Some(release) => release.lock().unwrap().recv().unwrap(),
+            None => thread::sleep(Duration::from_millis(50)),
+        }
+
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        ResponseBeginBlock::default()
+    }
+}
+
+fn serve(app: Tracker) -> SocketAddr {
+    serve_through(Server::bind("127.0.0.1:0", app).unwrap())
+}
+
+fn serve_through(server: Server<Tracker>) -> SocketAddr {
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.listen());
+    addr
+}
+
+#[test]
+fn server_dispatches_requests_to_application() {
+    let mut client = Client::connect(serve(Tracker::default())).unwrap();
+
+    let echo = client
+        .echo(RequestEcho {
+            message: "hello".to_string(),
+        })
+        .unwrap();
+    assert_eq!(echo.message, "hello");
+
+    let info = client.info(RequestInfo::default()).unwrap();
+    assert_eq!(info.data, "tracker");
+}
+
+#[test]
+fn connection_rejects_requests_of_other_roles() {
+    let mut client = Client::connect(serve(Tracker::default())).unwrap();
+
+    client
+        .check_tx(RequestCheckTx {
+            tx: b"tx".to_vec(),
+            ..Default::default()
+        })
+        .unwrap();
+
+    match client.info(RequestInfo::default()) {
+        Err(Error::Exception(error)) => assert!(error.contains("Mempool")),
+        result => panic!("unexpected result: {:?}", result),
+    }
+
+    // The connection keeps its role after rejecting a request
+    let check = client
+        .check_tx(RequestCheckTx {
+            tx: b"other".to_vec(),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(check.data, b"other".to_vec());
+}
+
+#[test]
+fn consensus_requests_are_serialized_across_connections() {
+    let app = Tracker::default();
+    let addr = serve(app.clone());
+
+    let handles = (0..4)
+        .map(|_| {
+            thread::spawn(move || {
+                let mut client = Client::connect(addr).unwrap();
+                client.begin_block(RequestBeginBlock::default()).unwrap();
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(app.max_running.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn other_requests_are_served_during_consensus_requests() {
+    let (release, receiver) = mpsc::channel();
+    let app = Tracker {
+        release: Some(Arc::new(Mutex::new(receiver))),
+        ..Default::default()
+    };
+    let addr = serve(app.clone());
+
+    let consensus = thread::spawn(move || {
+        let mut client = Client::connect(addr).unwrap();
+        client.begin_block(RequestBeginBlock::default()).unwrap();
+    });
+    while app.running.load(Ordering::SeqCst) == 0 {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    let mut info = Client::connect(addr).unwrap();
+    assert_eq!(info.info(RequestInfo::default()).unwrap().data, "tracker");
+
+    let mut mempool = Client::connect(addr).unwrap();
+    let check = mempool
+        .check_tx(RequestCheckTx {
+            tx: b"tx".to_vec(),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(check.data, b"tx".to_vec());
+
+    release.send(()).unwrap();
+    consensus.join().unwrap();
+}
+
+#[test]
+fn state_sync_is_aborted_by_default() {
+    let mut client = Client::connect(serve(Tracker::default())).unwrap();
+
+    assert!(client.list_snapshots().unwrap().snapshots.is_empty());
+
+    let offer = client
+        .offer_snapshot(RequestOfferSnapshot::default())
+        .unwrap();
+    assert_eq!(offer.result, response_offer_snapshot::Result::Abort as i32);
+
+    let apply = client
+        .apply_snapshot_chunk(RequestApplySnapshotChunk::default())
+        .unwrap();
+    assert_eq!(
+        apply.result,
+        response_apply_snapshot_chunk::Result::Abort as i32
+    );
+}
+
+/// Middleware recording the requests and responses it sees, tagged with its name
+#[derive(Clone)]
+struct Recorder {
+    name: &'static str,
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl Middleware for Recorder {
+    fn before(&self, request: &request::Value) {
+        let method = tendermint_abci::middleware::method_name(request);
+        let event = format!("{} before {}", self.name, method);
+        self.events.lock().unwrap().push(event);
+    }
+
+    fn after(&self, method: &'static str, response: &mut response::Value) {
+        if let response::Value::Info(info) = response {
+            info.data.push_str(self.name);
+        }
+        let event = format!("{} after {}", self.name, method);
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+#[test]
+fn middleware_sees_requests_and_responses() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let server = Server::bind("127.0.0.1:0", Tracker::default())
+        .unwrap()
+        .middleware(Recorder {
+            name: "outer",
+            events: events.clone(),
+        })
+        .middleware(Recorder {
+            name: "inner",
+            events: events.clone(),
+        });
+    let mut client = Client::connect(serve_through(server)).unwrap();
+
+    let info = client.info(RequestInfo::default()).unwrap();
+    assert_eq!(info.data, "trackerinnerouter");
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            "outer before info",
+            "inner before info",
+            "inner after info",
+            "outer after info",
+            "outer before flush",
+            "inner before flush",
+            "inner after flush",
+            "outer after flush",
+        ]
+    );
+}
+
+#[test]
+fn catch_panic_answers_with_exception() {
+    let server = Server::bind("127.0.0.1:0", Tracker::default())
+        .unwrap()
+        .middleware(CatchPanic);
+    let mut client = Client::connect(serve_through(server)).unwrap();
+
+    match client.query(RequestQuery::default()) {
+        Err(Error::Exception(error)) => {
+            assert_eq!(
+                error,
+                "application panicked on query: query is not supported"
+            );
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+
+    // The connection survives the panic
+    assert_eq!(client.info(RequestInfo::default()).unwrap().data, "tracker");
+}
@@ -0,0 +1,159 @@
+#![forbid(unsafe_code)]
+#![deny(
+    warnings,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications,
+    rust_2018_idioms,
+    nonstandard_style
+)]
+
+//! WebAssembly bindings to the verification of the Tendermint light client.
+//!
+//! These bindings let browser applications, such as wallets, verify the light blocks
+//! returned by an RPC endpoint rather than trusting it, and keep their trusted state
+//! in the local storage of the browser.
+//!
+//! Light blocks, options and verification outcomes are exchanged as JavaScript objects
+//! with the same shape as their JSON serialization, and times as RFC 3339 strings.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use web_sys::Storage;
+
+use tendermint_light_client::{
+    audit::Outcome,
+    components::verifier::{ProdVerifier, Verifier},
+    light_client::Options,
+    types::{LightBlock, Time},
+};
+
+/// Result of the verification of an untrusted light block against a trusted one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VerificationResult {
+    /// Outcome of the verification
+    pub outcome: Outcome,
+    /// Whether the untrusted light block is now the trusted state in local storage
+    pub stored: bool,
+}
+
+/// Verify the `untrusted` light block against the `trusted` one, with the given `options`
+/// at time `now`.
+///
+/// Returns the outcome of the verification, which is `"Verified"` if the untrusted light
+/// block can be trusted.
+#[wasm_bindgen]
+pub fn verify(
+    untrusted: &JsValue,
+    trusted: &JsValue,
+    options: &JsValue,
+    now: &str,
+) -> Result<JsValue, JsValue> {
+    let untrusted: LightBlock = from_js(untrusted)?;
+    let trusted: LightBlock = from_js(trusted)?;
+    let options: Options = from_js(options)?;
+    let now = parse_time(now)?;
+
+    let outcome = verify_light_block(&untrusted, &trusted, &options, now);
+    to_js(&outcome)
+}
+
+/// Store the given light block as the trusted state under the given key of the
+/// local storage, replacing the one stored there.
+#[wasm_bindgen]
+pub fn store_trusted(key: &str, light_block: &JsValue) -> Result<(), JsValue> {
+    let light_block: LightBlock = from_js(light_block)?;
+    save(&local_storage()?, key, &light_block)
+}
+
+/// Load the trusted state stored under the given key of the local storage.
+///
+/// Returns `null` if no trusted state is stored under this key.
+#[wasm_bindgen]
+pub fn load_trusted(key: &str) -> Result<JsValue, JsValue> {
+    match load(&local_storage()?, key)? {
+        Some(light_block) => to_js(&light_block),
+        None => Ok(JsValue::NULL),
+    }
+}
+
+/// Verify the `untrusted` light block against the trusted state stored under the given key
+/// of the local storage, with the given `options` at time `now`.
+///
+/// If the untrusted light block is verified and more recent than the trusted state, it
+/// replaces the trusted state in local storage.
+///
+/// Fails if no trusted state is stored under this key, in which case the application must
+/// first obtain a trusted light block out of band and store it with `store_trusted`.
+#[wasm_bindgen]
+pub fn verify_and_store(
+    key: &str,
+    untrusted: &JsValue,
+    options: &JsValue,
+    now: &str,
+) -> Result<JsValue, JsValue> {
+    let storage = local_storage()?;
+
+    let trusted = load(&storage, key)?
+        .ok_or_else(|| JsValue::from_str(&format!("no trusted state stored under '{}'", key)))?;
+
+    let untrusted: LightBlock = from_js(untrusted)?;
+    let options: Options = from_js(options)?;
+    let now = parse_time(now)?;
+
+    let outcome = verify_light_block(&untrusted, &trusted, &options, now);
+
+    let stored = outcome == Outcome::Verified && untrusted.height() > trusted.height();
+    if stored {
+        save(&storage, key, &untrusted)?;
+    }
+
+    to_js(&VerificationResult { outcome, stored })
+}
+
+fn verify_light_block(
+    untrusted: &LightBlock,
+    trusted: &LightBlock,
+    options: &Options,
+    now: Time,
+) -> Outcome {
+    let verdict = ProdVerifier::default().verify(untrusted, trusted, options, now);
+    Outcome::from(&verdict)
+}
+
+fn local_storage() -> Result<Storage, JsValue> {
+    web_sys::window()
+        .ok_or_else(|| JsValue::from_str("no global window"))?
+        .local_storage()?
+        .ok_or_else(|| JsValue::from_str("local storage is not available"))
+}
+
+fn load(storage: &Storage, key: &str) -> Result<Option<LightBlock>, JsValue> {
+    match storage.get_item(key)? {
+        Some(json) => serde_json::from_str(&json).map(Some).map_err(to_js_error),
+        None => Ok(None),
+    }
+}
+
+fn save(storage: &Storage, key: &str, light_block: &LightBlock) -> Result<(), JsValue> {
+    let json = serde_json::to_string(light_block).map_err(to_js_error)?;
+    storage.set_item(key, &json)
+}
+
+fn parse_time(time: &str) -> Result<Time, JsValue> {
+    Time::parse_from_rfc3339(time).map_err(to_js_error)
+}
+
+fn from_js<T: for<'de> Deserialize<'de>>(value: &JsValue) -> Result<T, JsValue> {
+    value.into_serde().map_err(to_js_error)
+}
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    JsValue::from_serde(value).map_err(to_js_error)
+}
+
+fn to_js_error(e: impl std::fmt::Display) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::{path::PathBuf, time::Duration};
+
+use gumdrop::Options;
+
+use tendermint_light_client::{
+    components::{
+        clock::FixedClock,
+        io::{
+            replay::{Recording, RecordingIo, ReplayIo},
+            AtHeight, Io, ProdIo,
+        },
+        scheduler::VerificationStrategy,
+        verifier::ProdVerifier,
+    },
+    light_client::{self, LightClient},
+    state::State,
+    store::{memory::MemoryStore, LightStore},
+    types::{Height, PeerId, Status, Time, TrustThreshold},
+};
+
+#[derive(Debug, Options)]
+struct CliOptions {
+    #[options(help = "print this help message")]
+    help: bool,
+
+    #[options(command)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Options)]
+enum Command {
+    #[options(help = "verify a block and record the responses of the node to a file")]
+    Capture(CaptureOpts),
+    #[options(help = "verify a block again from the responses recorded to a file")]
+    Replay(ReplayOpts),
+}
+
+#[derive(Debug, Options)]
+struct CaptureOpts {
+    #[options(help = "show help for this command")]
+    help: bool,
+    #[options(
+        help = "address of the Tendermint node to connect to",
+        meta = "ADDR",
+        default = "tcp://127.0.0.1:26657"
+    )]
+    address: tendermint::net::Address,
+    #[options(free, required, help = "path to the recording to write")]
+    recording: PathBuf,
+    #[options(
+        required,
+        help = "height of the initial trusted state",
+        meta = "HEIGHT"
+    )]
+    trusted_height: Height,
+    #[options(
+        help = "height of the block to verify (default: latest)",
+        meta = "HEIGHT"
+    )]
+    target_height: Option<Height>,
+}
+
+#[derive(Debug, Options)]
+struct ReplayOpts {
+    #[options(help = "show help for this command")]
+    help: bool,
+    #[options(free, required, help = "path to the recording to replay")]
+    recording: PathBuf,
+    #[options(
+        required,
+        help = "height of the initial trusted state",
+        meta = "HEIGHT"
+    )]
+    trusted_height: Height,
+    #[options(
+        help = "height of the block to verify (default: latest)",
+        meta = "HEIGHT"
+    )]
+    target_height: Option<Height>,
+}
+
+fn main() {
+    let opts = CliOptions::parse_args_default_or_exit();
+    match opts.command {
+        None => {
+            eprintln!("Please specify a command:");
+            eprintln!("{}\n", CliOptions::command_list().unwrap());
+            eprintln!("{}\n", CliOptions::usage());
+            std::process::exit(1);
+        }
+        Some(Command::Capture(capture_opts)) => capture_cmd(capture_opts),
+        Some(Command::Replay(replay_opts)) => replay_cmd(replay_opts),
+    }
+}
+
+fn peer_id() -> PeerId {
+    "BADFADAD0BEFEEDC0C0ADEADBEEFC0FFEEFACADE".parse().unwrap()
+}
+
+fn verify(io: impl Io + 'static, now: Time, trusted_height: Height, target_height: Option<Height>) {
+    let peer_id = peer_id();
+
+    let trusted_state = io
+        .fetch_light_block(peer_id, AtHeight::At(trusted_height))
+        .unwrap_or_else(|e| {
+            println!("[ error ] could not retrieve trusted header: {}", e);
+            std::process::exit(1);
+        });
+
+    let mut light_store = MemoryStore::new();
+    light_store.insert(trusted_state, Status::Trusted);
+
+    let mut state = State {
+        light_store: Box::new(light_store),
+        verification_trace: HashMap::new(),
+        audit_log: None,
+    };
+
+    let options = light_client::Options {
+        trust_threshold: TrustThreshold {
+            numerator: 1,
+            denominator: 3,
+        },
+        trusting_period: Duration::from_secs(36000),
+        clock_drift: Duration::from_secs(1),
+        max_bisection_depth: None,
+        request_timeout: None,
+        max_verification_time: None,
+    };
+
+    let mut light_client = LightClient::new(
+        peer_id,
+        options,
+        FixedClock(now),
+        VerificationStrategy::default(),
+        ProdVerifier::default(),
+        io,
+    );
+
+    let result = match target_height {
+        Some(height) => light_client.verify_to_target(height, &mut state),
+        None => light_client.verify_to_highest(&mut state),
+    };
+
+    match result {
+        Ok(light_block) => println!("[info] verified block {}", light_block.height()),
+        Err(err) => println!("[error] verification failed: {}", err),
+    }
+}
+
+fn capture_cmd(opts: CaptureOpts) {
+    let mut peer_map = HashMap::new();
+    peer_map.insert(peer_id(), opts.address);
+
+    let io = RecordingIo::new(ProdIo::new(peer_map, Some(Duration::from_secs(10))));
+    let now = io.recording().time;
+
+    verify(io.clone(), now, opts.trusted_height, opts.target_height);
+
+    io.recording().save(&opts.recording).unwrap_or_else(|e| {
+        println!("[ error ] could not save recording: {}", e);
+        std::process::exit(1);
+    });
+
+    println!("[info] recorded responses to {}", opts.recording.display());
+}
+
+fn replay_cmd(opts: ReplayOpts) {
+    let recording = Recording::load(&opts.recording).unwrap_or_else(|e| {
+        println!("[ error ] could not load recording: {}", e);
+        std::process::exit(1);
+    });
+
+    let now = recording.time;
+    let io = ReplayIo::new(recording);
+
+    verify(io, now, opts.trusted_height, opts.target_height);
+}
@@ -112,6 +112,7 @@ fn make_instance(
     let state = State {
         light_store: Box::new(light_store),
         verification_trace: HashMap::new(),
+        audit_log: None,
     };
 
     let options = light_client::Options {
@@ -121,6 +122,9 @@ fn make_instance(
         },
         trusting_period: Duration::from_secs(36000),
         clock_drift: Duration::from_secs(1),
+        max_bisection_depth: None,
+        request_timeout: None,
+        max_verification_time: None,
     };
 
     let verifier = ProdVerifier::default();
@@ -0,0 +1,238 @@
+//! Supervisor driven asynchronously, eg. from within a tokio service.
+
+use std::path::PathBuf;
+
+use crate::bail;
+use crate::errors::{Error, ErrorKind};
+use crate::evidence::AsyncEvidenceReporter;
+use crate::fork_detector::ForkDetector;
+use crate::metrics::{MetricsRecorder, Recorder};
+use crate::peer_list::PeerList;
+use crate::persistence::SupervisorState;
+use crate::renewal::Renewal;
+use crate::store::pruning::Pruner;
+use crate::supervisor::{Conclusion, Instance, SupervisorCore};
+use crate::types::{Height, LatestStatus, LightBlock, PeerId};
+
+/// Counterpart of the [`Supervisor`](crate::supervisor::Supervisor) whose methods can be awaited
+/// from within a tokio runtime, without dedicated threads nor blocking the current thread.
+///
+/// Its light client instances should be constructed with `LightClient::new_async`, fetching light
+/// blocks with an `AsyncIo` component such as `ProdIo`, and fork evidence is reported with an
+/// `AsyncEvidenceReporter` such as `ProdEvidenceReporter`. Forks are detected with
+/// `ForkDetector::detect_forks_async`, which the `ProdForkDetector` implements without blocking.
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// let mut supervisor = AsyncSupervisor::new(peer_list, ProdForkDetector::default(), reporter);
+///
+/// loop {
+///     match supervisor.verify_to_highest().await {
+///         Ok(light_block) => {
+///             println!("[info] synced to block {}", light_block.height());
+///         }
+///         Err(e) => {
+///             println!("[error] sync failed: {}", e);
+///         }
+///     }
+///
+///     tokio::time::delay_for(Duration::from_millis(800)).await;
+/// }
+/// ```
+pub struct AsyncSupervisor {
+    /// State and verification steps shared with the `Supervisor`
+    core: SupervisorCore,
+    /// Reporter of fork evidence
+    evidence_reporter: Box<dyn AsyncEvidenceReporter>,
+}
+
+impl std::fmt::Debug for AsyncSupervisor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncSupervisor")
+            .field("peers", &self.core.peers)
+            .finish()
+    }
+}
+
+// Ensure the `AsyncSupervisor` can be sent across thread boundaries, eg. to be shared by tasks.
+static_assertions::assert_impl_all!(AsyncSupervisor: Send);
+
+impl AsyncSupervisor {
+    /// Constructs a new supervisor from the given list of peers, fork detector and evidence
+    /// reporter.
+    pub fn new(
+        peers: PeerList<Instance>,
+        fork_detector: impl ForkDetector + 'static,
+        evidence_reporter: impl AsyncEvidenceReporter + 'static,
+    ) -> Self {
+        Self {
+            core: SupervisorCore::new(peers, Box::new(fork_detector)),
+            evidence_reporter: Box::new(evidence_reporter),
+        }
+    }
+
+    /// Only trust a light block once the given number of providers, including the primary,
+    /// returned matching light blocks, see `Supervisor::with_quorum`.
+    pub fn with_quorum(mut self, quorum: usize) -> Self {
+        self.core.quorum = quorum;
+        self
+    }
+
+    /// Save the state of the supervisor at the given path after every verification, resuming
+    /// from the state previously saved there, if any.
+    pub fn with_state_path(mut self, path: impl Into<PathBuf>) -> Result<Self, Error> {
+        self.core.restore(path.into())?;
+        Ok(self)
+    }
+
+    /// Get the runtime state of the supervisor.
+    pub fn state(&self) -> SupervisorState {
+        self.core.state()
+    }
+
+    /// Report the divergences of witnesses, the trusted heights and the size of the light store
+    /// of the primary to the given recorder, eg. a `PrometheusMetrics` with the
+    /// `metrics-prometheus` feature.
+    pub fn with_metrics_recorder(mut self, recorder: impl MetricsRecorder + 'static) -> Self {
+        self.core.metrics = Some(Recorder(std::sync::Arc::new(recorder)));
+        self
+    }
+
+    /// Prune the light stores of the instances with the given pruner after every successful
+    /// verification.
+    pub fn with_pruner(mut self, pruner: Pruner) -> Self {
+        self.core.pruner = Some(pruner);
+        self
+    }
+
+    /// Renew the trusted state with the given renewal whenever `renew` is called.
+    pub fn with_renewal(mut self, renewal: Renewal) -> Self {
+        self.core.renewal = Some(renewal);
+        self
+    }
+
+    /// Renew the trusted state by verifying the highest block if the latest trusted block
+    /// expires within the margin of the renewal policy, warning the hooks of the renewal if
+    /// that fails.
+    ///
+    /// Meant to be called every `check_interval` of the renewal policy, eg. from a task driven
+    /// by a tokio interval. Returns the new latest trusted block, if renewed.
+    pub async fn renew(&mut self) -> Option<LightBlock> {
+        let (latest_trusted, expires_at) = self.core.renewal_due()?;
+
+        match self.verify_to_highest().await {
+            Ok(light_block) => Some(light_block),
+            Err(e) => {
+                if let Some(renewal) = &self.core.renewal {
+                    renewal.warn(&latest_trusted, expires_at, e.kind());
+                }
+                None
+            }
+        }
+    }
+
+    /// Get the latest trusted block.
+    pub fn latest_trusted(&self) -> Option<LightBlock> {
+        self.core.latest_trusted()
+    }
+
+    /// Return latest trusted status summary.
+    pub fn latest_status(&self) -> LatestStatus {
+        self.core.latest_status()
+    }
+
+    /// Add the given instance as a new witness. Returns `false` if the peer was already listed.
+    pub fn add_witness(&mut self, peer_id: PeerId, instance: Instance) -> bool {
+        self.core.add_witness(peer_id, instance)
+    }
+
+    /// Remove the given witness. Returns `false` if the peer was not a witness.
+    pub fn remove_witness(&mut self, peer_id: PeerId) -> bool {
+        self.core.remove_witness(peer_id)
+    }
+
+    /// Verify to the highest block.
+    pub async fn verify_to_highest(&mut self) -> Result<LightBlock, Error> {
+        let result = self.verify(None).await;
+        self.core.save_state()?;
+        result
+    }
+
+    /// Verify to the block at the given height.
+    pub async fn verify_to_target(&mut self, height: Height) -> Result<LightBlock, Error> {
+        let result = self.verify(Some(height)).await;
+        self.core.save_state()?;
+        result
+    }
+
+    /// Verify either to the latest block (if `height == None`) or to a given block (if `height ==
+    /// Some(height)`).
+    async fn verify(&mut self, height: Option<Height>) -> Result<LightBlock, Error> {
+        // Retry reporting the evidence of forks which could not be reported so far
+        self.report_pending_forks().await;
+
+        loop {
+            let primary = self.core.peers.primary_mut();
+
+            // Perform light client core verification for the given height (or highest).
+            let verdict = match height {
+                None => {
+                    primary
+                        .light_client
+                        .verify_to_highest_async(&mut primary.state)
+                        .await
+                }
+                Some(height) => {
+                    primary
+                        .light_client
+                        .verify_to_target_async(height, &mut primary.state)
+                        .await
+                }
+            };
+
+            let verified_block = match self.core.check_verdict(verdict)? {
+                Some(verified_block) => verified_block,
+                // Verification failed, continue with the new primary
+                None => continue,
+            };
+
+            // Perform fork detection with the highest verified block and the trusted block.
+            let trusted_block = self.core.trusted_block()?;
+            let detection = self
+                .core
+                .detect_forks_async(&verified_block, &trusted_block)
+                .await?;
+
+            match self.core.conclude(verified_block, detection)? {
+                // No fork detected, exiting
+                Conclusion::Trusted(verified_block) => return Ok(verified_block),
+                // There was a fork or a faulty peer
+                Conclusion::Detected(forked) => {
+                    self.report_pending_forks().await;
+
+                    if !forked.is_empty() {
+                        // Fork detected, exiting
+                        bail!(ErrorKind::ForkDetected(forked))
+                    }
+
+                    // If there were no hard forks, perform verification again
+                }
+            }
+        }
+    }
+
+    /// Report the evidence of the pending forks, keeping the reports which could not be
+    /// delivered pending until the next verification.
+    async fn report_pending_forks(&mut self) {
+        for (report, evidence) in self.core.pending_reports() {
+            let result = self
+                .evidence_reporter
+                .report_async(evidence, report.provider)
+                .await;
+            self.core.record_report(report, result);
+        }
+
+        self.core.clear_reported_forks();
+    }
+}
@@ -0,0 +1,69 @@
+//! Structured record of the verification decisions of the light client, which can be serialized
+//! and exported for audits and bug reports.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::verifier::Verdict,
+    light_client::Options,
+    operations::voting_power::VotingPowerTally,
+    predicates::errors::VerificationError,
+    types::{Height, LightBlock, Time},
+};
+
+/// Verification decisions recorded by the light client, in the order they were made.
+pub type AuditLog = Vec<VerificationRecord>;
+
+/// Outcome of the verification of an untrusted light block against a trusted one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Outcome {
+    /// All predicates hold, the untrusted light block is verified
+    Verified,
+    /// The untrusted light block is valid, but not signed by enough of the trusted validators,
+    /// which engages bisection
+    NotEnoughTrust(VerificationError),
+    /// The predicate raising the given error does not hold, while the ones evaluated before it
+    /// do, and the untrusted light block is invalid
+    Invalid(VerificationError),
+}
+
+impl From<&Verdict> for Outcome {
+    fn from(verdict: &Verdict) -> Self {
+        match verdict {
+            Verdict::Success => Self::Verified,
+            Verdict::NotEnoughTrust(e) => Self::NotEnoughTrust(e.clone()),
+            Verdict::Invalid(e) => Self::Invalid(e.clone()),
+        }
+    }
+}
+
+/// Verification of an untrusted light block against a trusted one, with all its inputs.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VerificationRecord {
+    /// Height of the block the light client was asked to verify
+    pub target_height: Height,
+    /// Light block being verified
+    pub untrusted: LightBlock,
+    /// Light block it was verified against
+    pub trusted: LightBlock,
+    /// Verification parameters
+    pub options: Options,
+    /// Time of the verification
+    pub now: Time,
+    /// Outcome of the verification
+    pub outcome: Outcome,
+}
+
+impl VerificationRecord {
+    /// Voting power tally of the signers which led to the outcome, if verification failed
+    /// for lack of voting power.
+    pub fn tally(&self) -> Option<&VotingPowerTally> {
+        match &self.outcome {
+            Outcome::NotEnoughTrust(VerificationError::NotEnoughTrust(tally))
+            | Outcome::Invalid(VerificationError::NotEnoughTrust(tally))
+            | Outcome::NotEnoughTrust(VerificationError::InsufficientSignersOverlap(tally))
+            | Outcome::Invalid(VerificationError::InsufficientSignersOverlap(tally)) => Some(tally),
+            _ => None,
+        }
+    }
+}
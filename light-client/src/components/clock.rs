@@ -1,4 +1,7 @@
-//! Provides an interface and a default implementation of the `Clock` component
+//! Provides an interface and default implementations of the `Clock` component
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use crate::types::Time;
 
@@ -8,11 +11,82 @@ pub trait Clock: Send {
     fn now(&self) -> Time;
 }
 
+impl<F: Send> Clock for F
+where
+    F: Fn() -> Time,
+{
+    fn now(&self) -> Time {
+        self()
+    }
+}
+
 /// Provides the current wall clock time.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub struct SystemClock;
 impl Clock for SystemClock {
     fn now(&self) -> Time {
         Time::now()
     }
 }
+
+/// Always provides the same time, eg. for deterministic tests.
+#[derive(Copy, Clone, Debug)]
+pub struct FixedClock(pub Time);
+impl Clock for FixedClock {
+    fn now(&self) -> Time {
+        self.0
+    }
+}
+
+/// Provides a time which only changes when set or advanced manually.
+///
+/// Clones share the same time, so that a clone kept by a test can advance the time seen by a
+/// light client.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Arc<Mutex<Time>>,
+}
+
+impl ManualClock {
+    /// Create a new clock starting at the given time
+    pub fn new(now: Time) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    /// Set the current time
+    pub fn set(&self, now: Time) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// Advance the current time by the given duration
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now = *now + duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Time {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clock_is_shared_by_clones() {
+        let start = Time::unix_epoch();
+        let clock = ManualClock::new(start);
+        let light_client_clock: Box<dyn Clock> = Box::new(clock.clone());
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(light_client_clock.now(), start + Duration::from_secs(10));
+
+        clock.set(start);
+        assert_eq!(light_client_clock.now(), start);
+    }
+}
@@ -1,23 +1,32 @@
 //! Provides an interface and a default implementation of the `Io` component
 
-use std::collections::HashMap;
 use std::time::Duration;
 
-use contracts::{contract_trait, post, pre};
+use contracts::{contract_trait, post};
+use futures::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use tendermint::{
-    block::signed_header::SignedHeader as TMSignedHeader, validator::Set as TMValidatorSet,
-};
-
 use tendermint_rpc as rpc;
 
-use crate::{
-    bail,
-    types::{Height, LightBlock, PeerId},
+use crate::types::{Height, LightBlock, PeerId};
+
+#[cfg(feature = "rpc-client")]
+use {
+    crate::{
+        bail,
+        operations::{Hasher, ProdHasher, ValidatorSetCache},
+        types::Hash,
+    },
+    futures::future::FutureExt,
+    std::collections::HashMap,
+    tendermint::{
+        block::signed_header::SignedHeader as TMSignedHeader, validator::Set as TMValidatorSet,
+    },
 };
 
+pub mod replay;
+
 /// Type for selecting either a specific height or the latest one
 pub enum AtHeight {
     /// A specific height
@@ -36,6 +45,12 @@ impl From<Height> for AtHeight {
     }
 }
 
+impl From<Option<Height>> for AtHeight {
+    fn from(height: Option<Height>) -> Self {
+        height.map_or(Self::Highest, Self::At)
+    }
+}
+
 /// I/O errors
 #[derive(Clone, Debug, Error, PartialEq, Serialize, Deserialize)]
 pub enum IoError {
@@ -50,12 +65,20 @@ pub enum IoError {
     /// The request timed out.
     #[error("request to peer {0} timed out")]
     Timeout(PeerId),
+
+    /// No response from the given peer at the given height was recorded.
+    #[error("no response from peer {0} recorded at height {1}")]
+    NotRecorded(PeerId, String),
 }
 
 impl IoError {
     /// Whether this error means that a timeout occured when querying a node.
     pub fn is_timeout(&self) -> bool {
-        matches!(self, Self::Timeout(_))
+        match self {
+            Self::Timeout(_) => true,
+            Self::IoError(e) => e.code() == rpc::error::Code::TimeoutError,
+            _ => false,
+        }
     }
 }
 
@@ -69,6 +92,19 @@ pub trait Io: Send {
     /// - The provider of the returned light block matches the given peer [LCV-IO-POST-PROVIDER]
     #[post(ret.as_ref().map(|lb| lb.provider == peer).unwrap_or(true))]
     fn fetch_light_block(&self, peer: PeerId, height: AtHeight) -> Result<LightBlock, IoError>;
+
+    /// Fetch a light block at the given height from the peer with the given peer ID,
+    /// failing with `IoError::Timeout` if it takes longer than the given timeout.
+    ///
+    /// Implementations which cannot time out requests fall back to `fetch_light_block`.
+    fn fetch_light_block_with_timeout(
+        &self,
+        peer: PeerId,
+        height: AtHeight,
+        _timeout: Option<Duration>,
+    ) -> Result<LightBlock, IoError> {
+        self.fetch_light_block(peer, height)
+    }
 }
 
 #[contract_trait]
@@ -81,29 +117,93 @@ where
     }
 }
 
+/// Interface for fetching light blocks from a full node asynchronously, typically via the RPC
+/// client, without blocking the current thread.
+///
+/// Used by light clients constructed with `LightClient::new_async`.
+pub trait AsyncIo: Send + Sync {
+    /// Fetch a light block at the given height from the peer with the given peer ID.
+    ///
+    /// ## Postcondition
+    /// - The provider of the returned light block matches the given peer [LCV-IO-POST-PROVIDER]
+    fn fetch_light_block_async(
+        &self,
+        peer: PeerId,
+        height: AtHeight,
+    ) -> BoxFuture<'_, Result<LightBlock, IoError>>;
+
+    /// Fetch a light block at the given height from the peer with the given peer ID,
+    /// failing with `IoError::Timeout` if it takes longer than the given timeout.
+    ///
+    /// Implementations which cannot time out requests fall back to `fetch_light_block_async`.
+    fn fetch_light_block_with_timeout_async(
+        &self,
+        peer: PeerId,
+        height: AtHeight,
+        _timeout: Option<Duration>,
+    ) -> BoxFuture<'_, Result<LightBlock, IoError>> {
+        self.fetch_light_block_async(peer, height)
+    }
+}
+
 /// Production implementation of the Io component, which fetches
 /// light blocks from full nodes via RPC.
+#[cfg(feature = "rpc-client")]
 #[derive(Clone, Debug)]
 pub struct ProdIo {
     peer_map: HashMap<PeerId, tendermint::net::Address>,
     timeout: Option<Duration>,
+    validator_sets: ValidatorSetCache,
 }
 
+#[cfg(feature = "rpc-client")]
 #[contract_trait]
 impl Io for ProdIo {
     fn fetch_light_block(&self, peer: PeerId, height: AtHeight) -> Result<LightBlock, IoError> {
-        let signed_header = self.fetch_signed_header(peer, height)?;
-        let height = signed_header.header.height;
+        self.fetch_light_block_with_timeout(peer, height, None)
+    }
 
-        let validator_set = self.fetch_validator_set(peer, height.into())?;
-        let next_validator_set = self.fetch_validator_set(peer, height.increment().into())?;
+    fn fetch_light_block_with_timeout(
+        &self,
+        peer: PeerId,
+        height: AtHeight,
+        timeout: Option<Duration>,
+    ) -> Result<LightBlock, IoError> {
+        block_on(self.fetch(peer, height), peer, self.timeout_or(timeout))?
+    }
+}
 
-        let light_block = LightBlock::new(signed_header, validator_set, next_validator_set, peer);
+#[cfg(feature = "rpc-client")]
+impl AsyncIo for ProdIo {
+    fn fetch_light_block_async(
+        &self,
+        peer: PeerId,
+        height: AtHeight,
+    ) -> BoxFuture<'_, Result<LightBlock, IoError>> {
+        self.fetch_light_block_with_timeout_async(peer, height, None)
+    }
 
-        Ok(light_block)
+    fn fetch_light_block_with_timeout_async(
+        &self,
+        peer: PeerId,
+        height: AtHeight,
+        timeout: Option<Duration>,
+    ) -> BoxFuture<'_, Result<LightBlock, IoError>> {
+        let fetch = self.fetch(peer, height);
+
+        match self.timeout_or(timeout) {
+            Some(timeout) => async move {
+                tokio::time::timeout(timeout, fetch)
+                    .await
+                    .map_err(|_| IoError::Timeout(peer))?
+            }
+            .boxed(),
+            None => fetch.boxed(),
+        }
     }
 }
 
+#[cfg(feature = "rpc-client")]
 impl ProdIo {
     /// Constructs a new ProdIo component.
     ///
@@ -112,27 +212,59 @@ impl ProdIo {
         peer_map: HashMap<PeerId, tendermint::net::Address>,
         timeout: Option<Duration>,
     ) -> Self {
-        Self { peer_map, timeout }
+        Self {
+            peer_map,
+            timeout,
+            validator_sets: ValidatorSetCache::default(),
+        }
+    }
+
+    /// Cache the fetched validator sets in the given cache, eg. to share it with the
+    /// `CachingHasher` of the verifier, instead of a cache of its own.
+    pub fn with_validator_set_cache(mut self, cache: ValidatorSetCache) -> Self {
+        self.validator_sets = cache;
+        self
+    }
+
+    /// The shortest of the timeout of this component and the given one, if any.
+    fn timeout_or(&self, timeout: Option<Duration>) -> Option<Duration> {
+        match (self.timeout, timeout) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
     }
 
-    #[pre(self.peer_map.contains_key(&peer))]
-    fn fetch_signed_header(
+    async fn fetch(&self, peer: PeerId, height: AtHeight) -> Result<LightBlock, IoError> {
+        let signed_header = self.fetch_signed_header(peer, height).await?;
+        let height = signed_header.header.height;
+
+        let validator_set = self
+            .fetch_validator_set_cached(peer, height, signed_header.header.validators_hash)
+            .await?;
+        let next_validator_set = self
+            .fetch_validator_set_cached(
+                peer,
+                height.increment(),
+                signed_header.header.next_validators_hash,
+            )
+            .await?;
+
+        let light_block = LightBlock::new(signed_header, validator_set, next_validator_set, peer);
+
+        Ok(light_block)
+    }
+
+    async fn fetch_signed_header(
         &self,
         peer: PeerId,
         height: AtHeight,
     ) -> Result<TMSignedHeader, IoError> {
         let rpc_client = self.rpc_client_for(peer);
 
-        let res = block_on(
-            async {
-                match height {
-                    AtHeight::Highest => rpc_client.latest_commit().await,
-                    AtHeight::At(height) => rpc_client.commit(height).await,
-                }
-            },
-            peer,
-            self.timeout,
-        )?;
+        let res = match height {
+            AtHeight::Highest => rpc_client.latest_commit().await,
+            AtHeight::At(height) => rpc_client.commit(height).await,
+        };
 
         match res {
             Ok(response) => Ok(response.signed_header),
@@ -140,8 +272,30 @@ impl ProdIo {
         }
     }
 
-    #[pre(self.peer_map.contains_key(&peer))]
-    fn fetch_validator_set(
+    /// Fetch the validator set at the given height, unless the validator set with the given
+    /// hash, as found in the header, was already fetched from the given peer at that height.
+    async fn fetch_validator_set_cached(
+        &self,
+        peer: PeerId,
+        height: Height,
+        hash: Hash,
+    ) -> Result<TMValidatorSet, IoError> {
+        if let Some(validator_set) = self.validator_sets.get_fetched(&hash, peer, height) {
+            return Ok(validator_set);
+        }
+
+        let validator_set = self.fetch_validator_set(peer, height.into()).await?;
+
+        // Cache the validator set under its actual hash, which the verifier will check against
+        // the one found in the header.
+        let hash = ProdHasher.hash_validator_set(&validator_set);
+        self.validator_sets
+            .insert_fetched(hash, peer, height, validator_set.clone());
+
+        Ok(validator_set)
+    }
+
+    async fn fetch_validator_set(
         &self,
         peer: PeerId,
         height: AtHeight,
@@ -153,16 +307,12 @@ impl ProdIo {
             AtHeight::At(height) => height,
         };
 
-        let res = block_on(
-            self.rpc_client_for(peer).validators(height),
-            peer,
-            self.timeout,
-        )?;
-
-        match res {
-            Ok(response) => Ok(TMValidatorSet::new(response.validators)),
-            Err(err) => Err(IoError::IoError(err)),
-        }
+        // Follow pagination, lest the validator sets of large chains be truncated and not
+        // match the hash found in their header
+        self.rpc_client_for(peer)
+            .all_validators(height)
+            .await
+            .map_err(IoError::IoError)
     }
 
     // FIXME: Cannot enable precondition because of "autoref lifetime" issue
@@ -173,7 +323,8 @@ impl ProdIo {
     }
 }
 
-fn block_on<F: std::future::Future>(
+#[cfg(feature = "rpc-client")]
+pub(crate) fn block_on<F: std::future::Future>(
     f: F,
     peer: PeerId,
     timeout: Option<Duration>,
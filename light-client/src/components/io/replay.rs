@@ -0,0 +1,285 @@
+//! Recording of the responses of providers, and their hermetic replay, eg. to reproduce
+//! deterministically a verification failure reported from production.
+//!
+//! Wrap the `Io` component of a light client in a [`RecordingIo`] to capture the responses
+//! of its providers, save the resulting [`Recording`] alongside the bug report, and later
+//! replay it with a [`ReplayIo`] and a [`FixedClock`](crate::components::clock::FixedClock)
+//! set to the time of the recording.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use contracts::contract_trait;
+use futures::future::{self, BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+
+use super::{AsyncIo, AtHeight, Io, IoError};
+use crate::{
+    errors::{Error, ErrorKind},
+    types::{Height, LightBlock, PeerId, Time},
+};
+
+/// Response of a provider to a request for a light block.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    /// Provider the light block was requested from
+    pub peer: PeerId,
+    /// Requested height, or `None` for the latest height
+    pub height: Option<Height>,
+    /// Light block returned by the provider, or the error it failed with
+    pub response: Result<LightBlock, IoError>,
+}
+
+/// Responses of providers, in the order they were received.
+///
+/// Recordings are saved as JSON, so that they can be inspected and trimmed by hand
+/// before being attached to a bug report.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    /// Time at which the recording started
+    pub time: Time,
+    /// Recorded responses
+    pub responses: Vec<RecordedResponse>,
+}
+
+impl Recording {
+    /// Create an empty recording started at the given time
+    pub fn new(time: Time) -> Self {
+        Self {
+            time,
+            responses: Vec::new(),
+        }
+    }
+
+    /// Load the recording saved at the given path
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let bytes = fs::read(path).map_err(|e| ErrorKind::Store.context(e))?;
+        let recording = serde_json::from_slice(&bytes).map_err(|e| ErrorKind::Store.context(e))?;
+        Ok(recording)
+    }
+
+    /// Save the recording at the given path
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let bytes = serde_json::to_vec_pretty(self).map_err(|e| ErrorKind::Store.context(e))?;
+        fs::write(path, bytes).map_err(|e| ErrorKind::Store.context(e))?;
+        Ok(())
+    }
+}
+
+/// `Io` component recording the responses of the wrapped `Io` component.
+///
+/// Clones share the same recording, so that a clone kept aside can save the responses
+/// received by a light client.
+#[derive(Clone, Debug)]
+pub struct RecordingIo<I> {
+    io: I,
+    recording: Arc<Mutex<Recording>>,
+}
+
+impl<I> RecordingIo<I> {
+    /// Record the responses of the given `Io` component, starting now
+    pub fn new(io: I) -> Self {
+        Self::starting_at(io, Time::now())
+    }
+
+    /// Record the responses of the given `Io` component, starting at the given time
+    pub fn starting_at(io: I, time: Time) -> Self {
+        Self {
+            io,
+            recording: Arc::new(Mutex::new(Recording::new(time))),
+        }
+    }
+
+    /// The responses recorded so far
+    pub fn recording(&self) -> Recording {
+        self.recording.lock().unwrap().clone()
+    }
+
+    fn record(&self, peer: PeerId, height: Option<Height>, response: &Result<LightBlock, IoError>) {
+        self.recording
+            .lock()
+            .unwrap()
+            .responses
+            .push(RecordedResponse {
+                peer,
+                height,
+                response: response.clone(),
+            });
+    }
+}
+
+#[contract_trait]
+impl<I: Io> Io for RecordingIo<I> {
+    fn fetch_light_block(&self, peer: PeerId, height: AtHeight) -> Result<LightBlock, IoError> {
+        self.fetch_light_block_with_timeout(peer, height, None)
+    }
+
+    fn fetch_light_block_with_timeout(
+        &self,
+        peer: PeerId,
+        height: AtHeight,
+        timeout: Option<Duration>,
+    ) -> Result<LightBlock, IoError> {
+        let height = requested_height(&height);
+        let response = self
+            .io
+            .fetch_light_block_with_timeout(peer, height.into(), timeout);
+        self.record(peer, height, &response);
+        response
+    }
+}
+
+impl<I: AsyncIo> AsyncIo for RecordingIo<I> {
+    fn fetch_light_block_async(
+        &self,
+        peer: PeerId,
+        height: AtHeight,
+    ) -> BoxFuture<'_, Result<LightBlock, IoError>> {
+        self.fetch_light_block_with_timeout_async(peer, height, None)
+    }
+
+    fn fetch_light_block_with_timeout_async(
+        &self,
+        peer: PeerId,
+        height: AtHeight,
+        timeout: Option<Duration>,
+    ) -> BoxFuture<'_, Result<LightBlock, IoError>> {
+        let height = requested_height(&height);
+
+        async move {
+            let response = self
+                .io
+                .fetch_light_block_with_timeout_async(peer, height.into(), timeout)
+                .await;
+            self.record(peer, height, &response);
+            response
+        }
+        .boxed()
+    }
+}
+
+/// `Io` component replaying the responses of a [`Recording`], without any network access.
+///
+/// The responses to the same request are replayed in the order they were recorded, the last
+/// one being repeated once they are exhausted. Requests without any recorded response fail
+/// with [`IoError::NotRecorded`].
+#[derive(Clone, Debug)]
+pub struct ReplayIo {
+    responses: HashMap<(PeerId, Option<Height>), Vec<Result<LightBlock, IoError>>>,
+    replayed: Arc<Mutex<HashMap<(PeerId, Option<Height>), usize>>>,
+}
+
+impl ReplayIo {
+    /// Replay the responses of the given recording
+    pub fn new(recording: Recording) -> Self {
+        let mut responses = HashMap::<_, Vec<_>>::new();
+
+        for recorded in recording.responses {
+            responses
+                .entry((recorded.peer, recorded.height))
+                .or_default()
+                .push(recorded.response);
+        }
+
+        Self {
+            responses,
+            replayed: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Replay the responses of the recording saved at the given path
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Recording::load(path).map(Self::new)
+    }
+
+    fn replay(&self, peer: PeerId, height: Option<Height>) -> Result<LightBlock, IoError> {
+        let key = (peer, height);
+
+        let responses = self.responses.get(&key).ok_or_else(|| {
+            let height = height.map_or_else(|| "latest".to_string(), |h| h.to_string());
+            IoError::NotRecorded(peer, height)
+        })?;
+
+        let mut replayed = self.replayed.lock().unwrap();
+        let index = replayed.entry(key).or_default();
+        let response = responses[(*index).min(responses.len() - 1)].clone();
+        *index += 1;
+
+        response
+    }
+}
+
+#[contract_trait]
+impl Io for ReplayIo {
+    fn fetch_light_block(&self, peer: PeerId, height: AtHeight) -> Result<LightBlock, IoError> {
+        self.replay(peer, requested_height(&height))
+    }
+}
+
+impl AsyncIo for ReplayIo {
+    fn fetch_light_block_async(
+        &self,
+        peer: PeerId,
+        height: AtHeight,
+    ) -> BoxFuture<'_, Result<LightBlock, IoError>> {
+        future::ready(self.replay(peer, requested_height(&height))).boxed()
+    }
+}
+
+fn requested_height(height: &AtHeight) -> Option<Height> {
+    match height {
+        AtHeight::At(height) => Some(*height),
+        AtHeight::Highest => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn peer() -> PeerId {
+        "BADFADAD0BEFEEDC0C0ADEADBEEFC0FFEEFACADE".parse().unwrap()
+    }
+
+    fn response(n: usize) -> Result<LightBlock, IoError> {
+        Err(IoError::InvalidHeight(n.to_string()))
+    }
+
+    #[test]
+    fn replays_recorded_responses_in_order() {
+        let calls = AtomicUsize::new(0);
+        let io = RecordingIo::starting_at(
+            move |_peer: PeerId, _height: AtHeight| response(calls.fetch_add(1, Ordering::SeqCst)),
+            Time::unix_epoch(),
+        );
+
+        let height = Height::from(1_u64);
+        let _ = io.fetch_light_block(peer(), AtHeight::At(height));
+        let _ = io.fetch_light_block(peer(), AtHeight::At(height));
+        let _ = io.fetch_light_block(peer(), AtHeight::Highest);
+
+        let recording = io.recording();
+        assert_eq!(recording.time, Time::unix_epoch());
+        assert_eq!(recording.responses.len(), 3);
+
+        let replay = ReplayIo::new(recording);
+
+        let at_height = || replay.fetch_light_block(peer(), AtHeight::At(height));
+        assert_eq!(at_height(), response(0));
+        assert_eq!(at_height(), response(1));
+        assert_eq!(at_height(), response(1));
+
+        assert_eq!(
+            replay.fetch_light_block(peer(), AtHeight::Highest),
+            response(2)
+        );
+        assert_eq!(
+            replay.fetch_light_block(peer(), AtHeight::At(Height::from(2_u64))),
+            Err(IoError::NotRecorded(peer(), "2".to_string()))
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! Provides an interface and default implementation of the `Scheduler` component
 
 use contracts::*;
+use serde::{Deserialize, Serialize};
 
 use crate::store::LightStore;
 use crate::types::Height;
@@ -74,6 +75,97 @@ pub fn basic_bisecting_schedule(
     }
 }
 
+/// Sequential scheduler which verifies every block from the latest trusted or verified one
+/// to the target, for environments which require each height to be verified.
+///
+/// ## Precondition
+/// - The light store contains at least one verified block. [LCV-SCHEDULE-PRE.1]
+///
+/// ## Postcondition
+/// - The resulting height must be valid according to `valid_schedule`. [LCV-SCHEDULE-POST.1]
+#[pre(light_store.latest_trusted_or_verified().is_some())]
+#[post(valid_schedule(ret, target_height, current_height, light_store))]
+pub fn sequential_schedule(
+    light_store: &dyn LightStore,
+    current_height: Height,
+    target_height: Height,
+) -> Height {
+    let trusted_height = light_store
+        .latest_trusted_or_verified()
+        .map(|lb| lb.height())
+        .unwrap();
+
+    if trusted_height < target_height {
+        trusted_height.increment()
+    } else {
+        target_height
+    }
+}
+
+/// Hybrid scheduler which skips from the latest trusted or verified block straight to the
+/// target, and falls back to the next height whenever skipping fails instead of bisecting.
+///
+/// ## Precondition
+/// - The light store contains at least one verified block. [LCV-SCHEDULE-PRE.1]
+///
+/// ## Postcondition
+/// - The resulting height must be valid according to `valid_schedule`. [LCV-SCHEDULE-POST.1]
+#[pre(light_store.latest_trusted_or_verified().is_some())]
+#[post(valid_schedule(ret, target_height, current_height, light_store))]
+pub fn hybrid_schedule(
+    light_store: &dyn LightStore,
+    current_height: Height,
+    target_height: Height,
+) -> Height {
+    let trusted_height = light_store
+        .latest_trusted_or_verified()
+        .map(|lb| lb.height())
+        .unwrap();
+
+    if trusted_height == current_height {
+        // Try to skip to the target height from the newly verified block
+        target_height
+    } else {
+        // Skipping failed, verify the next height instead
+        sequential_schedule(light_store, current_height, target_height)
+    }
+}
+
+/// Strategy used to verify a target block from a trusted one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerificationStrategy {
+    /// Skip to the target block and bisect when it cannot be trusted yet,
+    /// see `basic_bisecting_schedule`.
+    Skipping,
+    /// Verify every block up to the target, see `sequential_schedule`.
+    Sequential,
+    /// Skip to the target block and verify the next block when it cannot be trusted yet,
+    /// see `hybrid_schedule`.
+    Hybrid,
+}
+
+impl Default for VerificationStrategy {
+    fn default() -> Self {
+        Self::Skipping
+    }
+}
+
+#[contract_trait]
+impl Scheduler for VerificationStrategy {
+    fn schedule(
+        &self,
+        light_store: &dyn LightStore,
+        current_height: Height,
+        target_height: Height,
+    ) -> Height {
+        match self {
+            Self::Skipping => basic_bisecting_schedule(light_store, current_height, target_height),
+            Self::Sequential => sequential_schedule(light_store, current_height, target_height),
+            Self::Hybrid => hybrid_schedule(light_store, current_height, target_height),
+        }
+    }
+}
+
 /// Checks whether the given `scheduled_height` is a valid schedule according to the
 /// following specification.
 ///
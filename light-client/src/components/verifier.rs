@@ -2,15 +2,17 @@
 
 use crate::predicates as preds;
 use crate::{
-    errors::ErrorExt,
+    errors::{Error, ErrorExt},
     light_client::Options,
     operations::{
-        CommitValidator, Hasher, ProdCommitValidator, ProdHasher, ProdVotingPowerCalculator,
-        VotingPowerCalculator,
+        CachingHasher, CommitValidator, Hasher, ProdCommitValidator, ProdHasher,
+        ProdVotingPowerCalculator, VotingPowerCalculator,
     },
-    types::{LightBlock, Time},
+    types::{LightBlock, SignedHeader, Time, TrustThreshold, ValidatorSet},
+    upgrades::UpgradeSchedule,
 };
 use preds::{errors::VerificationError, ProdPredicates, VerificationPredicates};
+use tendermint::trust_threshold::ThresholdSchedule;
 
 /// Represents the result of the verification performed by the
 /// verifier component.
@@ -35,6 +37,57 @@ impl From<Result<(), VerificationError>> for Verdict {
     }
 }
 
+/// Verify the given untrusted signed header and validator set against the given trusted light
+/// block, with the production predicates, without any supervisor, light store nor I/O component.
+///
+/// As the next validators of the untrusted block are not given, the next validators hash of its
+/// header is not checked, and has to be checked against the next validator set once known before
+/// trusting blocks on top of the untrusted one.
+///
+/// The chain-id of the untrusted header must be the trusted one, or the one of the latest of the
+/// given upgrades between them.
+pub fn verify_header(
+    trusted: &LightBlock,
+    untrusted_header: &SignedHeader,
+    untrusted_validators: &ValidatorSet,
+    upgrades: &UpgradeSchedule,
+    options: &Options,
+    now: Time,
+) -> Verdict {
+    let untrusted = LightBlock::new(
+        untrusted_header.clone(),
+        untrusted_validators.clone(),
+        untrusted_validators.clone(),
+        trusted.provider,
+    );
+
+    preds::verify(
+        &HeaderPredicates,
+        &ProdVotingPowerCalculator::default(),
+        &ProdCommitValidator::default(),
+        &ProdHasher::default(),
+        upgrades,
+        trusted,
+        &untrusted,
+        options,
+        now,
+    )
+    .into()
+}
+
+/// Production predicates, except for the next validators which are unknown in `verify_header`.
+struct HeaderPredicates;
+
+impl VerificationPredicates for HeaderPredicates {
+    fn next_validators_match(
+        &self,
+        _light_block: &LightBlock,
+        _hasher: &dyn Hasher,
+    ) -> Result<(), VerificationError> {
+        Ok(())
+    }
+}
+
 /// The verifier checks:
 ///
 /// a) whether a given untrusted light block is valid, and
@@ -63,12 +116,18 @@ pub trait Verifier: Send {
 /// - A commit validator
 /// - A header hasher
 ///
-/// For regular use, one can construct a standard implementation with `ProdVerifier::default()`.
+/// For regular use, one can construct a standard implementation with `ProdVerifier::default()`,
+/// which caches the hashes of validator sets across verifications.
+///
+/// Chains which change their chain-id in coordinated upgrades must give the schedule of these
+/// upgrades with `with_upgrade_schedule`, as light blocks whose chain-id differs from the trusted
+/// one are otherwise invalid.
 pub struct ProdVerifier {
     predicates: Box<dyn VerificationPredicates>,
     voting_power_calculator: Box<dyn VotingPowerCalculator>,
     commit_validator: Box<dyn CommitValidator>,
     hasher: Box<dyn Hasher>,
+    upgrades: UpgradeSchedule,
 }
 
 impl ProdVerifier {
@@ -84,8 +143,53 @@ impl ProdVerifier {
             voting_power_calculator: Box::new(voting_power_calculator),
             commit_validator: Box::new(commit_validator),
             hasher: Box::new(hasher),
+            upgrades: UpgradeSchedule::default(),
         }
     }
+
+    /// Expect the chain-ids of the given upgrades in the light blocks above their heights.
+    pub fn with_upgrade_schedule(mut self, upgrades: UpgradeSchedule) -> Self {
+        self.upgrades = upgrades;
+        self
+    }
+
+    /// Check light blocks with the given predicates instead of the current ones.
+    pub fn with_predicates(mut self, predicates: impl VerificationPredicates + 'static) -> Self {
+        self.predicates = Box::new(predicates);
+        self
+    }
+
+    /// Compute voting powers with the given calculator instead of the current one, eg. a
+    /// `ProdVotingPowerCalculator` with a commit threshold schedule.
+    pub fn with_voting_power_calculator(
+        mut self,
+        voting_power_calculator: impl VotingPowerCalculator + 'static,
+    ) -> Self {
+        self.voting_power_calculator = Box::new(voting_power_calculator);
+        self
+    }
+
+    /// Constructs a standard verifier for chains committing blocks once signed by more than
+    /// the given share of the voting power, eg. `TrustThreshold::ONE_HALF`.
+    ///
+    /// Fails with `ErrorKind::InvalidCommitThreshold` unless the threshold is between
+    /// 1/3 and 1.
+    pub fn with_commit_threshold(commit_threshold: TrustThreshold) -> Result<Self, Error> {
+        Ok(Self::default()
+            .with_voting_power_calculator(ProdVotingPowerCalculator::new(commit_threshold)?))
+    }
+
+    /// Constructs a standard verifier for chains committing blocks once signed by more than
+    /// the share of the voting power scheduled at their height, eg. switching from 2/3 to 1/2
+    /// at an upgrade height.
+    pub fn with_commit_threshold_schedule(commit_thresholds: ThresholdSchedule) -> Self {
+        Self::new(
+            ProdPredicates::default(),
+            ProdVotingPowerCalculator::with_schedule(commit_thresholds),
+            ProdCommitValidator::default(),
+            CachingHasher::default(),
+        )
+    }
 }
 
 impl Default for ProdVerifier {
@@ -94,7 +198,7 @@ impl Default for ProdVerifier {
             ProdPredicates::default(),
             ProdVotingPowerCalculator::default(),
             ProdCommitValidator::default(),
-            ProdHasher::default(),
+            CachingHasher::default(),
         )
     }
 }
@@ -112,6 +216,7 @@ impl Verifier for ProdVerifier {
             &*self.voting_power_calculator,
             &*self.commit_validator,
             &*self.hasher,
+            &self.upgrades,
             &trusted,
             &untrusted,
             options,
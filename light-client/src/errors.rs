@@ -1,6 +1,7 @@
 //! Toplevel errors raised by the light client.
 
 use std::fmt::Debug;
+use std::time::Duration;
 
 use anomaly::{BoxError, Context};
 use crossbeam_channel as crossbeam;
@@ -11,7 +12,7 @@ use crate::{
     components::io::IoError,
     light_client::Options,
     predicates::errors::VerificationError,
-    types::{Height, LightBlock, PeerId, Status},
+    types::{Height, LightBlock, PeerId, Status, TrustThreshold},
 };
 
 /// An error raised by this library
@@ -40,6 +41,15 @@ pub enum ErrorKind {
     #[error("no witness left")]
     NoWitnessLeft,
 
+    /// Fewer providers than required returned matching light blocks
+    #[error("only {matching} providers returned matching light blocks, {quorum} required")]
+    NoQuorum {
+        /// Number of providers which returned matching light blocks
+        matching: usize,
+        /// Number of providers required to return matching light blocks
+        quorum: usize,
+    },
+
     /// A fork has been detected between some peers
     #[error("fork detected peers={0:?}")]
     ForkDetected(Vec<PeerId>),
@@ -70,6 +80,24 @@ pub enum ErrorKind {
         options: Options,
     },
 
+    /// The verification to the target height required verifying more light blocks than allowed
+    #[error("verification of target at height {target_height} exceeded the maximum bisection depth ({max_depth})")]
+    BisectionDepthExceeded {
+        /// Target height
+        target_height: Height,
+        /// Maximum number of light blocks to verify
+        max_depth: usize,
+    },
+
+    /// The verification to the target height took longer than allowed
+    #[error("verification of target at height {target_height} exceeded the maximum verification time ({max_time:?})")]
+    VerificationTimeExceeded {
+        /// Target height
+        target_height: Height,
+        /// Maximum verification time
+        max_time: Duration,
+    },
+
     /// Bisection failed when reached trusted state
     #[error("bisection for target at height {0} failed when reached trusted state at height {1}")]
     BisectionFailed(Height, Height),
@@ -78,6 +106,22 @@ pub enum ErrorKind {
     #[error("invalid light block: {0}")]
     InvalidLightBlock(#[source] VerificationError),
 
+    /// An RPC response does not match the verified light block
+    #[error("response does not match verified light block: {0}")]
+    ResponseMismatch(String),
+
+    /// A commit threshold is not between 1/3 and 1
+    #[error("invalid commit threshold {0}, must be between 1/3 and 1")]
+    InvalidCommitThreshold(TrustThreshold),
+
+    /// A blocking method was called on a light client fetching light blocks asynchronously
+    #[error("light client fetches light blocks asynchronously, use its async methods instead")]
+    BlockingOnAsyncIo,
+
+    /// The operation is not supported by the implementation it was called on
+    #[error("unsupported operation: {0}")]
+    Unsupported(String),
+
     /// Internal channel disconnected
     #[error("internal channel disconnected")]
     ChannelDisconnected,
@@ -125,10 +169,10 @@ impl ErrorExt for ErrorKind {
 
     /// Whether this error means that a timeout occured when querying a node.
     fn is_timeout(&self) -> bool {
-        if let Self::Io(e) = self {
-            e.is_timeout()
-        } else {
-            false
+        match self {
+            Self::Io(e) => e.is_timeout(),
+            Self::VerificationTimeExceeded { .. } => true,
+            _ => false,
         }
     }
 }
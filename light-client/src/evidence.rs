@@ -3,10 +3,14 @@
 use crate::{components::io::IoError, types::PeerId};
 
 use tendermint::abci::transaction::Hash;
-use tendermint_rpc as rpc;
 
-use contracts::{contract_trait, pre};
-use std::collections::HashMap;
+use contracts::contract_trait;
+use futures::future::BoxFuture;
+
+#[cfg(feature = "rpc-client")]
+use {
+    contracts::pre, futures::future::FutureExt, std::collections::HashMap, tendermint_rpc as rpc,
+};
 
 pub use tendermint::evidence::Evidence;
 
@@ -18,26 +22,46 @@ pub trait EvidenceReporter: Send {
     fn report(&self, e: Evidence, peer: PeerId) -> Result<Hash, IoError>;
 }
 
+/// Interface for reporting evidence to full nodes asynchronously, typically via the RPC client,
+/// without blocking the current thread.
+pub trait AsyncEvidenceReporter: Send + Sync {
+    /// Report evidence to all connected full nodes.
+    fn report_async(&self, e: Evidence, peer: PeerId) -> BoxFuture<'_, Result<Hash, IoError>>;
+}
+
 /// Production implementation of the EvidenceReporter component, which reports evidence to full
 /// nodes via RPC.
+#[cfg(feature = "rpc-client")]
 #[derive(Clone, Debug)]
 pub struct ProdEvidenceReporter {
     peer_map: HashMap<PeerId, tendermint::net::Address>,
 }
 
+#[cfg(feature = "rpc-client")]
 #[contract_trait]
 impl EvidenceReporter for ProdEvidenceReporter {
     #[pre(self.peer_map.contains_key(&peer))]
     fn report(&self, e: Evidence, peer: PeerId) -> Result<Hash, IoError> {
-        let res = block_on(self.rpc_client_for(peer).broadcast_evidence(e));
+        block_on(self.report_async(e, peer))
+    }
+}
+
+#[cfg(feature = "rpc-client")]
+impl AsyncEvidenceReporter for ProdEvidenceReporter {
+    fn report_async(&self, e: Evidence, peer: PeerId) -> BoxFuture<'_, Result<Hash, IoError>> {
+        let rpc_client = self.rpc_client_for(peer);
 
-        match res {
-            Ok(response) => Ok(response.hash),
-            Err(err) => Err(IoError::IoError(err)),
+        async move {
+            match rpc_client.broadcast_evidence(e).await {
+                Ok(response) => Ok(response.hash),
+                Err(err) => Err(IoError::IoError(err)),
+            }
         }
+        .boxed()
     }
 }
 
+#[cfg(feature = "rpc-client")]
 impl ProdEvidenceReporter {
     /// Constructs a new ProdEvidenceReporter component.
     ///
@@ -54,6 +78,7 @@ impl ProdEvidenceReporter {
     }
 }
 
+#[cfg(feature = "rpc-client")]
 fn block_on<F: std::future::Future>(f: F) -> F::Output {
     tokio::runtime::Builder::new()
         .basic_scheduler()
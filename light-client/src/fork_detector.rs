@@ -1,5 +1,6 @@
 //! Fork detection data structures and implementation.
 
+use futures::future::{self, FutureExt, LocalBoxFuture};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -46,6 +47,21 @@ pub trait ForkDetector: Send {
         trusted_block: &LightBlock,
         witnesses: Vec<&Instance>,
     ) -> Result<ForkDetection, Error>;
+
+    /// Detect forks like `detect_forks`, without blocking the current thread on the witnesses
+    /// fetching light blocks asynchronously, eg. for an `AsyncSupervisor`.
+    ///
+    /// Defaults to `detect_forks`, which only supports witnesses fetching light blocks with a
+    /// blocking `Io` component.
+    fn detect_forks_async<'a>(
+        &'a self,
+        verified_block: &'a LightBlock,
+        trusted_block: &'a LightBlock,
+        witnesses: Vec<&'a mut Instance>,
+    ) -> LocalBoxFuture<'a, Result<ForkDetection, Error>> {
+        let witnesses = witnesses.into_iter().map(|witness| &*witness).collect();
+        future::ready(self.detect_forks(verified_block, trusted_block, witnesses)).boxed_local()
+    }
 }
 
 /// A production-ready fork detector which compares
@@ -69,6 +85,40 @@ impl ProdForkDetector {
             hasher: Box::new(hasher),
         }
     }
+
+    /// Detect whether the given witness forked from the given verified block, without blocking
+    /// the current thread. See the documentation `ProdForkDetector` for details.
+    pub async fn detect_fork_async(
+        &self,
+        verified_block: &LightBlock,
+        trusted_block: &LightBlock,
+        witness: &mut Instance,
+    ) -> Result<Option<Fork>, Error> {
+        let mut state = State::new(MemoryStore::new());
+
+        let (witness_block, _) = witness
+            .light_client
+            .get_or_fetch_block_async(verified_block.height(), &mut state)
+            .await?;
+
+        let primary_hash = self
+            .hasher
+            .hash_header(&verified_block.signed_header.header);
+        let witness_hash = self.hasher.hash_header(&witness_block.signed_header.header);
+
+        if primary_hash == witness_hash {
+            return Ok(None);
+        }
+
+        let mut state = witness_state(trusted_block, &witness_block);
+
+        let result = witness
+            .light_client
+            .verify_to_target_async(verified_block.height(), &mut state)
+            .await;
+
+        Ok(Some(classify(verified_block, witness_block, result)))
+    }
 }
 
 impl Default for ProdForkDetector {
@@ -105,40 +155,85 @@ impl ForkDetector for ProdForkDetector {
                 continue;
             }
 
-            state
-                .light_store
-                .insert(trusted_block.clone(), Status::Verified);
-
-            state
-                .light_store
-                .insert(witness_block.clone(), Status::Unverified);
+            let mut state = witness_state(trusted_block, &witness_block);
 
             let result = witness
                 .light_client
                 .verify_to_target(verified_block.height(), &mut state);
 
-            match result {
-                Ok(_) => forks.push(Fork::Forked {
-                    primary: verified_block.clone(),
-                    witness: witness_block,
-                }),
-                Err(e) if e.kind().has_expired() => {
-                    forks.push(Fork::Forked {
-                        primary: verified_block.clone(),
-                        witness: witness_block,
-                    });
-                }
-                Err(e) if e.kind().is_timeout() => {
-                    forks.push(Fork::Timeout(witness_block.provider, e.kind().clone()))
-                }
-                Err(e) => forks.push(Fork::Faulty(witness_block, e.kind().clone())),
-            }
+            forks.push(classify(verified_block, witness_block, result));
         }
 
-        if forks.is_empty() {
-            Ok(ForkDetection::NotDetected)
-        } else {
-            Ok(ForkDetection::Detected(forks))
+        Ok(detection(forks))
+    }
+
+    /// Perform fork detection without blocking the current thread. See the documentation
+    /// `ProdForkDetector` for details.
+    fn detect_forks_async<'a>(
+        &'a self,
+        verified_block: &'a LightBlock,
+        trusted_block: &'a LightBlock,
+        witnesses: Vec<&'a mut Instance>,
+    ) -> LocalBoxFuture<'a, Result<ForkDetection, Error>> {
+        async move {
+            let mut forks = Vec::with_capacity(witnesses.len());
+
+            for witness in witnesses {
+                let fork = self
+                    .detect_fork_async(verified_block, trusted_block, witness)
+                    .await?;
+
+                forks.extend(fork);
+            }
+
+            Ok(detection(forks))
         }
+        .boxed_local()
+    }
+}
+
+/// Outcome of fork detection given the forks found with the witnesses, if any
+fn detection(forks: Vec<Fork>) -> ForkDetection {
+    if forks.is_empty() {
+        ForkDetection::NotDetected
+    } else {
+        ForkDetection::Detected(forks)
+    }
+}
+
+/// State in which to verify the block of a witness which does not match the verified block,
+/// against the trusted block only
+fn witness_state(trusted_block: &LightBlock, witness_block: &LightBlock) -> State {
+    let mut state = State::new(MemoryStore::new());
+
+    state
+        .light_store
+        .insert(trusted_block.clone(), Status::Verified);
+
+    state
+        .light_store
+        .insert(witness_block.clone(), Status::Unverified);
+
+    state
+}
+
+/// Kind of fork given the outcome of the verification of the block of a witness which does not
+/// match the verified block
+fn classify(
+    verified_block: &LightBlock,
+    witness_block: LightBlock,
+    result: Result<LightBlock, Error>,
+) -> Fork {
+    match result {
+        Ok(_) => Fork::Forked {
+            primary: verified_block.clone(),
+            witness: witness_block,
+        },
+        Err(e) if e.kind().has_expired() => Fork::Forked {
+            primary: verified_block.clone(),
+            witness: witness_block,
+        },
+        Err(e) if e.kind().is_timeout() => Fork::Timeout(witness_block.provider, e.kind().clone()),
+        Err(e) => Fork::Faulty(witness_block, e.kind().clone()),
     }
 }
@@ -16,20 +16,28 @@
 
 //! See the `light_client` module for the main documentation.
 
+pub mod async_supervisor;
+pub mod audit;
 pub mod components;
 pub mod contracts;
 pub mod errors;
 pub mod evidence;
 pub mod fork_detector;
 pub mod light_client;
+pub mod metrics;
 pub mod operations;
 pub mod peer_list;
+pub mod persistence;
 pub mod predicates;
+pub mod renewal;
 pub mod state;
 mod std_ext;
 pub mod store;
 pub mod supervisor;
 pub mod types;
+pub mod upgrades;
+#[cfg(feature = "rpc-client")]
+pub mod verified_client;
 
 mod macros;
 
@@ -4,16 +4,32 @@
 
 use contracts::*;
 use derive_more::Display;
+use futures::future::{self, BoxFuture, FutureExt};
 use serde::{Deserialize, Serialize};
-use std::{fmt, time::Duration};
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tendermint::trust_threshold::ThresholdSchedule;
 
-use crate::components::{clock::Clock, io::*, scheduler::*, verifier::*};
+use crate::components::{
+    clock::{Clock, SystemClock},
+    io::*,
+    scheduler::*,
+    verifier::*,
+};
 use crate::contracts::*;
 use crate::{
-    bail,
+    audit::{Outcome, VerificationRecord},
+    bail, ensure,
     errors::{Error, ErrorKind},
+    metrics::{MetricsRecorder, Recorder, VerificationMetrics},
+    operations::ProdVotingPowerCalculator,
+    predicates::VerificationPredicates,
     state::State,
-    types::{Height, LightBlock, PeerId, Status, TrustThreshold},
+    types::{Height, LightBlock, PeerId, Status, Time, TrustThreshold},
+    upgrades::UpgradeSchedule,
 };
 
 /// Verification parameters
@@ -36,6 +52,19 @@ pub struct Options {
     /// is the maximum amount that the local clock may drift behind a timestamp from the
     /// blockchain.
     pub clock_drift: Duration,
+
+    /// Maximum number of light blocks to verify to reach a target height, ie. the maximum
+    /// depth of bisection, if any.
+    #[serde(default)]
+    pub max_bisection_depth: Option<usize>,
+
+    /// Maximum time to wait for a light block from the primary, if any.
+    #[serde(default)]
+    pub request_timeout: Option<Duration>,
+
+    /// Maximum time to verify a target height, including fetching light blocks, if any.
+    #[serde(default)]
+    pub max_verification_time: Option<Duration>,
 }
 
 /// The light client implements a read operation of a header from the blockchain,
@@ -56,7 +85,22 @@ pub struct LightClient {
     clock: Box<dyn Clock>,
     scheduler: Box<dyn Scheduler>,
     verifier: Box<dyn Verifier>,
-    io: Box<dyn Io>,
+    io: Fetcher,
+    metrics: Option<Recorder>,
+}
+
+/// The component through which a light client fetches light blocks
+enum Fetcher {
+    Blocking(Box<dyn Io>),
+    Async(Box<dyn AsyncIo>),
+}
+
+/// Outcome of a step of the core verification loop
+enum Step {
+    /// The block at the target height is trusted
+    Done(LightBlock),
+    /// The block at the current height must be verified against the given trusted state
+    Verify(LightBlock, Time),
 }
 
 impl fmt::Debug for LightClient {
@@ -84,7 +128,49 @@ impl LightClient {
             clock: Box::new(clock),
             scheduler: Box::new(scheduler),
             verifier: Box::new(verifier),
-            io: Box::new(io),
+            io: Fetcher::Blocking(Box::new(io)),
+            metrics: None,
+        }
+    }
+
+    /// Returns a builder of a light client connected to the given peer, which fetches light
+    /// blocks with the given `Io` component and defaults to the production components.
+    pub fn builder(peer: PeerId, options: Options, io: impl Io + 'static) -> LightClientBuilder {
+        LightClientBuilder::new(peer, options, Fetcher::Blocking(Box::new(io)))
+    }
+
+    /// Returns a builder of a light client connected to the given peer, which fetches light
+    /// blocks asynchronously with the given `AsyncIo` component, see `new_async`.
+    pub fn builder_async(
+        peer: PeerId,
+        options: Options,
+        io: impl AsyncIo + 'static,
+    ) -> LightClientBuilder {
+        LightClientBuilder::new(peer, options, Fetcher::Async(Box::new(io)))
+    }
+
+    /// Constructs a new light client fetching light blocks asynchronously.
+    ///
+    /// Such a light client is meant to be driven by the `*_async` methods, eg. by an
+    /// `AsyncSupervisor` within a tokio runtime. Its blocking methods, which cannot wait for the
+    /// fetched light blocks without a runtime, fail with `ErrorKind::BlockingOnAsyncIo` instead
+    /// whenever a light block must be fetched.
+    pub fn new_async(
+        peer: PeerId,
+        options: Options,
+        clock: impl Clock + 'static,
+        scheduler: impl Scheduler + 'static,
+        verifier: impl Verifier + 'static,
+        io: impl AsyncIo + 'static,
+    ) -> Self {
+        Self {
+            peer,
+            options,
+            clock: Box::new(clock),
+            scheduler: Box::new(scheduler),
+            verifier: Box::new(verifier),
+            io: Fetcher::Async(Box::new(io)),
+            metrics: None,
         }
     }
 
@@ -92,12 +178,28 @@ impl LightClient {
     ///
     /// Note: This function delegates the actual work to `verify_to_target`.
     pub fn verify_to_highest(&mut self, state: &mut State) -> Result<LightBlock, Error> {
-        let target_block = match self.io.fetch_light_block(self.peer, AtHeight::Highest) {
+        let timeout = self.options.request_timeout;
+        let target_block = self.fetch_blocking(AtHeight::Highest, timeout)?;
+
+        self.verify_to_target(target_block.height(), state)
+    }
+
+    /// Attempt to update the light client to the highest block of the primary node,
+    /// without blocking the current thread.
+    ///
+    /// See `verify_to_highest`.
+    pub async fn verify_to_highest_async(
+        &mut self,
+        state: &mut State,
+    ) -> Result<LightBlock, Error> {
+        let timeout = self.options.request_timeout;
+        let target_block = match self.fetch(AtHeight::Highest, timeout).await {
             Ok(last_block) => last_block,
             Err(io_error) => bail!(ErrorKind::Io(io_error)),
         };
 
-        self.verify_to_target(target_block.height(), state)
+        self.verify_to_target_async(target_block.height(), state)
+            .await
     }
 
     /// Update the light client to a block of the primary node at the given height.
@@ -130,6 +232,7 @@ impl LightClient {
     /// - If the core verification loop invariant is violated [LCV-INV-TP.1]
     /// - If verification of a light block fails
     /// - If it cannot fetch a block from the blockchain
+    /// - If it exceeds the maximum bisection depth or verification time set in the options
     // #[pre(
     //     light_store_contains_block_within_trusting_period(
     //         state.light_store.as_ref(),
@@ -147,6 +250,24 @@ impl LightClient {
         &self,
         target_height: Height,
         state: &mut State,
+    ) -> Result<LightBlock, Error> {
+        let started = Instant::now();
+        let mut steps = 0;
+
+        let result = self.verify_steps(target_height, state, started, &mut steps);
+        self.record_verification(target_height, started, steps, &result);
+
+        result
+    }
+
+    /// Core verification loop of `verify_to_target`, started at `started`, counting the verified
+    /// light blocks in `steps`.
+    fn verify_steps(
+        &self,
+        target_height: Height,
+        state: &mut State,
+        started: Instant,
+        steps: &mut usize,
     ) -> Result<LightBlock, Error> {
         // Let's first look in the store to see whether we have already successfully verified this
         // block.
@@ -157,76 +278,270 @@ impl LightClient {
         let mut current_height = target_height;
 
         loop {
-            let now = self.clock.now();
+            let (trusted_state, now) = match self.step(target_height, current_height, state)? {
+                Step::Done(light_block) => return Ok(light_block),
+                Step::Verify(trusted_state, now) => (trusted_state, now),
+            };
 
-            // Get the latest trusted state
-            let trusted_state = state
-                .light_store
-                .latest_trusted_or_verified()
-                .ok_or_else(|| ErrorKind::NoInitialTrustedState)?;
+            let timeout = self.check_limits(target_height, started, *steps)?;
 
-            if target_height < trusted_state.height() {
-                bail!(ErrorKind::TargetLowerThanTrustedState {
+            // Fetch the block at the current height from the light store if already present,
+            // or from the primary peer otherwise.
+            let fetched = self.get_or_fetch_block_within(current_height, state, timeout);
+            let (current_block, status) = self.check_time(fetched, target_height, started)?;
+
+            *steps += 1;
+            current_height = self.verify_block(
+                current_block,
+                status,
+                &trusted_state,
+                now,
+                target_height,
+                state,
+            )?;
+        }
+    }
+
+    /// Update the light client to a block of the primary node at the given height,
+    /// without blocking the current thread.
+    ///
+    /// See `verify_to_target`.
+    pub async fn verify_to_target_async(
+        &mut self,
+        target_height: Height,
+        state: &mut State,
+    ) -> Result<LightBlock, Error> {
+        let started = Instant::now();
+        let mut steps = 0;
+
+        let result = self
+            .verify_steps_async(target_height, state, started, &mut steps)
+            .await;
+        self.record_verification(target_height, started, steps, &result);
+
+        result
+    }
+
+    /// Core verification loop of `verify_to_target_async`, started at `started`, counting the
+    /// verified light blocks in `steps`.
+    async fn verify_steps_async(
+        &mut self,
+        target_height: Height,
+        state: &mut State,
+        started: Instant,
+        steps: &mut usize,
+    ) -> Result<LightBlock, Error> {
+        // Let's first look in the store to see whether we have already successfully verified this
+        // block.
+        if let Some(light_block) = state.light_store.get_trusted_or_verified(target_height) {
+            return Ok(light_block);
+        }
+
+        let mut current_height = target_height;
+
+        loop {
+            let (trusted_state, now) = match self.step(target_height, current_height, state)? {
+                Step::Done(light_block) => return Ok(light_block),
+                Step::Verify(trusted_state, now) => (trusted_state, now),
+            };
+
+            let timeout = self.check_limits(target_height, started, *steps)?;
+
+            let fetched = self
+                .get_or_fetch_block_within_async(current_height, state, timeout)
+                .await;
+            let (current_block, status) = self.check_time(fetched, target_height, started)?;
+
+            *steps += 1;
+            current_height = self.verify_block(
+                current_block,
+                status,
+                &trusted_state,
+                now,
+                target_height,
+                state,
+            )?;
+        }
+    }
+
+    /// Check that the verification of the given target height, started at `started` and
+    /// having verified `steps` light blocks so far, may verify another light block.
+    ///
+    /// Returns the timeout of the request for that light block, which is the request timeout
+    /// or the time left to verify the target height, whichever is shorter.
+    fn check_limits(
+        &self,
+        target_height: Height,
+        started: Instant,
+        steps: usize,
+    ) -> Result<Option<Duration>, Error> {
+        if let Some(max_depth) = self.options.max_bisection_depth {
+            ensure!(
+                steps < max_depth,
+                ErrorKind::BisectionDepthExceeded {
                     target_height,
-                    trusted_height: trusted_state.height()
-                });
-            }
+                    max_depth,
+                }
+            );
+        }
 
-            // Check invariant [LCV-INV-TP.1]
-            if !is_within_trust_period(&trusted_state, self.options.trusting_period, now) {
-                bail!(ErrorKind::TrustedStateOutsideTrustingPeriod {
-                    trusted_state: Box::new(trusted_state),
-                    options: self.options,
-                });
+        let time_left = match self.options.max_verification_time {
+            Some(max_time) => {
+                let time_left = max_time
+                    .checked_sub(started.elapsed())
+                    .filter(|time_left| *time_left > Duration::from_secs(0));
+
+                match time_left {
+                    Some(time_left) => Some(time_left),
+                    None => bail!(ErrorKind::VerificationTimeExceeded {
+                        target_height,
+                        max_time,
+                    }),
+                }
             }
+            None => None,
+        };
 
-            // Log the current height as a dependency of the block at the target height
-            state.trace_block(target_height, current_height);
+        Ok(match (self.options.request_timeout, time_left) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        })
+    }
 
-            // If the trusted state is now at a height equal to the target height, we are done.
-            // [LCV-DIST-LIFE.1]
-            if target_height == trusted_state.height() {
-                return Ok(trusted_state);
+    /// Report a failure to fetch a light block as exceeding the maximum verification time,
+    /// if that time has elapsed since `started`.
+    fn check_time<T>(
+        &self,
+        result: Result<T, Error>,
+        target_height: Height,
+        started: Instant,
+    ) -> Result<T, Error> {
+        match self.options.max_verification_time {
+            Some(max_time) if result.is_err() && started.elapsed() >= max_time => {
+                bail!(ErrorKind::VerificationTimeExceeded {
+                    target_height,
+                    max_time,
+                })
             }
+            _ => result,
+        }
+    }
 
-            // Fetch the block at the current height from the light store if already present,
-            // or from the primary peer otherwise.
-            let (current_block, status) = self.get_or_fetch_block(current_height, state)?;
-
-            // Validate and verify the current block
-            let verdict = self
-                .verifier
-                .verify(&current_block, &trusted_state, &self.options, now);
-
-            match verdict {
-                Verdict::Success => {
-                    // Verification succeeded, add the block to the light store with
-                    // the `Verified` status or higher if already trusted.
-                    let new_status = Status::most_trusted(Status::Verified, status);
-                    state.light_store.update(&current_block, new_status);
-                }
-                Verdict::Invalid(e) => {
-                    // Verification failed, add the block to the light store with `Failed` status,
-                    // and abort.
-                    state.light_store.update(&current_block, Status::Failed);
+    /// Report the metrics of a verification to the metrics recorder, if any.
+    fn record_verification(
+        &self,
+        target_height: Height,
+        started: Instant,
+        steps: usize,
+        result: &Result<LightBlock, Error>,
+    ) {
+        if let Some(Recorder(recorder)) = &self.metrics {
+            recorder.record_verification(&VerificationMetrics {
+                target_height,
+                duration: started.elapsed(),
+                steps,
+                error: result.as_ref().err(),
+            });
+        }
+    }
 
-                    bail!(ErrorKind::InvalidLightBlock(e))
-                }
-                Verdict::NotEnoughTrust(_) => {
-                    // The current block cannot be trusted because of a missing overlap in the
-                    // validator sets. Add the block to the light store with
-                    // the `Unverified` status. This will engage bisection in an
-                    // attempt to raise the height of the highest trusted state
-                    // until there is enough overlap.
-                    state.light_store.update(&current_block, Status::Unverified);
-                }
+    /// Check the latest trusted state against the target height and the trusting period,
+    /// returning it if the block at the current height must still be verified against it.
+    fn step(
+        &self,
+        target_height: Height,
+        current_height: Height,
+        state: &mut State,
+    ) -> Result<Step, Error> {
+        let now = self.clock.now();
+
+        // Get the latest trusted state
+        let trusted_state = state
+            .light_store
+            .latest_trusted_or_verified()
+            .ok_or_else(|| ErrorKind::NoInitialTrustedState)?;
+
+        if target_height < trusted_state.height() {
+            bail!(ErrorKind::TargetLowerThanTrustedState {
+                target_height,
+                trusted_height: trusted_state.height()
+            });
+        }
+
+        // Check invariant [LCV-INV-TP.1]
+        if !is_within_trust_period(&trusted_state, self.options.trusting_period, now) {
+            bail!(ErrorKind::TrustedStateOutsideTrustingPeriod {
+                trusted_state: Box::new(trusted_state),
+                options: self.options,
+            });
+        }
+
+        // Log the current height as a dependency of the block at the target height
+        state.trace_block(target_height, current_height);
+
+        // If the trusted state is now at a height equal to the target height, we are done.
+        // [LCV-DIST-LIFE.1]
+        if target_height == trusted_state.height() {
+            return Ok(Step::Done(trusted_state));
+        }
+
+        Ok(Step::Verify(trusted_state, now))
+    }
+
+    /// Validate and verify the current block against the trusted state, record the outcome in
+    /// the light store, and compute the next height to fetch and verify.
+    fn verify_block(
+        &self,
+        current_block: LightBlock,
+        status: Status,
+        trusted_state: &LightBlock,
+        now: Time,
+        target_height: Height,
+        state: &mut State,
+    ) -> Result<Height, Error> {
+        let verdict = self
+            .verifier
+            .verify(&current_block, trusted_state, &self.options, now);
+
+        state.record(|| VerificationRecord {
+            target_height,
+            untrusted: current_block.clone(),
+            trusted: trusted_state.clone(),
+            options: self.options,
+            now,
+            outcome: Outcome::from(&verdict),
+        });
+
+        match verdict {
+            Verdict::Success => {
+                // Verification succeeded, add the block to the light store with
+                // the `Verified` status or higher if already trusted.
+                let new_status = Status::most_trusted(Status::Verified, status);
+                state.light_store.update(&current_block, new_status);
             }
+            Verdict::Invalid(e) => {
+                // Verification failed, add the block to the light store with `Failed` status,
+                // and abort.
+                state.light_store.update(&current_block, Status::Failed);
 
-            // Compute the next height to fetch and verify
-            current_height =
-                self.scheduler
-                    .schedule(state.light_store.as_ref(), current_height, target_height);
+                bail!(ErrorKind::InvalidLightBlock(e))
+            }
+            Verdict::NotEnoughTrust(_) => {
+                // The current block cannot be trusted because of a missing overlap in the
+                // validator sets. Add the block to the light store with
+                // the `Unverified` status. This will engage bisection in an
+                // attempt to raise the height of the highest trusted state
+                // until there is enough overlap.
+                state.light_store.update(&current_block, Status::Unverified);
+            }
         }
+
+        // Compute the next height to fetch and verify
+        Ok(self.scheduler.schedule(
+            state.light_store.as_ref(),
+            current_block.height(),
+            target_height,
+        ))
     }
 
     /// Look in the light store for a block from the given peer at the given height,
@@ -242,6 +557,57 @@ impl LightClient {
         &self,
         height: Height,
         state: &mut State,
+    ) -> Result<(LightBlock, Status), Error> {
+        let timeout = self.options.request_timeout;
+        self.get_or_fetch_block_within(height, state, timeout)
+    }
+
+    /// Look in the light store for a block from the given peer at the given height, or fetch it
+    /// without blocking the current thread.
+    ///
+    /// See `get_or_fetch_block`.
+    pub async fn get_or_fetch_block_async(
+        &mut self,
+        height: Height,
+        state: &mut State,
+    ) -> Result<(LightBlock, Status), Error> {
+        let timeout = self.options.request_timeout;
+        self.get_or_fetch_block_within_async(height, state, timeout)
+            .await
+    }
+
+    /// Look in the light store for a block from the given peer at the given height, or fetch it
+    /// within the given timeout.
+    ///
+    /// See `get_or_fetch_block`.
+    fn get_or_fetch_block_within(
+        &self,
+        height: Height,
+        state: &mut State,
+        timeout: Option<Duration>,
+    ) -> Result<(LightBlock, Status), Error> {
+        let block = state.light_store.get_non_failed(height);
+
+        if let Some(block) = block {
+            return Ok(block);
+        }
+
+        let block = self.fetch_blocking(AtHeight::At(height), timeout)?;
+
+        state.light_store.insert(block.clone(), Status::Unverified);
+
+        Ok((block, Status::Unverified))
+    }
+
+    /// Look in the light store for a block from the given peer at the given height, or fetch it
+    /// within the given timeout without blocking the current thread.
+    ///
+    /// See `get_or_fetch_block`.
+    async fn get_or_fetch_block_within_async(
+        &self,
+        height: Height,
+        state: &mut State,
+        timeout: Option<Duration>,
     ) -> Result<(LightBlock, Status), Error> {
         let block = state.light_store.get_non_failed(height);
 
@@ -250,12 +616,188 @@ impl LightClient {
         }
 
         let block = self
-            .io
-            .fetch_light_block(self.peer, AtHeight::At(height))
+            .fetch(AtHeight::At(height), timeout)
+            .await
             .map_err(ErrorKind::Io)?;
 
         state.light_store.insert(block.clone(), Status::Unverified);
 
         Ok((block, Status::Unverified))
     }
+
+    /// Fetch the light block at the given height from the peer this client is connected to,
+    /// within the given timeout, on the current thread.
+    ///
+    /// Fails with `ErrorKind::BlockingOnAsyncIo` if the client fetches light blocks
+    /// asynchronously, as waiting for them here would panic outside of a runtime and could
+    /// deadlock within one.
+    fn fetch_blocking(
+        &self,
+        height: AtHeight,
+        timeout: Option<Duration>,
+    ) -> Result<LightBlock, Error> {
+        match &self.io {
+            Fetcher::Blocking(io) => Ok(io
+                .fetch_light_block_with_timeout(self.peer, height, timeout)
+                .map_err(ErrorKind::Io)?),
+            Fetcher::Async(_) => bail!(ErrorKind::BlockingOnAsyncIo),
+        }
+    }
+
+    /// Fetch the light block at the given height from the peer this client is connected to,
+    /// within the given timeout
+    fn fetch(
+        &self,
+        height: AtHeight,
+        timeout: Option<Duration>,
+    ) -> BoxFuture<'_, Result<LightBlock, IoError>> {
+        match &self.io {
+            Fetcher::Blocking(io) => {
+                future::ready(io.fetch_light_block_with_timeout(self.peer, height, timeout)).boxed()
+            }
+            Fetcher::Async(io) => {
+                io.fetch_light_block_with_timeout_async(self.peer, height, timeout)
+            }
+        }
+    }
+}
+
+/// Builder of a [`LightClient`], using the `SystemClock`, the skipping verification strategy
+/// and the `ProdVerifier` unless other components are given.
+///
+/// The predicates, commit thresholds and upgrades of the `ProdVerifier` can be configured
+/// independently of each other, in any order, unless another verifier is given.
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// // Reject the light blocks of another chain on top of the production checks
+/// struct ChainPredicates;
+///
+/// impl VerificationPredicates for ChainPredicates {
+///     fn additional_checks(
+///         &self,
+///         untrusted: &LightBlock,
+///         _trusted: &LightBlock,
+///     ) -> Result<(), VerificationError> {
+///         ensure!(
+///             untrusted.signed_header.header.chain_id.as_str() == "my-chain",
+///             VerificationError::ImplementationSpecific("unexpected chain id".to_string())
+///         );
+///
+///         Ok(())
+///     }
+/// }
+///
+/// let light_client = LightClient::builder(peer_id, options, io)
+///     .predicates(ChainPredicates)
+///     .commit_threshold_schedule(commit_thresholds)
+///     .build();
+/// ```
+pub struct LightClientBuilder {
+    peer: PeerId,
+    options: Options,
+    clock: Box<dyn Clock>,
+    scheduler: Box<dyn Scheduler>,
+    verifier: Option<Box<dyn Verifier>>,
+    prod_verifier: ProdVerifier,
+    io: Fetcher,
+    metrics: Option<Recorder>,
+}
+
+impl fmt::Debug for LightClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LightClientBuilder")
+            .field("peer", &self.peer)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+impl LightClientBuilder {
+    fn new(peer: PeerId, options: Options, io: Fetcher) -> Self {
+        Self {
+            peer,
+            options,
+            clock: Box::new(SystemClock),
+            scheduler: Box::new(VerificationStrategy::default()),
+            verifier: None,
+            prod_verifier: ProdVerifier::default(),
+            io,
+            metrics: None,
+        }
+    }
+
+    /// Use the given clock.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Use the given scheduler, eg. a `VerificationStrategy`.
+    pub fn scheduler(mut self, scheduler: impl Scheduler + 'static) -> Self {
+        self.scheduler = Box::new(scheduler);
+        self
+    }
+
+    /// Use the given verifier instead of the `ProdVerifier`.
+    pub fn verifier(mut self, verifier: impl Verifier + 'static) -> Self {
+        self.verifier = Some(Box::new(verifier));
+        self
+    }
+
+    /// Use the production verifier with the given predicates instead of `ProdPredicates`,
+    /// eg. to add chain-specific checks or to relax some checks in testing.
+    ///
+    /// This replaces the verifier set with `verifier`, if any.
+    pub fn predicates(mut self, predicates: impl VerificationPredicates + 'static) -> Self {
+        self.prod_verifier = self.prod_verifier.with_predicates(predicates);
+        self.verifier = None;
+        self
+    }
+
+    /// Use the production verifier with the given commit thresholds instead of 2/3 of the
+    /// voting power, see `ProdVerifier::with_commit_threshold_schedule`.
+    ///
+    /// This replaces the verifier set with `verifier`, if any.
+    pub fn commit_threshold_schedule(mut self, commit_thresholds: ThresholdSchedule) -> Self {
+        self.prod_verifier = self.prod_verifier.with_voting_power_calculator(
+            ProdVotingPowerCalculator::with_schedule(commit_thresholds),
+        );
+        self.verifier = None;
+        self
+    }
+
+    /// Use the production verifier with the given upgrades, see
+    /// `ProdVerifier::with_upgrade_schedule`.
+    ///
+    /// This replaces the verifier set with `verifier`, if any.
+    pub fn upgrade_schedule(mut self, upgrades: UpgradeSchedule) -> Self {
+        self.prod_verifier = self.prod_verifier.with_upgrade_schedule(upgrades);
+        self.verifier = None;
+        self
+    }
+
+    /// Report the metrics of every verification to the given recorder,
+    /// eg. a `PrometheusMetrics` with the `metrics-prometheus` feature.
+    pub fn metrics_recorder(mut self, recorder: impl MetricsRecorder + 'static) -> Self {
+        self.metrics = Some(Recorder(Arc::new(recorder)));
+        self
+    }
+
+    /// Build the light client.
+    pub fn build(self) -> LightClient {
+        LightClient {
+            peer: self.peer,
+            options: self.options,
+            clock: self.clock,
+            scheduler: self.scheduler,
+            verifier: match self.verifier {
+                Some(verifier) => verifier,
+                None => Box::new(self.prod_verifier),
+            },
+            io: self.io,
+            metrics: self.metrics,
+        }
+    }
 }
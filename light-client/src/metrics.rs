@@ -0,0 +1,122 @@
+//! Instrumentation hooks reporting operational metrics of light clients and supervisors
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::Error;
+use crate::fork_detector::Fork;
+use crate::store::LightStore;
+use crate::types::{Height, LightBlock, Status, Time};
+
+#[cfg(feature = "metrics-prometheus")]
+mod prometheus_metrics;
+#[cfg(feature = "metrics-prometheus")]
+pub use prometheus_metrics::PrometheusMetrics;
+
+/// Receiver of the metrics of a [`LightClient`](crate::light_client::LightClient) or a
+/// supervisor, e.g. to feed them into Prometheus or statsd and alert when a light client is
+/// struggling or stalled.
+///
+/// Called from the verification loop, so implementations should return quickly.
+pub trait MetricsRecorder: Send + Sync {
+    /// Record the metrics of a completed verification to a target height
+    fn record_verification(&self, _metrics: &VerificationMetrics<'_>) {}
+
+    /// Record that a witness diverged from the primary during fork detection
+    fn record_divergence(&self, _divergence: Divergence) {}
+
+    /// Record the number of light blocks with the given status in the light store
+    /// of the primary, after each verification by a supervisor
+    fn record_store_size(&self, _status: Status, _size: usize) {}
+
+    /// Record that a supervisor trusts the block at the given height, `lag` being the time
+    /// elapsed since the block was committed
+    fn record_trusted(&self, _height: Height, _lag: Duration) {}
+}
+
+/// Metrics of a completed verification to a target height
+#[derive(Clone, Debug)]
+pub struct VerificationMetrics<'a> {
+    /// Height of the block to verify
+    pub target_height: Height,
+
+    /// Time taken by the verification, including fetching light blocks
+    pub duration: Duration,
+
+    /// Number of light blocks verified to reach the target, ie. the depth of bisection
+    pub steps: usize,
+
+    /// Error the verification failed with, if any
+    pub error: Option<&'a Error>,
+}
+
+impl VerificationMetrics<'_> {
+    /// Did the verification succeed?
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// How a witness diverged from the primary
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Divergence {
+    /// The witness provided a conflicting light block
+    Forked,
+    /// The witness provided an invalid light block
+    Faulty,
+    /// The witness did not respond in time
+    Timeout,
+}
+
+impl Divergence {
+    /// Name of the divergence, eg. to label metrics
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Forked => "forked",
+            Self::Faulty => "faulty",
+            Self::Timeout => "timeout",
+        }
+    }
+}
+
+/// Shared handle to a [`MetricsRecorder`]
+#[derive(Clone)]
+pub(crate) struct Recorder(pub(crate) Arc<dyn MetricsRecorder>);
+
+impl Recorder {
+    /// Record how the witnesses diverged from the primary in the given forks
+    pub(crate) fn record_forks(&self, forks: &[Fork]) {
+        for fork in forks {
+            let divergence = match fork {
+                Fork::Forked { .. } => Divergence::Forked,
+                Fork::Faulty(_, _) => Divergence::Faulty,
+                Fork::Timeout(_, _) => Divergence::Timeout,
+            };
+
+            self.0.record_divergence(divergence);
+        }
+    }
+
+    /// Record that the given light block is trusted, along with the size of the given light store
+    pub(crate) fn record_trusted(&self, light_block: &LightBlock, light_store: &dyn LightStore) {
+        let lag = Time::now()
+            .duration_since(light_block.signed_header.header.time)
+            .unwrap_or_default();
+
+        self.0.record_trusted(light_block.height(), lag);
+
+        for status in Status::iter() {
+            self.0
+                .record_store_size(*status, light_store.all(*status).count());
+        }
+    }
+}
+
+impl fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MetricsRecorder")
+    }
+}
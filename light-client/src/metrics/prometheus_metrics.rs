@@ -0,0 +1,149 @@
+//! Metrics of light clients exported to Prometheus
+
+use std::time::Duration;
+
+use prometheus::{
+    Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+};
+
+use super::{Divergence, MetricsRecorder, VerificationMetrics};
+use crate::types::{Height, Status};
+
+/// [`MetricsRecorder`] registering the standard metrics of light clients into a
+/// Prometheus registry:
+///
+/// - `tendermint_light_client_verification_duration_seconds`: latencies of verifications by
+///   `status` (`ok` or `error`)
+/// - `tendermint_light_client_verification_steps`: light blocks verified per verification,
+///   ie. the depth of bisection
+/// - `tendermint_light_client_witness_divergences_total`: witnesses diverging from the primary
+///   by `kind` (`forked`, `faulty` or `timeout`)
+/// - `tendermint_light_client_store_blocks`: light blocks in the store of the primary by
+///   `status`
+/// - `tendermint_light_client_trusted_height`: latest trusted height
+/// - `tendermint_light_client_trusted_lag_seconds`: time elapsed between the commit of a block
+///   and it being trusted
+///
+/// One recorder can be shared by any number of light clients and supervisors, e.g. via
+/// [`LightClientBuilder::metrics_recorder`](crate::light_client::LightClientBuilder::metrics_recorder)
+/// and [`Supervisor::with_metrics_recorder`](crate::supervisor::Supervisor::with_metrics_recorder).
+#[derive(Clone, Debug)]
+pub struct PrometheusMetrics {
+    durations: HistogramVec,
+    steps: Histogram,
+    divergences: IntCounterVec,
+    store_blocks: IntGaugeVec,
+    trusted_height: IntGauge,
+    trusted_lag: Histogram,
+}
+
+impl PrometheusMetrics {
+    /// Create the metrics and register them into the given registry
+    pub fn register(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let metrics = Self {
+            durations: HistogramVec::new(
+                HistogramOpts::new(
+                    "tendermint_light_client_verification_duration_seconds",
+                    "Duration of verifications, including fetching light blocks",
+                ),
+                &["status"],
+            )?,
+            steps: Histogram::with_opts(
+                HistogramOpts::new(
+                    "tendermint_light_client_verification_steps",
+                    "Light blocks verified per verification",
+                )
+                .buckets(vec![1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0]),
+            )?,
+            divergences: IntCounterVec::new(
+                Opts::new(
+                    "tendermint_light_client_witness_divergences_total",
+                    "Witnesses diverging from the primary during fork detection",
+                ),
+                &["kind"],
+            )?,
+            store_blocks: IntGaugeVec::new(
+                Opts::new(
+                    "tendermint_light_client_store_blocks",
+                    "Light blocks in the light store of the primary",
+                ),
+                &["status"],
+            )?,
+            trusted_height: IntGauge::new(
+                "tendermint_light_client_trusted_height",
+                "Latest trusted height",
+            )?,
+            trusted_lag: Histogram::with_opts(HistogramOpts::new(
+                "tendermint_light_client_trusted_lag_seconds",
+                "Time elapsed between the commit of a block and it being trusted",
+            ))?,
+        };
+
+        registry.register(Box::new(metrics.durations.clone()))?;
+        registry.register(Box::new(metrics.steps.clone()))?;
+        registry.register(Box::new(metrics.divergences.clone()))?;
+        registry.register(Box::new(metrics.store_blocks.clone()))?;
+        registry.register(Box::new(metrics.trusted_height.clone()))?;
+        registry.register(Box::new(metrics.trusted_lag.clone()))?;
+
+        Ok(metrics)
+    }
+}
+
+impl MetricsRecorder for PrometheusMetrics {
+    fn record_verification(&self, metrics: &VerificationMetrics<'_>) {
+        let status = if metrics.is_success() { "ok" } else { "error" };
+
+        self.durations
+            .with_label_values(&[status])
+            .observe(metrics.duration.as_secs_f64());
+        self.steps.observe(metrics.steps as f64);
+    }
+
+    fn record_divergence(&self, divergence: Divergence) {
+        self.divergences
+            .with_label_values(&[divergence.as_str()])
+            .inc();
+    }
+
+    fn record_store_size(&self, status: Status, size: usize) {
+        let status = format!("{:?}", status).to_lowercase();
+        self.store_blocks
+            .with_label_values(&[&status])
+            .set(size as i64);
+    }
+
+    fn record_trusted(&self, height: Height, lag: Duration) {
+        self.trusted_height.set(height.value() as i64);
+        self.trusted_lag.observe(lag.as_secs_f64());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_divergences_by_kind() {
+        let registry = Registry::new();
+        let metrics = PrometheusMetrics::register(&registry).unwrap();
+
+        metrics.record_divergence(Divergence::Timeout);
+        metrics.record_divergence(Divergence::Timeout);
+        metrics.record_divergence(Divergence::Forked);
+        metrics.record_store_size(Status::Trusted, 3);
+        metrics.record_trusted(Height::from(42_u64), Duration::from_secs(5));
+
+        assert_eq!(metrics.divergences.with_label_values(&["timeout"]).get(), 2);
+        assert_eq!(metrics.divergences.with_label_values(&["forked"]).get(), 1);
+        assert_eq!(
+            metrics.store_blocks.with_label_values(&["trusted"]).get(),
+            3
+        );
+        assert_eq!(metrics.trusted_height.get(), 42);
+        assert_eq!(registry.gather().len(), 6);
+
+        // Metrics can only be registered once per registry
+        assert!(PrometheusMetrics::register(&registry).is_err());
+    }
+}
@@ -1,9 +1,15 @@
 //! Provides an interface and default implementation for the `Hasher` operation
 
-use crate::types::{Header, ValidatorSet};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::types::{Header, Height, PeerId, ValidatorSet};
 
 use tendermint::{merkle, Hash};
 
+/// Default number of validator sets kept by a `ValidatorSetCache`
+pub const DEFAULT_VALIDATOR_SET_CACHE_CAPACITY: usize = 32;
+
 /// Hashing for headers and validator sets
 pub trait Hasher: Send {
     /// Hash the given header
@@ -11,6 +17,15 @@ pub trait Hasher: Send {
 
     /// Hash the given validator set
     fn hash_validator_set(&self, validator_set: &ValidatorSet) -> Hash;
+
+    /// Hash the given validator set, expected to have the given hash, eg. the one found in
+    /// the header it comes with.
+    ///
+    /// Implementations may check the validator set against the one known to have that hash
+    /// instead of hashing it. By default, the validator set is hashed.
+    fn hash_validator_set_expecting(&self, validator_set: &ValidatorSet, _expected: Hash) -> Hash {
+        self.hash_validator_set(validator_set)
+    }
 }
 
 /// Default implementation of a hasher
@@ -33,3 +48,266 @@ impl Hasher for ProdHasher {
         Hash::Sha256(merkle::simple_hash_from_byte_vectors(validator_bytes))
     }
 }
+
+/// Bounded cache of validator sets keyed by their hash, shared by its clones.
+///
+/// During bisection, the same validator sets are fetched and hashed again and again, as
+/// validator sets rarely change between the heights being verified. Caching them spares
+/// the redundant RPC calls and hashing work.
+#[derive(Clone, Debug)]
+pub struct ValidatorSetCache {
+    entries: Arc<Mutex<Entries>>,
+    capacity: usize,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    by_hash: HashMap<Hash, Entry>,
+    uses: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    validator_set: ValidatorSet,
+    /// Peer and height the validator set was fetched from and at, if it was fetched
+    fetched: Option<(PeerId, Height)>,
+    last_use: u64,
+}
+
+impl ValidatorSetCache {
+    /// Constructs a new cache keeping up to the given number of validator sets, evicting the
+    /// least recently used ones first.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(Entries::default())),
+            capacity,
+        }
+    }
+
+    /// Get the validator set with the given hash, if it was fetched from the given peer
+    /// at the given height.
+    ///
+    /// A validator set with the same hash fetched at another height is not returned, as
+    /// the proposer priorities of its validators, which are not hashed, differ.
+    pub fn get_fetched(&self, hash: &Hash, peer: PeerId, height: Height) -> Option<ValidatorSet> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.touch(hash)?;
+
+        if entry.fetched == Some((peer, height)) {
+            Some(entry.validator_set.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Whether the given validator set has the given hash, ie. it is made of the same
+    /// validators with the same voting powers as the validator set cached with that hash.
+    ///
+    /// Returns `false` if no validator set with that hash is cached.
+    pub fn has_hash(&self, validator_set: &ValidatorSet, hash: &Hash) -> bool {
+        let mut entries = self.entries.lock().unwrap();
+
+        entries.touch(hash).map_or(false, |entry| {
+            same_hash_bytes(&entry.validator_set, validator_set)
+        })
+    }
+
+    /// Cache the given validator set with the given hash, which must be its actual hash.
+    pub fn insert(&self, hash: Hash, validator_set: ValidatorSet) {
+        self.insert_entry(hash, validator_set, None);
+    }
+
+    /// Cache the given validator set fetched from the given peer at the given height with
+    /// the given hash, which must be its actual hash.
+    pub fn insert_fetched(
+        &self,
+        hash: Hash,
+        peer: PeerId,
+        height: Height,
+        validator_set: ValidatorSet,
+    ) {
+        self.insert_entry(hash, validator_set, Some((peer, height)));
+    }
+
+    fn insert_entry(
+        &self,
+        hash: Hash,
+        validator_set: ValidatorSet,
+        fetched: Option<(PeerId, Height)>,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+
+        if !entries.by_hash.contains_key(&hash) && entries.by_hash.len() >= self.capacity {
+            let least_recently_used = entries
+                .by_hash
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_use)
+                .map(|(hash, _)| *hash);
+
+            if let Some(evicted) = least_recently_used {
+                entries.by_hash.remove(&evicted);
+            }
+        }
+
+        entries.uses += 1;
+        let last_use = entries.uses;
+
+        entries.by_hash.insert(
+            hash,
+            Entry {
+                validator_set,
+                fetched,
+                last_use,
+            },
+        );
+    }
+}
+
+impl Entries {
+    /// Get the entry with the given hash, and mark it as the most recently used.
+    fn touch(&mut self, hash: &Hash) -> Option<&Entry> {
+        self.uses += 1;
+        let uses = self.uses;
+
+        let entry = self.by_hash.get_mut(hash)?;
+        entry.last_use = uses;
+
+        Some(entry)
+    }
+}
+
+impl Default for ValidatorSetCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_VALIDATOR_SET_CACHE_CAPACITY)
+    }
+}
+
+/// Whether the given validator sets hash the same bytes, ie. they are made of the same
+/// validators, in the same order, with the same voting powers.
+fn same_hash_bytes(a: &ValidatorSet, b: &ValidatorSet) -> bool {
+    let (a, b) = (a.validators(), b.validators());
+
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .all(|(a, b)| a.pub_key == b.pub_key && a.voting_power == b.voting_power)
+}
+
+/// Hasher caching the hashes of validator sets, and otherwise delegating to another hasher,
+/// `ProdHasher` by default.
+///
+/// Validator sets are looked up by the hash they are expected to have, see
+/// `Hasher::hash_validator_set_expecting`.
+#[derive(Clone, Debug, Default)]
+pub struct CachingHasher<H = ProdHasher> {
+    hasher: H,
+    cache: ValidatorSetCache,
+}
+
+impl<H: Hasher> CachingHasher<H> {
+    /// Constructs a new hasher caching the hashes computed by the given hasher in the given cache.
+    pub fn new(hasher: H, cache: ValidatorSetCache) -> Self {
+        Self { hasher, cache }
+    }
+}
+
+impl<H: Hasher> Hasher for CachingHasher<H> {
+    fn hash_header(&self, header: &Header) -> Hash {
+        self.hasher.hash_header(header)
+    }
+
+    fn hash_validator_set(&self, validator_set: &ValidatorSet) -> Hash {
+        self.hasher.hash_validator_set(validator_set)
+    }
+
+    fn hash_validator_set_expecting(&self, validator_set: &ValidatorSet, expected: Hash) -> Hash {
+        if self.cache.has_hash(validator_set, &expected) {
+            return expected;
+        }
+
+        let hash = self.hasher.hash_validator_set(validator_set);
+        self.cache.insert(hash, validator_set.clone());
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tendermint_testgen::{Generator, Validator};
+
+    fn validator_set(validators: &[(&str, u64, i64)]) -> ValidatorSet {
+        ValidatorSet::new(
+            validators
+                .iter()
+                .map(|(id, power, priority)| {
+                    Validator::new(id)
+                        .voting_power(*power)
+                        .proposer_priority(*priority)
+                        .generate()
+                        .unwrap()
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn cache_evicts_least_recently_used() {
+        let cache = ValidatorSetCache::new(2);
+        let empty = ValidatorSet::new(vec![]);
+        let hashes = [
+            Hash::Sha256([1; 32]),
+            Hash::Sha256([2; 32]),
+            Hash::Sha256([3; 32]),
+        ];
+
+        cache.insert(hashes[0], empty.clone());
+        cache.insert(hashes[1], empty.clone());
+        assert!(cache.has_hash(&empty, &hashes[0]));
+
+        cache.insert(hashes[2], empty.clone());
+        assert!(cache.has_hash(&empty, &hashes[0]));
+        assert!(!cache.has_hash(&empty, &hashes[1]));
+        assert!(cache.has_hash(&empty, &hashes[2]));
+    }
+
+    #[test]
+    fn fetched_validator_sets_are_only_returned_at_their_height() {
+        let cache = ValidatorSetCache::default();
+        let peer = "BADFADAD0BEFEEDC0C0ADEADBEEFC0FFEEFACADE".parse().unwrap();
+        let validators = validator_set(&[("a", 50, 1)]);
+        let hash = ProdHasher.hash_validator_set(&validators);
+
+        cache.insert_fetched(hash, peer, Height::from(1_u64), validators.clone());
+
+        assert_eq!(
+            cache.get_fetched(&hash, peer, Height::from(1_u64)),
+            Some(validators)
+        );
+        assert_eq!(cache.get_fetched(&hash, peer, Height::from(2_u64)), None);
+    }
+
+    #[test]
+    fn caching_hasher_checks_validator_sets_against_the_expected_hash() {
+        let hasher = CachingHasher::default();
+        let validators = validator_set(&[("a", 50, 1), ("b", 50, -1)]);
+        let hash = ProdHasher.hash_validator_set(&validators);
+
+        assert_eq!(hasher.hash_validator_set_expecting(&validators, hash), hash);
+
+        // Proposer priorities are not hashed
+        let reprioritized = validator_set(&[("a", 50, -1), ("b", 50, 1)]);
+        assert_eq!(
+            hasher.hash_validator_set_expecting(&reprioritized, hash),
+            hash
+        );
+
+        let reweighted = validator_set(&[("a", 60, 1), ("b", 40, -1)]);
+        assert_ne!(hasher.hash_validator_set_expecting(&reweighted, hash), hash);
+    }
+}
@@ -2,8 +2,9 @@
 
 use crate::{
     bail,
+    errors::{Error, ErrorKind},
     predicates::errors::VerificationError,
-    types::{Commit, SignedHeader, TrustThreshold, ValidatorSet},
+    types::{Commit, Height, SignedHeader, TrustThreshold, Validator, ValidatorSet},
 };
 
 use serde::{Deserialize, Serialize};
@@ -11,7 +12,7 @@ use std::collections::HashSet;
 use std::fmt;
 
 use tendermint::block::CommitSig;
-use tendermint::trust_threshold::TrustThreshold as _;
+use tendermint::trust_threshold::{ThresholdSchedule, TrustThreshold as _};
 use tendermint::vote::{SignedVote, Vote};
 
 /// Tally for the voting power computed by the `VotingPowerCalculator`
@@ -67,14 +68,23 @@ pub trait VotingPowerCalculator: Send {
         }
     }
 
-    /// Check against the given threshold that there is enough signers
+    /// Share of the voting power of a validator set which must have signed the commit
+    /// of the block at the given height for it to be valid, ie. the vote rate of the
+    /// chain's consensus at that height.
+    ///
+    /// Defaults to 2/3 at every height, as on Tendermint chains.
+    fn commit_threshold(&self, _height: Height) -> TrustThreshold {
+        TrustThreshold::TWO_THIRDS
+    }
+
+    /// Check against the commit threshold that there is enough signers
     /// overlap between an untrusted header and untrusted validator set
     fn check_signers_overlap(
         &self,
         untrusted_header: &SignedHeader,
         untrusted_validators: &ValidatorSet,
     ) -> Result<(), VerificationError> {
-        let trust_threshold = TrustThreshold::TWO_THIRDS;
+        let trust_threshold = self.commit_threshold(untrusted_header.header.height);
         let voting_power =
             self.voting_power_in(untrusted_header, untrusted_validators, trust_threshold)?;
 
@@ -98,10 +108,47 @@ pub trait VotingPowerCalculator: Send {
 }
 
 /// Default implementation of a `VotingPowerCalculator`
-#[derive(Copy, Clone, Debug, Default)]
-pub struct ProdVotingPowerCalculator;
+///
+/// Commits are valid once signed by more than 2/3 of the voting power by default.
+/// Chains whose consensus commits blocks with another vote rate, eg. 1/2, must
+/// construct it with `ProdVotingPowerCalculator::new`, and chains switching from one
+/// vote rate to another at some height with `ProdVotingPowerCalculator::with_schedule`.
+#[derive(Clone, Debug)]
+pub struct ProdVotingPowerCalculator {
+    commit_thresholds: ThresholdSchedule,
+}
+
+impl ProdVotingPowerCalculator {
+    /// Constructs a voting power calculator for chains committing blocks once signed
+    /// by more than the given share of the voting power
+    ///
+    /// Fails with `ErrorKind::InvalidCommitThreshold` unless the threshold is between
+    /// 1/3 and 1.
+    pub fn new(commit_threshold: TrustThreshold) -> Result<Self, Error> {
+        let commit_thresholds = ThresholdSchedule::constant(commit_threshold)
+            .map_err(|e| ErrorKind::InvalidCommitThreshold(commit_threshold).context(e))?;
+
+        Ok(Self::with_schedule(commit_thresholds))
+    }
+
+    /// Constructs a voting power calculator for chains committing blocks once signed
+    /// by more than the share of the voting power scheduled at their height
+    pub fn with_schedule(commit_thresholds: ThresholdSchedule) -> Self {
+        Self { commit_thresholds }
+    }
+}
+
+impl Default for ProdVotingPowerCalculator {
+    fn default() -> Self {
+        Self::with_schedule(ThresholdSchedule::default())
+    }
+}
 
 impl VotingPowerCalculator for ProdVotingPowerCalculator {
+    fn commit_threshold(&self, height: Height) -> TrustThreshold {
+        self.commit_thresholds.threshold_at(height)
+    }
+
     fn voting_power_in(
         &self,
         signed_header: &SignedHeader,
@@ -122,6 +169,8 @@ impl VotingPowerCalculator for ProdVotingPowerCalculator {
             }
         });
 
+        let mut signed_votes = Vec::new();
+
         for (signature, vote) in non_absent_votes {
             // Ensure we only count a validator's power once
             if seen_validators.contains(&vote.validator_address) {
@@ -144,19 +193,6 @@ impl VotingPowerCalculator for ProdVotingPowerCalculator {
                 vote.signature,
             );
 
-            // Check vote is valid
-            let sign_bytes = signed_vote.sign_bytes();
-            if validator
-                .verify_signature(&sign_bytes, signed_vote.signature())
-                .is_err()
-            {
-                bail!(VerificationError::InvalidSignature {
-                    signature: signed_vote.signature().to_bytes(),
-                    validator: Box::new(validator),
-                    sign_bytes,
-                });
-            }
-
             // If the vote is neither absent nor nil, tally its power
             if signature.is_commit() {
                 tallied_voting_power += validator.power();
@@ -165,10 +201,16 @@ impl VotingPowerCalculator for ProdVotingPowerCalculator {
                 // to measure validator availability.
             }
 
+            let sign_bytes = signed_vote.sign_bytes();
+            signed_votes.push((validator, sign_bytes, signed_vote));
+
             // TODO: Break out of the loop when we have enough voting power.
             // See https://github.com/informalsystems/tendermint-rs/issues/235
         }
 
+        // Check votes are valid
+        verify_signatures(&signed_votes)?;
+
         let voting_power = VotingPowerTally {
             total: self.total_power_of(validator_set),
             tallied: tallied_voting_power,
@@ -179,6 +221,50 @@ impl VotingPowerCalculator for ProdVotingPowerCalculator {
     }
 }
 
+/// Number of signatures verified by each parallel task
+const SIGNATURES_PER_BATCH: usize = 64;
+
+/// Verify the signatures of the given votes by batches, in parallel with the `rayon` feature.
+///
+/// Every signature is verified on its own, with the cofactorless verification equation full
+/// nodes check, so that commits are accepted exactly as full nodes accept them. Ed25519 batch
+/// verification, see `PublicKey::verify_batch`, checks the cofactored equation instead, and may
+/// thus accept signatures with small-order components which full nodes reject.
+fn verify_signatures(
+    signed_votes: &[(Validator, Vec<u8>, SignedVote)],
+) -> Result<(), VerificationError> {
+    let verify_batch =
+        |batch: &[(Validator, Vec<u8>, SignedVote)]| -> Result<(), VerificationError> {
+            for (validator, sign_bytes, signed_vote) in batch {
+                if validator
+                    .verify_signature(sign_bytes, signed_vote.signature())
+                    .is_err()
+                {
+                    bail!(VerificationError::InvalidSignature {
+                        signature: signed_vote.signature().to_bytes(),
+                        validator: Box::new(validator.clone()),
+                        sign_bytes: sign_bytes.clone(),
+                    });
+                }
+            }
+
+            Ok(())
+        };
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        signed_votes
+            .par_chunks(SIGNATURES_PER_BATCH)
+            .try_for_each(verify_batch)
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    signed_votes
+        .chunks(SIGNATURES_PER_BATCH)
+        .try_for_each(verify_batch)
+}
+
 fn non_absent_vote(commit_sig: &CommitSig, validator_index: u64, commit: &Commit) -> Option<Vote> {
     let (validator_address, timestamp, signature, block_id) = match commit_sig {
         CommitSig::BlockIDFlagAbsent { .. } => return None,
@@ -215,11 +301,83 @@ fn non_absent_vote(commit_sig: &CommitSig, validator_index: u64, commit: &Commit
 mod tests {
     use super::*;
     use serde::Deserialize;
+    use std::convert::TryFrom;
     use std::fs;
     use std::path::Path;
+    use tendermint::signature::{Ed25519 as Ed25519Signature, Signature};
+    use tendermint::trust_threshold::TrustThreshold as _;
 
     const TEST_FILES_PATH: &str = "./tests/support/voting_power/";
 
+    #[test]
+    fn commit_threshold_is_configurable() {
+        let height = Height::from(1_u64);
+        let default = ProdVotingPowerCalculator::default().commit_threshold(height);
+        let one_half = ProdVotingPowerCalculator::new(TrustThreshold::ONE_HALF)
+            .unwrap()
+            .commit_threshold(height);
+
+        assert!(!default.is_enough_power(60, 100));
+        assert!(one_half.is_enough_power(60, 100));
+        assert!(!one_half.is_enough_power(50, 100));
+    }
+
+    #[test]
+    fn commit_threshold_out_of_range_is_rejected() {
+        let one_tenth = TrustThreshold {
+            numerator: 1,
+            denominator: 10,
+        };
+        let more_than_all = TrustThreshold {
+            numerator: 3,
+            denominator: 2,
+        };
+
+        for threshold in vec![one_tenth, more_than_all] {
+            let error = ProdVotingPowerCalculator::new(threshold).unwrap_err();
+            assert_eq!(error.kind(), &ErrorKind::InvalidCommitThreshold(threshold));
+        }
+    }
+
+    #[test]
+    fn commit_threshold_switches_at_scheduled_height() {
+        let calculator = ProdVotingPowerCalculator::with_schedule(
+            ThresholdSchedule::default()
+                .switching_at(Height::from(100_u64), TrustThreshold::ONE_HALF)
+                .unwrap(),
+        );
+
+        let before = calculator.commit_threshold(Height::from(99_u64));
+        let after = calculator.commit_threshold(Height::from(100_u64));
+
+        assert!(!before.is_enough_power(60, 100));
+        assert!(after.is_enough_power(60, 100));
+    }
+
+    #[test]
+    fn invalid_signature_is_reported() {
+        let tc = read_test_case(Path::new(TEST_FILES_PATH).join("3_1_one_invalid_signature.json"));
+
+        let tally = ProdVotingPowerCalculator::default().voting_power_in(
+            &tc.signed_header,
+            &tc.validator_set,
+            TrustThreshold::default(),
+        );
+
+        match tally {
+            Err(VerificationError::InvalidSignature {
+                signature,
+                validator,
+                sign_bytes,
+            }) => {
+                let signature = Ed25519Signature::try_from(signature.as_slice()).unwrap();
+                let signature = Signature::Ed25519(signature);
+                assert!(validator.verify_signature(&sign_bytes, &signature).is_err());
+            }
+            result => panic!("expected InvalidSignature, got: {:?}", result),
+        }
+    }
+
     #[test]
     fn json_testcases() {
         run_all_tests();
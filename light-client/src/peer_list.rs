@@ -7,8 +7,56 @@ use crate::{
 };
 
 use contracts::{post, pre};
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeSet, HashMap};
 
+/// Health of a peer, as observed by the supervisor.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerHealth {
+    /// Number of light blocks the peer provided
+    pub responses: u64,
+    /// Number of requests to the peer which failed or timed out
+    pub failures: u64,
+    /// Number of times the light block of the peer matched the verified one
+    pub agreements: u64,
+    /// Number of times the peer provided a conflicting or invalid light block
+    pub disagreements: u64,
+}
+
+impl PeerHealth {
+    /// Score between 0 (unhealthy) and 1 (healthy) of the peer, starting at 1/2 for peers
+    /// without any observation.
+    pub fn score(&self) -> f64 {
+        let good = self.responses + self.agreements;
+        let bad = self.failures + self.disagreements;
+
+        (good + 1) as f64 / (good + bad + 2) as f64
+    }
+}
+
+/// Roles and health of the peers of a `PeerList`, eg. to persist them across restarts.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PeerListState {
+    /// The primary peer
+    pub primary: PeerId,
+    /// The witnesses
+    pub witnesses: BTreeSet<PeerId>,
+    /// The full nodes
+    pub full_nodes: BTreeSet<PeerId>,
+    /// The faulty nodes
+    pub faulty_nodes: BTreeSet<PeerId>,
+    /// The health of the peers
+    pub health: HashMap<PeerId, PeerHealth>,
+}
+
+/// Role of a peer other than the primary
+#[derive(Copy, Clone, Debug)]
+enum Role {
+    Witness,
+    FullNode,
+    Faulty,
+}
+
 /// A generic container mapping `PeerId`s to some type `T`,
 /// which keeps track of the primary peer, witnesses, full nodes,
 /// and faulty nodes. Provides lifecycle methods to swap the primary,
@@ -21,6 +69,7 @@ pub struct PeerList<T> {
     witnesses: BTreeSet<PeerId>,
     full_nodes: BTreeSet<PeerId>,
     faulty_nodes: BTreeSet<PeerId>,
+    health: HashMap<PeerId, PeerHealth>,
 }
 
 impl<T> PeerList<T> {
@@ -96,6 +145,20 @@ impl<T> PeerList<T> {
         &self.witnesses
     }
 
+    /// Get mutable references to the instances of all the witnesses, ordered by peer id
+    pub fn witnesses_mut(&mut self) -> Vec<&mut T> {
+        let witnesses = &self.witnesses;
+
+        let mut values = self
+            .values
+            .iter_mut()
+            .filter(|(peer_id, _)| witnesses.contains(peer_id))
+            .collect::<Vec<_>>();
+
+        values.sort_by_key(|(peer_id, _)| **peer_id);
+        values.into_iter().map(|(_, value)| value).collect()
+    }
+
     /// Get all the full nodes peer ids
     pub fn full_nodes_ids(&self) -> &BTreeSet<PeerId> {
         &self.full_nodes
@@ -106,6 +169,117 @@ impl<T> PeerList<T> {
         &self.faulty_nodes
     }
 
+    /// Get the health of the given peer, as recorded so far.
+    pub fn health(&self, peer_id: &PeerId) -> PeerHealth {
+        self.health.get(peer_id).copied().unwrap_or_default()
+    }
+
+    /// Record whether the given peer responded to a request.
+    pub fn record_response(&mut self, peer_id: PeerId, responded: bool) {
+        let health = self.health.entry(peer_id).or_default();
+        if responded {
+            health.responses += 1;
+        } else {
+            health.failures += 1;
+        }
+    }
+
+    /// Record whether the light block provided by the given peer matched the verified one.
+    pub fn record_agreement(&mut self, peer_id: PeerId, agreed: bool) {
+        let health = self.health.entry(peer_id).or_default();
+        if agreed {
+            health.agreements += 1;
+        } else {
+            health.disagreements += 1;
+        }
+    }
+
+    /// Register the given peer id and value as a new witness, eg. while the supervisor is running.
+    ///
+    /// ## Precondition
+    /// - The given peer must not already be in the list
+    #[pre(!self.values.contains_key(&peer_id))]
+    #[post(Self::invariant(&self))]
+    pub fn add_witness(&mut self, peer_id: PeerId, value: T) {
+        self.values.insert(peer_id, value);
+        self.witnesses.insert(peer_id);
+    }
+
+    /// Remove the given peer from the list altogether, and return its value if it was listed.
+    ///
+    /// ## Precondition
+    /// - The given peer id must not be the primary peer id.
+    #[pre(peer_id != self.primary)]
+    #[post(Self::invariant(&self))]
+    pub fn remove(&mut self, peer_id: PeerId) -> Option<T> {
+        self.witnesses.remove(&peer_id);
+        self.full_nodes.remove(&peer_id);
+        self.faulty_nodes.remove(&peer_id);
+        self.health.remove(&peer_id);
+
+        self.values.remove(&peer_id)
+    }
+
+    /// Get the roles and health of the peers.
+    pub fn state(&self) -> PeerListState {
+        PeerListState {
+            primary: self.primary,
+            witnesses: self.witnesses.clone(),
+            full_nodes: self.full_nodes.clone(),
+            faulty_nodes: self.faulty_nodes.clone(),
+            health: self.health.clone(),
+        }
+    }
+
+    /// Restore the roles and health of the peers from the given state, eg. after a restart.
+    ///
+    /// Peers which are not in this list are ignored, and peers which are not in the given state
+    /// keep their current role, except for the current primary which becomes a witness if
+    /// another primary is restored.
+    #[post(Self::invariant(&self))]
+    pub fn restore(&mut self, state: &PeerListState) {
+        if self.values.contains_key(&state.primary) && state.primary != self.primary {
+            let previous = self.primary;
+            self.move_to(state.primary, None);
+            self.primary = state.primary;
+            self.move_to(previous, Some(Role::Witness));
+        }
+
+        for (ids, role) in &[
+            (&state.witnesses, Role::Witness),
+            (&state.full_nodes, Role::FullNode),
+            (&state.faulty_nodes, Role::Faulty),
+        ] {
+            for id in ids.iter() {
+                if *id != self.primary && self.values.contains_key(id) {
+                    self.move_to(*id, Some(*role));
+                }
+            }
+        }
+
+        self.health = state
+            .health
+            .iter()
+            .filter(|(id, _)| self.values.contains_key(id))
+            .map(|(id, health)| (*id, *health))
+            .collect();
+    }
+
+    /// Remove the given peer from the witnesses, full nodes and faulty nodes, and add it to the
+    /// ones of the given role, if any.
+    fn move_to(&mut self, peer_id: PeerId, role: Option<Role>) {
+        self.witnesses.remove(&peer_id);
+        self.full_nodes.remove(&peer_id);
+        self.faulty_nodes.remove(&peer_id);
+
+        match role {
+            Some(Role::Witness) => self.witnesses.insert(peer_id),
+            Some(Role::FullNode) => self.full_nodes.insert(peer_id),
+            Some(Role::Faulty) => self.faulty_nodes.insert(peer_id),
+            None => false,
+        };
+    }
+
     /// Remove the given peer from the list of witnesses,
     /// and mark it as faulty. Get a new witness from
     /// the list of full nodes, if there are any left.
@@ -132,7 +306,7 @@ impl<T> PeerList<T> {
         result
     }
 
-    /// Mark the primary as faulty and swap it for the next available witness, if any.
+    /// Mark the primary as faulty and swap it for the healthiest witness, if any.
     /// Returns the new primary on success.
     ///
     /// ## Errors
@@ -144,7 +318,16 @@ impl<T> PeerList<T> {
     ) -> Result<PeerId, Error> {
         self.faulty_nodes.insert(self.primary);
 
-        if let Some(new_primary) = self.witnesses.iter().next().copied() {
+        // Witnesses are ordered by id, so the first is picked among the healthiest
+        let mut healthiest: Option<(PeerId, f64)> = None;
+        for witness in &self.witnesses {
+            let score = self.health(witness).score();
+            if healthiest.map_or(true, |(_, best)| score > best) {
+                healthiest = Some((*witness, score));
+            }
+        }
+
+        if let Some((new_primary, _)) = healthiest {
             self.primary = new_primary;
             self.witnesses.remove(&new_primary);
             return Ok(new_primary);
@@ -227,6 +410,7 @@ impl<T> PeerListBuilder<T> {
             witnesses: self.witnesses,
             full_nodes: self.full_nodes,
             faulty_nodes: self.faulty_nodes,
+            health: HashMap::new(),
         }
     }
 }
@@ -307,6 +491,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn replace_faulty_primary_picks_healthiest_witness() {
+        let mut peer_list = dummy_peer_list();
+        peer_list.add_witness(d(), 4_u32);
+        assert_eq!(peer_list.witnesses_ids().to_vec(), vec![b(), d()]);
+
+        peer_list.record_response(b(), false);
+        peer_list.record_agreement(d(), true);
+        assert!(peer_list.health(&d()).score() > peer_list.health(&b()).score());
+
+        let new_primary = peer_list.replace_faulty_primary(None);
+        assert_eq!(new_primary.unwrap(), d());
+        assert_eq!(peer_list.primary(), &4);
+    }
+
+    #[test]
+    fn restore_succeeds() {
+        let mut peer_list = dummy_peer_list();
+        peer_list.record_response(c(), true);
+
+        let mut state = peer_list.state();
+        state.primary = c();
+        state.full_nodes.clear();
+        state.faulty_nodes.insert(d());
+
+        let mut restored = dummy_peer_list();
+        restored.restore(&state);
+
+        assert_eq!(restored.primary_id(), c());
+        assert_eq!(restored.witnesses_ids().to_vec(), vec![b(), a()]);
+        assert!(restored.full_nodes_ids().is_empty());
+        assert!(restored.faulty_nodes_ids().is_empty());
+        assert_eq!(restored.health(&c()).responses, 1);
+    }
+
+    #[test]
+    fn remove_succeeds() {
+        let mut peer_list = dummy_peer_list();
+        assert_eq!(peer_list.remove(b()), Some(2));
+        assert!(peer_list.witnesses_ids().is_empty());
+        assert_eq!(peer_list.remove(b()), None);
+        assert!(PeerList::invariant(&peer_list));
+    }
+
     #[test]
     fn replace_faulty_witness_succeeds() {
         let mut peer_list = dummy_peer_list();
@@ -0,0 +1,119 @@
+//! Persistence of the runtime state of supervisors, so that they resume where they left off
+//! after a restart.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bail,
+    errors::{Error, ErrorKind},
+    peer_list::{PeerList, PeerListState},
+    supervisor::Instance,
+    types::{LightBlock, Status},
+};
+
+/// A fork whose evidence has not been reported to both the primary and the forked witness yet.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PendingFork {
+    /// Light block provided by the primary
+    pub primary: LightBlock,
+    /// Conflicting light block provided by the witness
+    pub witness: LightBlock,
+    /// Whether the evidence of the fork was reported to the witness already
+    #[serde(default)]
+    pub reported_to_witness: bool,
+    /// Whether the evidence of the fork was reported to the primary already
+    #[serde(default)]
+    pub reported_to_primary: bool,
+}
+
+impl PendingFork {
+    /// A fork between the given light blocks of the primary and the witness, whose evidence has
+    /// not been reported to either of them yet.
+    pub fn new(primary: LightBlock, witness: LightBlock) -> Self {
+        Self {
+            primary,
+            witness,
+            reported_to_witness: false,
+            reported_to_primary: false,
+        }
+    }
+
+    /// Whether the evidence of the fork was reported to both the primary and the witness
+    pub fn is_reported(&self) -> bool {
+        self.reported_to_witness && self.reported_to_primary
+    }
+}
+
+/// Runtime state of a supervisor.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SupervisorState {
+    /// Latest block trusted by the primary
+    pub latest_trusted: Option<LightBlock>,
+    /// Roles and health of the peers
+    pub peers: PeerListState,
+    /// Forks whose evidence remains to be reported
+    pub pending_forks: Vec<PendingFork>,
+}
+
+impl SupervisorState {
+    /// Load the state saved at the given path, if any.
+    pub fn load(path: impl AsRef<Path>) -> Result<Option<Self>, Error> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => bail!(ErrorKind::Store.context(e)),
+        };
+
+        let state = serde_cbor::from_slice(&bytes).map_err(|e| ErrorKind::Store.context(e))?;
+        Ok(Some(state))
+    }
+
+    /// Save the state at the given path.
+    ///
+    /// The state is first written next to the given path and then moved to it, so that a
+    /// complete state is found at the given path even after a crash.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let tmp_path = path.with_extension("tmp");
+
+        let bytes = serde_cbor::to_vec(self).map_err(|e| ErrorKind::Store.context(e))?;
+        fs::write(&tmp_path, bytes).map_err(|e| ErrorKind::Store.context(e))?;
+        fs::rename(&tmp_path, path).map_err(|e| ErrorKind::Store.context(e))?;
+
+        Ok(())
+    }
+}
+
+/// Runtime state of a supervisor with the given peers and pending forks.
+pub(crate) fn snapshot(
+    peers: &PeerList<Instance>,
+    pending_forks: &[PendingFork],
+) -> SupervisorState {
+    SupervisorState {
+        latest_trusted: peers.primary().latest_trusted(),
+        peers: peers.state(),
+        pending_forks: pending_forks.to_vec(),
+    }
+}
+
+/// Restore the roles and health of the given peers from the given state, and trust its latest
+/// trusted block if the primary does not trust a more recent one yet.
+pub(crate) fn restore(peers: &mut PeerList<Instance>, state: &SupervisorState) {
+    peers.restore(&state.peers);
+
+    if let Some(latest_trusted) = &state.latest_trusted {
+        let primary = peers.primary_mut();
+        let trusted_height = primary.latest_trusted().map(|lb| lb.height());
+
+        if trusted_height.map_or(true, |height| height < latest_trusted.height()) {
+            primary
+                .state
+                .light_store
+                .insert(latest_trusted.clone(), Status::Trusted);
+        }
+    }
+}
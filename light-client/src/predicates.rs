@@ -5,6 +5,7 @@ use crate::{
     light_client::Options,
     operations::{CommitValidator, Hasher, VotingPowerCalculator},
     types::{Header, LightBlock, SignedHeader, Time, TrustThreshold, ValidatorSet},
+    upgrades::UpgradeSchedule,
 };
 
 use errors::VerificationError;
@@ -23,7 +24,8 @@ impl VerificationPredicates for ProdPredicates {}
 /// A default, spec abiding implementation is provided for each method.
 ///
 /// This enables test implementations to only override a single method rather than
-/// have to re-define every predicate.
+/// have to re-define every predicate, and chain-specific implementations to add their own
+/// checks with `additional_checks`.
 pub trait VerificationPredicates: Send {
     /// Compare the provided validator_set_hash against the hash produced from hashing the validator
     /// set.
@@ -32,7 +34,10 @@ pub trait VerificationPredicates: Send {
         light_block: &LightBlock,
         hasher: &dyn Hasher,
     ) -> Result<(), VerificationError> {
-        let validators_hash = hasher.hash_validator_set(&light_block.validators);
+        let validators_hash = hasher.hash_validator_set_expecting(
+            &light_block.validators,
+            light_block.signed_header.header.validators_hash,
+        );
 
         ensure!(
             light_block.signed_header.header.validators_hash == validators_hash,
@@ -51,7 +56,10 @@ pub trait VerificationPredicates: Send {
         light_block: &LightBlock,
         hasher: &dyn Hasher,
     ) -> Result<(), VerificationError> {
-        let next_validators_hash = hasher.hash_validator_set(&light_block.next_validators);
+        let next_validators_hash = hasher.hash_validator_set_expecting(
+            &light_block.next_validators,
+            light_block.signed_header.header.next_validators_hash,
+        );
 
         ensure!(
             light_block.signed_header.header.next_validators_hash == next_validators_hash,
@@ -147,6 +155,31 @@ pub trait VerificationPredicates: Send {
         Ok(())
     }
 
+    /// Check that the untrusted header carries the chain-id of the trusted one, or the one of
+    /// the latest upgrade scheduled between them.
+    fn is_matching_chain_id(
+        &self,
+        untrusted_header: &Header,
+        trusted_header: &Header,
+        upgrades: &UpgradeSchedule,
+    ) -> Result<(), VerificationError> {
+        let expected = upgrades.expected_chain_id(
+            trusted_header.height,
+            trusted_header.chain_id,
+            untrusted_header.height,
+        );
+
+        ensure!(
+            untrusted_header.chain_id == expected,
+            VerificationError::ChainIdMismatch {
+                got: untrusted_header.chain_id,
+                expected,
+            }
+        );
+
+        Ok(())
+    }
+
     /// Check that the height increased between the trusted header and the untrusted one.
     fn is_monotonic_height(
         &self,
@@ -209,6 +242,16 @@ pub trait VerificationPredicates: Send {
 
         Ok(())
     }
+
+    /// Additional checks of the untrusted light block, eg. chain-specific header checks.
+    /// There are none by default.
+    fn additional_checks(
+        &self,
+        _untrusted: &LightBlock,
+        _trusted: &LightBlock,
+    ) -> Result<(), VerificationError> {
+        Ok(())
+    }
 }
 
 /// Validate the given light block.
@@ -218,16 +261,19 @@ pub trait VerificationPredicates: Send {
 /// - Ensure the header next validator hashes match the given next validators
 /// - Additional implementation specific validation via `commit_validator`
 /// - Check that the untrusted block is more recent than the trusted state
+/// - Check that the untrusted block carries the expected chain-id, as per the given upgrades
 /// - If the untrusted block is the very next block after the trusted block,
 /// check that their (next) validator sets hashes match.
 /// - Otherwise, ensure that the untrusted block has a greater height than
 /// the trusted block.
+/// - Perform the additional checks of the predicates, if any.
 #[allow(clippy::too_many_arguments)]
 pub fn verify(
     vp: &dyn VerificationPredicates,
     voting_power_calculator: &dyn VotingPowerCalculator,
     commit_validator: &dyn CommitValidator,
     hasher: &dyn Hasher,
+    upgrades: &UpgradeSchedule,
     trusted: &LightBlock,
     untrusted: &LightBlock,
     options: &Options,
@@ -261,6 +307,14 @@ pub fn verify(
         &trusted.signed_header.header,
     )?;
 
+    // Check that the untrusted block carries the chain-id of the trusted state,
+    // or the one of a scheduled upgrade
+    vp.is_matching_chain_id(
+        &untrusted.signed_header.header,
+        &trusted.signed_header.header,
+        upgrades,
+    )?;
+
     let trusted_next_height = trusted.height().increment();
 
     if untrusted.height() == trusted_next_height {
@@ -285,12 +339,16 @@ pub fn verify(
         )?;
     }
 
-    // Verify that more than 2/3 of the validators correctly committed the block.
+    // Verify that more than the commit threshold (2/3 by default) of the validators correctly
+    // committed the block.
     vp.has_sufficient_signers_overlap(
         &untrusted.signed_header,
         &untrusted.validators,
         voting_power_calculator,
     )?;
 
+    // Perform the additional checks of the predicates, if any
+    vp.additional_checks(&untrusted, &trusted)?;
+
     Ok(())
 }
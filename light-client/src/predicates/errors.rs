@@ -4,6 +4,8 @@ use anomaly::{BoxError, Context};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use tendermint::chain;
+
 use crate::errors::ErrorExt;
 use crate::operations::voting_power::VotingPowerTally;
 use crate::types::{Hash, Height, Time, Validator, ValidatorAddress};
@@ -75,6 +77,16 @@ pub enum VerificationError {
         validators_hash: Hash,
     },
 
+    /// The chain-id of the untrusted header is neither the trusted one nor the one of a
+    /// scheduled upgrade
+    #[error("chain-id mismatch: got={got} expected={expected}")]
+    ChainIdMismatch {
+        /// Chain-id of the untrusted header
+        got: chain::Id,
+        /// Expected chain-id
+        expected: chain::Id,
+    },
+
     /// Unexpected header of non-increasing height compared to what was expected
     #[error("non increasing height: got={got} expected={expected}")]
     NonIncreasingHeight {
@@ -0,0 +1,142 @@
+//! Renewal of the trusted state of supervisors before it expires, so that light clients which
+//! are seldom asked to verify blocks do not end up unable to sync.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::clock::Clock,
+    errors::ErrorKind,
+    types::{Height, LightBlock, Time},
+};
+
+/// When to renew the trusted state of a supervisor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenewalPolicy {
+    /// Renew the trusted state once it expires within this margin,
+    /// typically a fraction of the trusting period
+    pub margin: Duration,
+    /// How often to check whether the trusted state must be renewed
+    pub check_interval: Duration,
+}
+
+impl RenewalPolicy {
+    /// Whether a trusted state expiring at the given time must be renewed by now.
+    pub fn is_due(&self, expires_at: Time, now: Time) -> bool {
+        expires_at < now + self.margin
+    }
+}
+
+/// Warning that the trusted state of a supervisor expires soon and could not be renewed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExpiryWarning {
+    /// Height of the latest trusted light block
+    pub trusted_height: Height,
+    /// Time at which the latest trusted light block expires
+    pub expires_at: Time,
+    /// Error the renewal failed with
+    pub error: ErrorKind,
+}
+
+/// Hook through which an application is warned that the trusted state of a supervisor
+/// expires soon, eg. to log it or page an operator.
+pub trait ExpiryHook: Send {
+    /// Called every time the renewal of a trusted state expiring soon fails.
+    fn expiring(&self, warning: &ExpiryWarning);
+}
+
+impl<F: Send> ExpiryHook for F
+where
+    F: Fn(&ExpiryWarning),
+{
+    fn expiring(&self, warning: &ExpiryWarning) {
+        self(warning)
+    }
+}
+
+/// Renews the trusted state of a supervisor according to a [`RenewalPolicy`], warning its
+/// [`ExpiryHook`]s when the renewal fails.
+pub struct Renewal {
+    policy: RenewalPolicy,
+    clock: Box<dyn Clock>,
+    hooks: Vec<Box<dyn ExpiryHook>>,
+}
+
+impl std::fmt::Debug for Renewal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Renewal")
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl Renewal {
+    /// Constructs a new renewal with the given policy, using the given clock to tell when the
+    /// trusted state expires.
+    pub fn new(policy: RenewalPolicy, clock: impl Clock + 'static) -> Self {
+        Self {
+            policy,
+            clock: Box::new(clock),
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Add a hook which is warned when the renewal of a trusted state fails.
+    pub fn with_hook(mut self, hook: impl ExpiryHook + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// The renewal policy.
+    pub fn policy(&self) -> &RenewalPolicy {
+        &self.policy
+    }
+
+    /// Time at which the given trusted light block expires, if it must be renewed by now.
+    pub fn due(&self, latest_trusted: &LightBlock, trusting_period: Duration) -> Option<Time> {
+        let expires_at = latest_trusted.signed_header.header.time + trusting_period;
+
+        if self.policy.is_due(expires_at, self.clock.now()) {
+            Some(expires_at)
+        } else {
+            None
+        }
+    }
+
+    /// Warn the hooks that the renewal of the given trusted light block, expiring at the given
+    /// time, failed with the given error.
+    pub(crate) fn warn(&self, latest_trusted: &LightBlock, expires_at: Time, error: &ErrorKind) {
+        let warning = ExpiryWarning {
+            trusted_height: latest_trusted.height(),
+            expires_at,
+            error: error.clone(),
+        };
+
+        for hook in &self.hooks {
+            hook.expiring(&warning);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> Time {
+        Time::unix_epoch() + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn due_within_margin() {
+        let policy = RenewalPolicy {
+            margin: Duration::from_secs(100),
+            check_interval: Duration::from_secs(10),
+        };
+
+        assert!(!policy.is_due(at(1_000), at(800)));
+        assert!(!policy.is_due(at(1_000), at(900)));
+        assert!(policy.is_due(at(1_000), at(901)));
+        assert!(policy.is_due(at(1_000), at(1_100)));
+    }
+}
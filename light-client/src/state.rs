@@ -1,6 +1,7 @@
 //! State maintained by the light client.
 
 use crate::{
+    audit::{AuditLog, VerificationRecord},
     store::LightStore,
     types::{Height, LightBlock, Status},
 };
@@ -19,6 +20,9 @@ pub struct State {
 
     /// Records which blocks were needed to verify a target block, eg. during bisection.
     pub verification_trace: VerificationTrace,
+
+    /// Records every verification decision, if enabled with `with_audit_log`.
+    pub audit_log: Option<AuditLog>,
 }
 
 impl State {
@@ -27,6 +31,20 @@ impl State {
         Self {
             light_store: Box::new(light_store),
             verification_trace: VerificationTrace::new(),
+            audit_log: None,
+        }
+    }
+
+    /// Record every verification decision in the audit log from now on.
+    pub fn with_audit_log(mut self) -> Self {
+        self.audit_log = Some(AuditLog::new());
+        self
+    }
+
+    /// Record the given verification decision in the audit log, if enabled.
+    pub fn record(&mut self, record: impl FnOnce() -> VerificationRecord) {
+        if let Some(audit_log) = &mut self.audit_log {
+            audit_log.push(record());
         }
     }
 
@@ -1,13 +1,21 @@
 //! Interface and implementations of the light block store.
 //!
-//! See the `memory` and `sled` modules for:
+//! See the `memory`, `sled` and `rocksdb` modules for:
 //! - a transient, in-memory implementation for testing purposes
-//! - a persistent, on-disk, sled-backed implementation for production
+//! - a persistent, on-disk, sled-backed implementation for production (`sled` feature, default)
+//! - a persistent, on-disk, rocksdb-backed implementation for production (`rocksdb` feature)
+//!
+//! See the `pruning` module for pruning the light blocks accumulated in a store.
 
 use crate::std_ext;
 use crate::types::{Height, LightBlock, Status};
 
 pub mod memory;
+pub mod pruning;
+
+#[cfg(feature = "rocksdb")]
+pub mod rocksdb;
+#[cfg(feature = "sled")]
 pub mod sled;
 
 /// Store for light blocks.
@@ -23,6 +31,9 @@ pub trait LightStore: std::fmt::Debug + Send {
     fn get(&self, height: Height, status: Status) -> Option<LightBlock>;
 
     /// Update the `status` of the given `light_block`.
+    ///
+    /// The light block is removed from the other statuses. Persistent stores do so atomically,
+    /// so that a light block is never found with two statuses or none after a restart.
     fn update(&mut self, light_block: &LightBlock, status: Status);
 
     /// Insert a new light block in the store with the given status.
@@ -0,0 +1,189 @@
+//! Pruning of the light blocks accumulated in a light store.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::clock::Clock,
+    store::LightStore,
+    types::{Height, LightBlock, Status, Time},
+};
+
+/// Statuses of the light blocks subject to pruning
+const PRUNED_STATUSES: &[Status] = &[Status::Trusted, Status::Verified];
+
+/// Which trusted and verified light blocks to keep when pruning a light store.
+///
+/// A light block is kept as long as one of the enabled rules keeps it. When no rule is enabled,
+/// as is the case by default, no light block is pruned. The latest trusted and verified light
+/// blocks are always kept, as they are needed to verify the next ones.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PruningPolicy {
+    /// Keep the given number of light blocks of greatest height
+    pub keep_last: Option<usize>,
+    /// Keep the light blocks whose header time is within the given period,
+    /// typically the trusting period
+    pub keep_within: Option<Duration>,
+    /// Keep the light blocks whose height is a multiple of the given interval
+    pub checkpoint_interval: Option<u64>,
+}
+
+impl PruningPolicy {
+    /// Whether this policy keeps the light block with the given height and header time,
+    /// `rank` being the number of light blocks of greater height.
+    pub fn keeps(&self, height: Height, time: Time, rank: usize, now: Time) -> bool {
+        if *self == Self::default() {
+            return true;
+        }
+
+        let keep_last = self.keep_last.map_or(false, |n| rank < n);
+        let keep_within = self.keep_within.map_or(false, |period| time + period > now);
+        let checkpoint = self.checkpoint_interval.map_or(false, |interval| {
+            interval > 0 && height.value() % interval == 0
+        });
+
+        keep_last || keep_within || checkpoint
+    }
+}
+
+/// Hook through which an application can veto the pruning of light blocks it still needs.
+pub trait PruningHook: Send {
+    /// Whether the given light block may be pruned.
+    fn may_prune(&self, light_block: &LightBlock) -> bool;
+}
+
+impl<F: Send> PruningHook for F
+where
+    F: Fn(&LightBlock) -> bool,
+{
+    fn may_prune(&self, light_block: &LightBlock) -> bool {
+        self(light_block)
+    }
+}
+
+/// Prunes the trusted and verified light blocks of a light store according to a
+/// [`PruningPolicy`], unless vetoed by one of its [`PruningHook`]s.
+pub struct Pruner {
+    policy: PruningPolicy,
+    clock: Box<dyn Clock>,
+    hooks: Vec<Box<dyn PruningHook>>,
+}
+
+impl std::fmt::Debug for Pruner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pruner")
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl Pruner {
+    /// Constructs a new pruner with the given policy, using the given clock to tell which light
+    /// blocks are within the period of time to keep.
+    pub fn new(policy: PruningPolicy, clock: impl Clock + 'static) -> Self {
+        Self {
+            policy,
+            clock: Box::new(clock),
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Add a hook which can veto the pruning of light blocks.
+    pub fn with_hook(mut self, hook: impl PruningHook + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// The pruning policy.
+    pub fn policy(&self) -> &PruningPolicy {
+        &self.policy
+    }
+
+    /// Prune the trusted and verified light blocks of the given light store.
+    /// Returns the heights of the pruned light blocks.
+    pub fn prune(&self, light_store: &mut dyn LightStore) -> Vec<Height> {
+        let now = self.clock.now();
+
+        let latest = PRUNED_STATUSES
+            .iter()
+            .filter_map(|status| light_store.latest(*status))
+            .map(|lb| lb.height())
+            .collect::<Vec<_>>();
+
+        let mut blocks = PRUNED_STATUSES
+            .iter()
+            .flat_map(|status| light_store.all(*status).map(move |lb| (lb, *status)))
+            .collect::<Vec<_>>();
+
+        // Greatest heights first, so that the rank of a block is its index
+        blocks.sort_by_key(|(lb, _)| std::cmp::Reverse(lb.height()));
+
+        let mut pruned = Vec::new();
+
+        for (rank, (light_block, status)) in blocks.iter().enumerate() {
+            let height = light_block.height();
+
+            let keep = latest.contains(&height)
+                || self
+                    .policy
+                    .keeps(height, light_block.signed_header.header.time, rank, now)
+                || self.hooks.iter().any(|hook| !hook.may_prune(light_block));
+
+            if !keep {
+                light_store.remove(height, *status);
+                pruned.push(height);
+            }
+        }
+
+        pruned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> Time {
+        Time::unix_epoch() + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn default_policy_keeps_everything() {
+        let policy = PruningPolicy::default();
+        assert!(policy.keeps(Height::from(1_u64), at(0), 100, at(1_000)));
+    }
+
+    #[test]
+    fn keep_last() {
+        let policy = PruningPolicy {
+            keep_last: Some(2),
+            ..PruningPolicy::default()
+        };
+
+        assert!(policy.keeps(Height::from(10_u64), at(0), 1, at(0)));
+        assert!(!policy.keeps(Height::from(9_u64), at(0), 2, at(0)));
+    }
+
+    #[test]
+    fn keep_within() {
+        let policy = PruningPolicy {
+            keep_within: Some(Duration::from_secs(100)),
+            ..PruningPolicy::default()
+        };
+
+        assert!(policy.keeps(Height::from(1_u64), at(950), 10, at(1_000)));
+        assert!(!policy.keeps(Height::from(1_u64), at(900), 10, at(1_000)));
+    }
+
+    #[test]
+    fn checkpoints() {
+        let policy = PruningPolicy {
+            checkpoint_interval: Some(100),
+            ..PruningPolicy::default()
+        };
+
+        assert!(policy.keeps(Height::from(200_u64), at(0), 10, at(1_000)));
+        assert!(!policy.keeps(Height::from(201_u64), at(0), 10, at(1_000)));
+    }
+}
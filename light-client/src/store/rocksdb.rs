@@ -0,0 +1,160 @@
+//! Persistent store backed by an on-disk `rocksdb` database.
+
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use ::rocksdb::{Direction, IteratorMode, WriteBatch, DB};
+
+use crate::{
+    errors::{Error, ErrorKind},
+    types::{Height, LightBlock},
+};
+
+use super::{LightStore, Status};
+
+const UNVERIFIED_PREFIX: &[u8] = b"light_store/unverified/";
+const VERIFIED_PREFIX: &[u8] = b"light_store/verified/";
+const TRUSTED_PREFIX: &[u8] = b"light_store/trusted/";
+const FAILED_PREFIX: &[u8] = b"light_store/failed/";
+
+/// Persistent store backed by an on-disk `rocksdb` database.
+///
+/// Light blocks are keyed by their status and big-endian height, so that blocks of the same
+/// status are stored in order of their height.
+#[derive(Clone)]
+pub struct RocksDbStore {
+    db: Arc<DB>,
+}
+
+impl RocksDbStore {
+    /// Create a new persistent store from a rocksdb database
+    pub fn new(db: DB) -> Self {
+        Self { db: Arc::new(db) }
+    }
+
+    /// Open, or create, a persistent store in the rocksdb database at the given path
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let db = DB::open_default(path).map_err(|e| ErrorKind::Store.context(e))?;
+        Ok(Self::new(db))
+    }
+
+    fn prefix(status: Status) -> &'static [u8] {
+        match status {
+            Status::Unverified => UNVERIFIED_PREFIX,
+            Status::Verified => VERIFIED_PREFIX,
+            Status::Trusted => TRUSTED_PREFIX,
+            Status::Failed => FAILED_PREFIX,
+        }
+    }
+
+    fn key(status: Status, height: Height) -> Vec<u8> {
+        let mut key = Self::prefix(status).to_vec();
+        key.extend_from_slice(&height.value().to_be_bytes());
+        key
+    }
+
+    fn get_block(&self, height: Height, status: Status) -> Result<Option<LightBlock>, Error> {
+        let value_bytes = self
+            .db
+            .get(Self::key(status, height))
+            .map_err(|e| ErrorKind::Store.context(e))?;
+
+        match value_bytes {
+            Some(bytes) => {
+                let value =
+                    serde_cbor::from_slice(&bytes).map_err(|e| ErrorKind::Store.context(e))?;
+                Ok(Some(value))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Apply the given write to the database
+    fn write(&self, f: impl FnOnce(&mut WriteBatch) -> Result<(), Error>) -> Result<(), Error> {
+        let mut batch = WriteBatch::default();
+        f(&mut batch)?;
+
+        self.db
+            .write(batch)
+            .map_err(|e| ErrorKind::Store.context(e))?;
+
+        Ok(())
+    }
+
+    /// Iterate over the light blocks with the given status, starting from the given height, in
+    /// the given direction.
+    fn iter_from(
+        &self,
+        status: Status,
+        height: Height,
+        direction: Direction,
+    ) -> impl Iterator<Item = LightBlock> + '_ {
+        let prefix = Self::prefix(status);
+
+        self.db
+            .iterator(IteratorMode::From(&Self::key(status, height), direction))
+            .take_while(move |(key, _)| key.starts_with(prefix))
+            .map(|(_, value)| serde_cbor::from_slice(&value))
+            .flatten()
+    }
+}
+
+impl fmt::Debug for RocksDbStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RocksDbStore")
+            .field("path", &self.db.path())
+            .finish()
+    }
+}
+
+impl LightStore for RocksDbStore {
+    fn get(&self, height: Height, status: Status) -> Option<LightBlock> {
+        self.get_block(height, status).ok().flatten()
+    }
+
+    fn update(&mut self, light_block: &LightBlock, status: Status) {
+        // Moving the block between statuses is atomic, so that it is never found with two
+        // statuses or none, even after a crash.
+        self.write(|batch| {
+            let height = light_block.height();
+            for other in Status::iter() {
+                if status != *other {
+                    batch.delete(Self::key(*other, height));
+                }
+            }
+
+            let value = serde_cbor::to_vec(light_block).map_err(|e| ErrorKind::Store.context(e))?;
+            batch.put(Self::key(status, height), value);
+            Ok(())
+        })
+        .ok();
+    }
+
+    fn insert(&mut self, light_block: LightBlock, status: Status) {
+        self.write(|batch| {
+            let value =
+                serde_cbor::to_vec(&light_block).map_err(|e| ErrorKind::Store.context(e))?;
+            batch.put(Self::key(status, light_block.height()), value);
+            Ok(())
+        })
+        .ok();
+    }
+
+    fn remove(&mut self, height: Height, status: Status) {
+        self.db.delete(Self::key(status, height)).ok();
+    }
+
+    fn latest(&self, status: Status) -> Option<LightBlock> {
+        self.iter_from(status, Height::from(u64::MAX), Direction::Reverse)
+            .next()
+    }
+
+    fn all(&self, status: Status) -> Box<dyn Iterator<Item = LightBlock>> {
+        let blocks = self
+            .iter_from(status, Height::from(0_u64), Direction::Forward)
+            .collect::<Vec<_>>();
+
+        Box::new(blocks.into_iter())
+    }
+}
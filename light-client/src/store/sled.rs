@@ -2,7 +2,10 @@
 
 pub mod utils;
 
+use std::path::Path;
+
 use crate::{
+    errors::{Error, ErrorKind},
     store::sled::utils::*,
     types::{Height, LightBlock},
 };
@@ -37,6 +40,34 @@ impl SledStore {
         }
     }
 
+    /// Open, or create, a persistent store in the sled database at the given path
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let db = ::sled::open(path).map_err(|e| ErrorKind::Store.context(e))?;
+        Ok(Self::new(db))
+    }
+
+    /// Move the given light block to the given status, atomically removing it from the other
+    /// statuses, so that it is never found with two statuses or none, even after a crash.
+    fn move_to(&self, light_block: &LightBlock, status: Status) -> Result<(), Error> {
+        let height = light_block.height();
+        let mut batch = ::sled::Batch::default();
+
+        for other in Status::iter() {
+            if status != *other {
+                self.db(*other).remove_in(&mut batch, &height)?;
+            }
+        }
+
+        self.db(status)
+            .insert_in(&mut batch, &height, light_block)?;
+
+        self.db
+            .apply_batch(batch)
+            .map_err(|e| ErrorKind::Store.context(e))?;
+
+        Ok(())
+    }
+
     fn db(&self, status: Status) -> &KeyValueDb<Height, LightBlock> {
         match status {
             Status::Unverified => &self.unverified_db,
@@ -53,15 +84,7 @@ impl LightStore for SledStore {
     }
 
     fn update(&mut self, light_block: &LightBlock, status: Status) {
-        let height = light_block.height();
-
-        for other in Status::iter() {
-            if status != *other {
-                self.db(*other).remove(&self.db, &height).ok();
-            }
-        }
-
-        self.db(status).insert(&self.db, &height, light_block).ok();
+        self.move_to(light_block, status).ok();
     }
 
     fn insert(&mut self, light_block: LightBlock, status: Status) {
@@ -125,9 +125,32 @@ where
         Ok(())
     }
 
+    /// Add the insertion of a value associated with a key within this view to the given batch,
+    /// to be applied atomically with the other operations of the batch.
+    pub fn insert_in(&self, batch: &mut sled::Batch, key: &K, value: &V) -> Result<(), Error> {
+        let key_bytes = serde_cbor::to_vec(&key).map_err(|e| ErrorKind::Store.context(e))?;
+        let prefixed_key_bytes = self.prefixed_key(key_bytes);
+        let value_bytes = serde_cbor::to_vec(&value).map_err(|e| ErrorKind::Store.context(e))?;
+
+        batch.insert(prefixed_key_bytes, value_bytes);
+
+        Ok(())
+    }
+
+    /// Add the removal of the value associated with a key within this view to the given batch,
+    /// to be applied atomically with the other operations of the batch.
+    pub fn remove_in(&self, batch: &mut sled::Batch, key: &K) -> Result<(), Error> {
+        let key_bytes = serde_cbor::to_vec(&key).map_err(|e| ErrorKind::Store.context(e))?;
+        let prefixed_key_bytes = self.prefixed_key(key_bytes);
+
+        batch.remove(prefixed_key_bytes);
+
+        Ok(())
+    }
+
     /// Iterate over all values within this view in the given sled database.
     pub fn iter(&self, db: &sled::Db) -> impl DoubleEndedIterator<Item = V> {
-        db.iter()
+        db.scan_prefix(&self.prefix)
             .flatten()
             .map(|(_, v)| serde_cbor::from_slice(&v))
             .flatten()
@@ -1,43 +1,63 @@
 //! Supervisor and Handle implementation.
 
 use crossbeam_channel as channel;
+use std::path::PathBuf;
+use std::time::Instant;
 
+use tendermint::abci::transaction::Hash;
 use tendermint::evidence::{ConflictingHeadersEvidence, Evidence};
 
-use crate::bail;
+use crate::components::io::IoError;
 use crate::errors::{Error, ErrorKind};
 use crate::evidence::EvidenceReporter;
 use crate::fork_detector::{Fork, ForkDetection, ForkDetector};
 use crate::light_client::LightClient;
+use crate::metrics::{MetricsRecorder, Recorder};
 use crate::peer_list::PeerList;
+use crate::persistence::{self, PendingFork, SupervisorState};
+use crate::renewal::Renewal;
 use crate::state::State;
-use crate::types::{Height, LatestStatus, LightBlock, PeerId, Status};
+use crate::store::pruning::Pruner;
+use crate::types::{Height, LatestStatus, LightBlock, PeerId, Status, Time};
+use crate::{bail, ensure};
 
 /// Provides an interface to the supervisor for use in downstream code.
+///
+/// Every method fails with `ErrorKind::Unsupported` unless implemented.
 pub trait Handle {
     /// Get latest trusted block.
     fn latest_trusted(&self) -> Result<Option<LightBlock>, Error> {
-        todo!()
+        bail!(ErrorKind::Unsupported("latest_trusted".to_string()))
     }
 
     /// Get the latest status.
     fn latest_status(&self) -> Result<LatestStatus, Error> {
-        todo!()
+        bail!(ErrorKind::Unsupported("latest_status".to_string()))
     }
 
     /// Verify to the highest block.
     fn verify_to_highest(&self) -> Result<LightBlock, Error> {
-        todo!()
+        bail!(ErrorKind::Unsupported("verify_to_highest".to_string()))
     }
 
     /// Verify to the block at the given height.
     fn verify_to_target(&self, _height: Height) -> Result<LightBlock, Error> {
-        todo!()
+        bail!(ErrorKind::Unsupported("verify_to_target".to_string()))
+    }
+
+    /// Add the given instance as a new witness. Returns `false` if the peer was already listed.
+    fn add_witness(&self, _peer_id: PeerId, _instance: Instance) -> Result<bool, Error> {
+        bail!(ErrorKind::Unsupported("add_witness".to_string()))
+    }
+
+    /// Remove the given witness. Returns `false` if the peer was not a witness.
+    fn remove_witness(&self, _peer_id: PeerId) -> Result<bool, Error> {
+        bail!(ErrorKind::Unsupported("remove_witness".to_string()))
     }
 
     /// Terminate the underlying [`Supervisor`].
     fn terminate(&self) -> Result<(), Error> {
-        todo!()
+        bail!(ErrorKind::Unsupported("terminate".to_string()))
     }
 }
 
@@ -55,6 +75,10 @@ enum HandleInput {
     LatestTrusted(channel::Sender<Option<LightBlock>>),
     /// Get the current status of the LightClient
     GetStatus(channel::Sender<LatestStatus>),
+    /// Add a new witness, call the provided callback with whether it was added
+    AddWitness(PeerId, Box<Instance>, channel::Sender<bool>),
+    /// Remove a witness, call the provided callback with whether it was removed
+    RemoveWitness(PeerId, channel::Sender<bool>),
 }
 
 /// A light client `Instance` packages a `LightClient` together with its `State`.
@@ -124,10 +148,8 @@ impl Instance {
 /// }
 /// ```
 pub struct Supervisor {
-    /// List of peers and their instances (primary, witnesses, full and faulty nodes)
-    peers: PeerList<Instance>,
-    /// An instance of the fork detector
-    fork_detector: Box<dyn ForkDetector>,
+    /// State and verification steps shared with the `AsyncSupervisor`
+    core: SupervisorCore,
     /// Reporter of fork evidence
     evidence_reporter: Box<dyn EvidenceReporter>,
     /// Channel through which to reply to `Handle`s
@@ -139,7 +161,7 @@ pub struct Supervisor {
 impl std::fmt::Debug for Supervisor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Supervisor")
-            .field("peers", &self.peers)
+            .field("peers", &self.core.peers)
             .finish()
     }
 }
@@ -157,30 +179,293 @@ impl Supervisor {
         let (sender, receiver) = channel::unbounded::<HandleInput>();
 
         Self {
-            peers,
+            core: SupervisorCore::new(peers, Box::new(fork_detector)),
+            evidence_reporter: Box::new(evidence_reporter),
             sender,
             receiver,
-            fork_detector: Box::new(fork_detector),
-            evidence_reporter: Box::new(evidence_reporter),
         }
     }
 
+    /// Only trust a light block once the given number of providers, including the primary,
+    /// returned matching light blocks, instead of as soon as no witness contradicts the primary.
+    ///
+    /// Witnesses which do not respond or provide invalid light blocks do not count towards the
+    /// quorum, so that the verification fails with `ErrorKind::NoQuorum` if too few are left.
+    pub fn with_quorum(mut self, quorum: usize) -> Self {
+        self.core.quorum = quorum;
+        self
+    }
+
+    /// Save the state of the supervisor at the given path after every verification, resuming
+    /// from the state previously saved there, if any.
+    pub fn with_state_path(mut self, path: impl Into<PathBuf>) -> Result<Self, Error> {
+        self.core.restore(path.into())?;
+        Ok(self)
+    }
+
+    /// Get the runtime state of the supervisor.
+    pub fn state(&self) -> SupervisorState {
+        self.core.state()
+    }
+
+    /// Report the divergences of witnesses, the trusted heights and the size of the light store
+    /// of the primary to the given recorder, eg. a `PrometheusMetrics` with the
+    /// `metrics-prometheus` feature.
+    pub fn with_metrics_recorder(mut self, recorder: impl MetricsRecorder + 'static) -> Self {
+        self.core.metrics = Some(Recorder(std::sync::Arc::new(recorder)));
+        self
+    }
+
+    /// Prune the light stores of the instances with the given pruner after every successful
+    /// verification.
+    pub fn with_pruner(mut self, pruner: Pruner) -> Self {
+        self.core.pruner = Some(pruner);
+        self
+    }
+
+    /// Renew the trusted state with the given renewal while running, by verifying the highest
+    /// block whenever the latest trusted block expires within the margin of its policy.
+    pub fn with_renewal(mut self, renewal: Renewal) -> Self {
+        self.core.renewal = Some(renewal);
+        self
+    }
+
     /// Create a new handle to this supervisor.
     pub fn handle(&mut self) -> impl Handle {
         SupervisorHandle::new(self.sender.clone())
     }
 
-    fn latest_trusted(&self) -> Option<LightBlock> {
-        self.peers.primary().latest_trusted()
-    }
-
     /// Verify to the highest block.
     pub fn verify_to_highest(&mut self) -> Result<LightBlock, Error> {
-        self.verify(None)
+        let result = self.verify(None);
+        self.core.save_state()?;
+        result
     }
 
-    /// Return latest trusted status summary.
-    fn latest_status(&mut self) -> LatestStatus {
+    /// Verify to the block at the given height.
+    pub fn verify_to_target(&mut self, height: Height) -> Result<LightBlock, Error> {
+        let result = self.verify(Some(height));
+        self.core.save_state()?;
+        result
+    }
+
+    /// Renew the trusted state if it is due for renewal, warning the hooks of the renewal if
+    /// that fails.
+    fn renew(&mut self) {
+        let (latest_trusted, expires_at) = match self.core.renewal_due() {
+            Some(due) => due,
+            None => return,
+        };
+
+        if let Err(e) = self.verify_to_highest() {
+            if let Some(renewal) = &self.core.renewal {
+                renewal.warn(&latest_trusted, expires_at, e.kind());
+            }
+        }
+    }
+
+    /// Time of the next check of the renewal of the trusted state, if any.
+    fn next_renewal_check(&self) -> Option<Instant> {
+        self.core
+            .renewal
+            .as_ref()
+            .map(|renewal| Instant::now() + renewal.policy().check_interval)
+    }
+
+    /// Verify either to the latest block (if `height == None`) or to a given block (if `height ==
+    /// Some(height)`).
+    fn verify(&mut self, height: Option<Height>) -> Result<LightBlock, Error> {
+        // Retry reporting the evidence of forks which could not be reported so far
+        self.report_pending_forks();
+
+        loop {
+            let primary = self.core.peers.primary_mut();
+
+            // Perform light client core verification for the given height (or highest).
+            let verdict = match height {
+                None => primary.light_client.verify_to_highest(&mut primary.state),
+                Some(height) => primary
+                    .light_client
+                    .verify_to_target(height, &mut primary.state),
+            };
+
+            let verified_block = match self.core.check_verdict(verdict)? {
+                Some(verified_block) => verified_block,
+                // Verification failed, continue with the new primary
+                None => continue,
+            };
+
+            // Perform fork detection with the highest verified block and the trusted block.
+            let trusted_block = self.core.trusted_block()?;
+            let detection = self.core.detect_forks(&verified_block, &trusted_block)?;
+
+            match self.core.conclude(verified_block, detection)? {
+                // No fork detected, exiting
+                Conclusion::Trusted(verified_block) => return Ok(verified_block),
+                // There was a fork or a faulty peer
+                Conclusion::Detected(forked) => {
+                    self.report_pending_forks();
+
+                    if !forked.is_empty() {
+                        // Fork detected, exiting
+                        bail!(ErrorKind::ForkDetected(forked))
+                    }
+
+                    // If there were no hard forks, perform verification again
+                }
+            }
+        }
+    }
+
+    /// Report the evidence of the pending forks, keeping the reports which could not be
+    /// delivered pending until the next verification.
+    fn report_pending_forks(&mut self) {
+        for (report, evidence) in self.core.pending_reports() {
+            let result = self.evidence_reporter.report(evidence, report.provider);
+            self.core.record_report(report, result);
+        }
+
+        self.core.clear_reported_forks();
+    }
+
+    /// Run the supervisor event loop in the same thread.
+    ///
+    /// This method should typically be called within a new thread with `std::thread::spawn`.
+    pub fn run(mut self) -> Result<(), Error> {
+        let mut next_renewal_check = self.next_renewal_check();
+
+        loop {
+            let event = match next_renewal_check {
+                Some(check_at) => {
+                    let timeout = check_at.saturating_duration_since(Instant::now());
+
+                    match self.receiver.recv_timeout(timeout) {
+                        Ok(event) => event,
+                        Err(channel::RecvTimeoutError::Timeout) => {
+                            self.renew();
+                            next_renewal_check = self.next_renewal_check();
+                            continue;
+                        }
+                        Err(channel::RecvTimeoutError::Disconnected) => {
+                            bail!(ErrorKind::ChannelDisconnected)
+                        }
+                    }
+                }
+                None => self.receiver.recv().map_err(ErrorKind::from)?,
+            };
+
+            match event {
+                HandleInput::LatestTrusted(sender) => {
+                    let outcome = self.core.latest_trusted();
+                    sender.send(outcome).map_err(ErrorKind::from)?;
+                }
+                HandleInput::Terminate(sender) => {
+                    sender.send(()).map_err(ErrorKind::from)?;
+                    return Ok(());
+                }
+                HandleInput::VerifyToTarget(height, sender) => {
+                    let outcome = self.verify_to_target(height);
+                    sender.send(outcome).map_err(ErrorKind::from)?;
+                }
+                HandleInput::VerifyToHighest(sender) => {
+                    let outcome = self.verify_to_highest();
+                    sender.send(outcome).map_err(ErrorKind::from)?;
+                }
+                HandleInput::GetStatus(sender) => {
+                    let outcome = self.core.latest_status();
+                    sender.send(outcome).map_err(ErrorKind::from)?;
+                }
+                HandleInput::AddWitness(peer_id, instance, sender) => {
+                    let outcome = self.core.add_witness(peer_id, *instance);
+                    sender.send(outcome).map_err(ErrorKind::from)?;
+                }
+                HandleInput::RemoveWitness(peer_id, sender) => {
+                    let outcome = self.core.remove_witness(peer_id);
+                    sender.send(outcome).map_err(ErrorKind::from)?;
+                }
+            }
+        }
+    }
+}
+
+/// Conclusion of a verification step once the witnesses have been checked against the primary
+#[derive(Debug)]
+pub(crate) enum Conclusion {
+    /// No witness contradicts the verified light block, which is now trusted
+    Trusted(LightBlock),
+    /// Forks were detected with the given witnesses, if any, or faulty witnesses were replaced
+    Detected(Vec<PeerId>),
+}
+
+/// State of a supervisor and the steps of its verifications, shared by the [`Supervisor`] and
+/// the `AsyncSupervisor`, which only differ in how they wait for light blocks and report
+/// evidence.
+pub(crate) struct SupervisorCore {
+    /// List of peers and their instances (primary, witnesses, full and faulty nodes)
+    pub(crate) peers: PeerList<Instance>,
+    /// An instance of the fork detector
+    pub(crate) fork_detector: Box<dyn ForkDetector>,
+    /// Pruner of the light stores of the instances, if any
+    pub(crate) pruner: Option<Pruner>,
+    /// Recorder of metrics, if any
+    pub(crate) metrics: Option<Recorder>,
+    /// Number of providers, including the primary, which must return matching light blocks
+    pub(crate) quorum: usize,
+    /// Forks whose evidence remains to be reported
+    pub(crate) pending_forks: Vec<PendingFork>,
+    /// Path at which the state of the supervisor is saved after every verification, if any
+    pub(crate) state_path: Option<PathBuf>,
+    /// Renewal of the trusted state before it expires, if any
+    pub(crate) renewal: Option<Renewal>,
+}
+
+impl SupervisorCore {
+    /// Constructs the state of a supervisor of the given peers, with a quorum of one.
+    pub(crate) fn new(peers: PeerList<Instance>, fork_detector: Box<dyn ForkDetector>) -> Self {
+        Self {
+            peers,
+            fork_detector,
+            pruner: None,
+            metrics: None,
+            quorum: 1,
+            pending_forks: Vec::new(),
+            state_path: None,
+            renewal: None,
+        }
+    }
+
+    /// Resume from the state saved at the given path, if any, and save the state there from now
+    /// on.
+    pub(crate) fn restore(&mut self, path: PathBuf) -> Result<(), Error> {
+        if let Some(state) = SupervisorState::load(&path)? {
+            persistence::restore(&mut self.peers, &state);
+            self.pending_forks = state.pending_forks;
+        }
+
+        self.state_path = Some(path);
+        Ok(())
+    }
+
+    /// Get the runtime state of the supervisor.
+    pub(crate) fn state(&self) -> SupervisorState {
+        persistence::snapshot(&self.peers, &self.pending_forks)
+    }
+
+    /// Save the state of the supervisor, if it has a state path.
+    pub(crate) fn save_state(&self) -> Result<(), Error> {
+        match &self.state_path {
+            Some(path) => self.state().save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Latest trusted light block of the primary.
+    pub(crate) fn latest_trusted(&self) -> Option<LightBlock> {
+        self.peers.primary().latest_trusted()
+    }
+
+    /// Latest trusted status summary.
+    pub(crate) fn latest_status(&self) -> LatestStatus {
         let latest_trusted = self.peers.primary().latest_trusted();
         let mut connected_nodes: Vec<PeerId> = Vec::new();
         connected_nodes.push(self.peers.primary_id());
@@ -198,80 +483,155 @@ impl Supervisor {
         }
     }
 
-    /// Verify to the block at the given height.
-    pub fn verify_to_target(&mut self, height: Height) -> Result<LightBlock, Error> {
-        self.verify(Some(height))
+    /// Latest trusted light block of the primary and the time at which it expires, if it is due
+    /// for renewal.
+    pub(crate) fn renewal_due(&self) -> Option<(LightBlock, Time)> {
+        let renewal = self.renewal.as_ref()?;
+        let primary = self.peers.primary();
+        let latest_trusted = primary.latest_trusted()?;
+        let expires_at = renewal.due(
+            &latest_trusted,
+            primary.light_client.options.trusting_period,
+        )?;
+
+        Some((latest_trusted, expires_at))
     }
 
-    /// Verify either to the latest block (if `height == None`) or to a given block (if `height ==
-    /// Some(height)`).
-    fn verify(&mut self, height: Option<Height>) -> Result<LightBlock, Error> {
-        let primary = self.peers.primary_mut();
-
-        // Perform light client core verification for the given height (or highest).
-        let verdict = match height {
-            None => primary.light_client.verify_to_highest(&mut primary.state),
-            Some(height) => primary
-                .light_client
-                .verify_to_target(height, &mut primary.state),
-        };
+    /// Add the given instance as a new witness, unless the peer is already listed.
+    pub(crate) fn add_witness(&mut self, peer_id: PeerId, instance: Instance) -> bool {
+        if self.peers.get(&peer_id).is_some() {
+            return false;
+        }
 
-        match verdict {
-            // Verification succeeded, let's perform fork detection
-            Ok(verified_block) => {
-                let trusted_block = primary
-                    .latest_trusted()
-                    .ok_or_else(|| ErrorKind::NoTrustedState(Status::Trusted))?;
-
-                // Perform fork detection with the highest verified block and the trusted block.
-                let outcome = self.detect_forks(&verified_block, &trusted_block)?;
-
-                match outcome {
-                    // There was a fork or a faulty peer
-                    ForkDetection::Detected(forks) => {
-                        let forked = self.process_forks(forks)?;
-                        if !forked.is_empty() {
-                            // Fork detected, exiting
-                            bail!(ErrorKind::ForkDetected(forked))
-                        }
+        self.peers.add_witness(peer_id, instance);
+        true
+    }
 
-                        // If there were no hard forks, perform verification again
-                        self.verify(height)
-                    }
-                    ForkDetection::NotDetected => {
-                        // We need to re-ask for the primary here as the compiler
-                        // is not smart enough to realize that we do not mutate
-                        // the `primary` field of `PeerList` between the initial
-                        // borrow of the primary and here (can't blame it, it's
-                        // not that obvious).
-                        self.peers.primary_mut().trust_block(&verified_block);
-
-                        // No fork detected, exiting
-                        Ok(verified_block)
-                    }
-                }
-            }
-            // Verification failed
+    /// Remove the given witness, if it is one.
+    pub(crate) fn remove_witness(&mut self, peer_id: PeerId) -> bool {
+        if !self.peers.witnesses_ids().contains(&peer_id) {
+            return false;
+        }
+
+        self.peers.remove(peer_id).is_some()
+    }
+
+    /// Record the verdict of the primary, returning the verified light block if verification
+    /// succeeded. Otherwise, swap the primary so that verification can continue with the new
+    /// one, if there is any witness left.
+    pub(crate) fn check_verdict(
+        &mut self,
+        verdict: Result<LightBlock, Error>,
+    ) -> Result<Option<LightBlock>, Error> {
+        let primary_id = self.peers.primary_id();
+        self.peers.record_response(primary_id, verdict.is_ok());
+
+        match verdict {
+            Ok(verified_block) => Ok(Some(verified_block)),
             Err(err) => {
-                // Swap primary, and continue with new primary, if there is any witness left.
                 self.peers.replace_faulty_primary(Some(err))?;
-                self.verify(height)
+                Ok(None)
             }
         }
     }
 
-    fn process_forks(&mut self, forks: Vec<Fork>) -> Result<Vec<PeerId>, Error> {
+    /// Latest trusted light block of the primary, against which to detect forks.
+    pub(crate) fn trusted_block(&self) -> Result<LightBlock, Error> {
+        Ok(self
+            .latest_trusted()
+            .ok_or_else(|| ErrorKind::NoTrustedState(Status::Trusted))?)
+    }
+
+    /// Perform fork detection with the given verified block and trusted block.
+    pub(crate) fn detect_forks(
+        &self,
+        verified_block: &LightBlock,
+        trusted_block: &LightBlock,
+    ) -> Result<ForkDetection, Error> {
+        if self.peers.witnesses_ids().is_empty() {
+            bail!(ErrorKind::NoWitnesses);
+        }
+
+        let witnesses = self
+            .peers
+            .witnesses_ids()
+            .iter()
+            .filter_map(|id| self.peers.get(id))
+            .collect();
+
+        self.fork_detector
+            .detect_forks(verified_block, trusted_block, witnesses)
+    }
+
+    /// Perform fork detection with the given verified block and trusted block, without blocking
+    /// the current thread.
+    pub(crate) async fn detect_forks_async(
+        &mut self,
+        verified_block: &LightBlock,
+        trusted_block: &LightBlock,
+    ) -> Result<ForkDetection, Error> {
+        if self.peers.witnesses_ids().is_empty() {
+            bail!(ErrorKind::NoWitnesses);
+        }
+
+        let witnesses = self.peers.witnesses_mut();
+
+        self.fork_detector
+            .detect_forks_async(verified_block, trusted_block, witnesses)
+            .await
+    }
+
+    /// Conclude the verification of the given light block given the outcome of fork detection.
+    ///
+    /// If no witness contradicts the primary, the light block is trusted once the quorum is
+    /// reached. Otherwise, the forks are queued for their evidence to be reported, and the
+    /// faulty witnesses are replaced.
+    pub(crate) fn conclude(
+        &mut self,
+        verified_block: LightBlock,
+        detection: ForkDetection,
+    ) -> Result<Conclusion, Error> {
+        let forks = match detection {
+            ForkDetection::Detected(forks) => forks,
+            ForkDetection::NotDetected => Vec::new(),
+        };
+
+        self.record_fork_detection(&forks);
+        if let Some(metrics) = &self.metrics {
+            metrics.record_forks(&forks);
+        }
+
+        if !forks.is_empty() {
+            return Ok(Conclusion::Detected(self.process_forks(forks)));
+        }
+
+        // All the witnesses returned a light block matching the primary's
+        self.check_quorum()?;
+
+        self.peers.primary_mut().trust_block(&verified_block);
+
+        self.prune();
+
+        if let Some(metrics) = &self.metrics {
+            let light_store = self.peers.primary().state.light_store.as_ref();
+            metrics.record_trusted(&verified_block, light_store);
+        }
+
+        Ok(Conclusion::Trusted(verified_block))
+    }
+
+    /// Queue the evidence of the actual forks for reporting and replace the faulty witnesses,
+    /// returning the forked peers.
+    fn process_forks(&mut self, forks: Vec<Fork>) -> Vec<PeerId> {
         let mut forked = Vec::with_capacity(forks.len());
 
         for fork in forks {
             match fork {
-                // An actual fork was detected, report evidence and record forked peer.
-                // TODO: also report to primary
+                // An actual fork was detected, report evidence to both the witness and
+                // the primary, and record forked peer.
                 Fork::Forked { primary, witness } => {
-                    let provider = witness.provider;
-                    self.report_evidence(provider, &primary, &witness)?;
-
-                    forked.push(provider);
+                    forked.push(witness.provider);
+                    self.pending_forks.push(PendingFork::new(primary, witness));
                 }
                 // A witness has timed out, remove it from the peer list.
                 Fork::Timeout(provider, _error) => {
@@ -286,82 +646,153 @@ impl Supervisor {
             }
         }
 
-        Ok(forked)
+        forked
     }
 
-    /// Report the given evidence of a fork.
-    fn report_evidence(
-        &mut self,
-        provider: PeerId,
-        primary: &LightBlock,
-        witness: &LightBlock,
-    ) -> Result<(), Error> {
-        let evidence = ConflictingHeadersEvidence::new(
-            primary.signed_header.clone(),
-            witness.signed_header.clone(),
-        );
-
-        self.evidence_reporter
-            .report(Evidence::ConflictingHeaders(Box::new(evidence)), provider)
-            .map_err(ErrorKind::Io)?;
+    /// Record the health of the witnesses after fork detection: witnesses without fork agree
+    /// with the primary, whereas the others either did not respond in time or disagree with it.
+    fn record_fork_detection(&mut self, forks: &[Fork]) {
+        let witnesses = self
+            .peers
+            .witnesses_ids()
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+
+        for witness in witnesses {
+            let fork = forks.iter().find(|fork| match fork {
+                Fork::Forked { witness: block, .. } => block.provider == witness,
+                Fork::Timeout(provider, _) => *provider == witness,
+                Fork::Faulty(block, _) => block.provider == witness,
+            });
+
+            match fork {
+                None => {
+                    self.peers.record_response(witness, true);
+                    self.peers.record_agreement(witness, true);
+                }
+                Some(Fork::Timeout(_, _)) => self.peers.record_response(witness, false),
+                Some(Fork::Forked { .. }) | Some(Fork::Faulty(_, _)) => {
+                    self.peers.record_response(witness, true);
+                    self.peers.record_agreement(witness, false);
+                }
+            }
+        }
+    }
+
+    /// Check that the primary and the witnesses reach the quorum, assuming they all returned
+    /// matching light blocks.
+    fn check_quorum(&self) -> Result<(), Error> {
+        let matching = 1 + self.peers.witnesses_ids().len();
+        let quorum = self.quorum;
+        ensure!(matching >= quorum, ErrorKind::NoQuorum { matching, quorum });
 
         Ok(())
     }
 
-    /// Perform fork detection with the given verified block and trusted block.
-    fn detect_forks(
-        &self,
-        verified_block: &LightBlock,
-        trusted_block: &LightBlock,
-    ) -> Result<ForkDetection, Error> {
-        if self.peers.witnesses_ids().is_empty() {
-            bail!(ErrorKind::NoWitnesses);
-        }
+    /// Evidence of the pending forks which remains to be reported, each to be recorded with
+    /// `record_report` once reported, made of the header the peer did not provide and the header
+    /// it provided.
+    pub(crate) fn pending_reports(&self) -> Vec<(EvidenceReport, Evidence)> {
+        let evidence = |conflicting: &LightBlock, own: &LightBlock| {
+            Evidence::ConflictingHeaders(Box::new(ConflictingHeadersEvidence::new(
+                conflicting.signed_header.clone(),
+                own.signed_header.clone(),
+            )))
+        };
 
-        let witnesses = self
-            .peers
-            .witnesses_ids()
-            .iter()
-            .filter_map(|id| self.peers.get(id))
-            .collect();
+        let mut reports = Vec::new();
 
-        self.fork_detector
-            .detect_forks(verified_block, &trusted_block, witnesses)
+        for (index, fork) in self.pending_forks.iter().enumerate() {
+            if !fork.reported_to_witness {
+                let report = EvidenceReport {
+                    fork: index,
+                    target: ReportTarget::Witness,
+                    provider: fork.witness.provider,
+                };
+                reports.push((report, evidence(&fork.primary, &fork.witness)));
+            }
+
+            if !fork.reported_to_primary {
+                let report = EvidenceReport {
+                    fork: index,
+                    target: ReportTarget::Primary,
+                    provider: fork.primary.provider,
+                };
+                reports.push((report, evidence(&fork.witness, &fork.primary)));
+            }
+        }
+
+        reports
     }
 
-    /// Run the supervisor event loop in the same thread.
+    /// Record the outcome of the given report, so that it is not attempted again once delivered.
     ///
-    /// This method should typically be called within a new thread with `std::thread::spawn`.
-    pub fn run(mut self) -> Result<(), Error> {
-        loop {
-            let event = self.receiver.recv().map_err(ErrorKind::from)?;
+    /// A report which could not be delivered is logged and remains pending, without failing the
+    /// verification, so that it is attempted again on the next verification.
+    pub(crate) fn record_report(&mut self, report: EvidenceReport, result: Result<Hash, IoError>) {
+        let fork = match self.pending_forks.get_mut(report.fork) {
+            Some(fork) => fork,
+            None => return,
+        };
 
-            match event {
-                HandleInput::LatestTrusted(sender) => {
-                    let outcome = self.latest_trusted();
-                    sender.send(outcome).map_err(ErrorKind::from)?;
-                }
-                HandleInput::Terminate(sender) => {
-                    sender.send(()).map_err(ErrorKind::from)?;
-                    return Ok(());
-                }
-                HandleInput::VerifyToTarget(height, sender) => {
-                    let outcome = self.verify_to_target(height);
-                    sender.send(outcome).map_err(ErrorKind::from)?;
-                }
-                HandleInput::VerifyToHighest(sender) => {
-                    let outcome = self.verify_to_highest();
-                    sender.send(outcome).map_err(ErrorKind::from)?;
-                }
-                HandleInput::GetStatus(sender) => {
-                    let outcome = self.latest_status();
-                    sender.send(outcome).map_err(ErrorKind::from)?;
-                }
+        match result {
+            Ok(_) => match report.target {
+                ReportTarget::Witness => fork.reported_to_witness = true,
+                ReportTarget::Primary => fork.reported_to_primary = true,
+            },
+            Err(e) => tracing::warn!(
+                peer = %report.provider,
+                height = %fork.primary.height(),
+                error = %e,
+                "failed to report evidence of fork, will retry on next verification"
+            ),
+        }
+    }
+
+    /// Forget the pending forks whose evidence was reported to all the peers involved.
+    pub(crate) fn clear_reported_forks(&mut self) {
+        self.pending_forks.retain(|fork| !fork.is_reported());
+    }
+
+    /// Prune the light stores of the primary and the witnesses, if there is a pruner.
+    fn prune(&mut self) {
+        let pruner = match &self.pruner {
+            Some(pruner) => pruner,
+            None => return,
+        };
+
+        let mut ids = vec![self.peers.primary_id()];
+        ids.extend(self.peers.witnesses_ids().iter().copied());
+
+        for id in ids {
+            if let Some(instance) = self.peers.get_mut(&id) {
+                pruner.prune(instance.state.light_store.as_mut());
             }
         }
     }
 }
 
+/// Peer involved in a fork, to which its evidence is reported
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ReportTarget {
+    /// The forked witness
+    Witness,
+    /// The primary
+    Primary,
+}
+
+/// Report of the evidence of a pending fork to one of the peers involved
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct EvidenceReport {
+    /// Index of the fork among the pending forks
+    fork: usize,
+    /// Peer to report the evidence to, by role
+    target: ReportTarget,
+    /// Peer to report the evidence to
+    pub(crate) provider: PeerId,
+}
+
 /// A [`Handle`] to the [`Supervisor`] which allows to communicate with
 /// the supervisor across thread boundaries via message passing.
 struct SupervisorHandle {
@@ -414,6 +845,26 @@ impl Handle for SupervisorHandle {
         self.verify(|sender| HandleInput::VerifyToTarget(height, sender))
     }
 
+    fn add_witness(&self, peer_id: PeerId, instance: Instance) -> Result<bool, Error> {
+        let (sender, receiver) = channel::bounded::<bool>(1);
+
+        self.sender
+            .send(HandleInput::AddWitness(peer_id, Box::new(instance), sender))
+            .map_err(ErrorKind::from)?;
+
+        Ok(receiver.recv().map_err(ErrorKind::from)?)
+    }
+
+    fn remove_witness(&self, peer_id: PeerId) -> Result<bool, Error> {
+        let (sender, receiver) = channel::bounded::<bool>(1);
+
+        self.sender
+            .send(HandleInput::RemoveWitness(peer_id, sender))
+            .map_err(ErrorKind::from)?;
+
+        Ok(receiver.recv().map_err(ErrorKind::from)?)
+    }
+
     fn terminate(&self) -> Result<(), Error> {
         let (sender, receiver) = channel::bounded::<()>(1);
 
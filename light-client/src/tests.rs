@@ -7,9 +7,10 @@ use tendermint::abci::transaction::Hash;
 use tendermint_rpc as rpc;
 
 use crate::components::clock::Clock;
-use crate::components::io::{AtHeight, Io, IoError};
+use crate::components::io::{AsyncIo, AtHeight, Io, IoError};
 use crate::evidence::EvidenceReporter;
 use contracts::contract_trait;
+use futures::future::{self, BoxFuture, FutureExt};
 use std::collections::HashMap;
 use tendermint::block::Height as HeightStr;
 use tendermint::evidence::{Duration as DurationStr, Evidence};
@@ -132,6 +133,16 @@ impl Io for MockIo {
     }
 }
 
+impl AsyncIo for MockIo {
+    fn fetch_light_block_async(
+        &self,
+        peer: PeerId,
+        height: AtHeight,
+    ) -> BoxFuture<'_, Result<LightBlock, IoError>> {
+        future::ready(self.fetch_light_block(peer, height)).boxed()
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct MockEvidenceReporter;
 
@@ -0,0 +1,94 @@
+//! Schedule of the coordinated upgrades of a chain, through which the light client keeps
+//! verifying blocks across chain-id changes.
+
+use serde::{Deserialize, Serialize};
+
+use tendermint::chain;
+
+use crate::types::Height;
+
+/// Coordinated upgrade of a chain, eg. a hard fork, after which blocks carry a new chain-id.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainUpgrade {
+    /// Height of the first block carrying the new chain-id
+    pub height: Height,
+    /// Chain-id of the blocks from that height on
+    pub chain_id: chain::Id,
+}
+
+/// Known upgrades of a chain, consulted by the verifier to tell which chain-id the blocks it
+/// verifies must carry.
+///
+/// Without any upgrade, as is the case by default, the chain-id of untrusted blocks must be
+/// the one of the trusted block they are verified against.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UpgradeSchedule {
+    upgrades: Vec<ChainUpgrade>,
+}
+
+impl UpgradeSchedule {
+    /// Constructs a schedule of the given upgrades
+    pub fn new(mut upgrades: Vec<ChainUpgrade>) -> Self {
+        upgrades.sort_by_key(|upgrade| upgrade.height);
+        Self { upgrades }
+    }
+
+    /// The upgrades in this schedule, by increasing height
+    pub fn upgrades(&self) -> &[ChainUpgrade] {
+        &self.upgrades
+    }
+
+    /// The latest upgrade above the given trusted height and up to the given untrusted height,
+    /// if any.
+    pub fn upgrade_between(
+        &self,
+        trusted_height: Height,
+        untrusted_height: Height,
+    ) -> Option<&ChainUpgrade> {
+        self.upgrades
+            .iter()
+            .rev()
+            .find(|upgrade| trusted_height < upgrade.height && upgrade.height <= untrusted_height)
+    }
+
+    /// The chain-id expected at the given untrusted height, given the chain-id at the given
+    /// trusted height.
+    pub fn expected_chain_id(
+        &self,
+        trusted_height: Height,
+        trusted_chain_id: chain::Id,
+        untrusted_height: Height,
+    ) -> chain::Id {
+        self.upgrade_between(trusted_height, untrusted_height)
+            .map_or(trusted_chain_id, |upgrade| upgrade.chain_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upgrade(height: u64, chain_id: &str) -> ChainUpgrade {
+        ChainUpgrade {
+            height: Height::from(height),
+            chain_id: chain_id.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn expected_chain_id() {
+        let schedule = UpgradeSchedule::new(vec![upgrade(200, "chain-3"), upgrade(100, "chain-2")]);
+        let chain_1 = "chain-1".parse().unwrap();
+
+        let expected = |trusted: u64, untrusted: u64| {
+            schedule
+                .expected_chain_id(Height::from(trusted), chain_1, Height::from(untrusted))
+                .to_string()
+        };
+
+        assert_eq!(expected(10, 99), "chain-1");
+        assert_eq!(expected(10, 100), "chain-2");
+        assert_eq!(expected(10, 250), "chain-3");
+    }
+}
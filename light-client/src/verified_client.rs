@@ -0,0 +1,96 @@
+//! RPC client whose results are verified by the light client.
+
+use tendermint_rpc as rpc;
+use tendermint_rpc::endpoint::{block, commit, validators};
+
+use crate::components::io::{block_on, IoError};
+use crate::ensure;
+use crate::errors::{Error, ErrorKind};
+use crate::supervisor::Handle;
+use crate::types::{Height, LightBlock, PeerId};
+
+/// Decorates an RPC client so that blocks, commits and validator sets are
+/// only handed out after the light client behind the given [`Handle`] has
+/// verified the header at their height.
+///
+/// Commits and validator sets are taken from the verified light block
+/// itself. Blocks are fetched from the RPC client and checked against the
+/// verified header, including the transactions against its data hash.
+#[derive(Debug)]
+pub struct VerifiedClient<H> {
+    peer: PeerId,
+    rpc_client: rpc::Client,
+    handle: H,
+}
+
+impl<H: Handle> VerifiedClient<H> {
+    /// Create a new verified client fetching blocks from the given peer with
+    /// the given RPC client, and verifying them with the given handle
+    pub fn new(peer: PeerId, rpc_client: rpc::Client, handle: H) -> Self {
+        Self {
+            peer,
+            rpc_client,
+            handle,
+        }
+    }
+
+    /// Verified block at the given height
+    pub fn block(&self, height: Height) -> Result<block::Response, Error> {
+        let light_block = self.handle.verify_to_target(height)?;
+        self.fetch_block(&light_block)
+    }
+
+    /// Verified block at the highest height the light client can verify
+    pub fn latest_block(&self) -> Result<block::Response, Error> {
+        let light_block = self.handle.verify_to_highest()?;
+        self.fetch_block(&light_block)
+    }
+
+    /// Verified commit at the given height
+    pub fn commit(&self, height: Height) -> Result<commit::Response, Error> {
+        let light_block = self.handle.verify_to_target(height)?;
+
+        Ok(commit::Response {
+            signed_header: light_block.signed_header,
+            canonical: true,
+        })
+    }
+
+    /// Verified validator set at the given height
+    pub fn validators(&self, height: Height) -> Result<validators::Response, Error> {
+        let light_block = self.handle.verify_to_target(height)?;
+
+        let validators = light_block.validators.validators().clone();
+        Ok(validators::Response {
+            block_height: light_block.height(),
+            total: validators.len() as u32,
+            validators,
+        })
+    }
+
+    /// Fetch the block of the given verified light block and check it
+    /// against its header
+    fn fetch_block(&self, light_block: &LightBlock) -> Result<block::Response, Error> {
+        let response = block_on(self.rpc_client.block(light_block.height()), self.peer, None)
+            .map_err(ErrorKind::Io)?
+            .map_err(|e| ErrorKind::Io(IoError::from(e)))?;
+
+        let verified_header = &light_block.signed_header.header;
+        ensure!(
+            response.block.header.hash() == verified_header.hash(),
+            ErrorKind::ResponseMismatch(format!(
+                "header of block at height {} differs",
+                light_block.height()
+            ))
+        );
+        ensure!(
+            response.block.data.hash() == verified_header.data_hash,
+            ErrorKind::ResponseMismatch(format!(
+                "transactions of block at height {} differ",
+                light_block.height()
+            ))
+        );
+
+        Ok(response)
+    }
+}
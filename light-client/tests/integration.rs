@@ -40,6 +40,7 @@ fn make_instance(peer_id: PeerId, options: light_client::Options, io: ProdIo) ->
     let state = State {
         light_store: Box::new(light_store),
         verification_trace: HashMap::new(),
+        audit_log: None,
     };
 
     let verifier = ProdVerifier::default();
@@ -89,6 +90,9 @@ fn sync() {
         },
         trusting_period: Duration::from_secs(60 * 60), // 60 minutes
         clock_drift: Duration::from_secs(5 * 60),      // 5 minutes
+        max_bisection_depth: None,
+        request_timeout: None,
+        max_verification_time: None,
     };
 
     let primary_instance = make_instance(primary, options, io.clone());
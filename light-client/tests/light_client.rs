@@ -1,20 +1,26 @@
 use std::collections::HashMap;
 use std::time::Duration;
 
+use futures::executor::block_on;
+
 use tendermint_light_client::{
+    audit::AuditLog,
     components::{
         io::{AtHeight, Io},
         scheduler,
-        verifier::{ProdVerifier, Verdict, Verifier},
+        verifier::{verify_header, ProdVerifier, Verdict, Verifier},
     },
     errors::{Error, ErrorKind},
     light_client::{LightClient, Options},
+    predicates::{errors::VerificationError, ProdPredicates, VerificationPredicates},
     state::State,
     store::{memory::MemoryStore, LightStore},
     tests::{Trusted, *},
     types::{Height, LightBlock, Status, Time, TrustThreshold},
+    upgrades::UpgradeSchedule,
 };
 
+use tendermint::trust_threshold::ThresholdSchedule;
 use tendermint_testgen::Tester;
 
 // Link to JSON test files repo:
@@ -42,12 +48,28 @@ fn verify_single(
         trust_threshold,
         trusting_period,
         clock_drift,
+        max_bisection_depth: None,
+        request_timeout: None,
+        max_verification_time: None,
     };
 
     let result = verifier.verify(&input, &trusted_state, &options, now);
 
     match result {
-        Verdict::Success => Ok(input),
+        Verdict::Success => {
+            // The stateless API agrees with the verifier
+            let verdict = verify_header(
+                &trusted_state,
+                &input.signed_header,
+                &input.validators,
+                &UpgradeSchedule::default(),
+                &options,
+                now,
+            );
+            assert!(matches!(verdict, Verdict::Success));
+
+            Ok(input)
+        }
         error => Err(error),
     }
 }
@@ -84,6 +106,9 @@ fn run_bisection_test(tc: TestBisection<LightBlock>) -> BisectionTestResult {
         trust_threshold,
         trusting_period: trusting_period.into(),
         clock_drift,
+        max_bisection_depth: None,
+        request_timeout: None,
+        max_verification_time: None,
     };
 
     let provider = tc.primary;
@@ -100,6 +125,7 @@ fn run_bisection_test(tc: TestBisection<LightBlock>) -> BisectionTestResult {
     let mut state = State {
         light_store: Box::new(light_store),
         verification_trace: HashMap::new(),
+        audit_log: Some(AuditLog::new()),
     };
 
     let verifier = ProdVerifier::default();
@@ -115,6 +141,12 @@ fn run_bisection_test(tc: TestBisection<LightBlock>) -> BisectionTestResult {
 
     let result = verify_bisection(untrusted_height, &mut light_client, &mut state);
 
+    // Every verification step towards the untrusted height is recorded
+    let audit_log = state.audit_log.take().unwrap();
+    assert!(audit_log
+        .iter()
+        .all(|record| record.target_height == untrusted_height));
+
     let untrusted_light_block = io
         .fetch_light_block(primary, AtHeight::At(untrusted_height))
         .expect("header at untrusted height not found");
@@ -227,6 +259,212 @@ fn bisection_lower_test(tc: TestBisection<AnonLightBlock>) {
     }
 }
 
+/// Predicates rejecting every light block on top of the production checks
+struct RejectingPredicates;
+
+impl VerificationPredicates for RejectingPredicates {
+    fn additional_checks(
+        &self,
+        _untrusted: &LightBlock,
+        _trusted: &LightBlock,
+    ) -> Result<(), VerificationError> {
+        Err(VerificationError::ImplementationSpecific(
+            "rejected".to_string(),
+        ))
+    }
+}
+
+/// Test that the predicates given to the light client builder are used for verification,
+/// by checking that no light block above the trusted one gets verified when they reject it.
+fn bisection_custom_predicates_test(tc: TestBisection<AnonLightBlock>) {
+    let tc: TestBisection<LightBlock> = tc.into();
+    let primary = default_peer_id();
+    let untrusted_height = tc.height_to_verify;
+    let trusted_height = tc.trust_options.height;
+
+    let options = Options {
+        trust_threshold: tc.trust_options.trust_level,
+        trusting_period: tc.trust_options.period.into(),
+        clock_drift: Duration::from_secs(10),
+        max_bisection_depth: None,
+        request_timeout: None,
+        max_verification_time: None,
+    };
+
+    let io = MockIo::new(tc.primary.chain_id, tc.primary.lite_blocks);
+    let trusted_state = io
+        .fetch_light_block(primary, AtHeight::At(trusted_height))
+        .expect("could not 'request' light block");
+
+    let mut light_store = MemoryStore::new();
+    light_store.insert(trusted_state, Status::Trusted);
+
+    let mut state = State {
+        light_store: Box::new(light_store),
+        verification_trace: HashMap::new(),
+        audit_log: None,
+    };
+
+    let mut light_client = LightClient::builder(primary, options, io)
+        .clock(MockClock { now: tc.now })
+        .predicates(RejectingPredicates)
+        .build();
+
+    let result = light_client.verify_to_target(untrusted_height, &mut state);
+
+    if untrusted_height > trusted_height {
+        assert!(result.is_err());
+    }
+}
+
+/// Test that the commit thresholds given to the light client builder are used for
+/// verification even when predicates are given afterwards, by checking that no light block
+/// above the trusted one gets verified when all the voting power must sign its commit.
+fn bisection_custom_commit_threshold_test(tc: TestBisection<AnonLightBlock>) {
+    let tc: TestBisection<LightBlock> = tc.into();
+    let primary = default_peer_id();
+    let untrusted_height = tc.height_to_verify;
+    let trusted_height = tc.trust_options.height;
+
+    let options = Options {
+        trust_threshold: tc.trust_options.trust_level,
+        trusting_period: tc.trust_options.period.into(),
+        clock_drift: Duration::from_secs(10),
+        max_bisection_depth: None,
+        request_timeout: None,
+        max_verification_time: None,
+    };
+
+    let io = MockIo::new(tc.primary.chain_id, tc.primary.lite_blocks);
+    let trusted_state = io
+        .fetch_light_block(primary, AtHeight::At(trusted_height))
+        .expect("could not 'request' light block");
+
+    let mut light_store = MemoryStore::new();
+    light_store.insert(trusted_state, Status::Trusted);
+
+    let mut state = State {
+        light_store: Box::new(light_store),
+        verification_trace: HashMap::new(),
+        audit_log: None,
+    };
+
+    let all_voting_power = TrustThreshold::new(1, 1).unwrap();
+    let light_client = LightClient::builder(primary, options, io)
+        .clock(MockClock { now: tc.now })
+        .commit_threshold_schedule(ThresholdSchedule::constant(all_voting_power).unwrap())
+        .predicates(ProdPredicates)
+        .build();
+
+    let result = light_client.verify_to_target(untrusted_height, &mut state);
+
+    if untrusted_height > trusted_height {
+        assert!(result.is_err());
+    }
+}
+
+/// Test that the light client fails with `ErrorKind::BisectionDepthExceeded` when it is not
+/// allowed to verify any light block to reach a target above the trusted state.
+fn bisection_max_depth_test(tc: TestBisection<AnonLightBlock>) {
+    let tc: TestBisection<LightBlock> = tc.into();
+    let primary = default_peer_id();
+    let untrusted_height = tc.height_to_verify;
+    let trusted_height = tc.trust_options.height;
+
+    let options = Options {
+        trust_threshold: tc.trust_options.trust_level,
+        trusting_period: tc.trust_options.period.into(),
+        clock_drift: Duration::from_secs(10),
+        max_bisection_depth: Some(0),
+        request_timeout: None,
+        max_verification_time: None,
+    };
+
+    let io = MockIo::new(tc.primary.chain_id, tc.primary.lite_blocks);
+    let trusted_state = io
+        .fetch_light_block(primary, AtHeight::At(trusted_height))
+        .expect("could not 'request' light block");
+
+    let mut light_store = MemoryStore::new();
+    light_store.insert(trusted_state, Status::Trusted);
+
+    let mut state = State {
+        light_store: Box::new(light_store),
+        verification_trace: HashMap::new(),
+        audit_log: None,
+    };
+
+    let light_client = LightClient::builder(primary, options, io)
+        .clock(MockClock { now: tc.now })
+        .build();
+
+    let result = light_client.verify_to_target(untrusted_height, &mut state);
+
+    if untrusted_height > trusted_height {
+        match result.map_err(|e| e.kind().clone()) {
+            Err(ErrorKind::BisectionDepthExceeded { max_depth: 0, .. }) => (),
+            result => panic!("expected BisectionDepthExceeded, got: {:?}", result),
+        }
+    }
+}
+
+/// Test that the blocking methods of a light client fetching light blocks asynchronously fail
+/// with `ErrorKind::BlockingOnAsyncIo` instead of blocking on the fetch, whereas its async
+/// methods verify the target as usual.
+fn bisection_async_io_test(tc: TestBisection<AnonLightBlock>) {
+    let tc: TestBisection<LightBlock> = tc.into();
+    let primary = default_peer_id();
+    let untrusted_height = tc.height_to_verify;
+    let trusted_height = tc.trust_options.height;
+
+    let options = Options {
+        trust_threshold: tc.trust_options.trust_level,
+        trusting_period: tc.trust_options.period.into(),
+        clock_drift: Duration::from_secs(10),
+        max_bisection_depth: None,
+        request_timeout: None,
+        max_verification_time: None,
+    };
+
+    let io = MockIo::new(tc.primary.chain_id, tc.primary.lite_blocks);
+    let trusted_state = io
+        .fetch_light_block(primary, AtHeight::At(trusted_height))
+        .expect("could not 'request' light block");
+
+    let new_state = || {
+        let mut light_store = MemoryStore::new();
+        light_store.insert(trusted_state.clone(), Status::Trusted);
+
+        State {
+            light_store: Box::new(light_store),
+            verification_trace: HashMap::new(),
+            audit_log: None,
+        }
+    };
+
+    let blocking_client = LightClient::builder(primary, options, io.clone())
+        .clock(MockClock { now: tc.now })
+        .build();
+    let mut async_client = LightClient::builder_async(primary, options, io)
+        .clock(MockClock { now: tc.now })
+        .build();
+
+    if untrusted_height > trusted_height {
+        let result = async_client.verify_to_target(untrusted_height, &mut new_state());
+        match result.map_err(|e| e.kind().clone()) {
+            Err(ErrorKind::BlockingOnAsyncIo) => (),
+            result => panic!("expected BlockingOnAsyncIo, got: {:?}", result),
+        }
+    }
+
+    let expected = blocking_client
+        .verify_to_target(untrusted_height, &mut new_state())
+        .map_err(|e| e.kind().clone());
+    let result = block_on(async_client.verify_to_target_async(untrusted_height, &mut new_state()))
+        .map_err(|e| e.kind().clone());
+    assert_eq!(result, expected);
+}
+
 #[test]
 fn run_single_step_tests() {
     let mut tester = Tester::new(TEST_FILES_PATH);
@@ -240,6 +478,16 @@ fn run_bisection_tests() {
     let mut tester = Tester::new(TEST_FILES_PATH);
     tester.add_test("bisection test", bisection_test);
     tester.add_test("bisection lower test", bisection_lower_test);
+    tester.add_test(
+        "bisection custom predicates test",
+        bisection_custom_predicates_test,
+    );
+    tester.add_test(
+        "bisection custom commit threshold test",
+        bisection_custom_commit_threshold_test,
+    );
+    tester.add_test("bisection max depth test", bisection_max_depth_test);
+    tester.add_test("bisection async io test", bisection_async_io_test);
     tester.run_foreach_in_dir("bisection/single_peer");
     tester.print_results();
 }
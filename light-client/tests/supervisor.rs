@@ -1,9 +1,11 @@
 use tendermint_light_client::{
     components::{
-        io::{AtHeight, Io},
+        io::{AtHeight, Io, IoError},
         scheduler,
         verifier::ProdVerifier,
     },
+    errors::ErrorKind,
+    evidence::{Evidence, EvidenceReporter},
     fork_detector::ProdForkDetector,
     light_client::{self, LightClient},
     peer_list::PeerList,
@@ -14,8 +16,12 @@ use tendermint_light_client::{
 };
 
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use contracts::contract_trait;
+use tendermint::abci::transaction::Hash;
+
 use tendermint_light_client::store::memory::MemoryStore;
 use tendermint_light_client::tests::{
     AnonLightBlock, MockClock, MockEvidenceReporter, MockIo, TestBisection, TrustOptions,
@@ -37,12 +43,16 @@ fn make_instance(peer_id: PeerId, trust_options: TrustOptions, io: MockIo, now:
     let state = State {
         light_store: Box::new(light_store),
         verification_trace: HashMap::new(),
+        audit_log: None,
     };
 
     let options = light_client::Options {
         trust_threshold: trust_options.trust_level,
         trusting_period: trust_options.period.into(),
         clock_drift: Duration::from_secs(10),
+        max_bisection_depth: None,
+        request_timeout: None,
+        max_verification_time: None,
     };
 
     let verifier = ProdVerifier::default();
@@ -54,6 +64,121 @@ fn make_instance(peer_id: PeerId, trust_options: TrustOptions, io: MockIo, now:
     Instance::new(light_client, state)
 }
 
+/// Test that verification fails when more providers than available must return matching
+/// light blocks.
+fn run_multipeer_quorum_test(tc: TestBisection<AnonLightBlock>) {
+    let tc: TestBisection<LightBlock> = tc.into();
+    let primary = tc.primary.lite_blocks[0].provider;
+    let quorum = tc.witnesses.len() + 2;
+
+    let io = MockIo::new(tc.primary.chain_id, tc.primary.lite_blocks);
+    let primary_instance = make_instance(primary, tc.trust_options.clone(), io, tc.now);
+
+    let mut peer_list = PeerList::builder();
+    peer_list = peer_list.primary(primary, primary_instance);
+
+    for provider in tc.witnesses.into_iter() {
+        let peer_id = provider.value.lite_blocks[0].provider;
+        let io = MockIo::new(provider.value.chain_id, provider.value.lite_blocks);
+        let instance = make_instance(peer_id, tc.trust_options.clone(), io, tc.now);
+        peer_list = peer_list.witness(peer_id, instance);
+    }
+
+    let mut supervisor = Supervisor::new(
+        peer_list.build(),
+        ProdForkDetector::default(),
+        MockEvidenceReporter::new(),
+    )
+    .with_quorum(quorum);
+
+    assert!(supervisor.verify_to_target(tc.height_to_verify).is_err());
+}
+
+/// Evidence reporter failing to report evidence to the given peers, which records the peers it
+/// reported evidence to
+#[derive(Clone, Default)]
+struct FlakyEvidenceReporter {
+    failing: Arc<Mutex<Vec<PeerId>>>,
+    reported: Arc<Mutex<Vec<PeerId>>>,
+}
+
+#[contract_trait]
+impl EvidenceReporter for FlakyEvidenceReporter {
+    fn report(&self, _e: Evidence, peer: PeerId) -> Result<Hash, IoError> {
+        if self.failing.lock().unwrap().contains(&peer) {
+            return Err(IoError::Timeout(peer));
+        }
+
+        self.reported.lock().unwrap().push(peer);
+        Ok(Hash::new([0; 32]))
+    }
+}
+
+/// Test that failing to report the evidence of a fork to the primary does not fail the
+/// verification with anything but the fork, and that the evidence is reported to the primary
+/// on the next verification once it is reachable, without reporting it to the witness again.
+fn run_multipeer_evidence_retry_test(tc: TestBisection<AnonLightBlock>) {
+    let tc: TestBisection<LightBlock> = tc.into();
+    let primary = tc.primary.lite_blocks[0].provider;
+
+    let io = MockIo::new(tc.primary.chain_id, tc.primary.lite_blocks);
+    let primary_instance = make_instance(primary, tc.trust_options.clone(), io, tc.now);
+
+    let mut peer_list = PeerList::builder();
+    peer_list = peer_list.primary(primary, primary_instance);
+
+    for provider in tc.witnesses.into_iter() {
+        let peer_id = provider.value.lite_blocks[0].provider;
+        let io = MockIo::new(provider.value.chain_id, provider.value.lite_blocks);
+        let instance = make_instance(peer_id, tc.trust_options.clone(), io, tc.now);
+        peer_list = peer_list.witness(peer_id, instance);
+    }
+
+    let reporter = FlakyEvidenceReporter::default();
+    reporter.failing.lock().unwrap().push(primary);
+
+    let mut supervisor = Supervisor::new(
+        peer_list.build(),
+        ProdForkDetector::default(),
+        reporter.clone(),
+    );
+
+    let result = supervisor.verify_to_target(tc.height_to_verify);
+
+    let pending_forks = supervisor.state().pending_forks;
+    if pending_forks.is_empty() {
+        // No fork in this test case
+        return;
+    }
+
+    match result.map_err(|e| e.kind().clone()) {
+        Err(ErrorKind::ForkDetected(_)) => (),
+        result => panic!("expected ForkDetected, got: {:?}", result),
+    }
+
+    let forked = pending_forks
+        .iter()
+        .map(|fork| fork.witness.provider)
+        .collect::<Vec<_>>();
+
+    assert!(pending_forks
+        .iter()
+        .all(|fork| fork.reported_to_witness && !fork.reported_to_primary));
+    assert_eq!(*reporter.reported.lock().unwrap(), forked);
+
+    // The primary is reachable again
+    reporter.failing.lock().unwrap().clear();
+    let _ = supervisor.verify_to_target(tc.height_to_verify);
+
+    // The pending evidence is reported to the primary first, and to the primary only
+    let reported = reporter.reported.lock().unwrap().clone();
+    assert_eq!(
+        reported[forked.len()..2 * forked.len()],
+        vec![primary; forked.len()][..]
+    );
+    assert!(supervisor.state().pending_forks.is_empty());
+}
+
 fn run_multipeer_test(tc: TestBisection<AnonLightBlock>) {
     let tc: TestBisection<LightBlock> = tc.into();
     let primary = tc.primary.lite_blocks[0].provider;
@@ -123,6 +248,11 @@ fn run_multipeer_test(tc: TestBisection<AnonLightBlock>) {
 fn run_multipeer_tests() {
     let mut tester = Tester::new(TEST_FILES_PATH);
     tester.add_test("multipeer test", run_multipeer_test);
+    tester.add_test("multipeer quorum test", run_multipeer_quorum_test);
+    tester.add_test(
+        "multipeer evidence retry test",
+        run_multipeer_evidence_retry_test,
+    );
     tester.run_foreach_in_dir("bisection/multi_peer");
     tester.print_results();
 }
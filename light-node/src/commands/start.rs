@@ -23,7 +23,6 @@ use std::time::Duration;
 
 use tendermint_light_client::components::clock::SystemClock;
 use tendermint_light_client::components::io::ProdIo;
-use tendermint_light_client::components::scheduler;
 use tendermint_light_client::components::verifier::ProdVerifier;
 use tendermint_light_client::evidence::ProdEvidenceReporter;
 use tendermint_light_client::fork_detector::ProdForkDetector;
@@ -137,11 +136,14 @@ impl StartCmd {
         let state = State {
             light_store: Box::new(light_store),
             verification_trace: HashMap::new(),
+            audit_log: None,
         };
 
-        let verifier = ProdVerifier::default();
+        let verifier =
+            ProdVerifier::with_commit_threshold_schedule(app_config().commit_thresholds.clone())
+                .with_upgrade_schedule(app_config().upgrades.clone());
         let clock = SystemClock;
-        let scheduler = scheduler::basic_bisecting_schedule;
+        let scheduler = light_config.verification_strategy;
 
         let light_client = LightClient::new(peer_id, options, clock, scheduler, verifier, io);
 
@@ -9,8 +9,11 @@ use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use std::time::Duration;
 
+use tendermint::trust_threshold::ThresholdSchedule;
+use tendermint_light_client::components::scheduler::VerificationStrategy;
 use tendermint_light_client::light_client;
 use tendermint_light_client::types::{PeerId, TrustThreshold};
+use tendermint_light_client::upgrades::UpgradeSchedule;
 
 /// LightNode Configuration
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -24,6 +27,19 @@ pub struct LightNodeConfig {
     pub trusting_period: Duration,
     /// Correction parameter dealing with only approximately synchronized clocks.
     pub clock_drift: Duration,
+    /// Maximum number of light blocks to verify to reach a target height, if any.
+    #[serde(default)]
+    pub max_bisection_depth: Option<usize>,
+    /// Maximum time to verify a target height, including fetching light blocks, if any.
+    #[serde(default)]
+    pub max_verification_time: Option<Duration>,
+    /// Coordinated upgrades of the chain changing its chain-id, if any.
+    #[serde(default)]
+    pub upgrades: UpgradeSchedule,
+    /// Share of the voting power which must sign a commit, by height.
+    /// Defaults to 2/3 at every height.
+    #[serde(default)]
+    pub commit_thresholds: ThresholdSchedule,
 
     /// RPC related config parameters.
     pub rpc_config: RpcConfig,
@@ -46,6 +62,10 @@ pub struct LightClientConfig {
     pub peer_id: PeerId,
     /// The data base folder for this instance's store.
     pub db_path: PathBuf,
+    /// Whether this instance verifies every block (`Sequential`), skips blocks and bisects
+    /// (`Skipping`, default) or skips blocks and falls back to sequential verification (`Hybrid`).
+    #[serde(default)]
+    pub verification_strategy: VerificationStrategy,
 }
 
 /// RpcConfig contains for the RPC server of the light node as
@@ -68,6 +88,7 @@ impl Default for LightClientConfig {
             db_path: "./lightstore/BADFADAD0BEFEEDC0C0ADEADBEEFC0FFEEFACADE"
                 .parse()
                 .unwrap(),
+            verification_strategy: VerificationStrategy::default(),
         }
     }
 }
@@ -82,6 +103,10 @@ impl Default for LightNodeConfig {
                 denominator: 3,
             },
             clock_drift: Duration::from_secs(1),
+            max_bisection_depth: None,
+            max_verification_time: None,
+            upgrades: UpgradeSchedule::default(),
+            commit_thresholds: ThresholdSchedule::default(),
             rpc_config: RpcConfig {
                 listen_addr: "127.0.0.1:8888".parse().unwrap(),
                 request_timeout: Duration::from_secs(60),
@@ -99,6 +124,9 @@ impl From<LightNodeConfig> for light_client::Options {
             trust_threshold: lnc.trust_threshold,
             trusting_period: lnc.trusting_period,
             clock_drift: lnc.clock_drift,
+            max_bisection_depth: lnc.max_bisection_depth,
+            request_timeout: Some(lnc.rpc_config.request_timeout),
+            max_verification_time: lnc.max_verification_time,
         }
     }
 }
@@ -38,9 +38,11 @@ mod sealed {
     use jsonrpc_core::types::Error;
     use jsonrpc_derive::rpc;
 
+    use tendermint_light_client::errors::Error as LightClientError;
     use tendermint_light_client::supervisor::Handle;
     use tendermint_light_client::types::LatestStatus;
     use tendermint_light_client::types::LightBlock;
+    use tendermint_light_client::types::{Height, SignedHeader};
 
     #[rpc]
     pub trait Rpc {
@@ -51,6 +53,10 @@ mod sealed {
         /// Returns the latest status.
         #[rpc(name = "status")]
         fn status(&self) -> FutureResult<LatestStatus, Error>;
+
+        /// Returns the signed header at the given height, verifying it first if needed.
+        #[rpc(name = "header")]
+        fn header(&self, height: Height) -> FutureResult<SignedHeader, Error>;
     }
 
     pub use self::rpc_impl_Rpc::gen_client::Client;
@@ -76,27 +82,34 @@ mod sealed {
         H: Handle + Send + Sync + 'static,
     {
         fn state(&self) -> FutureResult<Option<LightBlock>, Error> {
-            let res = self.handle.latest_trusted().map_err(|e| {
-                let mut err = Error::internal_error();
-                err.message = e.to_string();
-                err.data = serde_json::to_value(e.kind()).ok();
-                err
-            });
+            let res = self.handle.latest_trusted().map_err(internal_error);
 
             future::result(res)
         }
 
         fn status(&self) -> FutureResult<LatestStatus, Error> {
-            let res = self.handle.latest_status().map_err(|e| {
-                let mut err = Error::internal_error();
-                err.message = e.to_string();
-                err.data = serde_json::to_value(e.kind()).ok();
-                err
-            });
+            let res = self.handle.latest_status().map_err(internal_error);
+
+            future::result(res)
+        }
+
+        fn header(&self, height: Height) -> FutureResult<SignedHeader, Error> {
+            let res = self
+                .handle
+                .verify_to_target(height)
+                .map(|light_block| light_block.signed_header)
+                .map_err(internal_error);
 
             future::result(res)
         }
     }
+
+    fn internal_error(e: LightClientError) -> Error {
+        let mut err = Error::internal_error();
+        err.message = e.to_string();
+        err.data = serde_json::to_value(e.kind()).ok();
+        err
+    }
 }
 
 #[cfg(test)]
@@ -110,7 +123,7 @@ mod test {
     use tendermint_light_client::errors::Error;
     use tendermint_light_client::supervisor::Handle;
     use tendermint_light_client::types::LatestStatus;
-    use tendermint_light_client::types::LightBlock;
+    use tendermint_light_client::types::{Height, LightBlock};
 
     use super::{Client, Rpc as _, Server};
 
@@ -144,6 +157,23 @@ mod test {
         assert_eq!(have, want);
     }
 
+    #[tokio::test]
+    async fn header() {
+        let server = Server::new(MockHandle {});
+        let fut = {
+            let mut io = IoHandler::new();
+            io.extend_with(server.to_delegate());
+            let (client, server) = local::connect::<Client, _, _>(io);
+            client.header(Height::from(1_u64)).join(server)
+        };
+        let (have, _) = fut.compat().await.unwrap();
+        let want = serde_json::from_str::<LightBlock>(LIGHTBLOCK_JSON)
+            .unwrap()
+            .signed_header;
+
+        assert_eq!(have, want);
+    }
+
     struct MockHandle;
 
     impl Handle for MockHandle {
@@ -157,6 +187,12 @@ mod test {
 
             Ok(status)
         }
+        fn verify_to_target(&self, height: Height) -> Result<LightBlock, Error> {
+            let block: LightBlock = serde_json::from_str(LIGHTBLOCK_JSON).unwrap();
+            assert_eq!(block.height(), height);
+
+            Ok(block)
+        }
     }
 
     const LIGHTBLOCK_JSON: &str = r#"
@@ -1,31 +1,211 @@
 //! Tendermint RPC client
 
-use bytes::buf::ext::BufExt;
-use hyper::header;
+use async_trait::async_trait;
+use futures::future;
+use futures::stream::{self, Stream, TryStreamExt};
+use futures::Future;
+use hyper::header::{self, HeaderMap};
+use std::io::{Read, Write};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
-use tendermint::abci::{self, Transaction};
+use tendermint::abci::{self, event::FromAbciEvent, Transaction};
 use tendermint::block::Height;
 use tendermint::evidence::Evidence;
 use tendermint::net;
-use tendermint::Genesis;
+use tendermint::{chain, validator, Genesis, Hash};
 
-use crate::{endpoint::*, Error, Request, Response};
+use crate::{
+    endpoint::*,
+    error::{Code, Reason},
+    query::{EventType, Query},
+    response, Error, IdGenerator, Method, Order, Request, Response,
+};
+use batch::Batch;
+use body::LimitedReader;
+use compression::ContentEncoding;
+use metrics::Recorder;
+use rate_limit::RateLimiter;
+use subscription::OverflowPolicy;
+use transport::HttpTransport;
 
+mod balanced;
+mod batch;
+mod body;
+mod builder;
+mod cache;
+mod commit;
+mod compat;
+mod compression;
 pub mod event_listener;
+mod failover;
+mod lag;
+mod layer;
+mod metrics;
+pub mod mock;
+#[cfg(feature = "metrics-prometheus")]
+mod prometheus_metrics;
+mod rate_limit;
+mod replay;
+mod retry;
+pub mod subscription;
+mod tls;
+#[macro_use]
+mod trace;
+mod transport;
+mod tunnel;
+mod url;
+mod websocket;
+
+pub use self::url::Url;
+pub use balanced::{BalancedClient, CircuitBreaker, Strategy};
+pub use builder::ClientBuilder;
+pub use cache::{CacheStore, CachingClient, DiskCache, MemoryCache};
+pub use commit::CommittedTx;
+pub use compat::CompatMode;
+pub use failover::{FailoverClient, DEFAULT_COOLDOWN};
+pub use lag::{LagPolicy, MonitoredBlock};
+pub use layer::Layer;
+pub use metrics::{MetricsRecorder, RequestMetrics};
+#[cfg(feature = "metrics-prometheus")]
+pub use prometheus_metrics::PrometheusMetrics;
+pub use rate_limit::RateLimit;
+pub use retry::RetryPolicy;
+pub use transport::Transport;
+pub use websocket::WebSocketClient;
+
+/// Default timeout for establishing a TCP connection to the RPC server
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default time after which idle pooled connections are closed
+pub const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Default timeout for a full request/response roundtrip, including
+/// reading the response body
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maximum number of blocks `/blockchain` returns metadata for
+const BLOCKCHAIN_LIMIT: u64 = 20;
+
+/// Number of validators requested per page (the maximum allowed by
+/// Tendermint)
+const VALIDATORS_PAGE_SIZE: u8 = 100;
+
+/// Number of results requested per page when streaming search results
+/// (the maximum allowed by Tendermint)
+const SEARCH_PAGE_SIZE: u8 = 100;
+
+/// Time between polls while waiting for a transaction's commit or for a
+/// node to become healthy
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Tendermint RPC client.
 ///
 /// Presently supports JSONRPC via HTTP.
+///
+/// Use [`Client::builder`] to customize headers, timeouts and retries.
 #[derive(Clone, Debug)]
 pub struct Client {
-    /// Address of the RPC server
-    address: net::Address,
+    /// URL of the RPC server
+    url: Url,
+
+    /// Underlying HTTP client, which also pools connections
+    transport: HttpTransport,
+
+    /// Headers sent along with every request
+    headers: HeaderMap,
+
+    /// Timeout for a complete request (`None` means no timeout)
+    request_timeout: Option<Duration>,
+
+    /// Policy for retrying failed idempotent requests (`None` means no retries)
+    retry_policy: Option<RetryPolicy>,
+
+    /// Limiter of the request rate, shared by clones (`None` means no limit)
+    rate_limiter: Option<RateLimiter>,
+
+    /// Maximum size of a response body in bytes (`None` means no limit)
+    max_response_size: Option<usize>,
+
+    /// Recorder of request metrics, if any
+    metrics: Option<Recorder>,
+
+    /// Chain ID responses must match, if pinned
+    chain_id: Option<chain::Id>,
+
+    /// Release series of the node, if pinned or detected already, shared by
+    /// clones
+    compat_mode: Arc<RwLock<Option<CompatMode>>>,
+
+    /// Detect the release series before the first request if unknown
+    detect_compat_mode: bool,
+
+    /// Source of the IDs of single requests
+    id_generator: Arc<dyn IdGenerator>,
 }
 
 impl Client {
     /// Create a new Tendermint RPC client, connecting to the given address
+    ///
+    /// Both `tcp://` addresses and (on unix platforms) `unix://` socket paths
+    /// are supported.
+    ///
+    /// Uses [`DEFAULT_CONNECT_TIMEOUT`] and [`DEFAULT_REQUEST_TIMEOUT`].
     pub fn new(address: net::Address) -> Self {
-        Self { address }
+        Self::builder(address.into())
+            .build()
+            .expect("network addresses carry no credentials")
+    }
+
+    /// Create a new Tendermint RPC client with the given connect and request
+    /// timeouts. Passing `None` disables the corresponding timeout.
+    ///
+    /// Requests which exceed either timeout fail with an error whose code is
+    /// [`Code::TimeoutError`](crate::error::Code::TimeoutError).
+    pub fn new_with_timeouts(
+        address: net::Address,
+        connect_timeout: Option<Duration>,
+        request_timeout: Option<Duration>,
+    ) -> Self {
+        Self::builder(address.into())
+            .connect_timeout(connect_timeout)
+            .request_timeout(request_timeout)
+            .build()
+            .expect("network addresses carry no credentials")
+    }
+
+    /// Start building a client connecting to the given URL
+    pub fn builder(url: Url) -> ClientBuilder {
+        ClientBuilder::new(url)
+    }
+
+    /// URL of the RPC server
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Retry failed idempotent requests according to the given policy.
+    ///
+    /// Retries are disabled by default.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Client sending requests with the given timeout instead of the one it
+    /// was built with, e.g. for requests which legitimately take long:
+    ///
+    /// ```ignore
+    /// let genesis = client.with_timeout(Duration::from_secs(300)).genesis().await?;
+    /// ```
+    ///
+    /// The returned client shares connections and the rate limit with this
+    /// one.
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            request_timeout: Some(timeout),
+            ..self.clone()
+        }
     }
 
     /// `/abci_info`: get information about the ABCI application.
@@ -47,6 +227,39 @@ impl Client {
             .response)
     }
 
+    /// `/abci_query`: query the value stored under the given key in the given
+    /// store of a Cosmos SDK application (path `/store/<store>/key`), at the
+    /// given height or the latest one.
+    ///
+    /// With `prove` set, the result can be checked with
+    /// [`AbciQuery::verify`](abci_query::AbciQuery::verify).
+    pub async fn abci_query_store(
+        &self,
+        store: &str,
+        key: impl Into<Vec<u8>>,
+        height: Option<Height>,
+        prove: bool,
+    ) -> Result<abci_query::AbciQuery, Error> {
+        self.abci_query(Some(abci::Path::store_key(store)), key, height, prove)
+            .await
+    }
+
+    /// `/abci_query`: make an application-specific query with the path made
+    /// of the given segments (`/custom/<segment>/...`), at the given height
+    /// or the latest one.
+    ///
+    /// Results are usually JSON, see
+    /// [`AbciQuery::json_value`](abci_query::AbciQuery::json_value).
+    pub async fn abci_query_custom(
+        &self,
+        segments: &[&str],
+        data: impl Into<Vec<u8>>,
+        height: Option<Height>,
+    ) -> Result<abci_query::AbciQuery, Error> {
+        self.abci_query(Some(abci::Path::custom(segments)), data, height, false)
+            .await
+    }
+
     /// `/block`: get block at a given height.
     pub async fn block(&self, height: impl Into<Height>) -> Result<block::Response, Error> {
         self.perform(block::Request::new(height.into())).await
@@ -57,6 +270,11 @@ impl Client {
         self.perform(block::Request::default()).await
     }
 
+    /// `/block_by_hash`: get the block with the given hash.
+    pub async fn block_by_hash(&self, hash: Hash) -> Result<block_by_hash::Response, Error> {
+        self.perform(block_by_hash::Request::new(hash)).await
+    }
+
     /// `/block_results`: get ABCI results for a block at a particular height.
     pub async fn block_results<H>(&self, height: H) -> Result<block_results::Response, Error>
     where
@@ -71,6 +289,53 @@ impl Client {
         self.perform(block_results::Request::default()).await
     }
 
+    /// `/block_results`: stream the ABCI results of all blocks from the
+    /// given height on, fetching those of past blocks first and then those
+    /// of new blocks as the given WebSocket connection reports them.
+    ///
+    /// Results are emitted in order of height, each exactly once, e.g. for
+    /// indexers resuming where they stopped. New block events missed because
+    /// the subscription buffer of the given capacity overflowed leave no
+    /// gaps, as results are fetched for all heights up to the latest one
+    /// seen. The stream ends after yielding the first error.
+    pub async fn block_results_from(
+        &self,
+        websocket: &WebSocketClient,
+        start: impl Into<Height>,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Result<impl Stream<Item = Result<block_results::Response, Error>> + '_, Error> {
+        // Subscribing before looking up the latest height leaves no window
+        // for blocks to be committed unnoticed
+        let blocks = websocket
+            .subscribe(&EventType::NewBlock.into(), capacity, policy)
+            .await?
+            .blocks();
+        let latest = self.status().await?.sync_info.latest_block_height;
+
+        Ok(replay::replay(self, blocks, start.into(), latest))
+    }
+
+    /// Stream the new blocks reported by the given WebSocket connection,
+    /// subscribing afresh whenever the subscription stalls or falls behind
+    /// the latest height this client reports, according to the given
+    /// policy.
+    ///
+    /// Blocks missed in between are reported as a [`MonitoredBlock::Gap`],
+    /// so that consumers can fetch them, and blocks are emitted in order of
+    /// height, each at most once. Resubscribing ends all other subscriptions
+    /// to new blocks of the connection. The stream ends after yielding the
+    /// first error, e.g. once the connection failed.
+    pub fn new_blocks_monitored(
+        &self,
+        websocket: &WebSocketClient,
+        policy: LagPolicy,
+        capacity: usize,
+        overflow: OverflowPolicy,
+    ) -> impl Stream<Item = Result<MonitoredBlock, Error>> + '_ {
+        lag::monitor(self, websocket, policy, capacity, overflow)
+    }
+
     /// `/blockchain`: get block headers for `min` <= `height` <= `max`.
     ///
     /// Block headers are returned in descending order (highest first).
@@ -86,6 +351,42 @@ impl Client {
             .await
     }
 
+    /// `/blockchain`: stream the metadata of all blocks with
+    /// `min` <= `height` <= `max` in ascending order, fetching further
+    /// batches of at most 20 blocks as the stream is consumed.
+    ///
+    /// The stream ends early at the latest height of the chain.
+    pub fn blockchain_range(
+        &self,
+        min: impl Into<Height>,
+        max: impl Into<Height>,
+    ) -> impl Stream<Item = Result<tendermint::block::Meta, Error>> + '_ {
+        let max = max.into().value();
+
+        stream::try_unfold(Some(min.into().value()), move |next| async move {
+            let from = match next {
+                Some(from) if from <= max => from,
+                _ => return Ok(None),
+            };
+            let to = max.min(from.saturating_add(BLOCKCHAIN_LIMIT - 1));
+
+            let response = self.blockchain(from, to).await?;
+            let mut metas = response.block_metas;
+            metas.retain(|meta| (from..=to).contains(&meta.header.height.value()));
+            metas.sort_by_key(|meta| meta.header.height);
+
+            let next = if response.last_height.value() <= to {
+                None
+            } else {
+                to.checked_add(1)
+            };
+
+            let metas = stream::iter(metas.into_iter().map(Ok::<_, Error>));
+            Ok::<_, Error>(Some((metas, next)))
+        })
+        .try_flatten()
+    }
+
     /// `/broadcast_tx_async`: broadcast a transaction, returning immediately.
     pub async fn broadcast_tx_async(
         &self,
@@ -112,11 +413,57 @@ impl Client {
         self.perform(broadcast::tx_commit::Request::new(tx)).await
     }
 
+    /// `/broadcast_tx_commit`, falling back to polling `/tx` if waiting for
+    /// the commit times out, until the transaction is committed or the given
+    /// timeout elapses.
+    ///
+    /// Busy nodes frequently time out waiting for a commit even though the
+    /// transaction makes it into a block shortly after. Fails with
+    /// [`Code::TimeoutError`] if the transaction is not committed in time,
+    /// e.g. because it never entered the mempool.
+    pub async fn broadcast_tx_commit_or_poll(
+        &self,
+        tx: Transaction,
+        timeout: Duration,
+    ) -> Result<CommittedTx, Error> {
+        let deadline = Instant::now() + timeout;
+        let hash = tx.hash();
+
+        match tokio::time::timeout(timeout, self.broadcast_tx_commit(tx)).await {
+            Ok(Ok(response)) => return Ok(CommittedTx::Broadcast(response)),
+            Ok(Err(e))
+                if e.code() != Code::TimeoutError && e.reason() != Reason::TxCommitTimeout =>
+            {
+                return Err(e)
+            }
+            _ => (),
+        }
+
+        self.poll_tx(hash, deadline).await.map(CommittedTx::Polled)
+    }
+
+    /// `/check_tx`: check a transaction against the application's mempool
+    /// rules without broadcasting it.
+    pub async fn check_tx(&self, tx: Transaction) -> Result<check_tx::Response, Error> {
+        self.perform(check_tx::Request::new(tx)).await
+    }
+
     /// `/commit`: get block commit at a given height.
     pub async fn commit(&self, height: impl Into<Height>) -> Result<commit::Response, Error> {
         self.perform(commit::Request::new(height.into())).await
     }
 
+    /// `/consensus_state`: get a summary of the node's consensus state.
+    pub async fn consensus_state(&self) -> Result<consensus_state::Response, Error> {
+        self.perform(consensus_state::Request).await
+    }
+
+    /// `/dump_consensus_state`: get the node's full consensus state,
+    /// including that of its peers.
+    pub async fn dump_consensus_state(&self) -> Result<dump_consensus_state::Response, Error> {
+        self.perform(dump_consensus_state::Request).await
+    }
+
     /// `/validators`: get validators a given height.
     pub async fn validators<H>(&self, height: H) -> Result<validators::Response, Error>
     where
@@ -125,6 +472,36 @@ impl Client {
         self.perform(validators::Request::new(height.into())).await
     }
 
+    /// `/validators`: get the complete validator set at the given height,
+    /// following pagination on chains with more validators than fit on one
+    /// page.
+    pub async fn all_validators<H>(&self, height: H) -> Result<validator::Set, Error>
+    where
+        H: Into<Height>,
+    {
+        let height = height.into();
+        let mut validators = vec![];
+
+        for page in 1.. {
+            let response = self
+                .perform(validators::Request::new_with_page(
+                    height,
+                    page,
+                    VALIDATORS_PAGE_SIZE,
+                ))
+                .await?;
+            let fetched = response.validators.len();
+            validators.extend(response.validators);
+
+            // Nodes which do not page validators return them all at once
+            if fetched == 0 || validators.len() >= response.total as usize {
+                break;
+            }
+        }
+
+        Ok(validator::Set::new(validators))
+    }
+
     /// `/commit`: get the latest block commit
     pub async fn latest_commit(&self) -> Result<commit::Response, Error> {
         self.perform(commit::Request::default()).await
@@ -143,11 +520,70 @@ impl Client {
         Ok(self.perform(genesis::Request).await?.genesis)
     }
 
+    /// `/genesis_chunked`: get the genesis file chunk by chunk and reassemble
+    /// it.
+    ///
+    /// Unlike [`Client::genesis`], this works for genesis files too large
+    /// to be returned by `/genesis` at once (about 16 MB).
+    pub async fn genesis_chunked(&self) -> Result<Genesis, Error> {
+        let mut json = vec![];
+        self.genesis_chunked_to_writer(&mut json).await?;
+        let genesis: Genesis = serde_json::from_slice(&json).map_err(Error::parse_error)?;
+        self.check_chain_ids(vec![&genesis.chain_id])?;
+        Ok(genesis)
+    }
+
+    /// `/genesis_chunked`: write the JSON-encoded genesis file to the given
+    /// writer chunk by chunk, without holding all of it in memory.
+    ///
+    /// Fails if the node returns chunks out of order or changes the total
+    /// number of chunks midway.
+    pub async fn genesis_chunked_to_writer(&self, writer: &mut impl Write) -> Result<(), Error> {
+        let mut chunk = 0;
+        let mut total = None;
+
+        loop {
+            let response = self.perform(genesis_chunked::Request::new(chunk)).await?;
+
+            if response.chunk != chunk || total.map_or(false, |total| total != response.total) {
+                return Err(Error::server_error(format!(
+                    "unexpected genesis chunk {} of {} (expected chunk {})",
+                    response.chunk, response.total, chunk
+                )));
+            }
+
+            writer
+                .write_all(&response.data)
+                .map_err(|e| Error::new(Code::InternalError, Some(e.to_string())))?;
+
+            total = Some(response.total);
+            chunk += 1;
+
+            if chunk >= response.total {
+                return Ok(());
+            }
+        }
+    }
+
     /// `/net_info`: obtain information about P2P and other network connections.
     pub async fn net_info(&self) -> Result<net_info::Response, Error> {
         self.perform(net_info::Request).await
     }
 
+    /// `/num_unconfirmed_txs`: get the number and total size of the
+    /// transactions in the mempool.
+    pub async fn num_unconfirmed_txs(&self) -> Result<num_unconfirmed_txs::Response, Error> {
+        self.perform(num_unconfirmed_txs::Request).await
+    }
+
+    /// `/unconfirmed_txs`: get (at most `limit`) transactions in the mempool.
+    pub async fn unconfirmed_txs(
+        &self,
+        limit: Option<u32>,
+    ) -> Result<unconfirmed_txs::Response, Error> {
+        self.perform(unconfirmed_txs::Request::new(limit)).await
+    }
+
     /// `/status`: get Tendermint status including node info, pubkey, latest
     /// block hash, app hash, block height and time.
     pub async fn status(&self) -> Result<status::Response, Error> {
@@ -159,41 +595,1063 @@ impl Client {
         self.perform(evidence::Request::new(e)).await
     }
 
+    /// `/tx`: get the committed transaction with the given hash.
+    pub async fn tx(
+        &self,
+        hash: abci::transaction::Hash,
+        prove: bool,
+    ) -> Result<tx::Response, Error> {
+        self.perform(tx::Request::new(hash, prove)).await
+    }
+
+    /// Wait for the transaction with the given hash to be committed, polling
+    /// `/tx` until it is found or the given timeout elapses.
+    ///
+    /// Fails with [`Code::TimeoutError`] if the transaction is not committed
+    /// in time.
+    pub async fn wait_for_tx(
+        &self,
+        hash: abci::transaction::Hash,
+        timeout: Duration,
+    ) -> Result<tx::Response, Error> {
+        self.poll_tx(hash, Instant::now() + timeout).await
+    }
+
+    /// Wait for the node to be up and caught up with the chain, polling
+    /// `/health` and `/status` until it is or the given timeout elapses, e.g.
+    /// in test harnesses and deployment scripts.
+    ///
+    /// Returns the status of the healthy node. Fails with
+    /// [`Code::TimeoutError`], describing the last problem encountered, if
+    /// the node does not become healthy in time.
+    pub async fn wait_until_healthy(&self, timeout: Duration) -> Result<status::Response, Error> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let check = async {
+                self.health().await?;
+                self.status().await
+            };
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            let problem = match tokio::time::timeout(remaining, check).await {
+                Ok(Ok(status)) if !status.sync_info.catching_up => return Ok(status),
+                Ok(Ok(_)) => "node is catching up".to_owned(),
+                Ok(Err(e)) => e.to_string(),
+                Err(e) => e.to_string(),
+            };
+
+            let wait = match next_poll(deadline) {
+                Some(wait) => wait,
+                None => {
+                    return Err(Error::timeout_error(format!(
+                        "node not healthy in time: {}",
+                        problem
+                    )))
+                }
+            };
+            trace_debug!(problem = %problem, "waiting for node to become healthy");
+            tokio::time::delay_for(wait).await;
+        }
+    }
+
+    /// Poll `/tx` until the transaction with the given hash is committed or
+    /// the deadline passes
+    async fn poll_tx(
+        &self,
+        hash: abci::transaction::Hash,
+        deadline: Instant,
+    ) -> Result<tx::Response, Error> {
+        loop {
+            match self.tx(hash, false).await {
+                Err(e) if e.reason() == Reason::TxNotFound => (),
+                result => return result,
+            }
+
+            let wait = match next_poll(deadline) {
+                Some(wait) => wait,
+                None => {
+                    return Err(Error::timeout_error(format!(
+                        "transaction {} not committed in time",
+                        hash
+                    )))
+                }
+            };
+            tokio::time::delay_for(wait).await;
+        }
+    }
+
+    /// `/tx_search`: get the given page of transactions matching the query,
+    /// failing without sending a request if the query is invalid (see
+    /// [`Query::validate`]).
+    pub async fn tx_search(
+        &self,
+        query: Query,
+        prove: bool,
+        page: u32,
+        per_page: u8,
+        order: Order,
+    ) -> Result<tx_search::Response, Error> {
+        query.validate()?;
+        self.perform(tx_search::Request::new(
+            &query, prove, page, per_page, order,
+        ))
+        .await
+    }
+
+    /// `/tx_search`: stream all transactions matching the query, fetching
+    /// further pages of results as the stream is consumed.
+    ///
+    /// Failed page requests, including those rejected by rate limiting
+    /// (HTTP 429), are retried according to the client's [`RetryPolicy`].
+    pub fn tx_search_all(
+        &self,
+        query: Query,
+        prove: bool,
+        order: Order,
+    ) -> impl Stream<Item = Result<tx_search::ResultTx, Error>> + '_ {
+        search_all(move |page| {
+            let query = query.clone();
+
+            async move {
+                let response = self
+                    .tx_search(query, prove, page, SEARCH_PAGE_SIZE, order)
+                    .await?;
+                Ok((response.txs, response.total_count))
+            }
+        })
+    }
+
+    /// `/tx_search`: stream all transactions matching the query along with
+    /// their events of type `E`, decoded with [`FromAbciEvent`].
+    ///
+    /// Pages are fetched as for [`Client::tx_search_all`]. Transactions
+    /// whose events fail to decode yield an error, ending the stream.
+    pub fn tx_search_events<E>(
+        &self,
+        query: Query,
+        order: Order,
+    ) -> impl Stream<Item = Result<(tx_search::ResultTx, Vec<E>), Error>> + '_
+    where
+        E: FromAbciEvent + 'static,
+    {
+        self.tx_search_all(query, false, order).and_then(|tx| {
+            let events = E::from_abci_events(&tx.tx_result.events)
+                .map_err(|e| Error::parse_error(format!("cannot decode events: {}", e)));
+            future::ready(events.map(|events| (tx, events)))
+        })
+    }
+
+    /// `/block_search`: get the given page of blocks matching the query,
+    /// failing without sending a request if the query is invalid.
+    pub async fn block_search(
+        &self,
+        query: Query,
+        page: u32,
+        per_page: u8,
+        order: Order,
+    ) -> Result<block_search::Response, Error> {
+        query.validate()?;
+        self.perform(block_search::Request::new(&query, page, per_page, order))
+            .await
+    }
+
+    /// `/block_search`: stream all blocks matching the query, fetching
+    /// further pages of results as the stream is consumed.
+    ///
+    /// Failed page requests are retried as for [`Client::tx_search_all`].
+    pub fn block_search_all(
+        &self,
+        query: Query,
+        order: Order,
+    ) -> impl Stream<Item = Result<block::Response, Error>> + '_ {
+        search_all(move |page| {
+            let query = query.clone();
+
+            async move {
+                let response = self
+                    .block_search(query, page, SEARCH_PAGE_SIZE, order)
+                    .await?;
+                Ok((response.blocks, response.total_count))
+            }
+        })
+    }
+
     /// Perform a request against the RPC endpoint
+    ///
+    /// Idempotent requests are retried according to the client's
+    /// [`RetryPolicy`], if one was set. Responses from a chain other than
+    /// the one the client is pinned to, if any, are rejected with
+    /// [`Code::ChainIdMismatch`].
     pub async fn perform<R>(&self, request: R) -> Result<R::Response, Error>
     where
         R: Request,
     {
-        let request_body = request.into_json();
+        let method = request.method();
+        let compat_mode = self.prepare_compat_mode(method).await?;
+
+        let request_body = request.into_json_with_id(self.id_generator.next_id());
+
+        self.perform_raw(method, None, request_body, |body| {
+            let response =
+                compat::parse::<response::Wrapper<R::Response>>(compat_mode, method, body)?
+                    .into_result()?;
+            self.check_chain_ids(response.chain_ids())?;
+            Ok(response)
+        })
+        .await
+    }
+
+    /// Perform several requests of the same type in a single JSONRPC batch.
+    ///
+    /// The responses are returned in the order of the given requests. The
+    /// outer result fails if the batch as a whole could not be performed,
+    /// while each inner result carries the outcome of an individual request.
+    pub async fn perform_batch<R>(
+        &self,
+        requests: Vec<R>,
+    ) -> Result<Vec<Result<R::Response, Error>>, Error>
+    where
+        R: Request,
+    {
+        if requests.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let method = requests[0].method();
+        let compat_mode = self.prepare_compat_mode(method).await?;
+        let batch_size = requests.len();
+        let batch = Batch::new(requests);
+        let request_body = batch.to_json();
+
+        let responses: Vec<response::Wrapper<R::Response>> = self
+            .perform_raw(method, Some(batch_size), request_body, |body| {
+                compat::parse(compat_mode, method, body)
+            })
+            .await?;
+
+        Ok(batch
+            .demultiplex(responses)
+            .into_iter()
+            .map(|result| {
+                let response = result?;
+                self.check_chain_ids(response.chain_ids())?;
+                Ok(response)
+            })
+            .collect())
+    }
 
-        let (host, port) = match &self.address {
-            net::Address::Tcp { host, port, .. } => (host, port),
-            other => {
-                return Err(Error::invalid_params(&format!(
-                    "invalid RPC address: {:?}",
-                    other
-                )))
+    /// Release series of the node, if pinned or detected already
+    pub fn compat_mode(&self) -> Option<CompatMode> {
+        *self.compat_mode.read().unwrap()
+    }
+
+    /// Detect the release series of the node from the version it reports
+    /// in `/status`, and adapt to it from now on, along with all clones of
+    /// this client.
+    ///
+    /// Requests for methods the node does not support fail with
+    /// [`Code::MethodNotFound`] afterwards, without being sent.
+    pub async fn detect_compat_mode(&self) -> Result<CompatMode, Error> {
+        // Not via `status`, which would detect the release series first
+        let request_body = status::Request.into_json_with_id(self.id_generator.next_id());
+        let status = self
+            .perform_raw(Method::Status, None, request_body, |body| {
+                status::Response::from_reader(body)
+            })
+            .await?;
+
+        let mode = CompatMode::from_version(&status.node_info.version.to_string())?;
+        *self.compat_mode.write().unwrap() = Some(mode);
+        Ok(mode)
+    }
+
+    /// Release series to adapt a request for the given method to, detecting
+    /// it first if configured, and failing if the node does not support the
+    /// method
+    async fn prepare_compat_mode(&self, method: Method) -> Result<Option<CompatMode>, Error> {
+        let mode = match self.compat_mode() {
+            None if self.detect_compat_mode && method != Method::Status => {
+                Some(self.detect_compat_mode().await?)
             }
+            mode => mode,
         };
 
+        match mode {
+            Some(mode) if !mode.supports(method) => Err(Error::method_not_found(method.as_str())),
+            mode => Ok(mode),
+        }
+    }
+
+    /// Check the given chain IDs of a response against the pinned one
+    fn check_chain_ids(&self, chain_ids: Vec<&chain::Id>) -> Result<(), Error> {
+        match &self.chain_id {
+            Some(expected) => match chain_ids.into_iter().find(|&id| id != expected) {
+                Some(actual) => Err(Error::chain_id_mismatch(expected, actual)),
+                None => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Send the given JSON request body for the given method (or batch of
+    /// requests for it), parse the response body with the given function,
+    /// retry idempotent requests as configured and record metrics.
+    async fn perform_raw<T, F>(
+        &self,
+        method: Method,
+        batch_size: Option<usize>,
+        request_body: String,
+        parse: F,
+    ) -> Result<T, Error>
+    where
+        F: Fn(Box<dyn Read + '_>) -> Result<T, Error>,
+    {
+        let started = Instant::now();
+        let retry_policy = self.retry_policy.filter(|_| method.is_idempotent());
+        let mut attempt = 1;
+        let mut response_size = 0;
+
+        let attempts = async {
+            loop {
+                if let Some(rate_limiter) = &self.rate_limiter {
+                    rate_limiter.acquire().await;
+                }
+
+                let result = self
+                    .perform_once(request_body.clone(), &parse, &mut response_size)
+                    .await;
+
+                match (result, retry_policy) {
+                    (Err(e), Some(policy)) if policy.should_retry(attempt, &e) => {
+                        let backoff = policy.backoff_with_jitter(attempt);
+                        trace_debug!(attempt, error = %e, ?backoff, "retrying request");
+                        tokio::time::delay_for(backoff).await;
+                        attempt += 1;
+                    }
+                    (result, _) => {
+                        trace_debug!(
+                            attempts = attempt,
+                            duration = ?started.elapsed(),
+                            success = result.is_ok(),
+                            "request completed"
+                        );
+                        return result;
+                    }
+                }
+            }
+        };
+
+        #[cfg(feature = "tracing")]
+        let attempts = tracing::Instrument::instrument(
+            attempts,
+            trace::request_span(method, batch_size, &request_body),
+        );
+
+        let result = attempts.await;
+
+        if let Some(Recorder(recorder)) = &self.metrics {
+            recorder.record(&RequestMetrics {
+                method,
+                batch_size,
+                duration: started.elapsed(),
+                attempts: attempt,
+                request_size: request_body.len(),
+                response_size,
+                error: result.as_ref().err(),
+            });
+        }
+
+        result
+    }
+
+    /// Send the given JSON request body once and parse the response, storing
+    /// the size of the response body
+    async fn perform_once<T, F>(
+        &self,
+        request_body: String,
+        parse: &F,
+        response_size: &mut usize,
+    ) -> Result<T, Error>
+    where
+        F: Fn(Box<dyn Read + '_>) -> Result<T, Error>,
+    {
+        trace_debug!(request_size = request_body.len(), "sending request");
         let mut request = hyper::Request::builder()
             .method("POST")
-            .uri(&format!("http://{}:{}/", host, port))
+            .uri(self.transport.uri(&self.url)?)
             .body(hyper::Body::from(request_body.into_bytes()))?;
 
         {
             let headers = request.headers_mut();
+            headers.extend(self.headers.clone());
             headers.insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
-            headers.insert(
-                header::USER_AGENT,
-                format!("tendermint.rs/{}", env!("CARGO_PKG_VERSION"))
-                    .parse()
-                    .unwrap(),
+        }
+
+        let response = async {
+            let response = self.transport.request(request).await?;
+            let status = response.status();
+            let encoding = ContentEncoding::from_headers(response.headers());
+            body::check_content_length(response.headers(), self.max_response_size)?;
+            let body = body::read_body(response.into_body(), self.max_response_size).await?;
+            Ok::<_, Error>((status, encoding, body))
+        };
+
+        *response_size = 0;
+        let (status, encoding, response_body) = match self.request_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, response).await??,
+            None => response.await?,
+        };
+        *response_size = response_body.size();
+        trace_debug!(
+            status = status.as_u16(),
+            response_size = response_body.size(),
+            "received response"
+        );
+
+        let result = parse(Box::new(LimitedReader::new(
+            encoding.decode(response_body),
+            self.max_response_size,
+        )));
+
+        match result {
+            // Gateways, proxies and rate limiters answer with error pages
+            // which are not JSONRPC
+            Err(e) if !status.is_success() && e.code() == Code::ParseError => {
+                Err(Error::http_status_error(status.as_u16()))
+            }
+            result => result,
+        }
+    }
+}
+
+/// Sends requests as configured, e.g. over HTTP, with retries and metrics
+#[async_trait]
+impl Transport for Client {
+    async fn send(&self, request_body: String) -> Result<Vec<u8>, Error> {
+        let json: serde_json::Value =
+            serde_json::from_str(&request_body).map_err(Error::parse_error)?;
+        let (request, batch_size) = match &json {
+            serde_json::Value::Array(requests) => (requests.first(), Some(requests.len())),
+            request => (Some(request), None),
+        };
+        let method = request
+            .and_then(|request| request.get("method"))
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| Error::invalid_params("request without method"))?
+            .parse::<Method>()?;
+
+        self.perform_raw(method, batch_size, request_body, |mut body| {
+            let mut bytes = vec![];
+            body.read_to_end(&mut bytes)
+                .map_err(|e| Error::new(Code::InternalError, Some(e.to_string())))?;
+            // Error pages which are not JSON fail here, so that they are
+            // reported with the HTTP status they were served with
+            serde_json::from_slice::<serde::de::IgnoredAny>(&bytes).map_err(Error::parse_error)?;
+            Ok(bytes)
+        })
+        .await
+    }
+}
+
+/// Stream all results of a paginated search, fetching pages with the given
+/// function, which returns the results on a page and the total number of
+/// results.
+fn search_all<'a, T, F, Fut>(mut fetch_page: F) -> impl Stream<Item = Result<T, Error>> + 'a
+where
+    T: 'a,
+    F: FnMut(u32) -> Fut + 'a,
+    Fut: Future<Output = Result<(Vec<T>, u32), Error>> + 'a,
+{
+    stream::try_unfold(Some(1), move |page| {
+        let results = page.map(&mut fetch_page);
+
+        async move {
+            let (page, (items, total_count)) = match (page, results) {
+                (Some(page), Some(results)) => (page, results.await?),
+                _ => return Ok(None),
+            };
+
+            let fetched = (page - 1) * u32::from(SEARCH_PAGE_SIZE) + items.len() as u32;
+            let next_page = if items.is_empty() || fetched >= total_count {
+                None
+            } else {
+                Some(page + 1)
+            };
+
+            let items = stream::iter(items.into_iter().map(Ok::<T, Error>));
+            Ok::<_, Error>(Some((items, next_page)))
+        }
+    })
+    .try_flatten()
+}
+
+/// Time to wait before polling again for something due by the given deadline,
+/// which is the time left if less than [`POLL_INTERVAL`], or `None` once the
+/// deadline has passed.
+fn next_poll(deadline: Instant) -> Option<Duration> {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+
+    if remaining == Duration::from_secs(0) {
+        None
+    } else {
+        Some(remaining.min(POLL_INTERVAL))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::{Matcher, MockClient};
+    use super::*;
+    use crate::Method;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    /// Mock node answering `/status` requests with the status fixture
+    fn status_mock() -> MockClient {
+        MockClient::new()
+            .respond_with_fixture(Matcher::method(Method::Status), "tests/support/status.json")
+            .unwrap()
+    }
+
+    fn tx_search_page(len: usize, total_count: u32) -> serde_json::Value {
+        let tx = json!({
+            "hash": "57018296EE0919C9D351F2FFEA82A8D28DE223724D79965FC8D00A7477ED48BC",
+            "height": "18",
+            "index": 0,
+            "tx_result": {
+                "code": 0,
+                "data": null,
+                "log": "",
+                "info": "",
+                "gasWanted": "0",
+                "gasUsed": "0",
+                "events": [],
+                "codespace": ""
+            },
+            "tx": "a2V5Mj12YWx1ZTI="
+        });
+
+        json!({ "txs": vec![tx; len], "total_count": total_count.to_string() })
+    }
+
+    #[tokio::test]
+    async fn tx_search_all_pages_through_results() {
+        let mock = MockClient::new()
+            .respond(
+                Matcher::method(Method::TxSearch).param("page", "1"),
+                tx_search_page(100, 150),
+            )
+            .respond(
+                Matcher::method(Method::TxSearch).param("page", "2"),
+                tx_search_page(50, 150),
             );
+        let client = mock.client();
+
+        let txs: Vec<_> = client
+            .tx_search_all(Query::eq("tx.height", 18u64), false, Order::Ascending)
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(txs.len(), 150);
+        assert_eq!(mock.requests_for(Method::TxSearch).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn tx_search_events_decodes_events() {
+        #[derive(Debug)]
+        struct Transfer {
+            amount: u64,
         }
-        let http_client = hyper::Client::builder().build_http();
-        let response = http_client.request(request).await?;
-        let response_body = hyper::body::aggregate(response.into_body()).await?;
-        R::Response::from_reader(response_body.reader())
+
+        impl FromAbciEvent for Transfer {
+            const EVENT_TYPE: &'static str = "transfer";
+
+            fn from_attributes(
+                attributes: &abci::event::Attributes,
+            ) -> Result<Self, tendermint::Error> {
+                Ok(Transfer {
+                    amount: attributes.parse("amount")?,
+                })
+            }
+        }
+
+        let mut page = tx_search_page(2, 2);
+        page["txs"][0]["tx_result"]["events"] = json!([
+            { "type": "message", "attributes": [{ "key": "sender", "value": "cosmos1abc" }] },
+            { "type": "transfer", "attributes": [{ "key": "amount", "value": "10" }] }
+        ]);
+        let client = MockClient::new()
+            .respond(Matcher::method(Method::TxSearch), page)
+            .client();
+
+        let query =
+            Query::from(EventType::Tx).and_event_attribute("message", "sender", "cosmos1abc");
+        let txs: Vec<(_, Vec<Transfer>)> = client
+            .tx_search_events(query, Order::Ascending)
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(txs.len(), 2);
+        assert_eq!(txs[0].1.len(), 1);
+        assert_eq!(txs[0].1[0].amount, 10);
+        assert!(txs[1].1.is_empty());
+    }
+
+    #[tokio::test]
+    async fn searches_reject_invalid_queries() {
+        let mock =
+            MockClient::new().respond(Matcher::method(Method::TxSearch), tx_search_page(0, 0));
+
+        let query = Query::eq("transfer.memo", "it's");
+        let error = mock
+            .client()
+            .tx_search(query, false, 1, 10, Order::Ascending)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.code(), Code::InvalidParams);
+        assert!(mock.requests().is_empty());
+    }
+
+    fn blockchain_page(from: u64, to: u64, last_height: u64) -> serde_json::Value {
+        let fixture: serde_json::Value =
+            serde_json::from_str(include_str!("../tests/support/blockchain.json")).unwrap();
+        let metas = (from..=to)
+            .rev()
+            .map(|height| {
+                let mut meta = fixture["result"]["block_metas"][0].clone();
+                meta["header"]["height"] = json!(height.to_string());
+                meta
+            })
+            .collect::<Vec<_>>();
+
+        json!({ "last_height": last_height.to_string(), "block_metas": metas })
+    }
+
+    #[tokio::test]
+    async fn blockchain_range_walks_batches() {
+        let mock = MockClient::new()
+            .respond(
+                Matcher::method(Method::Blockchain).param("minHeight", "3"),
+                blockchain_page(3, 22, 45),
+            )
+            .respond(
+                Matcher::method(Method::Blockchain).param("minHeight", "23"),
+                blockchain_page(23, 42, 45),
+            )
+            .respond(
+                Matcher::method(Method::Blockchain).param("minHeight", "43"),
+                blockchain_page(43, 45, 45),
+            );
+        let client = mock.client();
+
+        let heights: Vec<u64> = client
+            .blockchain_range(3u64, 100u64)
+            .map_ok(|meta| meta.header.height.value())
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(heights, (3..=45).collect::<Vec<_>>());
+        assert_eq!(mock.requests_for(Method::Blockchain).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn all_validators_follows_pagination() {
+        let fixture: serde_json::Value =
+            serde_json::from_str(include_str!("../tests/support/validators.json")).unwrap();
+        let all = fixture["result"]["validators"].as_array().unwrap().clone();
+        let page = |validators: &[serde_json::Value]| json!({ "block_height": "10", "validators": validators, "count": validators.len().to_string(), "total": all.len().to_string() });
+
+        let mock = MockClient::new()
+            .respond(
+                Matcher::method(Method::Validators).param("page", "1"),
+                page(&all[..40]),
+            )
+            .respond(
+                Matcher::method(Method::Validators).param("page", "2"),
+                page(&all[40..]),
+            );
+
+        let set = mock.client().all_validators(10u64).await.unwrap();
+        assert_eq!(set.validators().len(), all.len());
+        assert_eq!(mock.requests_for(Method::Validators).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn timeouts_can_be_overridden_per_request() {
+        #[derive(Debug)]
+        struct Slow(Client);
+
+        #[async_trait]
+        impl Transport for Slow {
+            async fn send(&self, request_body: String) -> Result<Vec<u8>, Error> {
+                tokio::time::delay_for(Duration::from_millis(50)).await;
+                self.0.send(request_body).await
+            }
+        }
+
+        let mock = status_mock();
+        let client = Client::builder("http://example.invalid".parse().unwrap())
+            .transport(Slow(mock.client()))
+            .request_timeout(Some(Duration::from_millis(10)))
+            .build()
+            .unwrap();
+
+        let error = client.status().await.unwrap_err();
+        assert_eq!(error.code(), Code::TimeoutError);
+
+        client
+            .with_timeout(Duration::from_secs(5))
+            .status()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn abci_query_helpers_assemble_paths() {
+        let value = subtle_encoding::base64::encode(r#"{"supply":"100"}"#);
+        let mock = MockClient::new().respond(
+            Matcher::method(Method::AbciQuery),
+            json!({ "response": { "height": "10", "value": String::from_utf8(value).unwrap() } }),
+        );
+        let client = mock.client();
+
+        client
+            .abci_query_store("bank", b"key".to_vec(), Some(10u64.into()), true)
+            .await
+            .unwrap();
+        let supply: serde_json::Value = client
+            .abci_query_custom(&["supply", "total"], vec![], None)
+            .await
+            .unwrap()
+            .json_value()
+            .unwrap();
+        assert_eq!(supply["supply"], "100");
+
+        let requests = mock.requests();
+        assert_eq!(requests[0].param("path"), Some(&json!("/store/bank/key")));
+        assert_eq!(requests[0].param("data"), Some(&json!("6b6579")));
+        assert_eq!(requests[0].param("height"), Some(&json!("10")));
+        assert_eq!(
+            requests[1].param("path"),
+            Some(&json!("/custom/supply/total"))
+        );
+    }
+
+    #[tokio::test]
+    async fn layers_wrap_the_transport_in_order() {
+        #[derive(Debug)]
+        struct Tag(
+            &'static str,
+            Arc<std::sync::Mutex<Vec<&'static str>>>,
+            Arc<dyn Transport>,
+        );
+
+        #[async_trait]
+        impl Transport for Tag {
+            async fn send(&self, request_body: String) -> Result<Vec<u8>, Error> {
+                self.1.lock().unwrap().push(self.0);
+                self.2.send(request_body).await
+            }
+        }
+
+        let tags = Arc::new(std::sync::Mutex::new(vec![]));
+        let tag = |name: &'static str| {
+            let tags = tags.clone();
+            move |inner: Arc<dyn Transport>| -> Arc<dyn Transport> {
+                Arc::new(Tag(name, tags.clone(), inner))
+            }
+        };
+
+        let mock = status_mock();
+        let client = Client::builder("http://example.invalid".parse().unwrap())
+            .transport(mock.client())
+            .layer(tag("outer"))
+            .layer(tag("inner"))
+            .build()
+            .unwrap();
+
+        client.status().await.unwrap();
+        assert_eq!(*tags.lock().unwrap(), vec!["outer", "inner"]);
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn detects_compat_mode_before_first_request() {
+        let mock = status_mock();
+        let client = Client::builder("http://example.invalid".parse().unwrap())
+            .transport(mock.clone())
+            .detect_compat_mode(true)
+            .build()
+            .unwrap();
+        assert_eq!(client.compat_mode(), None);
+
+        let error = client
+            .block_search(Query::from(EventType::NewBlock), 1, 10, Order::Ascending)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), Code::MethodNotFound);
+        assert_eq!(client.compat_mode(), Some(CompatMode::V0_33));
+        assert_eq!(mock.requests_for(Method::BlockSearch).len(), 0);
+
+        client.status().await.unwrap();
+        assert_eq!(mock.requests_for(Method::Status).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn genesis_chunked_reassembles_chunks() {
+        let fixture: serde_json::Value =
+            serde_json::from_str(include_str!("../tests/support/genesis.json")).unwrap();
+        let genesis = fixture["result"]["genesis"].to_string();
+        let (first, second) = genesis.as_bytes().split_at(genesis.len() / 2);
+
+        let chunk = |index: u64, data: &[u8]| {
+            let data = String::from_utf8(subtle_encoding::base64::encode(data)).unwrap();
+            json!({ "chunk": index.to_string(), "total": "2", "data": data })
+        };
+
+        let mock = MockClient::new()
+            .respond(
+                Matcher::method(Method::GenesisChunked).param("chunk", "0"),
+                chunk(0, first),
+            )
+            .respond(
+                Matcher::method(Method::GenesisChunked).param("chunk", "1"),
+                chunk(1, second),
+            );
+
+        let genesis = mock.client().genesis_chunked().await.unwrap();
+        assert_eq!(genesis.chain_id.as_str(), "cosmoshub-2");
+    }
+
+    #[tokio::test]
+    async fn records_request_metrics() {
+        #[derive(Default)]
+        struct Collector(std::sync::Mutex<Vec<(Method, u32, bool, usize)>>);
+
+        impl MetricsRecorder for Arc<Collector> {
+            fn record(&self, metrics: &RequestMetrics<'_>) {
+                self.0.lock().unwrap().push((
+                    metrics.method,
+                    metrics.attempts,
+                    metrics.is_success(),
+                    metrics.response_size,
+                ));
+            }
+        }
+
+        let collector = Arc::new(Collector::default());
+        let mock = status_mock();
+        let mut client = mock.client();
+        client.metrics = Some(Recorder(Arc::new(collector.clone())));
+
+        client.status().await.unwrap();
+        client.health().await.unwrap_err();
+
+        let records = collector.0.lock().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].0, Method::Status);
+        assert!(records[0].2 && records[0].3 > 0);
+        assert_eq!(
+            (records[1].0, records[1].1, records[1].2),
+            (Method::Health, 1, false)
+        );
+    }
+
+    #[tokio::test]
+    async fn clients_are_transports() {
+        let mock = status_mock();
+        let client = Client::builder("http://example.invalid".parse().unwrap())
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+
+        let status = Transport::perform(&client, status::Request).await.unwrap();
+        assert_eq!(status.node_info.network.as_str(), "cosmoshub-2");
+        assert_eq!(mock.requests_for(Method::Status).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rejects_responses_from_other_chains() {
+        let mock = status_mock();
+        let mut client = mock.client();
+
+        client.chain_id = Some("cosmoshub-2".parse().unwrap());
+        client.status().await.unwrap();
+
+        client.chain_id = Some("cosmoshub-3".parse().unwrap());
+        let error = client.status().await.unwrap_err();
+        assert_eq!(error.code(), Code::ChainIdMismatch);
+    }
+
+    #[tokio::test]
+    async fn broadcast_tx_commit_falls_back_to_polling() {
+        let mock = MockClient::new()
+            .respond_with_error(
+                Matcher::method(Method::BroadcastTxCommit),
+                Error::new(
+                    Code::InternalError,
+                    Some("timed out waiting for tx to be included in a block".to_owned()),
+                ),
+            )
+            .respond_with_fixture(Matcher::method(Method::Tx), "tests/support/tx.json")
+            .unwrap();
+
+        let committed = mock
+            .client()
+            .broadcast_tx_commit_or_poll(Transaction::new("key1=value1"), Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert!(matches!(committed, CommittedTx::Polled(_)));
+        assert_eq!(committed.height().value(), 12);
+        assert!(committed.code().is_ok());
+    }
+
+    #[tokio::test]
+    async fn broadcast_tx_commit_failures_are_final() {
+        let mock = MockClient::new().respond_with_error(
+            Matcher::method(Method::BroadcastTxCommit),
+            Error::new(Code::InternalError, Some("mempool is full".to_owned())),
+        );
+
+        let result = mock
+            .client()
+            .broadcast_tx_commit_or_poll(Transaction::new("tx"), Duration::from_secs(5))
+            .await;
+
+        assert_eq!(result.unwrap_err().reason(), Reason::MempoolFull);
+        assert!(mock.requests_for(Method::Tx).is_empty());
+    }
+
+    #[tokio::test]
+    async fn wait_for_tx_times_out() {
+        let mock = MockClient::new().respond_with_error(
+            Matcher::method(Method::Tx),
+            Error::new(
+                Code::InternalError,
+                Some("tx (4CFCD46C59F54B5EA6A5F9B05C28B52FEF2864747194B5FDFC3D59C0057BF35A) not found".to_owned()),
+            ),
+        );
+
+        let error = mock
+            .client()
+            .wait_for_tx(
+                Transaction::new("key1=value1").hash(),
+                Duration::from_millis(10),
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.code(), Code::TimeoutError);
+        // polled once more at the deadline before giving up
+        assert_eq!(mock.requests_for(Method::Tx).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn identifies_requests_with_generated_ids() {
+        let mock = status_mock();
+        let client = Client::builder("http://example.invalid".parse().unwrap())
+            .transport(mock.clone())
+            .id_generator(crate::SequentialIds::new())
+            .build()
+            .unwrap();
+
+        client.status().await.unwrap();
+        client.status().await.unwrap();
+
+        let ids = mock
+            .requests()
+            .iter()
+            .map(|request| request.id().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![crate::Id::Num(1), crate::Id::Num(2)]);
+    }
+
+    #[tokio::test]
+    async fn wait_until_healthy() {
+        let mock = status_mock()
+            .respond_with_fixture(Matcher::method(Method::Health), "tests/support/health.json")
+            .unwrap();
+        let status = mock
+            .client()
+            .wait_until_healthy(Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(!status.sync_info.catching_up);
+
+        let down = MockClient::new().respond_with_error(
+            Matcher::method(Method::Health),
+            Error::http_error("connection refused"),
+        );
+        let error = down
+            .client()
+            .wait_until_healthy(Duration::from_millis(10))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), Code::TimeoutError);
+        assert_eq!(down.requests_for(Method::Health).len(), 2);
+        assert!(down.requests_for(Method::Status).is_empty());
+    }
+
+    /// Serve every HTTP request with the given raw response, counting them
+    async fn serve_raw(response: &'static str) -> (Url, Arc<std::sync::atomic::AtomicUsize>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        let served = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let counter = served.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                while !request.ends_with(b"\r\n\r\n") {
+                    request.push(stream.read_u8().await.unwrap());
+                }
+                let request = String::from_utf8_lossy(&request).to_lowercase();
+                let length = request
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length: "))
+                    .map_or(0, |length| length.trim().parse().unwrap());
+                let mut body = vec![0; length];
+                stream.read_exact(&mut body).await.unwrap();
+
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        (url, served)
+    }
+
+    #[tokio::test]
+    async fn layered_transport_keeps_http_status() {
+        let (url, served) = serve_raw(
+            "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 11\r\nconnection: close\r\n\r\nunavailable",
+        )
+        .await;
+
+        let client = Client::builder(url)
+            .retry_policy(RetryPolicy {
+                max_attempts: 2,
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+            })
+            .layer(|inner: Arc<dyn Transport>| inner)
+            .build()
+            .unwrap();
+
+        let error = client.status().await.unwrap_err();
+        assert_eq!(error.code(), Code::HttpError);
+        assert_eq!(error.http_status(), Some(503));
+        assert_eq!(served.load(std::sync::atomic::Ordering::SeqCst), 2);
     }
 }
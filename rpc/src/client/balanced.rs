@@ -0,0 +1,301 @@
+//! Transport balancing requests across several RPC endpoints
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use super::failover::{failover_error, should_fail_over};
+use super::transport::Transport;
+use super::{Client, Url};
+use crate::Error;
+
+/// How a [`BalancedClient`] picks the endpoint for a request
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Strategy {
+    /// Take turns between the endpoints
+    RoundRobin,
+
+    /// Pick the endpoint with the fewest requests in flight
+    LeastInFlight,
+}
+
+/// Circuit breaking settings of a [`BalancedClient`].
+///
+/// After `failure_threshold` consecutive failures, an endpoint's circuit
+/// opens and no requests are sent to it for `open_duration`. Afterwards
+/// requests are sent again, but a single further failure reopens the circuit
+/// until a request succeeds.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CircuitBreaker {
+    /// Number of consecutive failures opening the circuit
+    pub failure_threshold: u32,
+
+    /// Time the circuit stays open
+    pub open_duration: Duration,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+/// [`Transport`] distributing requests across several endpoints, e.g. for
+/// indexers performing many queries concurrently.
+///
+/// Failures caused by an endpoint (transport errors and unavailable heights)
+/// count towards opening its circuit, see [`CircuitBreaker`], but are not
+/// retried against another endpoint: configure a
+/// [`RetryPolicy`](super::RetryPolicy) on the wrapping [`Client`] for that.
+/// Requests fail right away while the circuits of all endpoints are open.
+///
+/// ```ignore
+/// let balanced = BalancedClient::new(clients, Strategy::LeastInFlight)?;
+/// let client = Client::builder(url).transport(balanced).retry_policy(RetryPolicy::default()).build()?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct BalancedClient {
+    endpoints: Arc<Vec<Endpoint>>,
+    strategy: Strategy,
+    circuit_breaker: CircuitBreaker,
+    /// Endpoint to start looking from for round robin
+    next: Arc<AtomicUsize>,
+}
+
+/// Client of a single endpoint along with its load and circuit
+#[derive(Debug)]
+struct Endpoint {
+    client: Client,
+    in_flight: AtomicUsize,
+    circuit: Mutex<Circuit>,
+}
+
+#[derive(Debug, Default)]
+struct Circuit {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl Endpoint {
+    fn is_available(&self, now: Instant) -> bool {
+        match self.circuit.lock().unwrap().open_until {
+            Some(until) => until <= now,
+            None => true,
+        }
+    }
+
+    fn record_success(&self) {
+        *self.circuit.lock().unwrap() = Circuit::default();
+    }
+
+    fn record_failure(&self, circuit_breaker: &CircuitBreaker) {
+        let mut circuit = self.circuit.lock().unwrap();
+        circuit.consecutive_failures = circuit.consecutive_failures.saturating_add(1);
+
+        if circuit.consecutive_failures >= circuit_breaker.failure_threshold {
+            circuit.open_until = Some(Instant::now() + circuit_breaker.open_duration);
+        }
+    }
+}
+
+/// Counts a request as in flight until dropped
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl<'a> InFlight<'a> {
+    fn new(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self(counter)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl BalancedClient {
+    /// Balance requests across the endpoints of the given clients with the
+    /// given strategy. Fails if no client is given.
+    pub fn new(clients: Vec<Client>, strategy: Strategy) -> Result<Self, Error> {
+        if clients.is_empty() {
+            return Err(Error::invalid_params("no endpoints to balance between"));
+        }
+
+        let endpoints = clients
+            .into_iter()
+            .map(|client| Endpoint {
+                client,
+                in_flight: AtomicUsize::new(0),
+                circuit: Mutex::new(Circuit::default()),
+            })
+            .collect();
+
+        Ok(Self {
+            endpoints: Arc::new(endpoints),
+            strategy,
+            circuit_breaker: CircuitBreaker::default(),
+            next: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// Open circuits according to the given settings
+    /// ([`CircuitBreaker::default`] by default)
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    /// URLs of the endpoints, along with the number of requests in flight
+    /// and whether their circuit is closed
+    pub fn endpoints(&self) -> Vec<(Url, usize, bool)> {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                (
+                    endpoint.client.url().clone(),
+                    endpoint.in_flight.load(Ordering::SeqCst),
+                    endpoint.is_available(now),
+                )
+            })
+            .collect()
+    }
+
+    /// Endpoint to send the next request to, if any is available
+    fn pick(&self) -> Option<&Endpoint> {
+        let now = Instant::now();
+        let len = self.endpoints.len();
+
+        match self.strategy {
+            Strategy::RoundRobin => {
+                let start = self.next.fetch_add(1, Ordering::SeqCst);
+                (0..len)
+                    .map(|offset| &self.endpoints[(start + offset) % len])
+                    .find(|endpoint| endpoint.is_available(now))
+            }
+            Strategy::LeastInFlight => self
+                .endpoints
+                .iter()
+                .filter(|endpoint| endpoint.is_available(now))
+                .min_by_key(|endpoint| endpoint.in_flight.load(Ordering::SeqCst)),
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for BalancedClient {
+    async fn send(&self, request_body: String) -> Result<Vec<u8>, Error> {
+        let endpoint = self.pick().ok_or_else(|| {
+            Error::http_error("circuits of all endpoints are open, not sending request")
+        })?;
+
+        let result = {
+            let _in_flight = InFlight::new(&endpoint.in_flight);
+            endpoint.client.send(request_body).await
+        };
+
+        let failure = match &result {
+            Ok(response_body) => failover_error(response_body),
+            Err(e) if should_fail_over(e) => Some(e.clone()),
+            Err(_) => None,
+        };
+
+        match failure {
+            Some(error) => {
+                endpoint.record_failure(&self.circuit_breaker);
+                Err(error)
+            }
+            None => {
+                endpoint.record_success();
+                result
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mock::{Matcher, MockClient};
+    use super::*;
+    use crate::Method;
+
+    fn status_mock() -> MockClient {
+        MockClient::new()
+            .respond_with_fixture(Matcher::method(Method::Status), "tests/support/status.json")
+            .unwrap()
+    }
+
+    fn client(transport: BalancedClient) -> Client {
+        Client::builder("http://example.invalid".parse().unwrap())
+            .transport(transport)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn round_robin() {
+        let mocks = vec![status_mock(), status_mock(), status_mock()];
+        let balanced = BalancedClient::new(
+            mocks.iter().map(MockClient::client).collect(),
+            Strategy::RoundRobin,
+        )
+        .unwrap();
+        let client = client(balanced);
+
+        for _ in 0..6 {
+            client.status().await.unwrap();
+        }
+
+        assert!(mocks.iter().all(|mock| mock.requests().len() == 2));
+    }
+
+    #[tokio::test]
+    async fn least_in_flight() {
+        let mocks = vec![status_mock(), status_mock()];
+        let balanced = BalancedClient::new(
+            mocks.iter().map(MockClient::client).collect(),
+            Strategy::LeastInFlight,
+        )
+        .unwrap();
+
+        let _busy = InFlight::new(&balanced.endpoints[0].in_flight);
+        client(balanced).status().await.unwrap();
+
+        assert!(mocks[0].requests().is_empty());
+        assert_eq!(mocks[1].requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn circuit_opens_after_failures() {
+        let down = MockClient::new().respond_with_error(
+            Matcher::method(Method::Status),
+            Error::http_error("connection refused"),
+        );
+        let up = status_mock();
+
+        let balanced = BalancedClient::new(vec![down.client(), up.client()], Strategy::RoundRobin)
+            .unwrap()
+            .with_circuit_breaker(CircuitBreaker {
+                failure_threshold: 2,
+                open_duration: Duration::from_secs(60),
+            });
+        let client = client(balanced.clone());
+
+        let mut results = vec![];
+        for _ in 0..8 {
+            results.push(client.status().await);
+        }
+
+        // Two failures open the circuit, then all requests go to the other
+        // endpoint
+        assert_eq!(results.iter().filter(|r| r.is_err()).count(), 2);
+        assert_eq!(down.requests().len(), 2);
+        assert!(!balanced.endpoints()[0].2);
+    }
+}
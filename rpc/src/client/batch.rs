@@ -0,0 +1,101 @@
+//! JSONRPC batch requests
+
+use crate::{request, response, Error, Id, Request, Response};
+
+/// A JSONRPC batch of requests of the same type.
+///
+/// Each request is tagged with its position in the batch as ID, which is
+/// used to put the responses, which servers may send in any order, back into
+/// the order of the requests.
+pub(crate) struct Batch {
+    /// Number of requests in this batch
+    len: usize,
+
+    /// Serialized JSON array of request envelopes
+    json: String,
+}
+
+impl Batch {
+    /// Create a new batch from the given requests
+    pub(crate) fn new<R>(requests: Vec<R>) -> Self
+    where
+        R: Request,
+    {
+        let len = requests.len();
+        let wrappers = requests
+            .into_iter()
+            .enumerate()
+            .map(|(index, request)| request::Wrapper::new_with_id(request, Id::Num(index as i64)))
+            .collect::<Vec<_>>();
+
+        Self {
+            len,
+            json: serde_json::to_string_pretty(&wrappers).unwrap(),
+        }
+    }
+
+    /// Serialize this batch as JSON
+    pub(crate) fn to_json(&self) -> String {
+        self.json.clone()
+    }
+
+    /// Match the given responses to the requests in this batch, returning
+    /// their results in the order of the requests.
+    pub(crate) fn demultiplex<T>(
+        &self,
+        responses: Vec<response::Wrapper<T>>,
+    ) -> Vec<Result<T, Error>>
+    where
+        T: Response,
+    {
+        let mut results = (0..self.len).map(|_| None).collect::<Vec<_>>();
+
+        for response in responses {
+            if let Id::Num(index) = *response.id() {
+                if index >= 0 && (index as usize) < self.len {
+                    results[index as usize] = Some(response.into_result());
+                }
+            }
+        }
+
+        results
+            .into_iter()
+            .map(|result| {
+                result.unwrap_or_else(|| {
+                    Err(Error::server_error(
+                        "server sent no response for a batched request",
+                    ))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint::health;
+
+    #[test]
+    fn responses_are_reordered_by_id() {
+        let batch = Batch::new(vec![health::Request, health::Request, health::Request]);
+
+        let json: serde_json::Value = serde_json::from_str(&batch.to_json()).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 3);
+        assert_eq!(json[2]["id"], 2);
+
+        let responses = serde_json::from_str::<Vec<response::Wrapper<health::Response>>>(
+            r#"[
+                {"jsonrpc": "2.0", "id": 2, "result": {}},
+                {"jsonrpc": "2.0", "id": 0, "error": {"code": -32603, "message": "Internal error", "data": "oops"}}
+            ]"#,
+        )
+        .unwrap();
+
+        let results = batch.demultiplex(responses);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap_err().data(), Some("oops"));
+        assert!(results[1].is_err());
+        assert!(results[2].is_ok());
+    }
+}
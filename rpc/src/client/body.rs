@@ -0,0 +1,175 @@
+//! Reading of HTTP response bodies with an optional size limit
+
+use std::collections::VecDeque;
+use std::io::{self, Read};
+
+use bytes::{Buf, Bytes};
+use hyper::body::HttpBody;
+use hyper::header::{HeaderMap, CONTENT_LENGTH};
+use hyper::Body;
+
+use crate::Error;
+
+/// Fail early if the `Content-Length` announced by the server exceeds the
+/// given maximum size.
+pub(crate) fn check_content_length(
+    headers: &HeaderMap,
+    max_size: Option<usize>,
+) -> Result<(), Error> {
+    let content_length = headers
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+
+    match (content_length, max_size) {
+        (Some(length), Some(max_size)) if length > max_size => {
+            Err(Error::response_too_large(max_size))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Read the given body into memory, aborting as soon as it exceeds the
+/// given maximum size.
+pub(crate) async fn read_body(mut body: Body, max_size: Option<usize>) -> Result<Chunks, Error> {
+    let mut chunks = Chunks::default();
+
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+
+        if let Some(max_size) = max_size {
+            if chunks.size + chunk.len() > max_size {
+                return Err(Error::response_too_large(max_size));
+            }
+        }
+
+        chunks.size += chunk.len();
+        chunks.chunks.push_back(chunk);
+    }
+
+    Ok(chunks)
+}
+
+/// Body as received in chunks.
+///
+/// Reading it hands out the chunks in order, without first copying them
+/// into one contiguous buffer, and releases each chunk once it has been
+/// read, so that deserializing a large response does not need twice its
+/// size in memory.
+#[derive(Debug, Default)]
+pub(crate) struct Chunks {
+    chunks: VecDeque<Bytes>,
+    /// Total size of all chunks as received, in bytes
+    size: usize,
+}
+
+impl Chunks {
+    /// Total size of the body as received, in bytes
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl Read for Chunks {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while let Some(chunk) = self.chunks.front_mut() {
+            if chunk.is_empty() {
+                self.chunks.pop_front();
+                continue;
+            }
+
+            let read = chunk.len().min(buf.len());
+            buf[..read].copy_from_slice(&chunk[..read]);
+            chunk.advance(read);
+            return Ok(read);
+        }
+
+        Ok(0)
+    }
+}
+
+/// Reader failing with an I/O error once more than the given number of bytes
+/// have been read from the inner reader.
+///
+/// Used to bound the size of decompressed response bodies.
+pub(crate) struct LimitedReader<R> {
+    inner: R,
+    remaining: Option<usize>,
+    max_size: usize,
+}
+
+impl<R: Read> LimitedReader<R> {
+    /// Limit the given reader to the given maximum size, if any
+    pub(crate) fn new(inner: R, max_size: Option<usize>) -> Self {
+        Self {
+            inner,
+            remaining: max_size,
+            max_size: max_size.unwrap_or(0),
+        }
+    }
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+
+        if let Some(remaining) = self.remaining {
+            if read > remaining {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    Error::response_too_large(self.max_size),
+                ));
+            }
+            self.remaining = Some(remaining - read);
+        }
+
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limited_reader_fails_past_limit() {
+        let mut out = Vec::new();
+        let mut reader = LimitedReader::new(&b"0123456789"[..], Some(4));
+        assert!(reader.read_to_end(&mut out).is_err());
+
+        let mut out = Vec::new();
+        let mut reader = LimitedReader::new(&b"0123456789"[..], Some(10));
+        assert_eq!(reader.read_to_end(&mut out).unwrap(), 10);
+
+        let mut out = Vec::new();
+        let mut reader = LimitedReader::new(&b"0123456789"[..], None);
+        assert_eq!(reader.read_to_end(&mut out).unwrap(), 10);
+    }
+
+    #[test]
+    fn chunks_are_read_in_order() {
+        let mut chunks = Chunks::default();
+        for chunk in &["{\"json", "", "rpc\":", "\"2.0\"}"] {
+            chunks.size += chunk.len();
+            chunks
+                .chunks
+                .push_back(Bytes::from_static(chunk.as_bytes()));
+        }
+        assert_eq!(chunks.size(), 17);
+
+        let mut out = String::new();
+        chunks.read_to_string(&mut out).unwrap();
+        assert_eq!(out, "{\"jsonrpc\":\"2.0\"}");
+        assert!(chunks.chunks.is_empty());
+    }
+
+    #[test]
+    fn content_length_is_checked() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_LENGTH, "1024".parse().unwrap());
+
+        assert!(check_content_length(&headers, Some(1023)).is_err());
+        assert!(check_content_length(&headers, Some(1024)).is_ok());
+        assert!(check_content_length(&headers, None).is_ok());
+    }
+}
@@ -0,0 +1,298 @@
+//! Transport caching responses carrying immutable data
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::transport::Transport;
+use crate::{Error, Method, Version};
+
+/// Storage of the responses cached by a [`CachingClient`]
+pub trait CacheStore: fmt::Debug + Send + Sync {
+    /// Cached value for the given key, if any
+    fn get(&self, key: &str) -> Option<Vec<u8>>;
+
+    /// Cache the given value under the given key
+    fn put(&self, key: &str, value: Vec<u8>);
+}
+
+/// [`Transport`] caching the results of requests for data which never
+/// changes once it exists, e.g. to cut the load on nodes when reindexing a
+/// chain.
+///
+/// Cached are the results of requests for blocks, commits, block results
+/// and validators at a given height, and for blocks and transactions by
+/// hash. Requests for the latest height, commits which are not canonical
+/// yet, errors and batches are never cached.
+///
+/// ```ignore
+/// let cache = CachingClient::new(Client::new(address), MemoryCache::new(10_000));
+/// let client = Client::builder(url).transport(cache).build()?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct CachingClient {
+    inner: Arc<dyn Transport>,
+    store: Arc<dyn CacheStore>,
+}
+
+impl CachingClient {
+    /// Cache the results of the requests sent over the given transport in
+    /// the given store
+    pub fn new(inner: impl Transport + 'static, store: impl CacheStore + 'static) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            store: Arc::new(store),
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for CachingClient {
+    async fn send(&self, request_body: String) -> Result<Vec<u8>, Error> {
+        let request = match serde_json::from_str::<Value>(&request_body) {
+            Ok(request) => request,
+            Err(_) => return self.inner.send(request_body).await,
+        };
+
+        let key = match cache_key(&request) {
+            Some(key) => key,
+            None => return self.inner.send(request_body).await,
+        };
+
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        if let Some(result) = self.store.get(&key) {
+            if let Ok(result) = serde_json::from_slice::<Value>(&result) {
+                let response = json!({ "jsonrpc": Version::current(), "id": id, "result": result });
+                return Ok(serde_json::to_vec(&response).expect("JSON values serialize"));
+            }
+        }
+
+        let response_body = self.inner.send(request_body).await?;
+
+        let result = serde_json::from_slice::<Value>(&response_body)
+            .ok()
+            .and_then(|mut response| response.get_mut("result").map(Value::take))
+            .filter(is_final);
+        if let Some(result) = result {
+            self.store.put(
+                &key,
+                serde_json::to_vec(&result).expect("JSON values serialize"),
+            );
+        }
+
+        Ok(response_body)
+    }
+}
+
+/// Key identifying the given request among requests for immutable data, or
+/// `None` if its result may change
+fn cache_key(request: &Value) -> Option<String> {
+    let method = request.get("method")?.as_str()?.parse::<Method>().ok()?;
+    let params = request.get("params")?;
+
+    let identified = match method {
+        Method::Block | Method::BlockResults | Method::Commit | Method::Validators => {
+            !params.get("height")?.is_null()
+        }
+        Method::BlockByHash | Method::Tx => !params.get("hash")?.is_null(),
+        _ => false,
+    };
+
+    if identified {
+        Some(format!("{}:{}", method, params))
+    } else {
+        None
+    }
+}
+
+/// Is the given result final? Commits for the latest height may still change
+/// until the next block is committed.
+fn is_final(result: &Value) -> bool {
+    match result.get("canonical") {
+        Some(canonical) => canonical.as_bool() == Some(true),
+        None => !result.is_null(),
+    }
+}
+
+/// In-memory [`CacheStore`] holding a bounded number of values, evicting
+/// the least recently used one when full
+#[derive(Debug)]
+pub struct MemoryCache {
+    capacity: usize,
+    entries: Mutex<LruEntries>,
+}
+
+#[derive(Debug, Default)]
+struct LruEntries {
+    values: HashMap<String, Vec<u8>>,
+    /// Keys from least to most recently used
+    recency: VecDeque<String>,
+}
+
+impl LruEntries {
+    fn touch(&mut self, key: &str) {
+        if let Some(position) = self.recency.iter().position(|k| k == key) {
+            let key = self.recency.remove(position).unwrap();
+            self.recency.push_back(key);
+        }
+    }
+}
+
+impl MemoryCache {
+    /// Create a cache holding at most the given number of values
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(LruEntries::default()),
+        }
+    }
+
+    /// Number of values held
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().values.len()
+    }
+
+    /// Is the cache empty?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl CacheStore for MemoryCache {
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let mut entries = self.entries.lock().unwrap();
+        let value = entries.values.get(key).cloned()?;
+        entries.touch(key);
+        Some(value)
+    }
+
+    fn put(&self, key: &str, value: Vec<u8>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.values.insert(key.to_owned(), value).is_some() {
+            entries.touch(key);
+            return;
+        }
+
+        entries.recency.push_back(key.to_owned());
+        if entries.recency.len() > self.capacity {
+            let evicted = entries.recency.pop_front().unwrap();
+            entries.values.remove(&evicted);
+        }
+    }
+}
+
+/// [`CacheStore`] keeping values in files within a directory, so that they
+/// survive restarts. Nothing is ever evicted.
+///
+/// Values which cannot be read or written are treated as not cached.
+#[derive(Clone, Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    /// Keep values in the given directory, creating it if necessary
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, Error> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|e| {
+            Error::invalid_params(&format!(
+                "cannot create cache directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        self.dir.join(format!("{:016x}", hasher.finish()))
+    }
+}
+
+impl CacheStore for DiskCache {
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        // Files start with the key, which tells keys with the same hash apart
+        let contents = fs::read(self.path(key)).ok()?;
+        let separator = contents.iter().position(|&byte| byte == b'\n')?;
+
+        if contents[..separator] == *key.as_bytes() {
+            Some(contents[separator + 1..].to_vec())
+        } else {
+            None
+        }
+    }
+
+    fn put(&self, key: &str, value: Vec<u8>) {
+        let mut contents = Vec::with_capacity(key.len() + 1 + value.len());
+        contents.extend_from_slice(key.as_bytes());
+        contents.push(b'\n');
+        contents.extend(value);
+
+        let _ = fs::write(self.path(key), contents);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mock::{Matcher, MockClient};
+    use super::super::Client;
+    use super::*;
+
+    #[tokio::test]
+    async fn caches_results_for_fixed_heights() {
+        let mock = MockClient::new()
+            .respond_with_fixture(Matcher::method(Method::Block), "tests/support/block.json")
+            .unwrap();
+        let client = Client::builder("http://example.invalid".parse().unwrap())
+            .transport(CachingClient::new(mock.client(), MemoryCache::new(10)))
+            .build()
+            .unwrap();
+
+        let block = client.block(10u64).await.unwrap();
+        let cached = client.block(10u64).await.unwrap();
+        assert_eq!(cached.block_id, block.block_id);
+        assert_eq!(mock.requests().len(), 1);
+
+        client.latest_block().await.unwrap();
+        client.latest_block().await.unwrap();
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[test]
+    fn memory_cache_evicts_least_recently_used() {
+        let cache = MemoryCache::new(2);
+        cache.put("a", b"1".to_vec());
+        cache.put("b", b"2".to_vec());
+        cache.get("a");
+        cache.put("c", b"3".to_vec());
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("a"), Some(b"1".to_vec()));
+        assert_eq!(cache.get("b"), None);
+    }
+
+    #[test]
+    fn disk_cache_round_trips() {
+        let dir = std::env::temp_dir().join(format!("tendermint-rpc-cache-{}", std::process::id()));
+        let cache = DiskCache::new(&dir).unwrap();
+
+        cache.put("block:{\"height\":\"10\"}", b"{}".to_vec());
+        assert_eq!(cache.get("block:{\"height\":\"10\"}"), Some(b"{}".to_vec()));
+        assert_eq!(cache.get("block:{\"height\":\"11\"}"), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
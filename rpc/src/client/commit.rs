@@ -0,0 +1,52 @@
+//! Outcome of broadcasting a transaction and waiting for its commit
+
+use tendermint::abci::{transaction, Code, Log};
+use tendermint::block;
+
+use crate::endpoint::{broadcast::tx_commit, tx};
+
+/// Committed transaction, as reported by `/broadcast_tx_commit` or found by
+/// polling `/tx` after waiting for the commit timed out, see
+/// [`Client::broadcast_tx_commit_or_poll`](super::Client::broadcast_tx_commit_or_poll)
+#[derive(Clone, Debug)]
+pub enum CommittedTx {
+    /// Response of `/broadcast_tx_commit`
+    Broadcast(tx_commit::Response),
+
+    /// Response of `/tx`
+    Polled(tx::Response),
+}
+
+impl CommittedTx {
+    /// Hash of the transaction
+    pub fn hash(&self) -> transaction::Hash {
+        match self {
+            CommittedTx::Broadcast(response) => response.hash,
+            CommittedTx::Polled(response) => response.hash,
+        }
+    }
+
+    /// Height of the block containing the transaction
+    pub fn height(&self) -> block::Height {
+        match self {
+            CommittedTx::Broadcast(response) => response.height,
+            CommittedTx::Polled(response) => response.height,
+        }
+    }
+
+    /// Result code of executing the transaction (`DeliverTx`)
+    pub fn code(&self) -> Code {
+        match self {
+            CommittedTx::Broadcast(response) => response.deliver_tx.code,
+            CommittedTx::Polled(response) => response.tx_result.code,
+        }
+    }
+
+    /// Log of executing the transaction (`DeliverTx`)
+    pub fn log(&self) -> &Log {
+        match self {
+            CommittedTx::Broadcast(response) => &response.deliver_tx.log,
+            CommittedTx::Polled(response) => &response.tx_result.log,
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Compatibility with the RPC of different Tendermint releases
+
+use std::io::Read;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{Error, Method};
+
+/// Keys renamed by later releases, and the names this crate parses them by
+const RENAMED_KEYS: &[(&str, &str)] = &[
+    ("gas_wanted", "gasWanted"),
+    ("gas_used", "gasUsed"),
+    ("finalize_block_events", "end_block_events"),
+];
+
+/// Release series of Tendermint (or CometBFT) whose RPC a node speaks, as
+/// detected by [`Client::detect_compat_mode`](super::Client::detect_compat_mode)
+/// or pinned with [`ClientBuilder::compat_mode`](super::ClientBuilder::compat_mode).
+///
+/// Clients knowing the compatibility mode of their node reject requests for
+/// methods it does not support, and adapt its responses to the form this
+/// crate parses.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum CompatMode {
+    /// Tendermint 0.33 and earlier
+    V0_33,
+
+    /// Tendermint 0.34
+    V0_34,
+
+    /// Tendermint 0.35 to 0.37 and CometBFT 0.37
+    V0_37,
+
+    /// CometBFT 0.38 and later
+    V0_38,
+}
+
+impl CompatMode {
+    /// Compatibility mode of nodes of the given version, as reported in
+    /// their node information, e.g. `0.34.24` or `v0.38.0-rc3`
+    pub fn from_version(version: &str) -> Result<Self, Error> {
+        let invalid = || Error::invalid_params(&format!("invalid node version: {}", version));
+
+        let mut parts = version.trim_start_matches('v').split('.');
+        let major = parts
+            .next()
+            .and_then(|part| part.parse::<u64>().ok())
+            .ok_or_else(invalid)?;
+        let minor = parts
+            .next()
+            .and_then(|part| part.parse::<u64>().ok())
+            .ok_or_else(invalid)?;
+
+        Ok(match (major, minor) {
+            (0, minor) if minor <= 33 => CompatMode::V0_33,
+            (0, 34) => CompatMode::V0_34,
+            (0, minor) if minor <= 37 => CompatMode::V0_37,
+            _ => CompatMode::V0_38,
+        })
+    }
+
+    /// Do nodes of this release serve the given method?
+    pub fn supports(self, method: Method) -> bool {
+        match method {
+            // Added in 0.34
+            Method::BlockSearch | Method::GenesisChunked => self >= CompatMode::V0_34,
+            _ => true,
+        }
+    }
+
+    /// Do responses to the given method need to be adapted before parsing?
+    fn adapts(self, method: Method) -> bool {
+        self >= CompatMode::V0_34
+            && matches!(
+                method,
+                Method::BlockResults | Method::BroadcastTxCommit | Method::Tx | Method::TxSearch
+            )
+    }
+}
+
+/// Parse the given response body to a request for the given method (or a
+/// batch of such responses), adapting it to the given compatibility mode if
+/// any
+pub(super) fn parse<T>(
+    mode: Option<CompatMode>,
+    method: Method,
+    body: impl Read,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    match mode.filter(|mode| mode.adapts(method)) {
+        Some(_) => {
+            let mut json: Value = serde_json::from_reader(body).map_err(Error::parse_error)?;
+            match &mut json {
+                Value::Array(responses) => responses.iter_mut().for_each(adapt),
+                response => adapt(response),
+            }
+            serde_json::from_value(json).map_err(Error::parse_error)
+        }
+        None => serde_json::from_reader(body).map_err(Error::parse_error),
+    }
+}
+
+/// Rename the keys of the result of the given response, wherever they occur
+fn adapt(response: &mut Value) {
+    if let Some(result) = response.get_mut("result") {
+        rename_keys(result);
+    }
+}
+
+fn rename_keys(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            for (old, new) in RENAMED_KEYS {
+                if !object.contains_key(*new) {
+                    if let Some(value) = object.remove(*old) {
+                        object.insert((*new).to_owned(), value);
+                    }
+                }
+            }
+            object.values_mut().for_each(rename_keys);
+        }
+        Value::Array(values) => values.iter_mut().for_each(rename_keys),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint::block_results;
+    use crate::response::Wrapper;
+    use serde_json::json;
+
+    #[test]
+    fn detects_release_series() {
+        for (version, mode) in &[
+            ("0.30.1", CompatMode::V0_33),
+            ("0.34.24", CompatMode::V0_34),
+            ("v0.37.0-rc1", CompatMode::V0_37),
+            ("0.38.2", CompatMode::V0_38),
+            ("1.0.0", CompatMode::V0_38),
+        ] {
+            assert_eq!(CompatMode::from_version(version).unwrap(), *mode);
+        }
+        assert!(CompatMode::from_version("unknown").is_err());
+
+        assert!(!CompatMode::V0_33.supports(Method::BlockSearch));
+        assert!(CompatMode::V0_34.supports(Method::BlockSearch));
+    }
+
+    #[test]
+    fn adapts_renamed_keys() {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "height": "10",
+                "txs_results": [{
+                    "code": 0,
+                    "data": null,
+                    "log": "",
+                    "info": "",
+                    "gas_wanted": "10",
+                    "gas_used": "5",
+                    "events": [],
+                    "codespace": ""
+                }],
+                "finalize_block_events": [],
+                "validator_updates": null,
+                "consensus_param_updates": null
+            }
+        })
+        .to_string();
+
+        let response = parse::<Wrapper<block_results::Response>>(
+            Some(CompatMode::V0_38),
+            Method::BlockResults,
+            body.as_bytes(),
+        )
+        .unwrap()
+        .into_result()
+        .unwrap();
+        assert_eq!(response.txs_results.unwrap()[0].gas_used.value(), 5);
+        assert_eq!(response.end_block_events.unwrap().len(), 0);
+
+        assert!(parse::<Wrapper<block_results::Response>>(
+            Some(CompatMode::V0_33),
+            Method::BlockResults,
+            body.as_bytes(),
+        )
+        .is_err());
+    }
+}
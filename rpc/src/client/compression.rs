@@ -0,0 +1,80 @@
+//! Transparent decompression of HTTP response bodies
+
+use std::io::Read;
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use hyper::header::{HeaderMap, HeaderValue, CONTENT_ENCODING};
+
+/// Value of the `Accept-Encoding` header sent when compression is enabled
+pub(crate) const ACCEPT_ENCODING: &str = "gzip, deflate";
+
+/// Encodings of a response body the client is able to decode
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ContentEncoding {
+    /// Body is not compressed
+    Identity,
+    /// Body is compressed with gzip
+    Gzip,
+    /// Body is compressed with deflate (zlib format)
+    Deflate,
+}
+
+impl ContentEncoding {
+    /// Determine the encoding of a response body from the response headers
+    pub(crate) fn from_headers(headers: &HeaderMap<HeaderValue>) -> Self {
+        let encoding = headers
+            .get(CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().to_ascii_lowercase());
+
+        match encoding.as_deref() {
+            Some("gzip") | Some("x-gzip") => ContentEncoding::Gzip,
+            Some("deflate") => ContentEncoding::Deflate,
+            _ => ContentEncoding::Identity,
+        }
+    }
+
+    /// Wrap the given reader of an encoded body into one yielding the
+    /// decoded body
+    pub(crate) fn decode<'a>(self, body: impl Read + 'a) -> Box<dyn Read + 'a> {
+        match self {
+            ContentEncoding::Identity => Box::new(body),
+            ContentEncoding::Gzip => Box::new(GzDecoder::new(body)),
+            ContentEncoding::Deflate => Box::new(ZlibDecoder::new(body)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    #[test]
+    fn decode_gzip_body() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"{\"jsonrpc\":\"2.0\"}").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        let encoding = ContentEncoding::from_headers(&headers);
+        assert_eq!(encoding, ContentEncoding::Gzip);
+
+        let mut decoded = String::new();
+        encoding
+            .decode(&compressed[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, "{\"jsonrpc\":\"2.0\"}");
+    }
+
+    #[test]
+    fn missing_header_means_identity() {
+        assert_eq!(
+            ContentEncoding::from_headers(&HeaderMap::new()),
+            ContentEncoding::Identity
+        );
+    }
+}
@@ -3,17 +3,27 @@
 // TODO(ismail): document fields or re-use the abci types
 #![allow(missing_docs)]
 
-use async_tungstenite::{tokio::connect_async, tokio::TokioAdapter, tungstenite::Message};
+use async_tungstenite::{tokio::client_async, tokio::TokioAdapter, tungstenite::Message};
 use futures::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error as stdError;
-use tokio::net::TcpStream;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{self, Instant};
 
 use tendermint::block;
 use tendermint::net;
 
+use crate::client::subscription::{OverflowPolicy, Subscription};
+use crate::client::tls::{self, ClientIdentity};
+use crate::client::transport::TransportOptions;
+use crate::client::tunnel::{self, Io};
+use crate::client::url::{is_sensitive_name, parse_header, REDACTED};
+use crate::client::Url;
 use crate::error::Code;
+use crate::query::{EventType, Query};
 use crate::response;
 use crate::response::Wrapper;
 use crate::Request;
@@ -39,45 +49,258 @@ impl EventSubscription {
     }
 }
 
+/// Keepalive settings for an [`EventListener`], detecting connections which
+/// died silently
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Keepalive {
+    /// Send a ping once nothing has been received for this long
+    pub ping_interval: Duration,
+
+    /// Consider the connection dead if nothing is received for this long
+    /// after sending a ping
+    pub pong_timeout: Duration,
+}
+
+impl Default for Keepalive {
+    fn default() -> Self {
+        Self {
+            ping_interval: Duration::from_secs(30),
+            pong_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Options of the WebSocket handshake, for gateways which expose the
+/// endpoint at a non-default path or require extra query parameters or
+/// headers.
+///
+/// By default the handshake goes to `/websocket` (or to the path of a `ws://`
+/// or `wss://` URL), with the query parameters of the URL and its
+/// `Authorization` header.
+///
+/// Connections to `https://` and `wss://` URLs are encrypted, trusting the
+/// platform's native root certificates unless configured otherwise, and can
+/// be tunneled through an HTTP proxy for networks without direct access.
+#[derive(Clone, Default)]
+pub struct WebSocketConfig {
+    path: Option<String>,
+    query: Vec<(String, String)>,
+    headers: http::HeaderMap,
+    transport_options: TransportOptions,
+}
+
+impl WebSocketConfig {
+    /// Default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connect to the endpoint at the given path instead of the default one
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        let path = path.into();
+        self.path = Some(if path.starts_with('/') {
+            path
+        } else {
+            format!("/{}", path)
+        });
+        self
+    }
+
+    /// Append the given query parameter to those of the URL
+    pub fn query_param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((name.into(), value.into()));
+        self
+    }
+
+    /// Send the given header with the handshake request, replacing any
+    /// previous value for the same header name, including the
+    /// `Authorization` header derived from the URL.
+    ///
+    /// Values of headers whose names suggest credentials are redacted in
+    /// debug output.
+    pub fn header(mut self, name: &str, value: &str) -> Result<Self, RPCError> {
+        let (name, value) = parse_header(name, value)?;
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
+    /// Connect through the HTTP proxy at the given `http://` URL, which must
+    /// support the `CONNECT` method. Credentials in the URL are sent in the
+    /// `Proxy-Authorization` header.
+    pub fn proxy(mut self, proxy: Url) -> Result<Self, RPCError> {
+        if proxy.scheme() != "http" {
+            return Err(RPCError::invalid_params(&format!(
+                "unsupported proxy URL scheme: {}",
+                proxy.scheme()
+            )));
+        }
+        self.transport_options.proxy = Some(proxy);
+        Ok(self)
+    }
+
+    /// Send the given header to the proxy with the `CONNECT` request,
+    /// replacing any previous value for the same header name, e.g. for
+    /// proxies authenticating clients otherwise than with basic credentials.
+    pub fn proxy_header(mut self, name: &str, value: &str) -> Result<Self, RPCError> {
+        let (name, value) = parse_header(name, value)?;
+        self.transport_options.proxy_headers.insert(name, value);
+        Ok(self)
+    }
+
+    /// Timeout for establishing the connection, including any proxy tunnel
+    /// and TLS handshake (`None`, the default, disables it)
+    pub fn connect_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.transport_options.connect_timeout = timeout;
+        self
+    }
+
+    /// Use the given TLS configuration for secure URLs instead of the default
+    /// one, which trusts the platform's native root certificates
+    pub fn tls_config(mut self, config: rustls::ClientConfig) -> Self {
+        self.transport_options.tls_config = Some(Arc::new(config));
+        self
+    }
+
+    /// Additionally trust the PEM-encoded root certificate(s) for secure
+    /// URLs, e.g. of a private certificate authority.
+    ///
+    /// Has no effect when a custom [`tls_config`](Self::tls_config) is used.
+    /// Fails if no valid certificate is found.
+    pub fn add_root_certificate_pem(mut self, pem: &[u8]) -> Result<Self, RPCError> {
+        tls::add_pem_roots(&mut rustls::RootCertStore::empty(), pem)?;
+        self.transport_options.root_certificates.push(pem.to_vec());
+        Ok(self)
+    }
+
+    /// Authenticate to secure endpoints requiring mutual TLS with the given
+    /// PEM-encoded certificate chain and private key (PKCS#8 or RSA)
+    pub fn client_certificate_pem(
+        mut self,
+        cert_chain: &[u8],
+        key: &[u8],
+    ) -> Result<Self, RPCError> {
+        self.transport_options.client_identity = Some(ClientIdentity::from_pem(cert_chain, key)?);
+        Ok(self)
+    }
+
+    /// Handshake request to the WebSocket endpoint of the given URL
+    fn request(&self, url: &Url) -> Result<http::Request<()>, RPCError> {
+        let mut request =
+            http::Request::builder().uri(url.websocket_uri_with(self.path.as_deref(), &self.query));
+        if let Some(authorization) = url.authorization()? {
+            request = request.header(http::header::AUTHORIZATION, authorization);
+        }
+        for (name, value) in &self.headers {
+            request = request.header(name, value.clone());
+        }
+        Ok(request.body(())?)
+    }
+}
+
+impl fmt::Debug for WebSocketConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let query = self
+            .query
+            .iter()
+            .map(|(name, value)| {
+                let value = if is_sensitive_name(name) {
+                    REDACTED
+                } else {
+                    value
+                };
+                (name, value)
+            })
+            .collect::<Vec<_>>();
+
+        f.debug_struct("WebSocketConfig")
+            .field("path", &self.path)
+            .field("query", &query)
+            .field("headers", &self.headers)
+            .field("transport_options", &self.transport_options)
+            .finish()
+    }
+}
+
 /// Event Listener over websocket.
 /// See: <https://docs.tendermint.com/master/rpc/#/Websocket/subscribe>
 pub struct EventListener {
-    socket: async_tungstenite::WebSocketStream<TokioAdapter<TcpStream>>,
+    socket: async_tungstenite::WebSocketStream<TokioAdapter<Box<dyn Io>>>,
+    keepalive: Option<Keepalive>,
+    /// When the last message of any kind was received
+    last_received: Instant,
+    /// When the ping awaiting an answer was sent, if any
+    ping_sent: Option<Instant>,
 }
 
 impl EventListener {
     /// Constructor for event listener
     pub async fn connect(address: net::Address) -> Result<EventListener, RPCError> {
-        let (host, port) = match address {
-            net::Address::Tcp { host, port, .. } => (host, port),
-            other => {
-                return Err(RPCError::invalid_params(&format!(
-                    "invalid RPC address: {:?}",
-                    other
-                )));
-            }
-        };
-        //TODO This doesn't have any way to handle a connection over TLS
-        let (ws_stream, _unused_tls_stream) =
-            connect_async(&format!("ws://{}:{}/websocket", host, port)).await?;
-        Ok(EventListener { socket: ws_stream })
+        Self::connect_url(address.into()).await
+    }
+
+    /// Connect to the WebSocket endpoint of the node at the given URL.
+    ///
+    /// Credentials in the URL are sent along in the `Authorization` header of
+    /// the handshake request.
+    pub async fn connect_url(url: Url) -> Result<EventListener, RPCError> {
+        Self::connect_with_config(url, &WebSocketConfig::default()).await
+    }
+
+    /// Connect to the WebSocket endpoint of the node at the given URL, with
+    /// the given handshake options.
+    pub async fn connect_with_config(
+        url: Url,
+        config: &WebSocketConfig,
+    ) -> Result<EventListener, RPCError> {
+        if url.scheme() == "unix" {
+            return Err(RPCError::invalid_params(&format!(
+                "invalid RPC address: {}",
+                url
+            )));
+        }
+
+        let request = config.request(&url)?;
+        let stream = tunnel::connect(&url, &config.transport_options).await?;
+        let (ws_stream, _response) = client_async(request, stream).await?;
+        Ok(EventListener {
+            socket: ws_stream,
+            keepalive: None,
+            last_received: Instant::now(),
+            ping_sent: None,
+        })
+    }
+
+    /// Ping the server whenever the connection is idle, and fail with
+    /// [`Code::TimeoutError`] once it stays silent for too long (disabled by
+    /// default, `None` disables it again).
+    ///
+    /// Pings sent by the server count as activity and are answered
+    /// regardless of this setting, whenever the listener is waiting for
+    /// messages.
+    pub fn set_keepalive(&mut self, keepalive: Option<Keepalive>) {
+        self.keepalive = keepalive;
+        self.last_received = Instant::now();
+        self.ping_sent = None;
     }
 
     /// Subscribe to event query stream over the websocket
     pub async fn subscribe(&mut self, query: EventSubscription) -> Result<(), Box<dyn stdError>> {
+        self.subscribe_raw(query.as_str().to_owned()).await
+    }
+
+    /// Subscribe to the events selected by the given query
+    pub async fn subscribe_query(&mut self, query: &Query) -> Result<(), Box<dyn stdError>> {
+        self.subscribe_raw(query.to_string()).await
+    }
+
+    async fn subscribe_raw(&mut self, query: String) -> Result<(), Box<dyn stdError>> {
         self.socket
-            .send(Message::text(
-                subscribe::Request::new(query.as_str().to_owned()).into_json(),
-            ))
+            .send(Message::text(subscribe::Request::new(query).into_json()))
             .await?;
         // TODO(ismail): this works if subscriptions are fired sequentially and no event or
         // ping message gets in the way:
         // Wait for an empty response on subscribe
-        let msg = self
-            .socket
-            .next()
-            .await
-            .ok_or_else(|| RPCError::websocket_error("web socket closed"))??;
+        let msg = self.next_message().await?;
         serde_json::from_str::<Wrapper<subscribe::Response>>(&msg.to_string())?.into_result()?;
 
         Ok(())
@@ -85,11 +308,7 @@ impl EventListener {
 
     /// Get the next event from the websocket
     pub async fn get_event(&mut self) -> Result<Option<ResultEvent>, RPCError> {
-        let msg = self
-            .socket
-            .next()
-            .await
-            .ok_or_else(|| RPCError::websocket_error("web socket closed"))??;
+        let msg = self.next_message().await?;
 
         if let Ok(result_event) = serde_json::from_str::<WrappedResultEvent>(&msg.to_string()) {
             // if we get an rpc error here, we will bubble it up:
@@ -111,6 +330,110 @@ impl EventListener {
             Some("received neither event nor generic string message".to_string()),
         ))
     }
+
+    /// Turn this listener into a stream of events, read by a background task
+    /// into a buffer of the given capacity.
+    ///
+    /// Events arriving while the buffer is full, because the stream is not
+    /// consumed fast enough, are handled according to the given policy. The
+    /// stream ends after yielding the first error.
+    pub fn into_subscription(self, capacity: usize, policy: OverflowPolicy) -> Subscription {
+        Subscription::spawn(self, capacity, policy)
+    }
+
+    /// Subscribe to new blocks and turn this listener into a stream of them,
+    /// see [`into_subscription`](Self::into_subscription)
+    pub async fn new_blocks(
+        mut self,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Result<impl Stream<Item = Result<block::Block, RPCError>> + Unpin, RPCError> {
+        self.subscribe_raw(Query::from(EventType::NewBlock).to_string())
+            .await
+            .map_err(|e| RPCError::websocket_error(e.to_string()))?;
+
+        Ok(self.into_subscription(capacity, policy).blocks())
+    }
+
+    /// Subscribe to the transactions selected by the given query and turn
+    /// this listener into a stream of their results, see
+    /// [`into_subscription`](Self::into_subscription).
+    ///
+    /// The query is restricted to transaction events if it does not select
+    /// an event type, and must not select any other event type.
+    pub async fn txs(
+        mut self,
+        query: &Query,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Result<impl Stream<Item = Result<TxResult, RPCError>> + Unpin, RPCError> {
+        let query = match query.event_type() {
+            Some(EventType::Tx) => query.to_string(),
+            None if query.conditions().is_empty() => Query::from(EventType::Tx).to_string(),
+            None => format!("{} AND {}", Query::from(EventType::Tx), query),
+            Some(event_type) => {
+                return Err(RPCError::invalid_params(&format!(
+                    "query selects {} events instead of transactions",
+                    event_type
+                )))
+            }
+        };
+
+        self.subscribe_raw(query)
+            .await
+            .map_err(|e| RPCError::websocket_error(e.to_string()))?;
+
+        Ok(self.into_subscription(capacity, policy).tx_results())
+    }
+
+    /// Send the given text message
+    pub(super) async fn send_text(&mut self, text: String) -> Result<(), RPCError> {
+        self.socket.send(Message::text(text)).await?;
+        Ok(())
+    }
+
+    /// Wait for the next data message, skipping pings and pongs and sending
+    /// keepalive pings as configured
+    pub(super) async fn next_message(&mut self) -> Result<Message, RPCError> {
+        loop {
+            let msg = match self.keepalive {
+                None => self.socket.next().await,
+                Some(keepalive) => {
+                    let deadline = match self.ping_sent {
+                        Some(sent) => sent + keepalive.pong_timeout,
+                        None => self.last_received + keepalive.ping_interval,
+                    };
+
+                    match time::timeout_at(deadline, self.socket.next()).await {
+                        Ok(msg) => msg,
+                        Err(_) if self.ping_sent.is_some() => {
+                            return Err(RPCError::timeout_error(
+                                "web socket connection went silent",
+                            ))
+                        }
+                        Err(_) => {
+                            self.socket.send(Message::Ping(vec![])).await?;
+                            self.ping_sent = Some(Instant::now());
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            let msg = msg.ok_or_else(|| RPCError::websocket_error("web socket closed"))??;
+            self.last_received = Instant::now();
+            self.ping_sent = None;
+
+            match msg {
+                // Pings are answered by the underlying web socket
+                Message::Ping(_) | Message::Pong(_) => continue,
+                Message::Close(_) => {
+                    return Err(RPCError::websocket_error("web socket closed by server"))
+                }
+                msg => return Ok(msg),
+            }
+        }
+    }
 }
 
 // TODO(ismail): this should live somewhere else; these events are also
@@ -0,0 +1,244 @@
+//! Transport failing over between several RPC endpoints
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use super::transport::Transport;
+use super::{Client, Url};
+use crate::error::{Code, Reason};
+use crate::Error;
+
+/// Default time an endpoint is avoided after it failed
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// [`Transport`] sending each request to the first healthy one of several
+/// endpoints, failing over to the next one when it cannot be reached or
+/// lags behind, for applications which must survive outages of single nodes.
+///
+/// An endpoint is considered unhealthy for a cooldown period after a
+/// transport error, or after it answered that the requested height is not
+/// available. Requests prefer healthy endpoints in the order given, so the
+/// first endpoint is used again as soon as it has recovered. When all
+/// endpoints are unhealthy, they are tried anyway, soonest to recover first.
+///
+/// Wrap it in a [`Client`] to perform typed requests:
+///
+/// ```ignore
+/// let failover = FailoverClient::new(vec![Client::new(primary), Client::new(backup)])?;
+/// let client = Client::builder(primary.into()).transport(failover).build()?;
+/// let status = client.status().await?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct FailoverClient {
+    endpoints: Arc<Vec<Endpoint>>,
+    cooldown: Duration,
+}
+
+/// Client of a single endpoint along with its health
+#[derive(Debug)]
+struct Endpoint {
+    client: Client,
+    /// End of the cooldown after the last failure, if any
+    unhealthy_until: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    /// End of the current cooldown, or `None` if the endpoint is healthy
+    fn cooldown(&self, now: Instant) -> Option<Instant> {
+        self.unhealthy_until
+            .lock()
+            .unwrap()
+            .filter(|&until| until > now)
+    }
+}
+
+impl FailoverClient {
+    /// Fail over between the endpoints of the given clients, in order of
+    /// preference. Fails if no client is given.
+    pub fn new(clients: Vec<Client>) -> Result<Self, Error> {
+        if clients.is_empty() {
+            return Err(Error::invalid_params("no endpoints to fail over between"));
+        }
+
+        let endpoints = clients
+            .into_iter()
+            .map(|client| Endpoint {
+                client,
+                unhealthy_until: Mutex::new(None),
+            })
+            .collect();
+
+        Ok(Self {
+            endpoints: Arc::new(endpoints),
+            cooldown: DEFAULT_COOLDOWN,
+        })
+    }
+
+    /// Avoid failed endpoints for the given time ([`DEFAULT_COOLDOWN`] by
+    /// default)
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// URLs of the endpoints, along with whether they are currently healthy
+    pub fn health(&self) -> Vec<(Url, bool)> {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                (
+                    endpoint.client.url().clone(),
+                    endpoint.cooldown(now).is_none(),
+                )
+            })
+            .collect()
+    }
+
+    /// Endpoints in the order they should be tried
+    fn candidates(&self) -> Vec<&Endpoint> {
+        let now = Instant::now();
+        let mut candidates = self.endpoints.iter().collect::<Vec<_>>();
+        // Healthy endpoints (no cooldown) sort first and keep their order
+        candidates.sort_by_key(|endpoint| endpoint.cooldown(now));
+        candidates
+    }
+}
+
+#[async_trait]
+impl Transport for FailoverClient {
+    async fn send(&self, request_body: String) -> Result<Vec<u8>, Error> {
+        let mut last_error = None;
+
+        for endpoint in self.candidates() {
+            let error = match endpoint.client.send(request_body.clone()).await {
+                Ok(response_body) => match failover_error(&response_body) {
+                    None => {
+                        *endpoint.unhealthy_until.lock().unwrap() = None;
+                        return Ok(response_body);
+                    }
+                    Some(error) => error,
+                },
+                Err(e) if should_fail_over(&e) => e,
+                Err(e) => return Err(e),
+            };
+
+            *endpoint.unhealthy_until.lock().unwrap() = Some(Instant::now() + self.cooldown);
+            last_error = Some(error);
+        }
+
+        Err(last_error.expect("there is at least one endpoint"))
+    }
+}
+
+/// Error carried by the given response body which warrants trying another
+/// endpoint, if any
+pub(super) fn failover_error(response_body: &[u8]) -> Option<Error> {
+    let response = match serde_json::from_slice::<serde_json::Value>(response_body) {
+        Ok(response) => response,
+        // Gateways and proxies answer with error pages which are not JSON
+        Err(_) => return Some(Error::http_error("response is not JSON")),
+    };
+
+    // Batches are passed on as they are
+    let error = response
+        .get("error")
+        .filter(|error| !error.is_null())
+        .cloned()?;
+
+    serde_json::from_value::<Error>(error)
+        .ok()
+        .filter(should_fail_over)
+}
+
+/// Is the given error specific to the endpoint, so that another one may
+/// succeed?
+pub(super) fn should_fail_over(error: &Error) -> bool {
+    matches!(
+        error.code(),
+        Code::HttpError | Code::WebSocketError | Code::TimeoutError
+    ) || error.reason() == Reason::HeightNotAvailable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mock::{Matcher, MockClient};
+    use super::*;
+    use crate::Method;
+
+    fn status_mock() -> MockClient {
+        MockClient::new()
+            .respond_with_fixture(Matcher::method(Method::Status), "tests/support/status.json")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn fails_over_and_recovers() {
+        let down = MockClient::new().respond_with_error(
+            Matcher::method(Method::Status),
+            Error::http_error("connection refused"),
+        );
+        let up = status_mock();
+
+        let failover = FailoverClient::new(vec![down.client(), up.client()]).unwrap();
+        let client = Client::builder("http://example.invalid".parse().unwrap())
+            .transport(failover.clone())
+            .build()
+            .unwrap();
+
+        client.status().await.unwrap();
+        client.status().await.unwrap();
+
+        // The failed endpoint is skipped during its cooldown
+        assert_eq!(down.requests().len(), 1);
+        assert_eq!(up.requests().len(), 2);
+        let health = failover.health();
+        assert!(!health[0].1 && health[1].1);
+    }
+
+    #[tokio::test]
+    async fn fails_over_on_missing_height() {
+        let lagging = MockClient::new().respond_with_error(
+            Matcher::method(Method::Block),
+            Error::new(
+                Code::InternalError,
+                Some(
+                    "height 20 must be less than or equal to the current blockchain height 10"
+                        .to_owned(),
+                ),
+            ),
+        );
+        let synced = MockClient::new()
+            .respond_with_fixture(Matcher::method(Method::Block), "tests/support/block.json")
+            .unwrap();
+
+        let failover = FailoverClient::new(vec![lagging.client(), synced.client()]).unwrap();
+        let client = Client::builder("http://example.invalid".parse().unwrap())
+            .transport(failover)
+            .build()
+            .unwrap();
+
+        client.block(20u64).await.unwrap();
+        assert_eq!(synced.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn node_errors_are_final() {
+        let failing = MockClient::new().respond_with_error(
+            Matcher::method(Method::Status),
+            Error::server_error("something broke"),
+        );
+        let up = status_mock();
+
+        let failover = FailoverClient::new(vec![failing.client(), up.client()]).unwrap();
+        let client = Client::builder("http://example.invalid".parse().unwrap())
+            .transport(failover)
+            .build()
+            .unwrap();
+
+        assert!(client.status().await.is_err());
+        assert!(up.requests().is_empty());
+    }
+}
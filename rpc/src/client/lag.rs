@@ -0,0 +1,277 @@
+//! New block subscriptions resubscribing when they fall behind
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use futures::stream::{self, Stream, StreamExt};
+use tendermint::block::{Block, Height};
+use tokio::time::{self, Instant};
+
+use super::subscription::OverflowPolicy;
+use super::{Client, WebSocketClient};
+use crate::query::{EventType, Query};
+use crate::Error;
+
+/// When a new block subscription of
+/// [`Client::new_blocks_monitored`] counts as lagging
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LagPolicy {
+    /// Resubscribe once the node's latest height is more than this many
+    /// blocks ahead of the last block received
+    pub max_lag: u64,
+
+    /// Resubscribe once no block has been received for this long
+    pub stall_timeout: Duration,
+
+    /// Compare the last block received with the node's latest height this
+    /// often
+    pub check_interval: Duration,
+}
+
+impl Default for LagPolicy {
+    fn default() -> Self {
+        Self {
+            max_lag: 5,
+            stall_timeout: Duration::from_secs(60),
+            check_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Item of the stream of [`Client::new_blocks_monitored`]
+#[derive(Clone, Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum MonitoredBlock {
+    /// New block
+    Block(Block),
+
+    /// Blocks at the given heights (inclusive) were missed, e.g. while
+    /// resubscribing, and need to be fetched separately if of interest
+    Gap {
+        /// Lowest height missed
+        from: Height,
+        /// Highest height missed
+        to: Height,
+    },
+}
+
+/// Progress of a monitored subscription
+struct Monitor<'a> {
+    client: &'a Client,
+    websocket: WebSocketClient,
+    policy: LagPolicy,
+    capacity: usize,
+    overflow: OverflowPolicy,
+    /// Blocks of the current subscription, if any
+    blocks: Option<Box<dyn Stream<Item = Result<Block, Error>> + Send + Unpin>>,
+    /// Has the node been subscribed before?
+    subscribed: bool,
+    /// Height of the last block emitted
+    last_height: Option<Height>,
+    /// When the last block was received, or the subscription was made
+    last_block_at: Instant,
+    next_check: Instant,
+    /// Items to emit before receiving more blocks
+    pending: VecDeque<MonitoredBlock>,
+}
+
+/// Stream of the new blocks reported by the given WebSocket connection,
+/// resubscribing whenever the subscription lags behind according to the
+/// given policy
+pub(super) fn monitor<'a>(
+    client: &'a Client,
+    websocket: &WebSocketClient,
+    policy: LagPolicy,
+    capacity: usize,
+    overflow: OverflowPolicy,
+) -> impl Stream<Item = Result<MonitoredBlock, Error>> + 'a {
+    let monitor = Monitor {
+        client,
+        websocket: websocket.clone(),
+        policy,
+        capacity,
+        overflow,
+        blocks: None,
+        subscribed: false,
+        last_height: None,
+        last_block_at: Instant::now(),
+        next_check: Instant::now() + policy.check_interval,
+        pending: VecDeque::new(),
+    };
+
+    stream::try_unfold(monitor, |mut monitor| async move {
+        let item = monitor.next().await?;
+        Ok(Some((item, monitor)))
+    })
+}
+
+impl Monitor<'_> {
+    async fn next(&mut self) -> Result<MonitoredBlock, Error> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Ok(item);
+            }
+
+            let blocks = match &mut self.blocks {
+                Some(blocks) => blocks,
+                None => {
+                    self.subscribe().await?;
+                    continue;
+                }
+            };
+
+            match time::timeout_at(self.next_check, blocks.next()).await {
+                Ok(Some(Ok(block))) => self.receive(block),
+                // Failed or ended subscriptions are replaced, unless the
+                // connection failed as well
+                Ok(Some(Err(_))) | Ok(None) => self.blocks = None,
+                Err(_) => self.check().await,
+            }
+        }
+    }
+
+    /// Subscribe afresh, unsubscribing the node first if it was subscribed
+    /// before
+    async fn subscribe(&mut self) -> Result<(), Error> {
+        let query = Query::from(EventType::NewBlock);
+        if self.subscribed {
+            self.websocket.unsubscribe(&query).await?;
+        }
+
+        let subscription = self
+            .websocket
+            .subscribe(&query, self.capacity, self.overflow)
+            .await?;
+        self.blocks = Some(Box::new(subscription.blocks()));
+        self.subscribed = true;
+        self.last_block_at = Instant::now();
+        Ok(())
+    }
+
+    /// Queue the given block, preceded by a gap if blocks were missed
+    fn receive(&mut self, block: Block) {
+        let height = block.header.height;
+        self.last_block_at = Instant::now();
+
+        match self.last_height {
+            // Seen already, e.g. when resubscribing
+            Some(last) if height <= last => return,
+            Some(last) if height.value() > last.value() + 1 => {
+                self.pending.push_back(MonitoredBlock::Gap {
+                    from: last.increment(),
+                    to: Height(height.value() - 1),
+                })
+            }
+            _ => {}
+        }
+
+        self.last_height = Some(height);
+        self.pending.push_back(MonitoredBlock::Block(block));
+    }
+
+    /// Drop the subscription if it stalled or lags behind the node
+    async fn check(&mut self) {
+        self.next_check = Instant::now() + self.policy.check_interval;
+
+        let stalled = self.last_block_at.elapsed() >= self.policy.stall_timeout;
+        // Without the latest height, only stalls are detected
+        let lagging = match (self.last_height, self.client.status().await) {
+            (Some(last), Ok(status)) => {
+                let latest = status.sync_info.latest_block_height.value();
+                latest.saturating_sub(last.value()) > self.policy.max_lag
+            }
+            _ => false,
+        };
+
+        if stalled || lagging {
+            self.blocks = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mock::{Matcher, MockClient, MockWebSocketServer};
+    use super::*;
+    use crate::client::event_listener::{EventDataNewBlock, ResultEvent, TMEventData};
+    use crate::endpoint::block;
+    use crate::{Method, Response};
+    use futures::future;
+    use std::fs;
+
+    fn new_block(height: u64) -> ResultEvent {
+        let mut block =
+            block::Response::from_string(fs::read_to_string("tests/support/block.json").unwrap())
+                .unwrap()
+                .block;
+        block.header.height = Height(height);
+
+        ResultEvent {
+            query: Query::from(EventType::NewBlock).to_string(),
+            data: TMEventData::EventDataNewBlock(EventDataNewBlock {
+                block: Some(block),
+                result_begin_block: None,
+                result_end_block: None,
+            }),
+            events: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn resubscribes_when_stalled_and_reports_gaps() {
+        let server = MockWebSocketServer::start(MockClient::new()).await.unwrap();
+        let websocket = WebSocketClient::connect(server.url()).await.unwrap();
+        // Status requests fail, so that only stalls are detected
+        let client = MockClient::new()
+            .respond_with_error(
+                Matcher::method(Method::Status),
+                Error::server_error("unavailable"),
+            )
+            .client();
+
+        let policy = LagPolicy {
+            max_lag: 5,
+            stall_timeout: Duration::from_millis(100),
+            check_interval: Duration::from_millis(20),
+        };
+        let mut blocks = Box::pin(monitor(
+            &client,
+            &websocket,
+            policy,
+            10,
+            OverflowPolicy::Terminate,
+        ));
+
+        // The first subscription is made on demand
+        let (first, _) = future::join(blocks.next(), async {
+            while server.subscriptions().is_empty() {
+                time::delay_for(Duration::from_millis(5)).await;
+            }
+            server.publish(new_block(10));
+        })
+        .await;
+        assert!(matches!(
+            first.unwrap().unwrap(),
+            MonitoredBlock::Block(block) if block.header.height == Height(10)
+        ));
+
+        // The stalled subscription is replaced, and the blocks missed in the
+        // meantime are reported as a gap
+        let ((gap, block), _) = future::join(
+            async { (blocks.next().await, blocks.next().await) },
+            async {
+                time::delay_for(Duration::from_millis(150)).await;
+                server.publish(new_block(13));
+            },
+        )
+        .await;
+        assert!(matches!(
+            gap.unwrap().unwrap(),
+            MonitoredBlock::Gap { from, to } if from == Height(11) && to == Height(12)
+        ));
+        assert!(matches!(
+            block.unwrap().unwrap(),
+            MonitoredBlock::Block(block) if block.header.height == Height(13)
+        ));
+    }
+}
@@ -0,0 +1,62 @@
+//! Middleware wrapping the transport of a client
+
+use std::fmt;
+use std::sync::Arc;
+
+use super::transport::Transport;
+
+/// Middleware wrapping the transport of a [`Client`](super::Client), e.g. to
+/// log, cache or authenticate requests, see
+/// [`ClientBuilder::layer`](super::ClientBuilder::layer).
+///
+/// Closures taking and returning a transport are layers:
+///
+/// ```ignore
+/// let client = Client::builder(url)
+///     .layer(|inner: Arc<dyn Transport>| -> Arc<dyn Transport> {
+///         Arc::new(CachingClient::new(inner, MemoryCache::new(10_000)))
+///     })
+///     .layer(|inner: Arc<dyn Transport>| -> Arc<dyn Transport> { Arc::new(Logging(inner)) })
+///     .build()?;
+/// ```
+pub trait Layer: Send + Sync {
+    /// Wrap the given transport
+    fn layer(&self, inner: Arc<dyn Transport>) -> Arc<dyn Transport>;
+}
+
+impl<F> Layer for F
+where
+    F: Fn(Arc<dyn Transport>) -> Arc<dyn Transport> + Send + Sync,
+{
+    fn layer(&self, inner: Arc<dyn Transport>) -> Arc<dyn Transport> {
+        self(inner)
+    }
+}
+
+/// Layers of a client, from the outermost to the innermost one
+#[derive(Clone, Default)]
+pub(crate) struct Layers(Vec<Arc<dyn Layer>>);
+
+impl Layers {
+    pub(crate) fn push(&mut self, layer: impl Layer + 'static) {
+        self.0.push(Arc::new(layer));
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Wrap the given transport in all layers
+    pub(crate) fn apply(&self, transport: Arc<dyn Transport>) -> Arc<dyn Transport> {
+        self.0
+            .iter()
+            .rev()
+            .fold(transport, |inner, layer| layer.layer(inner))
+    }
+}
+
+impl fmt::Debug for Layers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Layers({})", self.0.len())
+    }
+}
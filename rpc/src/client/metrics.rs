@@ -0,0 +1,67 @@
+//! Instrumentation hooks reporting metrics of RPC requests
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{Error, Method};
+
+/// Receiver of the metrics of each request performed by a [`Client`](super::Client),
+/// e.g. to feed them into Prometheus or statsd.
+///
+/// Called once per request after it completed, successfully or not, so
+/// implementations should return quickly.
+pub trait MetricsRecorder: Send + Sync {
+    /// Record the metrics of a completed request
+    fn record(&self, metrics: &RequestMetrics<'_>);
+
+    /// Record the number of subscriptions currently open on a
+    /// [`WebSocketClient`](super::WebSocketClient), whenever it changes
+    fn record_subscriptions(&self, _active: usize) {}
+
+    /// Record that a WebSocket connection was re-established after it failed
+    fn record_reconnect(&self) {}
+}
+
+/// Metrics of a completed request
+#[derive(Clone, Debug)]
+pub struct RequestMetrics<'a> {
+    /// Method called
+    pub method: Method,
+
+    /// Number of requests, if they were sent as a batch
+    pub batch_size: Option<usize>,
+
+    /// Time taken by all attempts together, including backoff between them
+    pub duration: Duration,
+
+    /// Number of attempts made, more than one if the request was retried
+    pub attempts: u32,
+
+    /// Size of the request body in bytes
+    pub request_size: usize,
+
+    /// Size of the body of the last response in bytes, as transferred (zero
+    /// if none was received)
+    pub response_size: usize,
+
+    /// Error the request failed with, if any
+    pub error: Option<&'a Error>,
+}
+
+impl RequestMetrics<'_> {
+    /// Did the request succeed?
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Shared handle to a [`MetricsRecorder`]
+#[derive(Clone)]
+pub(crate) struct Recorder(pub(crate) Arc<dyn MetricsRecorder>);
+
+impl fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MetricsRecorder")
+    }
+}
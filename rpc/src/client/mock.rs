@@ -0,0 +1,720 @@
+//! Mock RPC client for testing code which talks to a Tendermint node
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::time::{self, Instant};
+
+use super::event_listener::ResultEvent;
+use super::subscription::{OverflowPolicy, Subscription};
+use super::transport::{HttpTransport, Transport};
+use super::Client;
+use crate::{Error, Id, Method, Response};
+
+mod websocket;
+
+pub use websocket::MockWebSocketServer;
+
+/// URL reported by clients of a [`MockClient`]. Nothing is ever sent there.
+const MOCK_URL: &str = "http://mock.invalid:26657";
+
+/// Mock of a Tendermint RPC endpoint, answering requests with registered
+/// responses instead of sending them over the network.
+///
+/// Requests are answered by the first registered response whose [`Matcher`]
+/// matches them, and are recorded so tests can assert on them afterwards.
+/// Requests without a matching response fail with a server error.
+///
+/// ```ignore
+/// let mock = MockClient::new()
+///     .respond_with_fixture(Matcher::method(Method::Status), "tests/support/status.json")?
+///     .respond(Matcher::method(Method::Block).param("height", "5"), block_json);
+///
+/// let client = mock.client();
+/// client.block(5u64).await?;
+/// assert_eq!(mock.requests_for(Method::Block).len(), 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MockClient {
+    state: Arc<Mutex<MockState>>,
+}
+
+/// Registered responses and received requests of a [`MockClient`]
+#[derive(Debug, Default)]
+struct MockState {
+    responses: Vec<(Matcher, Result<Value, Error>)>,
+    requests: Vec<MockRequest>,
+}
+
+impl MockClient {
+    /// Create a new mock without any registered responses
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer requests matched by the given matcher with the given JSON
+    /// value as `result`
+    pub fn respond(self, matcher: Matcher, result: Value) -> Self {
+        self.register(matcher, Ok(result))
+    }
+
+    /// Answer requests matched by the given matcher with the given response
+    pub fn respond_with<R>(self, matcher: Matcher, response: R) -> Self
+    where
+        R: Response,
+    {
+        let result = serde_json::to_value(response).expect("responses serialize as JSON");
+        self.register(matcher, Ok(result))
+    }
+
+    /// Answer requests matched by the given matcher with the given error
+    pub fn respond_with_error(self, matcher: Matcher, error: Error) -> Self {
+        self.register(matcher, Err(error))
+    }
+
+    /// Answer requests matched by the given matcher with the JSON fixture at
+    /// the given path, e.g. a response recorded from a live node.
+    ///
+    /// The fixture may either be a complete JSONRPC response, whose `result`
+    /// or `error` is used, or just the `result` itself.
+    pub fn respond_with_fixture(
+        self,
+        matcher: Matcher,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).map_err(|e| {
+            Error::invalid_params(&format!("cannot read fixture {}: {}", path.display(), e))
+        })?;
+        let mut fixture: Value = serde_json::from_str(&json).map_err(Error::parse_error)?;
+
+        let response = if let Some(error) = fixture.get_mut("error").map(Value::take) {
+            Err(serde_json::from_value(error).map_err(Error::parse_error)?)
+        } else if let Some(result) = fixture.get_mut("result").map(Value::take) {
+            Ok(result)
+        } else {
+            Ok(fixture)
+        };
+
+        Ok(self.register(matcher, response))
+    }
+
+    /// Answer requests with the responses recorded by a
+    /// [`RecordingTransport`] in the file at the given path.
+    ///
+    /// Each recorded response answers the requests for the same method with
+    /// the same parameters. If a request was recorded several times, the
+    /// response recorded first is used.
+    pub fn from_recording(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let recording = fs::read_to_string(path).map_err(|e| {
+            Error::invalid_params(&format!("cannot read recording {}: {}", path.display(), e))
+        })?;
+
+        let mut mock = Self::new();
+        for line in recording.lines().filter(|line| !line.trim().is_empty()) {
+            let mut exchange: Value = serde_json::from_str(line).map_err(Error::parse_error)?;
+
+            let method = exchange
+                .get("method")
+                .and_then(Value::as_str)
+                .ok_or_else(|| Error::parse_error("recorded request without method"))?
+                .parse::<Method>()?;
+            let mut matcher = Matcher::method(method);
+            if let Some(Value::Object(params)) = exchange.get_mut("params").map(Value::take) {
+                for (name, value) in params {
+                    matcher = matcher.param(&name, value);
+                }
+            }
+
+            let response = match exchange.get_mut("error").map(Value::take) {
+                Some(error) => Err(serde_json::from_value(error).map_err(Error::parse_error)?),
+                None => Ok(exchange
+                    .get_mut("result")
+                    .map(Value::take)
+                    .unwrap_or_default()),
+            };
+            mock = mock.register(matcher, response);
+        }
+
+        Ok(mock)
+    }
+
+    /// Create a client whose requests are answered by this mock
+    pub fn client(&self) -> Client {
+        let mut client = Client::builder(MOCK_URL.parse().unwrap())
+            .build()
+            .expect("mock URL carries no credentials");
+        client.transport = HttpTransport::Custom(Arc::new(self.clone()));
+        client
+    }
+
+    /// All requests received so far, in the order they were received
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Requests for the given method received so far
+    pub fn requests_for(&self, method: Method) -> Vec<MockRequest> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request.method == method)
+            .collect()
+    }
+
+    /// Forget about the requests received so far
+    pub fn clear_requests(&self) {
+        self.state.lock().unwrap().requests.clear();
+    }
+
+    fn register(self, matcher: Matcher, response: Result<Value, Error>) -> Self {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .push((matcher, response));
+        self
+    }
+
+    /// Record the given JSONRPC request and build the response to it
+    fn answer(&self, request: Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+
+        let result = request
+            .get("method")
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::invalid_params("request without method"))
+            .and_then(|method| method.parse::<Method>())
+            .and_then(|method| {
+                let request = MockRequest {
+                    id: serde_json::from_value(id.clone()).unwrap_or(Id::None),
+                    method,
+                    params: request.get("params").cloned().unwrap_or(Value::Null),
+                };
+
+                let mut state = self.state.lock().unwrap();
+                state.requests.push(request.clone());
+
+                state
+                    .responses
+                    .iter()
+                    .find(|(matcher, _)| matcher.matches(&request))
+                    .map(|(_, response)| response.clone())
+                    .unwrap_or_else(|| {
+                        Err(Error::server_error(format!(
+                            "no mock response registered for `{}`",
+                            method
+                        )))
+                    })
+            });
+
+        match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+        }
+    }
+}
+
+/// Answers requests carrying a single JSONRPC request or a batch of them
+#[async_trait]
+impl Transport for MockClient {
+    async fn send(&self, request_body: String) -> Result<Vec<u8>, Error> {
+        let json: Value = serde_json::from_str(&request_body).map_err(Error::parse_error)?;
+
+        let response = match json {
+            Value::Array(requests) => {
+                Value::Array(requests.into_iter().map(|r| self.answer(r)).collect())
+            }
+            request => self.answer(request),
+        };
+
+        Ok(response.to_string().into_bytes())
+    }
+}
+
+/// [`Transport`] recording all requests sent through it and the responses
+/// to them, e.g. from a live node, so that tests can replay them later
+/// without one using [`MockClient::from_recording`].
+///
+/// Each exchange is appended to the recording file as a line of JSON with
+/// the `method` and `params` of the request and the `result` or `error` of
+/// the response. Requests failing without a JSONRPC response, e.g. due to
+/// network errors, are not recorded.
+///
+/// ```ignore
+/// let recorder = RecordingTransport::new(Client::new(url)?, "tests/support/recording.jsonl")?;
+/// let client = Client::builder(url).transport(recorder).build()?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct RecordingTransport {
+    inner: Arc<dyn Transport>,
+    file: Arc<Mutex<fs::File>>,
+}
+
+impl RecordingTransport {
+    /// Record the exchanges with the given transport to the file at the
+    /// given path, appending to it if it exists already
+    pub fn new(inner: impl Transport + 'static, path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| {
+                Error::invalid_params(&format!("cannot open recording {}: {}", path.display(), e))
+            })?;
+
+        Ok(Self {
+            inner: Arc::new(inner),
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    /// Append the exchanges of the given request and response bodies, each
+    /// either a single request or response or a batch of them
+    fn record(&self, request_body: &str, response_body: &[u8]) {
+        let (requests, responses) = match (
+            serde_json::from_str::<Value>(request_body),
+            serde_json::from_slice::<Value>(response_body),
+        ) {
+            (Ok(Value::Array(requests)), Ok(Value::Array(responses))) => (requests, responses),
+            (Ok(request), Ok(response)) => (vec![request], vec![response]),
+            _ => return,
+        };
+
+        let mut lines = String::new();
+        for request in &requests {
+            let response = responses
+                .iter()
+                .find(|response| response.get("id") == request.get("id"));
+            let response = match response {
+                Some(response) => response,
+                None => continue,
+            };
+
+            let mut exchange = json!({
+                "method": request.get("method"),
+                "params": request.get("params"),
+            });
+            for key in &["result", "error"] {
+                if let Some(value) = response.get(*key) {
+                    exchange[*key] = value.clone();
+                }
+            }
+            lines.push_str(&exchange.to_string());
+            lines.push('\n');
+        }
+
+        // A failed recording must not fail the request
+        let _ = self.file.lock().unwrap().write_all(lines.as_bytes());
+    }
+}
+
+#[async_trait]
+impl Transport for RecordingTransport {
+    async fn send(&self, request_body: String) -> Result<Vec<u8>, Error> {
+        let response_body = self.inner.send(request_body.clone()).await?;
+        self.record(&request_body, &response_body);
+        Ok(response_body)
+    }
+}
+
+/// Fault a [`SimulatedTransport`] injects into a request
+#[derive(Clone, Debug, PartialEq)]
+pub enum Fault {
+    /// Answer only after the given latency
+    Latency(Duration),
+
+    /// Never answer, so that the request times out
+    Drop,
+
+    /// Fail right away as if the connection was reset
+    Disconnect,
+}
+
+/// [`Transport`] simulating an unreliable network in front of another
+/// transport, usually a [`MockClient`], for deterministic tests of retries,
+/// timeouts, failover and the like.
+///
+/// Requests are delayed by a fixed latency, unless a scripted fault applies:
+/// each request takes the next fault scripted with [`then`](Self::then), and
+/// while the transport is [disconnected](Self::disconnect_for) all requests
+/// fail.
+///
+/// Latencies and disconnections are measured with tokio's timer, so they
+/// take no real time in tests which pause it with `tokio::time::pause` (see
+/// tokio's `test-util` feature) and advance it explicitly.
+///
+/// ```ignore
+/// let network = SimulatedTransport::new(mock.clone())
+///     .latency(Duration::from_millis(50))
+///     .then(Fault::Drop)
+///     .then(Fault::Disconnect);
+/// let client = Client::builder(url).transport(network.clone()).build()?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct SimulatedTransport {
+    inner: Arc<dyn Transport>,
+    network: Arc<Mutex<Network>>,
+}
+
+/// Behavior of a [`SimulatedTransport`]
+#[derive(Debug, Default)]
+struct Network {
+    latency: Duration,
+    /// Faults of the next requests, in order
+    faults: VecDeque<Fault>,
+    /// End of the current disconnection, if any
+    disconnected_until: Option<Instant>,
+}
+
+impl SimulatedTransport {
+    /// Simulate a perfect network in front of the given transport, until
+    /// faults are scripted
+    pub fn new(inner: impl Transport + 'static) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            network: Arc::new(Mutex::new(Network::default())),
+        }
+    }
+
+    /// Delay the responses to requests without a scripted fault by the given
+    /// latency
+    pub fn latency(self, latency: Duration) -> Self {
+        self.network.lock().unwrap().latency = latency;
+        self
+    }
+
+    /// Inject the given fault into the next request which has no fault
+    /// scripted yet
+    pub fn then(self, fault: Fault) -> Self {
+        self.network.lock().unwrap().faults.push_back(fault);
+        self
+    }
+
+    /// Fail all requests for the given time from now on, e.g. to simulate a
+    /// node restarting
+    pub fn disconnect_for(&self, duration: Duration) {
+        self.network.lock().unwrap().disconnected_until = Some(Instant::now() + duration);
+    }
+
+    /// Number of scripted faults not injected yet
+    pub fn pending_faults(&self) -> usize {
+        self.network.lock().unwrap().faults.len()
+    }
+}
+
+#[async_trait]
+impl Transport for SimulatedTransport {
+    async fn send(&self, request_body: String) -> Result<Vec<u8>, Error> {
+        let fault = {
+            let mut network = self.network.lock().unwrap();
+            match network.disconnected_until {
+                Some(until) if Instant::now() < until => Fault::Disconnect,
+                _ => network
+                    .faults
+                    .pop_front()
+                    .unwrap_or(Fault::Latency(network.latency)),
+            }
+        };
+
+        match fault {
+            Fault::Latency(latency) => {
+                time::delay_for(latency).await;
+                self.inner.send(request_body).await
+            }
+            Fault::Drop => futures::future::pending().await,
+            Fault::Disconnect => Err(Error::http_error("connection reset (simulated)")),
+        }
+    }
+}
+
+/// Subscription receiving the given events, each after its delay from now.
+///
+/// Events are received in the order of their delays, so that scripting the
+/// delays reorders events deterministically, and errors end the
+/// subscription, e.g. to simulate a connection closing. Without an error the
+/// subscription stays open after the last event. Delays are measured with
+/// tokio's timer, see [`SimulatedTransport`].
+pub fn simulated_subscription(
+    events: Vec<(Duration, Result<ResultEvent, Error>)>,
+    capacity: usize,
+    policy: OverflowPolicy,
+) -> Subscription {
+    let (subscription, publisher) = Subscription::with_publisher(capacity, policy);
+    let start = Instant::now();
+
+    let mut events = events;
+    // Stable, so that events with the same delay keep their order
+    events.sort_by_key(|(delay, _)| *delay);
+
+    tokio::spawn(async move {
+        for (delay, event) in events {
+            time::delay_until(start + delay).await;
+            if !publisher.publish(event) {
+                return;
+            }
+        }
+    });
+
+    subscription
+}
+
+/// Condition on the requests a [`MockClient`] response applies to
+#[derive(Clone, Debug)]
+pub struct Matcher {
+    method: Method,
+    params: Vec<(String, Value)>,
+}
+
+impl Matcher {
+    /// Match all requests for the given method
+    pub fn method(method: Method) -> Self {
+        Self {
+            method,
+            params: vec![],
+        }
+    }
+
+    /// Only match requests whose parameter with the given name has the given
+    /// value.
+    ///
+    /// Values are compared in their JSON encoding, in which Tendermint
+    /// represents heights as strings, e.g. `.param("height", "5")`.
+    pub fn param(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.params.push((name.to_owned(), value.into()));
+        self
+    }
+
+    /// Does this matcher apply to the given request?
+    pub fn matches(&self, request: &MockRequest) -> bool {
+        self.method == request.method
+            && self
+                .params
+                .iter()
+                .all(|(name, value)| request.param(name) == Some(value))
+    }
+}
+
+/// Request received by a [`MockClient`]
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    id: Id,
+    method: Method,
+    params: Value,
+}
+
+impl MockRequest {
+    /// JSONRPC ID of this request
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Method of this request
+    pub fn method(&self) -> Method {
+        self.method
+    }
+
+    /// Parameters of this request, as sent in JSON
+    pub fn params(&self) -> &Value {
+        &self.params
+    }
+
+    /// Value of the parameter with the given name, if present
+    pub fn param(&self, name: &str) -> Option<&Value> {
+        self.params.get(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint::block;
+    use crate::error::Code;
+
+    #[tokio::test]
+    async fn answers_with_fixtures_and_records_requests() {
+        let mock = MockClient::new()
+            .respond_with_fixture(Matcher::method(Method::Status), "tests/support/status.json")
+            .unwrap()
+            .respond_with_fixture(
+                Matcher::method(Method::Block).param("height", "10"),
+                "tests/support/block.json",
+            )
+            .unwrap();
+        let client = mock.client();
+
+        let status = client.status().await.unwrap();
+        assert_eq!(status.node_info.network.as_str(), "cosmoshub-2");
+
+        let block = client.block(10u64).await.unwrap();
+        assert_eq!(block.block.header.height.value(), 10);
+
+        let error = client.block(11u64).await.unwrap_err();
+        assert_eq!(error.code(), Code::ServerError);
+
+        let requests = mock.requests_for(Method::Block);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].param("height"), Some(&json!("11")));
+    }
+
+    #[tokio::test]
+    async fn simulates_faults() {
+        time::pause();
+
+        let mock = MockClient::new()
+            .respond_with_fixture(Matcher::method(Method::Status), "tests/support/status.json")
+            .unwrap();
+        let network = SimulatedTransport::new(mock.clone())
+            .latency(Duration::from_millis(20))
+            .then(Fault::Drop)
+            .then(Fault::Disconnect);
+        let client = Client::builder(MOCK_URL.parse().unwrap())
+            .transport(network.clone())
+            .request_timeout(Some(Duration::from_millis(100)))
+            .build()
+            .unwrap();
+
+        let start = Instant::now();
+        assert_eq!(
+            client.status().await.unwrap_err().code(),
+            Code::TimeoutError
+        );
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert_eq!(client.status().await.unwrap_err().code(), Code::HttpError);
+        assert_eq!(network.pending_faults(), 0);
+
+        let start = Instant::now();
+        client.status().await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        network.disconnect_for(Duration::from_secs(60));
+        assert!(client.status().await.is_err());
+        assert_eq!(mock.requests().len(), 1);
+
+        time::advance(Duration::from_secs(60)).await;
+        client.status().await.unwrap();
+        assert_eq!(mock.requests().len(), 2);
+    }
+
+    fn simulated_event(query: &str) -> Result<ResultEvent, Error> {
+        Ok(ResultEvent {
+            query: query.to_owned(),
+            data: crate::client::event_listener::TMEventData::GenericJSONEvent(Value::Null),
+            events: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn simulated_subscriptions_reorder_events() {
+        use futures::StreamExt;
+
+        time::pause();
+
+        let subscription = simulated_subscription(
+            vec![
+                (Duration::from_millis(30), simulated_event("a")),
+                (Duration::from_millis(10), simulated_event("b")),
+                (
+                    Duration::from_millis(40),
+                    Err(Error::websocket_error("web socket closed")),
+                ),
+            ],
+            10,
+            OverflowPolicy::Terminate,
+        );
+
+        let events = subscription.collect::<Vec<_>>().await;
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].as_ref().unwrap().query, "b");
+        assert_eq!(events[1].as_ref().unwrap().query, "a");
+        assert!(events[2].is_err());
+    }
+
+    #[tokio::test]
+    async fn simulated_subscriptions_stay_open_after_the_last_event() {
+        use futures::{FutureExt, StreamExt};
+
+        time::pause();
+
+        let mut subscription = simulated_subscription(
+            vec![(Duration::from_millis(10), simulated_event("a"))],
+            10,
+            OverflowPolicy::Terminate,
+        );
+
+        assert!(subscription.next().now_or_never().is_none());
+
+        time::advance(Duration::from_millis(10)).await;
+        let event = subscription.next().await.unwrap();
+        assert_eq!(event.unwrap().query, "a");
+
+        time::advance(Duration::from_secs(3600)).await;
+        assert!(subscription.next().now_or_never().is_none());
+    }
+
+    #[tokio::test]
+    async fn replays_recordings() {
+        let path = std::env::temp_dir().join(format!(
+            "tendermint-rpc-recording-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        let live = MockClient::new()
+            .respond_with_fixture(Matcher::method(Method::Status), "tests/support/status.json")
+            .unwrap()
+            .respond_with_fixture(
+                Matcher::method(Method::Block).param("height", "10"),
+                "tests/support/block.json",
+            )
+            .unwrap()
+            .respond_with_error(
+                Matcher::method(Method::Block),
+                Error::server_error("height not available"),
+            );
+        let client = Client::builder(MOCK_URL.parse().unwrap())
+            .transport(RecordingTransport::new(live, &path).unwrap())
+            .build()
+            .unwrap();
+        let status = client.status().await.unwrap();
+        let block = client.block(10u64).await.unwrap();
+        assert!(client.block(11u64).await.is_err());
+
+        let replay = MockClient::from_recording(&path).unwrap().client();
+        assert_eq!(
+            replay.status().await.unwrap().node_info.id,
+            status.node_info.id
+        );
+        assert_eq!(replay.block(10u64).await.unwrap().block_id, block.block_id);
+        assert_eq!(
+            replay.block(11u64).await.unwrap_err().code(),
+            Code::ServerError
+        );
+        assert!(replay.block(12u64).await.is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn answers_batches() {
+        let mock = MockClient::new()
+            .respond_with_fixture(Matcher::method(Method::Block), "tests/support/block.json")
+            .unwrap();
+
+        let results = mock
+            .client()
+            .perform_batch(vec![block::Request::new(1u64.into()); 2])
+            .await
+            .unwrap();
+
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(mock.requests().len(), 2);
+    }
+}
@@ -0,0 +1,272 @@
+//! Mock of the WebSocket endpoint of a Tendermint node
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_tungstenite::tungstenite::Message;
+use futures::channel::mpsc;
+use futures::future::Either;
+use futures::stream::{self, StreamExt};
+use futures::SinkExt;
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::super::event_listener::ResultEvent;
+use super::super::transport::Transport;
+use super::super::Url;
+use super::MockClient;
+use crate::Error;
+
+/// Mock of the WebSocket endpoint of a Tendermint node, listening on a local
+/// port, for testing subscription handling without a node.
+///
+/// The server confirms subscriptions and unsubscriptions, pushes the events
+/// published with [`publish`](Self::publish) to the connections subscribed
+/// to their query, and answers all other requests with the responses
+/// registered on a [`MockClient`]. [`disconnect`](Self::disconnect) closes
+/// all connections, e.g. to test reconnecting.
+///
+/// ```ignore
+/// let server = MockWebSocketServer::start(MockClient::new()).await?;
+/// let websocket = WebSocketClient::connect(server.url()).await?;
+/// let mut blocks = websocket.subscribe(&query, 10, OverflowPolicy::Terminate).await?;
+/// server.publish(event);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MockWebSocketServer {
+    url: Url,
+    connections: Arc<Mutex<Connections>>,
+}
+
+/// Open connections by number
+#[derive(Debug, Default)]
+struct Connections {
+    next: usize,
+    open: HashMap<usize, Connection>,
+}
+
+/// Open connection of a [`MockWebSocketServer`]
+#[derive(Debug)]
+struct Connection {
+    /// Messages to send to the client
+    outgoing: mpsc::UnboundedSender<Message>,
+    /// IDs of the subscription requests by query
+    subscriptions: HashMap<String, Value>,
+}
+
+impl MockWebSocketServer {
+    /// Start listening on a local port, answering requests other than
+    /// subscriptions with the given mock
+    pub async fn start(responses: MockClient) -> Result<Self, Error> {
+        let mut listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| Error::websocket_error(format!("cannot listen: {}", e)))?;
+        let address = listener
+            .local_addr()
+            .map_err(|e| Error::websocket_error(format!("cannot listen: {}", e)))?;
+
+        let server = Self {
+            url: format!("ws://{}/websocket", address).parse()?,
+            connections: Arc::new(Mutex::new(Connections::default())),
+        };
+
+        let connections = server.connections.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, connections.clone(), responses.clone()));
+            }
+        });
+
+        Ok(server)
+    }
+
+    /// URL to connect to this server
+    pub fn url(&self) -> Url {
+        self.url.clone()
+    }
+
+    /// Number of open connections
+    pub fn connections(&self) -> usize {
+        self.connections.lock().unwrap().open.len()
+    }
+
+    /// Queries subscribed to by any open connection
+    pub fn subscriptions(&self) -> Vec<String> {
+        let mut queries = self
+            .connections
+            .lock()
+            .unwrap()
+            .open
+            .values()
+            .flat_map(|connection| connection.subscriptions.keys().cloned())
+            .collect::<Vec<_>>();
+        queries.sort();
+        queries.dedup();
+        queries
+    }
+
+    /// Push the given event to all connections subscribed to its query,
+    /// returning the number of connections it was pushed to
+    pub fn publish(&self, event: ResultEvent) -> usize {
+        let result = serde_json::to_value(&event).expect("events serialize as JSON");
+        let connections = self.connections.lock().unwrap();
+
+        connections
+            .open
+            .values()
+            .filter_map(|connection| {
+                let id = connection.subscriptions.get(&event.query)?;
+                // Tendermint identifies events by the subscription request
+                let id = format!(
+                    "{}#event",
+                    id.as_str().map_or(id.to_string(), str::to_owned)
+                );
+                let message = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                connection
+                    .outgoing
+                    .unbounded_send(Message::text(message.to_string()))
+                    .ok()
+            })
+            .count()
+    }
+
+    /// Close all open connections
+    pub fn disconnect(&self) {
+        for (_, connection) in self.connections.lock().unwrap().open.drain() {
+            let _ = connection.outgoing.unbounded_send(Message::Close(None));
+        }
+    }
+}
+
+/// Serve the given connection until it is closed by either side
+async fn serve(stream: TcpStream, connections: Arc<Mutex<Connections>>, responses: MockClient) {
+    let socket = match async_tungstenite::tokio::accept_async(stream).await {
+        Ok(socket) => socket,
+        Err(_) => return,
+    };
+    let (mut sink, source) = socket.split();
+    let (outgoing, receiver) = mpsc::unbounded();
+
+    let number = {
+        let mut connections = connections.lock().unwrap();
+        let number = connections.next;
+        connections.next += 1;
+        connections.open.insert(
+            number,
+            Connection {
+                outgoing,
+                subscriptions: HashMap::new(),
+            },
+        );
+        number
+    };
+
+    let mut messages = stream::select(source.map(Either::Left), receiver.map(Either::Right));
+    while let Some(message) = messages.next().await {
+        let reply = match message {
+            Either::Left(Ok(Message::Text(text))) => {
+                match answer(&text, number, &connections, &responses).await {
+                    Some(reply) => Message::text(reply),
+                    None => continue,
+                }
+            }
+            Either::Left(Ok(Message::Close(_))) | Either::Left(Err(_)) => break,
+            Either::Left(Ok(_)) => continue,
+            Either::Right(message) => message,
+        };
+
+        let closing = reply.is_close();
+        if sink.send(reply).await.is_err() || closing {
+            break;
+        }
+    }
+
+    connections.lock().unwrap().open.remove(&number);
+}
+
+/// Answer the given request received by the connection with the given
+/// number, if it is a valid request
+async fn answer(
+    text: &str,
+    number: usize,
+    connections: &Mutex<Connections>,
+    responses: &MockClient,
+) -> Option<String> {
+    let request: Value = serde_json::from_str(text).ok()?;
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let query = request["params"]["query"].as_str().map(str::to_owned);
+
+    let handled = {
+        let mut connections = connections.lock().unwrap();
+        let subscriptions = &mut connections.open.get_mut(&number)?.subscriptions;
+        match (request.get("method").and_then(Value::as_str)?, query) {
+            ("subscribe", Some(query)) => {
+                subscriptions.insert(query, id.clone());
+                true
+            }
+            ("unsubscribe", Some(query)) => {
+                subscriptions.remove(&query);
+                true
+            }
+            ("unsubscribe_all", _) => {
+                subscriptions.clear();
+                true
+            }
+            _ => false,
+        }
+    };
+
+    if !handled {
+        let response = responses.send(text.to_owned()).await.ok()?;
+        return String::from_utf8(response).ok();
+    }
+
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": {} }).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::event_listener::TMEventData;
+    use super::super::super::subscription::OverflowPolicy;
+    use super::super::super::WebSocketClient;
+    use super::super::Matcher;
+    use super::*;
+    use crate::endpoint::status;
+    use crate::query::{EventType, Query};
+    use crate::Method;
+
+    fn event(query: &str) -> ResultEvent {
+        ResultEvent {
+            query: query.to_owned(),
+            data: TMEventData::GenericJSONEvent(json!({ "height": "1" })),
+            events: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn pushes_events_to_subscribers() {
+        let responses = MockClient::new()
+            .respond_with_fixture(Matcher::method(Method::Status), "tests/support/status.json")
+            .unwrap();
+        let server = MockWebSocketServer::start(responses).await.unwrap();
+        let websocket = WebSocketClient::connect(server.url()).await.unwrap();
+
+        let query: Query = EventType::NewBlock.into();
+        let mut subscription = websocket
+            .subscribe(&query, 10, OverflowPolicy::Terminate)
+            .await
+            .unwrap();
+        assert_eq!(server.subscriptions(), vec![query.to_string()]);
+
+        assert_eq!(server.publish(event(&query.to_string())), 1);
+        assert_eq!(server.publish(event("tm.event = 'Tx'")), 0);
+        let received = subscription.next().await.unwrap().unwrap();
+        assert_eq!(received.query, query.to_string());
+
+        websocket.perform(status::Request).await.unwrap();
+
+        server.disconnect();
+        assert!(subscription.next().await.unwrap().is_err());
+        assert_eq!(server.connections(), 0);
+    }
+}
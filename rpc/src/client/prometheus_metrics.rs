@@ -0,0 +1,155 @@
+//! Metrics of RPC clients exported to Prometheus
+
+use prometheus::{
+    Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+};
+
+use super::{MetricsRecorder, RequestMetrics};
+use crate::Error;
+
+/// [`MetricsRecorder`] registering the standard metrics of RPC clients into a
+/// Prometheus registry:
+///
+/// - `tendermint_rpc_requests_total`: requests by `method` and `status`
+///   (`ok` or the error code, e.g. `TimeoutError`)
+/// - `tendermint_rpc_request_duration_seconds`: latencies by `method`,
+///   including retries
+/// - `tendermint_rpc_request_attempts`: attempts per request
+/// - `tendermint_rpc_active_subscriptions`: subscriptions open on WebSocket
+///   clients
+/// - `tendermint_rpc_reconnects_total`: re-established WebSocket connections
+///
+/// One recorder can be shared by any number of clients, e.g. via
+/// [`ClientBuilder::metrics_recorder`](super::ClientBuilder::metrics_recorder)
+/// and [`WebSocketClient::new_with_metrics`](super::WebSocketClient::new_with_metrics).
+/// The subscription gauge is only meaningful when shared by a single
+/// WebSocket client.
+#[derive(Clone, Debug)]
+pub struct PrometheusMetrics {
+    requests: IntCounterVec,
+    durations: HistogramVec,
+    attempts: Histogram,
+    subscriptions: IntGauge,
+    reconnects: IntCounter,
+}
+
+impl PrometheusMetrics {
+    /// Create the metrics and register them into the given registry
+    pub fn register(registry: &Registry) -> Result<Self, Error> {
+        let metrics = Self {
+            requests: IntCounterVec::new(
+                Opts::new("tendermint_rpc_requests_total", "RPC requests performed"),
+                &["method", "status"],
+            )
+            .map_err(registration_error)?,
+            durations: HistogramVec::new(
+                HistogramOpts::new(
+                    "tendermint_rpc_request_duration_seconds",
+                    "Duration of RPC requests, including retries",
+                ),
+                &["method"],
+            )
+            .map_err(registration_error)?,
+            attempts: Histogram::with_opts(
+                HistogramOpts::new(
+                    "tendermint_rpc_request_attempts",
+                    "Attempts made per RPC request",
+                )
+                .buckets(vec![1.0, 2.0, 3.0, 5.0, 10.0]),
+            )
+            .map_err(registration_error)?,
+            subscriptions: IntGauge::new(
+                "tendermint_rpc_active_subscriptions",
+                "Subscriptions open on WebSocket connections",
+            )
+            .map_err(registration_error)?,
+            reconnects: IntCounter::new(
+                "tendermint_rpc_reconnects_total",
+                "WebSocket connections re-established after failing",
+            )
+            .map_err(registration_error)?,
+        };
+
+        registry
+            .register(Box::new(metrics.requests.clone()))
+            .and_then(|_| registry.register(Box::new(metrics.durations.clone())))
+            .and_then(|_| registry.register(Box::new(metrics.attempts.clone())))
+            .and_then(|_| registry.register(Box::new(metrics.subscriptions.clone())))
+            .and_then(|_| registry.register(Box::new(metrics.reconnects.clone())))
+            .map_err(registration_error)?;
+
+        Ok(metrics)
+    }
+}
+
+impl MetricsRecorder for PrometheusMetrics {
+    fn record(&self, metrics: &RequestMetrics<'_>) {
+        let method = metrics.method.as_str();
+        let status = match metrics.error {
+            Some(error) => format!("{:?}", error.code()),
+            None => "ok".to_owned(),
+        };
+
+        self.requests.with_label_values(&[method, &status]).inc();
+        self.durations
+            .with_label_values(&[method])
+            .observe(metrics.duration.as_secs_f64());
+        self.attempts.observe(f64::from(metrics.attempts));
+    }
+
+    fn record_subscriptions(&self, active: usize) {
+        self.subscriptions.set(active as i64);
+    }
+
+    fn record_reconnect(&self) {
+        self.reconnects.inc();
+    }
+}
+
+fn registration_error(error: prometheus::Error) -> Error {
+    Error::invalid_params(&format!("cannot register metrics: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Method;
+    use std::time::Duration;
+
+    #[test]
+    fn records_requests_by_method_and_status() {
+        let registry = Registry::new();
+        let metrics = PrometheusMetrics::register(&registry).unwrap();
+        let timeout = Error::timeout_error("too slow");
+
+        for error in &[None, None, Some(&timeout)] {
+            metrics.record(&RequestMetrics {
+                method: Method::Status,
+                batch_size: None,
+                duration: Duration::from_millis(10),
+                attempts: 1,
+                request_size: 0,
+                response_size: 0,
+                error: *error,
+            });
+        }
+        metrics.record_subscriptions(3);
+
+        assert_eq!(
+            metrics.requests.with_label_values(&["status", "ok"]).get(),
+            2
+        );
+        assert_eq!(
+            metrics
+                .requests
+                .with_label_values(&["status", "TimeoutError"])
+                .get(),
+            1
+        );
+        assert_eq!(metrics.subscriptions.get(), 3);
+        assert_eq!(registry.gather().len(), 5);
+
+        // Metrics can only be registered once per registry
+        assert!(PrometheusMetrics::register(&registry).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+//! Client-side limiting of the rate of RPC requests
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Limit on the rate of requests sent by a [`Client`](super::Client), e.g.
+/// to stay below the quotas of public RPC providers.
+///
+/// Requests may be sent in bursts of up to `burst` requests, after which
+/// they are delayed to keep to `requests_per_second` on average. Retries
+/// count as requests. Clones of a client share their limit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Number of requests per second sent on average
+    pub requests_per_second: u32,
+
+    /// Number of requests which may be sent at once after a quiet period
+    pub burst: u32,
+}
+
+/// Token bucket enforcing a [`RateLimit`]
+#[derive(Clone, Debug)]
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Requests which may be sent right away (negative while requests are
+    /// waiting for their turn)
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: f64::from(limit.burst),
+                updated: Instant::now(),
+            })),
+        }
+    }
+
+    /// Wait until the next request may be sent
+    pub(crate) async fn acquire(&self) {
+        let wait = self.reserve(Instant::now());
+        if wait > Duration::from_secs(0) {
+            tokio::time::delay_for(wait).await;
+        }
+    }
+
+    /// Reserve a token, returning how long to wait for it to become
+    /// available
+    fn reserve(&self, now: Instant) -> Duration {
+        let rate = f64::from(self.limit.requests_per_second);
+        let mut bucket = self.bucket.lock().unwrap();
+
+        let refill = now.saturating_duration_since(bucket.updated).as_secs_f64() * rate;
+        bucket.tokens = (bucket.tokens + refill).min(f64::from(self.limit.burst));
+        bucket.updated = now;
+
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::from_secs(0)
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_requests_beyond_burst() {
+        let limiter = RateLimiter::new(RateLimit {
+            requests_per_second: 10,
+            burst: 2,
+        });
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve(now), Duration::from_secs(0));
+        assert_eq!(limiter.reserve(now), Duration::from_secs(0));
+        assert_eq!(limiter.reserve(now), Duration::from_millis(100));
+        assert_eq!(limiter.reserve(now), Duration::from_millis(200));
+
+        // Waiting requests have used up the tokens refilled meanwhile
+        let later = now + Duration::from_millis(300);
+        assert_eq!(limiter.reserve(later), Duration::from_millis(0));
+        assert_eq!(
+            limiter.reserve(later + Duration::from_secs(10)),
+            Duration::from_secs(0)
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Replay of past block results followed by live ones
+
+use futures::stream::{self, Stream, StreamExt};
+use tendermint::block::{Block, Height};
+
+use super::Client;
+use crate::endpoint::block_results;
+use crate::Error;
+
+/// Progress of a replay
+struct Replay<'a, S> {
+    client: &'a Client,
+    /// New blocks as they are committed
+    blocks: S,
+    /// Height of the results to emit next
+    next: Height,
+    /// Highest height known to be committed
+    latest: Height,
+}
+
+/// Stream of the results of all blocks from `start` on, fetching those up to
+/// `latest` right away and those of later blocks as the given stream of new
+/// blocks reveals them.
+///
+/// Results are fetched for all heights up to the highest one seen so far, so
+/// that missed blocks leave no gaps, and blocks at heights emitted already
+/// are skipped.
+pub(super) fn replay<'a, S>(
+    client: &'a Client,
+    blocks: S,
+    start: Height,
+    latest: Height,
+) -> impl Stream<Item = Result<block_results::Response, Error>> + 'a
+where
+    S: Stream<Item = Result<Block, Error>> + Unpin + 'a,
+{
+    let replay = Replay {
+        client,
+        blocks,
+        next: start,
+        latest,
+    };
+
+    stream::try_unfold(replay, |mut replay| async move {
+        while replay.next > replay.latest {
+            match replay.blocks.next().await {
+                Some(block) => replay.latest = replay.latest.max(block?.header.height),
+                None => return Ok(None),
+            }
+        }
+
+        let results = replay.client.block_results(replay.next).await?;
+        replay.next = replay.next.increment();
+        Ok(Some((results, replay)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mock::{Matcher, MockClient};
+    use super::*;
+    use crate::endpoint::block;
+    use crate::{Method, Response};
+    use std::fs;
+
+    fn block(height: u64) -> Result<Block, Error> {
+        let mut block =
+            block::Response::from_string(fs::read_to_string("tests/support/block.json").unwrap())
+                .unwrap()
+                .block;
+        block.header.height = Height(height);
+        Ok(block)
+    }
+
+    #[tokio::test]
+    async fn emits_each_height_once_in_order() {
+        let mock = MockClient::new()
+            .respond_with_fixture(
+                Matcher::method(Method::BlockResults),
+                "tests/support/block_results.json",
+            )
+            .unwrap();
+        let client = mock.client();
+
+        // Height 4 was replayed already, 5 was missed and 6 arrives late
+        let blocks = stream::iter(vec![block(4), block(7), block(6)]);
+        let results = replay(&client, blocks, Height(3), Height(4))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(results.len(), 5);
+        assert!(results.iter().all(Result::is_ok));
+
+        let heights = mock
+            .requests()
+            .iter()
+            .map(|request| {
+                request
+                    .param("height")
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+                    .to_owned()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(heights, vec!["3", "4", "5", "6", "7"]);
+    }
+
+    #[tokio::test]
+    async fn ends_after_first_error() {
+        let client = MockClient::new()
+            .respond_with_fixture(
+                Matcher::method(Method::BlockResults),
+                "tests/support/block_results.json",
+            )
+            .unwrap()
+            .client();
+
+        let blocks = stream::iter(vec![
+            Err(Error::websocket_error("web socket closed")),
+            block(9),
+        ]);
+        let results = replay(&client, blocks, Height(1), Height(1))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+    }
+}
@@ -0,0 +1,140 @@
+//! Retrying of failed RPC requests with exponential backoff
+
+use std::time::Duration;
+
+use getrandom::getrandom;
+
+use crate::error::{Code, Error};
+
+/// Policy describing if and how often a failed request is retried.
+///
+/// Only idempotent requests (see [`Method::is_idempotent`](crate::Method::is_idempotent))
+/// are ever retried, and only when they failed because of a transport-level
+/// problem: an HTTP error or a timeout. Responses which carry no JSONRPC
+/// payload are only retried if their HTTP status tells the failure is
+/// temporary, e.g. 429 or 503. Errors returned by the node itself are final.
+///
+/// The delay before retry `n` is `initial_backoff * 2^(n - 1)`, capped at
+/// `max_backoff`, of which a random portion of up to one half is subtracted
+/// (jitter) so that many clients do not retry in lockstep.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one
+    pub max_attempts: u32,
+
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+
+    /// Upper bound on the delay between two attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Should a request which failed with the given error on the given
+    /// attempt (starting at 1) be attempted again?
+    pub fn should_retry(&self, attempt: u32, error: &Error) -> bool {
+        attempt < self.max_attempts && is_transient(error)
+    }
+
+    /// Backoff before the given retry (starting at 1), without jitter.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+
+        self.initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+
+    /// Backoff before the given retry (starting at 1), with jitter applied.
+    pub fn backoff_with_jitter(&self, retry: u32) -> Duration {
+        let backoff = self.backoff(retry);
+        let mut bytes = [0; 4];
+        getrandom(&mut bytes).expect("RNG failure!");
+
+        let ratio = f64::from(u32::from_le_bytes(bytes)) / f64::from(u32::max_value());
+        backoff - backoff.mul_f64(ratio / 2.0)
+    }
+}
+
+/// Is the given error caused by a temporary problem of the transport rather
+/// than by the node?
+fn is_transient(error: &Error) -> bool {
+    match error.code() {
+        Code::TimeoutError => true,
+        Code::HttpError => error.http_status().map_or(true, is_transient_status),
+        _ => false,
+    }
+}
+
+/// Is a response with the given HTTP status worth retrying: a timeout, rate
+/// limiting, or a server or gateway which is temporarily failing?
+fn is_transient_status(status: u16) -> bool {
+    matches!(status, 408 | 429 | 500 | 502 | 503 | 504)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1000),
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(5), Duration::from_millis(1000));
+        assert_eq!(policy.backoff(64), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let policy = RetryPolicy::default();
+
+        for retry in 1..10 {
+            let backoff = policy.backoff(retry);
+            let jittered = policy.backoff_with_jitter(retry);
+            assert!(jittered <= backoff);
+            assert!(jittered >= backoff / 2);
+        }
+    }
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        let policy = RetryPolicy::default();
+
+        assert!(policy.should_retry(1, &Error::http_error("connection refused")));
+        assert!(policy.should_retry(1, &Error::timeout_error("deadline has elapsed")));
+        assert!(!policy.should_retry(1, &Error::server_error("height too high")));
+        assert!(!policy.should_retry(
+            policy.max_attempts,
+            &Error::http_error("connection refused")
+        ));
+    }
+
+    #[test]
+    fn http_statuses_tell_whether_to_retry() {
+        let policy = RetryPolicy::default();
+
+        assert!(policy.should_retry(1, &Error::http_status_error(429)));
+        assert!(policy.should_retry(1, &Error::http_status_error(503)));
+        assert!(!policy.should_retry(1, &Error::http_status_error(401)));
+        assert!(!policy.should_retry(1, &Error::http_status_error(501)));
+        assert_eq!(Error::http_status_error(503).http_status(), Some(503));
+        assert_eq!(Error::http_error("connection refused").http_status(), None);
+    }
+}
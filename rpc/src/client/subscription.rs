@@ -0,0 +1,383 @@
+//! Buffered streams of events received by an [`EventListener`] or a
+//! [`WebSocketClient`](super::WebSocketClient)
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll, Waker};
+
+use futures::{future, Stream, StreamExt};
+use tendermint::block::Block;
+
+use super::event_listener::{
+    EventDataNewBlock, EventDataTx, EventListener, ResultEvent, TMEventData, TxResult,
+};
+use crate::Error;
+
+/// What to do when an event arrives while the buffer of a [`Subscription`]
+/// is full, because its consumer cannot keep up
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OverflowPolicy {
+    /// Discard the oldest buffered event to make room for the new one
+    DropOldest,
+
+    /// Discard the new event
+    DropNewest,
+
+    /// Stop listening and end the subscription with an error, once the
+    /// buffered events have been consumed
+    Terminate,
+}
+
+/// Stream of the events received by an [`EventListener`], which is driven
+/// by a background task and buffers at most a fixed number of events.
+///
+/// Created with [`EventListener::into_subscription`] or
+/// [`WebSocketClient::subscribe`](super::WebSocketClient::subscribe).
+/// Dropping it stops the background task once the next event arrives.
+#[derive(Debug)]
+pub struct Subscription {
+    buffer: Arc<Mutex<Buffer>>,
+}
+
+/// Events received but not consumed yet
+#[derive(Debug)]
+struct Buffer {
+    events: VecDeque<Result<ResultEvent, Error>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    /// Number of events discarded so far
+    dropped: u64,
+    /// Set once no more events will be pushed
+    closed: bool,
+    /// Task waiting for the next event
+    waker: Option<Waker>,
+}
+
+impl Subscription {
+    /// Spawn a task reading events from the listener into a new buffer of
+    /// the given capacity (at least one event)
+    pub(crate) fn spawn(
+        mut listener: EventListener,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Self {
+        let (subscription, publisher) = Self::with_publisher(capacity, policy);
+
+        tokio::spawn(async move {
+            loop {
+                let event = match listener.get_event().await {
+                    Ok(None) => continue,
+                    Ok(Some(event)) => Ok(event),
+                    Err(e) => Err(e),
+                };
+
+                if !publisher.publish(event) {
+                    return;
+                }
+            }
+        });
+
+        subscription
+    }
+
+    /// Create a subscription with a buffer of the given capacity (at least
+    /// one event), along with the publisher filling it
+    pub(crate) fn with_publisher(capacity: usize, policy: OverflowPolicy) -> (Self, Publisher) {
+        let subscription = Self::new(capacity, policy);
+        let publisher = Publisher(Arc::downgrade(&subscription.buffer));
+        (subscription, publisher)
+    }
+
+    fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            buffer: Arc::new(Mutex::new(Buffer {
+                events: VecDeque::with_capacity(capacity),
+                capacity,
+                policy,
+                dropped: 0,
+                closed: false,
+                waker: None,
+            })),
+        }
+    }
+
+    /// Number of events discarded so far because the buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.buffer.lock().unwrap().dropped
+    }
+
+    /// Number of events buffered but not consumed yet
+    pub fn len(&self) -> usize {
+        self.buffer.lock().unwrap().events.len()
+    }
+
+    /// Are there no buffered events?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Stream of the blocks carried by new block events, skipping all other
+    /// events
+    pub fn blocks(self) -> impl Stream<Item = Result<Block, Error>> + Unpin {
+        self.filter_map(|event| {
+            future::ready(match event {
+                Ok(ResultEvent {
+                    data:
+                        TMEventData::EventDataNewBlock(EventDataNewBlock {
+                            block: Some(block), ..
+                        }),
+                    ..
+                }) => Some(Ok(block)),
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            })
+        })
+    }
+
+    /// Stream of the results of the transactions carried by transaction
+    /// events, skipping all other events
+    pub fn tx_results(self) -> impl Stream<Item = Result<TxResult, Error>> + Unpin {
+        self.filter_map(|event| {
+            future::ready(match event {
+                Ok(ResultEvent {
+                    data: TMEventData::EventDataTx(EventDataTx { tx_result }),
+                    ..
+                }) => Some(Ok(tx_result)),
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            })
+        })
+    }
+}
+
+impl Stream for Subscription {
+    type Item = Result<ResultEvent, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut buffer = self.buffer.lock().unwrap();
+
+        match buffer.events.pop_front() {
+            Some(event) => Poll::Ready(Some(event)),
+            None if buffer.closed => Poll::Ready(None),
+            None => {
+                buffer.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Pushes events into the buffer of a [`Subscription`], without keeping the
+/// subscription alive
+#[derive(Debug)]
+pub(crate) struct Publisher(Weak<Mutex<Buffer>>);
+
+impl Publisher {
+    /// Buffer the given event, ending the subscription if it is an error.
+    ///
+    /// Returns `false` once the subscription has been dropped or ended, as
+    /// no more events will be consumed.
+    pub(crate) fn publish(&self, event: Result<ResultEvent, Error>) -> bool {
+        let buffer = match self.0.upgrade() {
+            Some(buffer) => buffer,
+            None => return false,
+        };
+        let mut buffer = buffer.lock().unwrap();
+
+        let failed = event.is_err();
+        buffer.push(event);
+        if failed {
+            buffer.close();
+        }
+
+        !buffer.closed
+    }
+
+    /// End the subscription once the events buffered so far have been
+    /// consumed
+    pub(crate) fn close(&self) {
+        if let Some(buffer) = self.0.upgrade() {
+            buffer.lock().unwrap().close();
+        }
+    }
+}
+
+impl Buffer {
+    /// Buffer the given event according to the overflow policy
+    fn push(&mut self, event: Result<ResultEvent, Error>) {
+        if self.closed {
+            return;
+        }
+
+        // Errors end the subscription and are always kept
+        if self.events.len() >= self.capacity && event.is_ok() {
+            match self.policy {
+                OverflowPolicy::DropOldest => {
+                    self.events.pop_front();
+                    self.dropped += 1;
+                }
+                OverflowPolicy::DropNewest => {
+                    self.dropped += 1;
+                    return;
+                }
+                OverflowPolicy::Terminate => {
+                    self.dropped += 1;
+                    self.events.push_back(Err(Error::websocket_error(format!(
+                        "subscription buffer of {} events overflowed",
+                        self.capacity
+                    ))));
+                    self.close();
+                    return;
+                }
+            }
+        }
+
+        self.events.push_back(event);
+        self.wake();
+    }
+
+    /// Stop accepting events, ending the stream once the buffered events
+    /// have been consumed
+    fn close(&mut self) {
+        self.closed = true;
+        self.wake();
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::event_listener::TxResultResult;
+    use crate::endpoint::block;
+    use crate::Response;
+    use std::fs;
+
+    fn event(query: &str) -> Result<ResultEvent, Error> {
+        Ok(ResultEvent {
+            query: query.to_owned(),
+            data: TMEventData::GenericJSONEvent(serde_json::Value::Null),
+            events: None,
+        })
+    }
+
+    async fn queries(subscription: Subscription) -> Vec<Result<String, Error>> {
+        subscription
+            .map(|event| event.map(|event| event.query))
+            .collect()
+            .await
+    }
+
+    fn fill(policy: OverflowPolicy) -> Subscription {
+        let subscription = Subscription::new(2, policy);
+        {
+            let mut buffer = subscription.buffer.lock().unwrap();
+            for query in &["a", "b", "c"] {
+                buffer.push(event(query));
+            }
+            buffer.close();
+        }
+        subscription
+    }
+
+    fn closed_with(events: Vec<Result<ResultEvent, Error>>) -> Subscription {
+        let subscription = Subscription::new(events.len(), OverflowPolicy::Terminate);
+        {
+            let mut buffer = subscription.buffer.lock().unwrap();
+            for event in events {
+                buffer.push(event);
+            }
+            buffer.close();
+        }
+        subscription
+    }
+
+    #[tokio::test]
+    async fn drop_oldest() {
+        let subscription = fill(OverflowPolicy::DropOldest);
+        assert_eq!(subscription.dropped(), 1);
+
+        let queries = queries(subscription).await;
+        assert_eq!(queries, vec![Ok("b".to_owned()), Ok("c".to_owned())]);
+    }
+
+    #[tokio::test]
+    async fn drop_newest() {
+        let subscription = fill(OverflowPolicy::DropNewest);
+        assert_eq!(subscription.dropped(), 1);
+
+        let queries = queries(subscription).await;
+        assert_eq!(queries, vec![Ok("a".to_owned()), Ok("b".to_owned())]);
+    }
+
+    #[tokio::test]
+    async fn terminate() {
+        let subscription = fill(OverflowPolicy::Terminate);
+        assert_eq!(subscription.len(), 3);
+
+        let queries = queries(subscription).await;
+        assert_eq!(&queries[..2], &[Ok("a".to_owned()), Ok("b".to_owned())]);
+        assert!(queries[2].is_err());
+    }
+
+    #[tokio::test]
+    async fn blocks() {
+        let block =
+            block::Response::from_string(fs::read_to_string("tests/support/block.json").unwrap())
+                .unwrap()
+                .block;
+        let new_block = Ok(ResultEvent {
+            query: "tm.event = 'NewBlock'".to_owned(),
+            data: TMEventData::EventDataNewBlock(EventDataNewBlock {
+                block: Some(block.clone()),
+                result_begin_block: None,
+                result_end_block: None,
+            }),
+            events: None,
+        });
+
+        let blocks = closed_with(vec![event("a"), new_block, event("b")])
+            .blocks()
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].as_ref().unwrap().header, block.header);
+    }
+
+    #[tokio::test]
+    async fn tx_results() {
+        let tx = Ok(ResultEvent {
+            query: "tm.event = 'Tx'".to_owned(),
+            data: TMEventData::EventDataTx(EventDataTx {
+                tx_result: TxResult {
+                    height: "10".to_owned(),
+                    index: 0,
+                    tx: "dHg=".to_owned(),
+                    result: TxResultResult {
+                        log: String::new(),
+                        gas_wanted: "1".to_owned(),
+                        gas_used: "1".to_owned(),
+                        events: vec![],
+                    },
+                },
+            }),
+            events: None,
+        });
+        let failure = Err(Error::websocket_error("web socket closed"));
+
+        let tx_results = closed_with(vec![event("a"), tx, failure])
+            .tx_results()
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(tx_results.len(), 2);
+        assert_eq!(tx_results[0].as_ref().unwrap().height, "10");
+        assert!(tx_results[1].is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! TLS configuration for `https://` RPC endpoints
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use rustls::internal::pemfile;
+use rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore};
+
+use super::transport::TransportOptions;
+use crate::Error;
+
+/// Certificate chain and private key the client authenticates itself with
+/// (mutual TLS)
+#[derive(Clone)]
+pub(crate) struct ClientIdentity {
+    cert_chain: Vec<Certificate>,
+    key: PrivateKey,
+}
+
+impl ClientIdentity {
+    /// Parse a PEM-encoded certificate chain and a PEM-encoded PKCS#8 or RSA
+    /// private key
+    pub(crate) fn from_pem(cert_chain: &[u8], key: &[u8]) -> Result<Self, Error> {
+        let cert_chain = match pemfile::certs(&mut Cursor::new(cert_chain)) {
+            Ok(certs) if !certs.is_empty() => certs,
+            _ => {
+                return Err(Error::invalid_params(
+                    "no valid PEM-encoded client certificate found",
+                ))
+            }
+        };
+
+        let mut keys = pemfile::pkcs8_private_keys(&mut Cursor::new(key)).unwrap_or_default();
+        if keys.is_empty() {
+            keys = pemfile::rsa_private_keys(&mut Cursor::new(key)).unwrap_or_default();
+        }
+
+        match keys.into_iter().next() {
+            Some(key) => Ok(Self { cert_chain, key }),
+            None => Err(Error::invalid_params(
+                "no valid PEM-encoded private key found",
+            )),
+        }
+    }
+}
+
+/// Build the TLS client configuration for the given transport options.
+///
+/// A configuration supplied by the user is used as is (apart from ALPN and
+/// the client certificate, if one is configured).
+/// Otherwise the platform's native root certificates are trusted, along with
+/// any additional PEM-encoded root certificates.
+pub(crate) fn client_config(options: &TransportOptions) -> Result<Arc<ClientConfig>, Error> {
+    let mut config = match &options.tls_config {
+        Some(config) => ClientConfig::clone(config),
+        None => {
+            let mut config = ClientConfig::new();
+            config.root_store = native_roots()?;
+
+            for pem in &options.root_certificates {
+                add_pem_roots(&mut config.root_store, pem)?;
+            }
+
+            config
+        }
+    };
+
+    if let Some(identity) = &options.client_identity {
+        config
+            .set_single_client_cert(identity.cert_chain.clone(), identity.key.clone())
+            .map_err(|e| Error::invalid_params(&format!("invalid client certificate: {}", e)))?;
+    }
+
+    config.alpn_protocols = if options.http2_only {
+        vec![b"h2".to_vec()]
+    } else {
+        vec![b"http/1.1".to_vec()]
+    };
+
+    Ok(Arc::new(config))
+}
+
+/// Load the root certificates trusted by the platform
+fn native_roots() -> Result<RootCertStore, Error> {
+    match rustls_native_certs::load_native_certs() {
+        Ok(store) => Ok(store),
+        // Some certificates could not be loaded, but the others are usable
+        Err((Some(store), _)) => Ok(store),
+        Err((None, e)) => Err(Error::invalid_params(&format!(
+            "could not load native root certificates: {}",
+            e
+        ))),
+    }
+}
+
+/// Add the PEM-encoded root certificates to the given store
+pub(crate) fn add_pem_roots(store: &mut RootCertStore, pem: &[u8]) -> Result<(), Error> {
+    match store.add_pem_file(&mut Cursor::new(pem)) {
+        Ok((valid, _)) if valid > 0 => Ok(()),
+        _ => Err(Error::invalid_params(
+            "no valid PEM-encoded root certificate found",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reject_invalid_pem() {
+        assert!(add_pem_roots(&mut RootCertStore::empty(), b"not a certificate").is_err());
+        assert!(ClientIdentity::from_pem(b"not a certificate", b"not a key").is_err());
+    }
+}
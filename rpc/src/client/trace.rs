@@ -0,0 +1,54 @@
+//! Tracing instrumentation of RPC requests, enabled by the `tracing` feature
+
+/// Emit a `tracing` event at debug level, or nothing if the `tracing`
+/// feature is disabled
+#[cfg(feature = "tracing")]
+macro_rules! trace_debug {
+    ($($arg:tt)*) => {
+        tracing::debug!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_debug {
+    ($($arg:tt)*) => {};
+}
+
+#[cfg(feature = "tracing")]
+use crate::Method;
+
+/// Span covering all attempts to perform a request, recording the height
+/// the request is for, if any
+#[cfg(feature = "tracing")]
+pub(crate) fn request_span(
+    method: Method,
+    batch_size: Option<usize>,
+    request_body: &str,
+) -> tracing::Span {
+    let span = tracing::debug_span!(
+        "rpc_request",
+        method = method.as_str(),
+        batch_size = ?batch_size,
+        height = tracing::field::Empty,
+    );
+
+    // Only look for the height when the span is actually recorded
+    if !span.is_disabled() {
+        if let Some(height) = height_param(request_body) {
+            span.record("height", &height.as_str());
+        }
+    }
+
+    span
+}
+
+/// Value of the `height` parameter of the given JSONRPC request
+#[cfg(feature = "tracing")]
+fn height_param(request_body: &str) -> Option<String> {
+    let request: serde_json::Value = serde_json::from_str(request_body).ok()?;
+    request
+        .get("params")?
+        .get("height")?
+        .as_str()
+        .map(ToOwned::to_owned)
+}
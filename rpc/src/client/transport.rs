@@ -0,0 +1,203 @@
+//! HTTP transports used by the RPC client
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use hyper::client::HttpConnector;
+use hyper::header::HeaderMap;
+use hyper::{Body, Uri};
+use hyper_rustls::HttpsConnector;
+
+use super::tls::{self, ClientIdentity};
+use super::tunnel::ProxyConnector;
+use super::Url;
+use crate::{Error, Request, Response};
+
+/// Transport carrying JSONRPC requests to a Tendermint node and its responses
+/// back, e.g. over a message queue or by dispatching them in-process.
+///
+/// Implementations only move serialized JSON; [`perform`](Transport::perform)
+/// takes care of typing requests and responses. A [`Client`](super::Client)
+/// can send its requests over a custom transport, see
+/// [`ClientBuilder::transport`](super::ClientBuilder::transport), and is a
+/// transport itself.
+#[async_trait]
+pub trait Transport: fmt::Debug + Send + Sync {
+    /// Send the given JSON request body (a single JSONRPC request or a batch
+    /// of them), returning the JSON response body
+    async fn send(&self, request_body: String) -> Result<Vec<u8>, Error>;
+
+    /// Perform the given request
+    async fn perform<R>(&self, request: R) -> Result<R::Response, Error>
+    where
+        R: Request + Send + 'static,
+        Self: Sized,
+    {
+        let response_body = self.send(request.into_json()).await?;
+        R::Response::from_string(response_body)
+    }
+}
+
+#[async_trait]
+impl Transport for Arc<dyn Transport> {
+    async fn send(&self, request_body: String) -> Result<Vec<u8>, Error> {
+        (**self).send(request_body).await
+    }
+}
+
+/// Options for establishing connections to the RPC server
+#[derive(Clone, Default)]
+pub(crate) struct TransportOptions {
+    /// Timeout for establishing a TCP connection
+    pub(crate) connect_timeout: Option<Duration>,
+
+    /// Speak HTTP/2 only, multiplexing concurrent requests over a single
+    /// connection
+    pub(crate) http2_only: bool,
+
+    /// TLS configuration supplied by the user, replacing the default one
+    pub(crate) tls_config: Option<Arc<rustls::ClientConfig>>,
+
+    /// Additional PEM-encoded root certificates to trust
+    pub(crate) root_certificates: Vec<Vec<u8>>,
+
+    /// Client certificate for mutual TLS
+    pub(crate) client_identity: Option<ClientIdentity>,
+
+    /// Maximum number of idle connections kept per host (unbounded if
+    /// `None`)
+    pub(crate) pool_max_idle_per_host: Option<usize>,
+
+    /// Time after which idle connections are closed (never if `None`)
+    pub(crate) pool_idle_timeout: Option<Duration>,
+
+    /// Interval of TCP keepalive probes on idle connections (disabled if
+    /// `None`)
+    pub(crate) tcp_keepalive: Option<Duration>,
+
+    /// HTTP proxy tunneling connections with `CONNECT` requests, if any
+    pub(crate) proxy: Option<Url>,
+
+    /// Headers sent to the proxy with every `CONNECT` request
+    pub(crate) proxy_headers: HeaderMap,
+}
+
+impl fmt::Debug for TransportOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransportOptions")
+            .field("connect_timeout", &self.connect_timeout)
+            .field("http2_only", &self.http2_only)
+            .field("custom_tls_config", &self.tls_config.is_some())
+            .field("root_certificates", &self.root_certificates.len())
+            .field("client_identity", &self.client_identity.is_some())
+            .field("pool_max_idle_per_host", &self.pool_max_idle_per_host)
+            .field("pool_idle_timeout", &self.pool_idle_timeout)
+            .field("tcp_keepalive", &self.tcp_keepalive)
+            .field("proxy", &self.proxy)
+            .field("proxy_headers", &self.proxy_headers)
+            .finish()
+    }
+}
+
+/// HTTP client speaking to the RPC server over the transport selected by the
+/// scheme of its URL: TCP for `http://` and `tcp://`, TLS for `https://`, a
+/// unix domain socket for `unix://`, unless tunneled through a proxy.
+/// Alternatively, requests are handed to a custom [`Transport`].
+#[derive(Clone, Debug)]
+pub(crate) enum HttpTransport {
+    /// JSONRPC over HTTP over TCP
+    Tcp(hyper::Client<HttpConnector>),
+
+    /// JSONRPC over HTTP over TLS
+    Tls(hyper::Client<HttpsConnector<HttpConnector>>),
+
+    /// JSONRPC over HTTP over a unix domain socket
+    #[cfg(unix)]
+    Unix(hyper::Client<hyperlocal::UnixConnector>),
+
+    /// JSONRPC over HTTP, over TLS for `https://`, tunneled through an HTTP
+    /// proxy
+    Proxied(hyper::Client<ProxyConnector>),
+
+    /// JSONRPC over a custom transport
+    Custom(Arc<dyn Transport>),
+}
+
+impl HttpTransport {
+    /// Create a transport suitable for the given URL
+    pub(crate) fn new(url: &Url, options: &TransportOptions) -> Result<Self, Error> {
+        let mut builder = hyper::Client::builder();
+        builder
+            .http2_only(options.http2_only)
+            .pool_idle_timeout(options.pool_idle_timeout);
+        if let Some(max_idle) = options.pool_max_idle_per_host {
+            builder.pool_max_idle_per_host(max_idle);
+        }
+
+        if options.proxy.is_some() {
+            if url.scheme() == "unix" {
+                return Err(Error::invalid_params(
+                    "unix domain sockets cannot be reached through a proxy",
+                ));
+            }
+            return Ok(HttpTransport::Proxied(
+                builder.build(ProxyConnector::new(options.clone())),
+            ));
+        }
+
+        let mut connector = HttpConnector::new();
+        connector.set_connect_timeout(options.connect_timeout);
+        connector.set_keepalive(options.tcp_keepalive);
+
+        Ok(match url.scheme() {
+            #[cfg(unix)]
+            "unix" => HttpTransport::Unix(builder.build(hyperlocal::UnixConnector)),
+            "https" => {
+                connector.enforce_http(false);
+                let tls_config = tls::client_config(options)?;
+                HttpTransport::Tls(builder.build(HttpsConnector::from((connector, tls_config))))
+            }
+            _ => HttpTransport::Tcp(builder.build(connector)),
+        })
+    }
+
+    /// URI to send requests for the given URL to
+    pub(crate) fn uri(&self, url: &Url) -> Result<Uri, Error> {
+        match self {
+            HttpTransport::Tcp(_) if url.scheme() == "unix" => Err(Error::invalid_params(
+                "unix domain sockets are not supported on this platform",
+            )),
+            HttpTransport::Tcp(_)
+            | HttpTransport::Tls(_)
+            | HttpTransport::Proxied(_)
+            | HttpTransport::Custom(_) => url
+                .http_uri()
+                .parse()
+                .map_err(|e| Error::invalid_params(&format!("invalid RPC URL: {}", e))),
+            #[cfg(unix)]
+            HttpTransport::Unix(_) => Ok(hyperlocal::Uri::new(url.path(), "/").into()),
+        }
+    }
+
+    /// Send the given request
+    pub(crate) async fn request(
+        &self,
+        request: hyper::Request<Body>,
+    ) -> Result<hyper::Response<Body>, Error> {
+        match self {
+            HttpTransport::Tcp(client) => Ok(client.request(request).await?),
+            HttpTransport::Tls(client) => Ok(client.request(request).await?),
+            #[cfg(unix)]
+            HttpTransport::Unix(client) => Ok(client.request(request).await?),
+            HttpTransport::Proxied(client) => Ok(client.request(request).await?),
+            HttpTransport::Custom(transport) => {
+                let body = hyper::body::to_bytes(request.into_body()).await?;
+                let body = String::from_utf8(body.to_vec()).map_err(Error::parse_error)?;
+                let response = transport.send(body).await?;
+                Ok(hyper::Response::new(Body::from(response)))
+            }
+        }
+    }
+}
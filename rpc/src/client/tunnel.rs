@@ -0,0 +1,319 @@
+//! Connections of WebSocket clients, and of HTTP clients configured with a
+//! proxy, optionally through an HTTP proxy and over TLS
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use hyper::client::connect::{Connected, Connection};
+use hyper::header::{self, HeaderMap};
+use hyper::service::Service;
+use hyper::Uri;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+
+use super::tls;
+use super::transport::TransportOptions;
+use super::Url;
+use crate::Error;
+
+/// Maximum size of the response head of a proxy
+const MAX_PROXY_RESPONSE_SIZE: usize = 8 * 1024;
+
+/// Byte stream of a connection, plain or encrypted
+pub(crate) trait Io: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Io for T {}
+
+/// Connect to the host of the given URL, tunneling through the HTTP proxy of
+/// the given options if any, and encrypting the connection if the URL is
+/// secure
+pub(crate) async fn connect(url: &Url, options: &TransportOptions) -> Result<Box<dyn Io>, Error> {
+    let target = format!("{}:{}", url.host(), url.port());
+    let connect = async {
+        let mut stream = match &options.proxy {
+            Some(proxy) => {
+                let mut stream = tcp_connect(&format!("{}:{}", proxy.host(), proxy.port())).await?;
+                tunnel(&mut stream, &target, proxy, &options.proxy_headers).await?;
+                stream
+            }
+            None => tcp_connect(&target).await?,
+        };
+
+        if !url.is_secure() {
+            return Ok(Box::new(stream) as Box<dyn Io>);
+        }
+
+        let name = webpki::DNSNameRef::try_from_ascii_str(url.host()).map_err(|_| {
+            Error::invalid_params(&format!("invalid TLS server name: {}", url.host()))
+        })?;
+        let stream = TlsConnector::from(tls::client_config(options)?)
+            .connect(name, stream)
+            .await
+            .map_err(|e| Error::websocket_error(format!("TLS handshake failed: {}", e)))?;
+        Ok(Box::new(stream) as Box<dyn Io>)
+    };
+
+    match options.connect_timeout {
+        Some(timeout) => tokio::time::timeout(timeout, connect).await?,
+        None => connect.await,
+    }
+}
+
+async fn tcp_connect(address: &str) -> Result<TcpStream, Error> {
+    TcpStream::connect(address)
+        .await
+        .map_err(|e| Error::websocket_error(format!("cannot connect to {}: {}", address, e)))
+}
+
+/// Ask the HTTP proxy the given stream is connected to for a tunnel to the
+/// given target, sending the given headers along, and authenticating with the
+/// credentials of its URL if any, unless a `Proxy-Authorization` header is
+/// given
+async fn tunnel(
+    stream: &mut TcpStream,
+    target: &str,
+    proxy: &Url,
+    headers: &HeaderMap,
+) -> Result<(), Error> {
+    let mut headers = headers.clone();
+    if !headers.contains_key(header::PROXY_AUTHORIZATION) {
+        if let Some(authorization) = proxy.authorization()? {
+            headers.insert(header::PROXY_AUTHORIZATION, authorization);
+        }
+    }
+
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target).into_bytes();
+    for (name, value) in &headers {
+        request.extend_from_slice(name.as_str().as_bytes());
+        request.extend_from_slice(b": ");
+        request.extend_from_slice(value.as_bytes());
+        request.extend_from_slice(b"\r\n");
+    }
+    request.extend_from_slice(b"\r\n");
+    stream.write_all(&request).await.map_err(proxy_error)?;
+
+    // Read byte by byte to leave whatever follows the response head to the
+    // tunneled connection
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_PROXY_RESPONSE_SIZE {
+            return Err(Error::websocket_error("proxy response too large"));
+        }
+        match stream.read_u8().await {
+            Ok(byte) => response.push(byte),
+            Err(e) => return Err(proxy_error(e)),
+        }
+    }
+
+    let status_line = String::from_utf8_lossy(&response);
+    let status_line = status_line.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        _ => Err(Error::websocket_error(format!(
+            "proxy refused to connect: {}",
+            status_line
+        ))),
+    }
+}
+
+fn proxy_error(error: std::io::Error) -> Error {
+    Error::websocket_error(format!("proxy connection failed: {}", error))
+}
+
+/// Connector of HTTP clients tunneling their connections through the proxy of
+/// their transport options
+#[derive(Clone, Debug)]
+pub(crate) struct ProxyConnector {
+    options: TransportOptions,
+}
+
+impl ProxyConnector {
+    pub(crate) fn new(options: TransportOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Service<Uri> for ProxyConnector {
+    type Response = ProxyStream;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<ProxyStream, Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let options = self.options.clone();
+        Box::pin(async move {
+            let url = uri.to_string().parse::<Url>()?;
+            Ok(ProxyStream(connect(&url, &options).await?))
+        })
+    }
+}
+
+/// Connection tunneled through a proxy
+pub(crate) struct ProxyStream(Box<dyn Io>);
+
+impl Connection for ProxyStream {
+    fn connected(&self) -> Connected {
+        Connected::new()
+    }
+}
+
+impl AsyncRead for ProxyStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for ProxyStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Run a proxy on a local port which answers a single `CONNECT` request
+    /// with the given status line and echoes everything after it. Returns
+    /// its URL and the request it received.
+    async fn proxy(status_line: &'static str) -> (Url, tokio::task::JoinHandle<String>) {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://user:pass@{}", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                request.push(stream.read_u8().await.unwrap());
+            }
+            stream
+                .write_all(format!("{}\r\n\r\n", status_line).as_bytes())
+                .await
+                .unwrap();
+
+            let mut echo = [0; 4];
+            if stream.read_exact(&mut echo).await.is_ok() {
+                stream.write_all(&echo).await.unwrap();
+            }
+            String::from_utf8(request).unwrap()
+        });
+
+        (url, handle)
+    }
+
+    /// Read the head of an HTTP request or response
+    async fn read_head(stream: &mut TcpStream) -> String {
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        String::from_utf8(head).unwrap()
+    }
+
+    fn through(proxy: Url) -> TransportOptions {
+        TransportOptions {
+            proxy: Some(proxy),
+            ..TransportOptions::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn tunnels_through_proxy() {
+        let (proxy, request) = proxy("HTTP/1.1 200 Connection established").await;
+        let url = "tcp://node.example.com:26657".parse().unwrap();
+
+        let mut stream = connect(&url, &through(proxy)).await.unwrap();
+        stream.write_all(b"ping").await.unwrap();
+        let mut echo = [0; 4];
+        stream.read_exact(&mut echo).await.unwrap();
+        assert_eq!(&echo, b"ping");
+
+        let request = request.await.unwrap();
+        assert!(request.starts_with("CONNECT node.example.com:26657 HTTP/1.1\r\n"));
+        assert!(request.contains("proxy-authorization: Basic dXNlcjpwYXNz\r\n"));
+    }
+
+    #[tokio::test]
+    async fn fails_when_proxy_refuses() {
+        let (proxy, _) = proxy("HTTP/1.1 407 Proxy Authentication Required").await;
+        let url = "tcp://node.example.com:26657".parse().unwrap();
+
+        let error = connect(&url, &through(proxy)).await.err().unwrap();
+        assert!(error.data().unwrap().contains("407"));
+    }
+
+    #[tokio::test]
+    async fn http_client_tunnels_through_proxy() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = format!("http://user:pass@{}", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+
+        // Proxy tunneling to a node answering a single request with its status
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let connect = read_head(&mut stream).await;
+            stream
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                .await
+                .unwrap();
+
+            let request = read_head(&mut stream).await.to_lowercase();
+            let length = request
+                .lines()
+                .find(|line| line.starts_with("content-length:"))
+                .and_then(|line| line["content-length:".len()..].trim().parse().ok())
+                .unwrap();
+            let mut body = vec![0; length];
+            stream.read_exact(&mut body).await.unwrap();
+
+            let status = include_str!("../../tests/support/status.json");
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                status.len(),
+                status
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            connect
+        });
+
+        let client = crate::Client::builder("http://node.example.com:26657".parse().unwrap())
+            .proxy(proxy)
+            .unwrap()
+            .proxy_header("x-proxy-token", "s3cret")
+            .unwrap()
+            .build()
+            .unwrap();
+        client.status().await.unwrap();
+
+        let connect = handle.await.unwrap();
+        assert!(connect.starts_with("CONNECT node.example.com:26657 HTTP/1.1\r\n"));
+        assert!(connect.contains("proxy-authorization: Basic dXNlcjpwYXNz\r\n"));
+        assert!(connect.contains("x-proxy-token: s3cret\r\n"));
+    }
+}
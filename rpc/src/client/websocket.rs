@@ -0,0 +1,703 @@
+//! WebSocket client sharing a single connection between many handles
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use async_tungstenite::tungstenite::Message;
+use futures::channel::{mpsc, oneshot};
+use futures::future::{self, Either};
+use futures::stream::{self, Stream, StreamExt};
+use serde_json::Value;
+
+use super::event_listener::{EventListener, ResultEvent, WebSocketConfig, WrappedResultEvent};
+use super::metrics::{MetricsRecorder, Recorder, RequestMetrics};
+use super::subscription::{OverflowPolicy, Publisher, Subscription};
+use super::transport::Transport;
+use super::Url;
+use crate::endpoint::{subscribe, unsubscribe};
+use crate::query::Query;
+use crate::response::Wrapper;
+use crate::{Error, Id, Method, Request, Response};
+
+/// Client multiplexing requests and subscriptions over a single WebSocket
+/// connection, e.g. for applications with many components consuming events.
+///
+/// Handles are cheap to clone and all share the connection, which is driven
+/// by a background task routing responses to requests by their ID and
+/// events to subscriptions by their query. Subscribing to a query which is
+/// subscribed to already adds a subscriber instead of subscribing again, and
+/// the node is unsubscribed from a query once all of its subscriptions have
+/// been dropped and the next event for it arrives.
+///
+/// Once the connection fails, all requests in flight and all subscriptions
+/// fail with the connection's error, and so do all later requests. The
+/// connection is closed once all handles and subscriptions are dropped.
+///
+/// A `WebSocketClient` is a [`Transport`] too, so that typed requests can be
+/// sent over the shared connection by a [`Client`](super::Client):
+///
+/// ```ignore
+/// let websocket = WebSocketClient::connect(url.clone()).await?;
+/// let blocks = websocket.subscribe(&EventType::NewBlock.into(), 100, OverflowPolicy::DropOldest).await?;
+/// let client = Client::builder(url).transport(websocket.clone()).build()?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct WebSocketClient {
+    commands: mpsc::UnboundedSender<Command>,
+    /// Recorder of request metrics, if any
+    metrics: Option<Recorder>,
+}
+
+/// Command sent by a handle to the task driving the connection
+#[derive(Debug)]
+enum Command {
+    /// Send the given request body, answering with the response body
+    Send {
+        request_body: String,
+        response: oneshot::Sender<Result<Vec<u8>, Error>>,
+    },
+
+    /// Publish the events selected by the given query with the publisher,
+    /// answering once the node confirmed the subscription
+    Subscribe {
+        query: String,
+        publisher: Publisher,
+        confirmed: oneshot::Sender<Result<(), Error>>,
+    },
+
+    /// End the subscriptions to the given query and unsubscribe from it with
+    /// the given request body with the given ID, answering with the response
+    /// body
+    Unsubscribe {
+        query: String,
+        id: Id,
+        request_body: String,
+        response: oneshot::Sender<Result<Vec<u8>, Error>>,
+    },
+}
+
+impl WebSocketClient {
+    /// Connect to the WebSocket endpoint of the node at the given URL, see
+    /// [`EventListener::connect_url`]
+    pub async fn connect(url: Url) -> Result<Self, Error> {
+        Ok(Self::new(EventListener::connect_url(url).await?))
+    }
+
+    /// Connect to the WebSocket endpoint of the node at the given URL, with
+    /// the given handshake options, e.g. a non-default path.
+    pub async fn connect_with_config(url: Url, config: &WebSocketConfig) -> Result<Self, Error> {
+        Ok(Self::new(
+            EventListener::connect_with_config(url, config).await?,
+        ))
+    }
+
+    /// Share the connection of the given listener, e.g. after configuring
+    /// its keepalive. The listener must not be subscribed to any events yet.
+    pub fn new(listener: EventListener) -> Self {
+        Self::spawn(listener, None)
+    }
+
+    /// Share the connection of the given listener like [`new`](Self::new),
+    /// reporting the metrics of requests and the number of open
+    /// subscriptions to the given recorder.
+    pub fn new_with_metrics(
+        listener: EventListener,
+        recorder: impl MetricsRecorder + 'static,
+    ) -> Self {
+        Self::spawn(listener, Some(Recorder(Arc::new(recorder))))
+    }
+
+    fn spawn(listener: EventListener, metrics: Option<Recorder>) -> Self {
+        let (commands, receiver) = mpsc::unbounded();
+        tokio::spawn(
+            Driver {
+                listener,
+                commands: Some(receiver),
+                pending: BTreeMap::new(),
+                routes: HashMap::new(),
+                metrics: metrics.clone(),
+                subscriptions: 0,
+            }
+            .run(),
+        );
+
+        Self { commands, metrics }
+    }
+
+    /// Subscribe to the events selected by the given query, buffering at most
+    /// the given number of them as described for
+    /// [`EventListener::into_subscription`]
+    pub async fn subscribe(
+        &self,
+        query: &Query,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Result<Subscription, Error> {
+        let (subscription, publisher) = Subscription::with_publisher(capacity, policy);
+        let (confirmed, confirmation) = oneshot::channel();
+
+        self.command(Command::Subscribe {
+            query: query.to_string(),
+            publisher,
+            confirmed,
+        })?;
+        confirmation.await.map_err(|_| connection_closed())??;
+
+        Ok(subscription)
+    }
+
+    /// Subscribe to the events selected by any of the given queries, merged
+    /// into a single stream, see [`subscribe`](Self::subscribe).
+    ///
+    /// Events selected by several of the queries are emitted only once,
+    /// unless more than 1024 other events arrive in between.
+    pub async fn subscribe_all(
+        &self,
+        queries: &[Query],
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Result<impl Stream<Item = Result<ResultEvent, Error>> + Unpin, Error> {
+        let mut subscriptions = Vec::with_capacity(queries.len());
+        for query in queries {
+            subscriptions.push(self.subscribe(query, capacity, policy).await?);
+        }
+
+        Ok(deduplicate(subscriptions))
+    }
+
+    /// End all subscriptions to the given query, including those of other
+    /// handles, once their buffered events have been consumed, and
+    /// unsubscribe the node from it, e.g. to subscribe afresh after the
+    /// node stopped sending events
+    pub async fn unsubscribe(&self, query: &Query) -> Result<(), Error> {
+        let (response, response_body) = oneshot::channel();
+        let id = Id::uuid_v4();
+
+        self.command(Command::Unsubscribe {
+            query: query.to_string(),
+            request_body: unsubscribe::Request::new(query.to_string())
+                .into_json_with_id(id.clone()),
+            id,
+            response,
+        })?;
+        let response_body = response_body.await.map_err(|_| connection_closed())??;
+        unsubscribe::Response::from_string(response_body)?;
+
+        Ok(())
+    }
+
+    /// Perform a request over the shared connection
+    pub async fn perform<R>(&self, request: R) -> Result<R::Response, Error>
+    where
+        R: Request,
+    {
+        let response_body = self.send(request.into_json()).await?;
+        R::Response::from_string(response_body)
+    }
+
+    fn command(&self, command: Command) -> Result<(), Error> {
+        self.commands
+            .unbounded_send(command)
+            .map_err(|_| connection_closed())
+    }
+}
+
+#[async_trait]
+impl Transport for WebSocketClient {
+    async fn send(&self, request_body: String) -> Result<Vec<u8>, Error> {
+        let started = Instant::now();
+        let method = request_method(&request_body);
+        let request_size = request_body.len();
+        let (response, response_body) = oneshot::channel();
+
+        self.command(Command::Send {
+            request_body,
+            response,
+        })?;
+        let result = response_body.await.map_err(|_| connection_closed())?;
+
+        if let (Some(Recorder(recorder)), Some(method)) = (&self.metrics, method) {
+            recorder.record(&RequestMetrics {
+                method,
+                batch_size: None,
+                duration: started.elapsed(),
+                attempts: 1,
+                request_size,
+                response_size: result.as_ref().map_or(0, Vec::len),
+                error: result.as_ref().err(),
+            });
+        }
+
+        result
+    }
+}
+
+/// Number of most recent events remembered to deduplicate the events of
+/// [`WebSocketClient::subscribe_all`]
+const DEDUPLICATION_WINDOW: usize = 1024;
+
+/// Merge the given subscriptions, skipping events equal to one of the last
+/// [`DEDUPLICATION_WINDOW`] events
+fn deduplicate(
+    subscriptions: Vec<Subscription>,
+) -> impl Stream<Item = Result<ResultEvent, Error>> + Unpin {
+    let mut seen = HashSet::new();
+    let mut recent = VecDeque::new();
+
+    stream::select_all(subscriptions).filter(move |event| {
+        let event = match event {
+            Ok(event) => event,
+            Err(_) => return future::ready(true),
+        };
+
+        // Events matching several queries differ only in the query
+        let mut hasher = DefaultHasher::new();
+        serde_json::to_string(&event.data)
+            .expect("JSON values serialize")
+            .hash(&mut hasher);
+        let identity = hasher.finish();
+
+        if !seen.insert(identity) {
+            return future::ready(false);
+        }
+        recent.push_back(identity);
+        if recent.len() > DEDUPLICATION_WINDOW {
+            seen.remove(&recent.pop_front().unwrap());
+        }
+
+        future::ready(true)
+    })
+}
+
+/// Error of requests to a connection which has already failed
+fn connection_closed() -> Error {
+    Error::websocket_error("web socket connection closed")
+}
+
+/// State of the task driving a shared connection
+struct Driver {
+    listener: EventListener,
+    /// Commands of the handles, until all of them have been dropped
+    commands: Option<mpsc::UnboundedReceiver<Command>>,
+    /// Requests awaiting their response, by ID
+    pending: BTreeMap<Id, Pending>,
+    /// Subscriptions by query
+    routes: HashMap<String, Route>,
+    /// Recorder of the number of open subscriptions, if any
+    metrics: Option<Recorder>,
+    /// Number of open subscriptions last recorded
+    subscriptions: usize,
+}
+
+/// Request awaiting its response
+enum Pending {
+    /// Request sent by a handle
+    Request(oneshot::Sender<Result<Vec<u8>, Error>>),
+
+    /// Subscription to the given query
+    Subscribe(String),
+}
+
+/// Subscribers to a query
+struct Route {
+    publishers: Vec<Publisher>,
+    /// Has the node confirmed the subscription?
+    confirmed: bool,
+    /// Subscribers waiting for the confirmation
+    waiting: Vec<oneshot::Sender<Result<(), Error>>>,
+}
+
+impl Driver {
+    async fn run(mut self) {
+        if let Err(e) = self.drive().await {
+            self.fail(e);
+        }
+        self.record_subscriptions();
+    }
+
+    /// Handle commands and messages until the connection fails or is not
+    /// needed anymore
+    async fn drive(&mut self) -> Result<(), Error> {
+        loop {
+            let next = match self.commands.as_mut() {
+                Some(commands) => {
+                    match future::select(Box::pin(self.listener.next_message()), commands.next())
+                        .await
+                    {
+                        Either::Left((message, _)) => Either::Left(message),
+                        Either::Right((command, _)) => Either::Right(command),
+                    }
+                }
+                None => Either::Left(self.listener.next_message().await),
+            };
+
+            match next {
+                Either::Left(message) => self.handle_message(message?).await?,
+                Either::Right(Some(command)) => self.handle_command(command).await?,
+                Either::Right(None) => self.commands = None,
+            }
+            self.record_subscriptions();
+
+            if self.commands.is_none() && self.pending.is_empty() && self.routes.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn handle_command(&mut self, command: Command) -> Result<(), Error> {
+        match command {
+            Command::Send {
+                request_body,
+                response,
+            } => {
+                let id = match request_id(&request_body) {
+                    Ok(id) if !self.pending.contains_key(&id) => id,
+                    Ok(id) => {
+                        let _ = response.send(Err(Error::invalid_params(&format!(
+                            "request ID already in flight: {:?}",
+                            id
+                        ))));
+                        return Ok(());
+                    }
+                    Err(e) => {
+                        let _ = response.send(Err(e));
+                        return Ok(());
+                    }
+                };
+
+                self.pending.insert(id, Pending::Request(response));
+                self.listener.send_text(request_body).await
+            }
+            Command::Subscribe {
+                query,
+                publisher,
+                confirmed,
+            } => {
+                if let Some(route) = self.routes.get_mut(&query) {
+                    route.publishers.push(publisher);
+                    if route.confirmed {
+                        let _ = confirmed.send(Ok(()));
+                    } else {
+                        route.waiting.push(confirmed);
+                    }
+                    return Ok(());
+                }
+
+                let id = Id::uuid_v4();
+                let request_body =
+                    subscribe::Request::new(query.clone()).into_json_with_id(id.clone());
+                self.routes.insert(
+                    query.clone(),
+                    Route {
+                        publishers: vec![publisher],
+                        confirmed: false,
+                        waiting: vec![confirmed],
+                    },
+                );
+                self.pending.insert(id, Pending::Subscribe(query));
+                self.listener.send_text(request_body).await
+            }
+            Command::Unsubscribe {
+                query,
+                id,
+                request_body,
+                response,
+            } => {
+                if let Some(route) = self.routes.remove(&query) {
+                    for confirmed in route.waiting {
+                        let _ = confirmed.send(Err(Error::websocket_error("unsubscribed")));
+                    }
+                    for publisher in route.publishers {
+                        publisher.close();
+                    }
+                }
+
+                self.pending.insert(id, Pending::Request(response));
+                self.listener.send_text(request_body).await
+            }
+        }
+    }
+
+    async fn handle_message(&mut self, message: Message) -> Result<(), Error> {
+        let text = message.into_text()?;
+
+        let pending = serde_json::from_str::<Value>(&text)
+            .ok()
+            .and_then(|mut response| {
+                serde_json::from_value::<Id>(response.get_mut("id")?.take()).ok()
+            })
+            .and_then(|id| self.pending.remove(&id));
+
+        match pending {
+            Some(Pending::Request(response)) => {
+                let _ = response.send(Ok(text.into_bytes()));
+            }
+            Some(Pending::Subscribe(query)) => {
+                let result = serde_json::from_str::<Wrapper<subscribe::Response>>(&text)
+                    .map_err(Error::parse_error)
+                    .and_then(Wrapper::into_result);
+                self.confirm(query, result.map(drop));
+            }
+            // Events are identified by the ID of the subscription request
+            // followed by `#event`
+            None => {
+                if let Ok(Ok(event)) =
+                    serde_json::from_str::<WrappedResultEvent>(&text).map(Wrapper::into_result)
+                {
+                    self.route(event).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Answer the subscribers waiting for the subscription to the given
+    /// query with the given result
+    fn confirm(&mut self, query: String, result: Result<(), Error>) {
+        match result {
+            Ok(()) => {
+                if let Some(route) = self.routes.get_mut(&query) {
+                    route.confirmed = true;
+                    for confirmed in route.waiting.drain(..) {
+                        let _ = confirmed.send(Ok(()));
+                    }
+                }
+            }
+            Err(e) => {
+                if let Some(route) = self.routes.remove(&query) {
+                    for confirmed in route.waiting {
+                        let _ = confirmed.send(Err(e.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Publish the given event to the subscribers of its query, unsubscribing
+    /// from the query if none is left
+    async fn route(&mut self, event: ResultEvent) -> Result<(), Error> {
+        let route = match self.routes.get_mut(&event.query) {
+            Some(route) => route,
+            None => return Ok(()),
+        };
+
+        route
+            .publishers
+            .retain(|publisher| publisher.publish(Ok(event.clone())));
+
+        if route.publishers.is_empty() && route.waiting.is_empty() {
+            self.routes.remove(&event.query);
+            // The response is of no interest
+            let request_body = unsubscribe::Request::new(event.query).into_json();
+            self.listener.send_text(request_body).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Report the number of open subscriptions if it changed
+    fn record_subscriptions(&mut self) {
+        let subscriptions = self
+            .routes
+            .values()
+            .map(|route| route.publishers.len())
+            .sum();
+
+        if subscriptions != self.subscriptions {
+            self.subscriptions = subscriptions;
+            if let Some(Recorder(recorder)) = &self.metrics {
+                recorder.record_subscriptions(subscriptions);
+            }
+        }
+    }
+
+    /// Fail all requests in flight and all subscriptions with the given error
+    fn fail(&mut self, error: Error) {
+        for (_, pending) in std::mem::take(&mut self.pending) {
+            if let Pending::Request(response) = pending {
+                let _ = response.send(Err(error.clone()));
+            }
+        }
+
+        for (_, route) in self.routes.drain() {
+            for confirmed in route.waiting {
+                let _ = confirmed.send(Err(error.clone()));
+            }
+            for publisher in route.publishers {
+                publisher.publish(Err(error.clone()));
+            }
+        }
+    }
+}
+
+/// Method of the given request body, if it is a single request
+fn request_method(request_body: &str) -> Option<Method> {
+    let request = serde_json::from_str::<Value>(request_body).ok()?;
+    request.get("method")?.as_str()?.parse().ok()
+}
+
+/// ID of the given request body, which must be a single request
+fn request_id(request_body: &str) -> Result<Id, Error> {
+    let request = serde_json::from_str::<Value>(request_body).map_err(Error::parse_error)?;
+
+    match request {
+        Value::Object(mut request) => request
+            .remove("id")
+            .and_then(|id| serde_json::from_value::<Id>(id).ok())
+            .filter(|id| *id != Id::None)
+            .ok_or_else(|| Error::invalid_params("requests over web sockets need an ID")),
+        _ => Err(Error::invalid_params(
+            "batches are not supported over web sockets",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::event_listener::TMEventData;
+    use crate::endpoint::status;
+    use crate::query::EventType;
+    use futures::{FutureExt, SinkExt};
+    use serde_json::json;
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+
+    /// Run a node answering subscriptions and status requests on a local
+    /// port, which publishes an event for each subscribed query after each
+    /// status request. Returns its URL and the methods of the requests it
+    /// received.
+    async fn node() -> (Url, Arc<Mutex<Vec<String>>>) {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("tcp://{}", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        let methods = Arc::new(Mutex::new(vec![]));
+        let received = methods.clone();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = async_tungstenite::tokio::accept_async(stream)
+                .await
+                .unwrap();
+            let status: Value =
+                serde_json::from_str(&fs::read_to_string("tests/support/status.json").unwrap())
+                    .unwrap();
+            let mut queries = vec![];
+
+            while let Some(Ok(Message::Text(text))) = socket.next().await {
+                let request: Value = serde_json::from_str(&text).unwrap();
+                let id = request["id"].clone();
+                let method = request["method"].as_str().unwrap().to_owned();
+                received.lock().unwrap().push(method.clone());
+
+                let mut responses = vec![];
+                match method.as_str() {
+                    "subscribe" => {
+                        queries.push(request["params"]["query"].clone());
+                        responses.push(json!({ "jsonrpc": "2.0", "id": id, "result": {} }));
+                    }
+                    "status" => {
+                        responses.push(
+                            json!({ "jsonrpc": "2.0", "id": id, "result": status["result"] }),
+                        );
+                        for query in &queries {
+                            responses.push(json!({
+                                "jsonrpc": "2.0",
+                                "id": "0#event",
+                                "result": {
+                                    "query": query,
+                                    "data": { "type": "GenericJSONEvent", "value": {} },
+                                    "events": null
+                                }
+                            }));
+                        }
+                    }
+                    _ => {}
+                }
+
+                for response in responses {
+                    socket
+                        .send(Message::text(response.to_string()))
+                        .await
+                        .unwrap();
+                }
+            }
+        });
+
+        (url, methods)
+    }
+
+    #[tokio::test]
+    async fn handles_share_a_connection() {
+        let (url, methods) = node().await;
+        let client = WebSocketClient::connect(url).await.unwrap();
+        let other = client.clone();
+
+        let query = Query::from(EventType::NewBlock);
+        let mut subscription = client
+            .subscribe(&query, 10, OverflowPolicy::Terminate)
+            .await
+            .unwrap();
+        let mut other_subscription = other
+            .subscribe(&query, 10, OverflowPolicy::Terminate)
+            .await
+            .unwrap();
+
+        let status = other.perform(status::Request).await.unwrap();
+        assert!(!status.node_info.network.to_string().is_empty());
+
+        let event = subscription.next().await.unwrap().unwrap();
+        assert_eq!(event.query, query.to_string());
+        let event = other_subscription.next().await.unwrap().unwrap();
+        assert_eq!(event.query, query.to_string());
+
+        // Subscribing to the same query again did not subscribe the node again
+        assert_eq!(*methods.lock().unwrap(), vec!["subscribe", "status"]);
+    }
+
+    #[tokio::test]
+    async fn merged_subscriptions_skip_duplicates() {
+        let event = |query: &str, height: u64| ResultEvent {
+            query: query.to_owned(),
+            data: TMEventData::GenericJSONEvent(json!({ "height": height })),
+            events: None,
+        };
+
+        let (first, first_publisher) = Subscription::with_publisher(10, OverflowPolicy::Terminate);
+        let (second, second_publisher) =
+            Subscription::with_publisher(10, OverflowPolicy::Terminate);
+        first_publisher.publish(Ok(event("a", 1)));
+        second_publisher.publish(Ok(event("b", 1)));
+        second_publisher.publish(Ok(event("b", 2)));
+
+        let mut events = deduplicate(vec![first, second]);
+        let mut heights = vec![];
+        for _ in 0..2 {
+            match events.next().await.unwrap().unwrap().data {
+                TMEventData::GenericJSONEvent(data) => heights.push(data["height"].as_u64()),
+                data => panic!("unexpected event data: {:?}", data),
+            }
+        }
+        heights.sort();
+
+        assert_eq!(heights, vec![Some(1), Some(2)]);
+        assert!(events.next().now_or_never().is_none());
+    }
+
+    #[test]
+    fn requests_need_an_id() {
+        assert_eq!(
+            request_id(r#"{"jsonrpc":"2.0","id":7,"method":"status"}"#).unwrap(),
+            Id::Num(7)
+        );
+        assert!(request_id(r#"{"jsonrpc":"2.0","method":"status"}"#).is_err());
+        assert!(request_id(r#"[{"jsonrpc":"2.0","id":7,"method":"status"}]"#).is_err());
+    }
+}
@@ -3,14 +3,25 @@
 pub mod abci_info;
 pub mod abci_query;
 pub mod block;
+pub mod block_by_hash;
 pub mod block_results;
+pub mod block_search;
 pub mod blockchain;
 pub mod broadcast;
+pub mod check_tx;
 pub mod commit;
+pub mod consensus_state;
+pub mod dump_consensus_state;
 pub mod evidence;
 pub mod genesis;
+pub mod genesis_chunked;
 pub mod health;
 pub mod net_info;
+pub mod num_unconfirmed_txs;
 pub mod status;
 pub mod subscribe;
+pub mod tx;
+pub mod tx_search;
+pub mod unconfirmed_txs;
+pub mod unsubscribe;
 pub mod validators;
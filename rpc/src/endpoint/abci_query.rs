@@ -1,9 +1,10 @@
 //! `/abci_query` endpoint JSONRPC wrapper
 
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use tendermint::abci::{Code, Log, Path};
 use tendermint::block;
+use tendermint::error::Kind;
 use tendermint::merkle::proof::Proof;
 use tendermint::serializers;
 
@@ -94,3 +95,37 @@ pub struct AbciQuery {
     #[serde(default = "String::new")]
     pub codespace: String,
 }
+
+impl AbciQuery {
+    /// Verify the proof of this query result against the given trusted app
+    /// hash, i.e. check that `value` is stored under `key` in the state of
+    /// the application.
+    ///
+    /// The app hash must be taken from the header at `height + 1`, which is
+    /// the first one committing to the state the query ran against. For
+    /// Cosmos SDK applications, `store` is the name of the queried store
+    /// (e.g. `bank` or `ibc`), for applications with a single Merkle tree it
+    /// is `None`.
+    ///
+    /// Fails if there is no proof, e.g. because the query was not made with
+    /// `prove` set, or if it does not verify.
+    pub fn verify(&self, app_hash: &[u8], store: Option<&str>) -> Result<(), tendermint::Error> {
+        let proof = match &self.proof {
+            Some(proof) => proof,
+            None => return Err(Kind::Crypto.context("query result carries no proof").into()),
+        };
+
+        let keys: Vec<&[u8]> = match store {
+            Some(store) => vec![store.as_bytes(), self.key.as_slice()],
+            None => vec![self.key.as_slice()],
+        };
+
+        proof.verify_value(app_hash, &keys, &self.value)
+    }
+
+    /// Deserialize the value of this query result from JSON, as returned by
+    /// most custom queries
+    pub fn json_value<T: DeserializeOwned>(&self) -> Result<T, crate::Error> {
+        serde_json::from_slice(&self.value).map_err(crate::Error::parse_error)
+    }
+}
@@ -3,6 +3,7 @@
 use serde::{Deserialize, Serialize};
 
 use tendermint::block::{self, Block};
+use tendermint::chain;
 
 /// Get information about a specific block
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
@@ -40,4 +41,8 @@ pub struct Response {
     pub block: Block,
 }
 
-impl crate::Response for Response {}
+impl crate::Response for Response {
+    fn chain_ids(&self) -> Vec<&chain::Id> {
+        vec![&self.block.header.chain_id]
+    }
+}
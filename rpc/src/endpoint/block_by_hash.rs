@@ -0,0 +1,52 @@
+//! `/block_by_hash` endpoint JSONRPC wrapper
+
+use serde::{Deserialize, Serialize};
+
+use tendermint::block::{self, Block};
+use tendermint::{chain, serializers, Hash};
+
+/// Get information about the block with a specific hash
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Request {
+    /// Hash of the block to request (base64-encoded in JSON)
+    #[serde(with = "serializers::bytes::base64string")]
+    hash: Vec<u8>,
+}
+
+impl Request {
+    /// Create a new request for information about the block with the given
+    /// hash
+    pub fn new(hash: Hash) -> Self {
+        Self {
+            hash: hash.as_bytes().to_vec(),
+        }
+    }
+}
+
+impl crate::Request for Request {
+    type Response = Response;
+
+    fn method(&self) -> crate::Method {
+        crate::Method::BlockByHash
+    }
+}
+
+/// Block responses
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Response {
+    /// Block ID (`None` if no block with the hash is known)
+    #[serde(deserialize_with = "serializers::parse_non_empty_block_id")]
+    pub block_id: Option<block::Id>,
+
+    /// Block data (`None` if no block with the hash is known)
+    pub block: Option<Block>,
+}
+
+impl crate::Response for Response {
+    fn chain_ids(&self) -> Vec<&chain::Id> {
+        self.block
+            .iter()
+            .map(|block| &block.header.chain_id)
+            .collect()
+    }
+}
@@ -43,6 +43,10 @@ pub struct Response {
     pub begin_block_events: Option<Vec<abci::Event>>,
 
     /// End block events (might be explicit null)
+    ///
+    /// With the `compat` feature, the events of finalizing the block reported
+    /// by nodes without separate begin and end block events are read here.
+    #[cfg_attr(feature = "compat", serde(alias = "finalize_block_events"))]
     pub end_block_events: Option<Vec<abci::Event>>,
 
     /// Validator updates (might be explicit null)
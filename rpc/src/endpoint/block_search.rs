@@ -0,0 +1,66 @@
+//! `/block_search` endpoint JSONRPC wrapper
+
+use serde::{Deserialize, Serialize};
+
+use tendermint::{chain, serializers};
+
+use crate::{endpoint::block, query::Query, Order};
+
+/// Search for blocks by their `BeginBlock` and `EndBlock` events
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Request {
+    /// Query selecting the blocks, e.g. `block.height > 5`
+    pub query: String,
+
+    /// Page of results to return (starting at 1)
+    #[serde(with = "serializers::from_str")]
+    pub page: u32,
+
+    /// Number of results per page (at most 100)
+    #[serde(with = "serializers::from_str")]
+    pub per_page: u8,
+
+    /// Order of the results by height
+    pub order_by: Order,
+}
+
+impl Request {
+    /// Create a new request for the given page of blocks matching the given
+    /// query
+    pub fn new(query: &Query, page: u32, per_page: u8, order_by: Order) -> Self {
+        Self {
+            query: query.to_string(),
+            page,
+            per_page,
+            order_by,
+        }
+    }
+}
+
+impl crate::Request for Request {
+    type Response = Response;
+
+    fn method(&self) -> crate::Method {
+        crate::Method::BlockSearch
+    }
+}
+
+/// Block search results
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Response {
+    /// Blocks on the requested page
+    pub blocks: Vec<block::Response>,
+
+    /// Total number of blocks matching the query, on all pages
+    #[serde(with = "serializers::from_str")]
+    pub total_count: u32,
+}
+
+impl crate::Response for Response {
+    fn chain_ids(&self) -> Vec<&chain::Id> {
+        self.blocks
+            .iter()
+            .map(|block| &block.block.header.chain_id)
+            .collect()
+    }
+}
@@ -3,7 +3,7 @@
 use serde::{Deserialize, Serialize};
 use std::ops::Range;
 
-use tendermint::block;
+use tendermint::{block, chain};
 
 /// Get information about a specific block
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -51,4 +51,11 @@ pub struct Response {
     pub block_metas: Vec<block::Meta>,
 }
 
-impl crate::Response for Response {}
+impl crate::Response for Response {
+    fn chain_ids(&self) -> Vec<&chain::Id> {
+        self.block_metas
+            .iter()
+            .map(|meta| &meta.header.chain_id)
+            .collect()
+    }
+}
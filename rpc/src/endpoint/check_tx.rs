@@ -0,0 +1,34 @@
+//! `/check_tx` endpoint JSONRPC wrapper
+
+use serde::{Deserialize, Serialize};
+
+use tendermint::abci::{CheckTx, Transaction};
+
+/// Check a transaction against the application's mempool rules without
+/// adding it to the mempool
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Request {
+    /// Transaction to check
+    pub tx: Transaction,
+}
+
+impl Request {
+    /// Create a new request checking the given transaction
+    pub fn new(tx: Transaction) -> Self {
+        Self { tx }
+    }
+}
+
+impl crate::Request for Request {
+    type Response = Response;
+
+    fn method(&self) -> crate::Method {
+        crate::Method::CheckTx
+    }
+}
+
+/// Result of `CheckTx`: a non-OK code means the transaction would be
+/// rejected by the mempool
+pub type Response = CheckTx;
+
+impl crate::Response for Response {}
@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use tendermint::{block, block::signed_header::SignedHeader};
+use tendermint::{block, block::signed_header::SignedHeader, chain};
 
 /// Get commit information about a specific block
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
@@ -37,4 +37,8 @@ pub struct Response {
     pub canonical: bool,
 }
 
-impl crate::Response for Response {}
+impl crate::Response for Response {
+    fn chain_ids(&self) -> Vec<&chain::Id> {
+        vec![&self.signed_header.header.chain_id]
+    }
+}
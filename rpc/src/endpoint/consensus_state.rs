@@ -0,0 +1,199 @@
+//! `/consensus_state` endpoint JSONRPC wrapper
+
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use tendermint::{account, block, serializers, Hash, Time};
+
+use crate::Error;
+
+/// Get a summary of the node's consensus state
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Request;
+
+impl crate::Request for Request {
+    type Response = Response;
+
+    fn method(&self) -> crate::Method {
+        crate::Method::ConsensusState
+    }
+}
+
+/// Consensus state summary
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Response {
+    /// Summary of the current round
+    pub round_state: RoundStateSummary,
+}
+
+impl crate::Response for Response {}
+
+/// Summary of the state of the current consensus round
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RoundStateSummary {
+    /// Height, round and step the node is at
+    #[serde(rename = "height/round/step", with = "serializers::from_str")]
+    pub height_round_step: HeightRoundStep,
+
+    /// When the round started
+    pub start_time: Time,
+
+    /// Hash of the proposed block, if any
+    #[serde(deserialize_with = "serializers::parse_non_empty_hash")]
+    pub proposal_block_hash: Option<Hash>,
+
+    /// Hash of the block the node is locked on, if any
+    #[serde(deserialize_with = "serializers::parse_non_empty_hash")]
+    pub locked_block_hash: Option<Hash>,
+
+    /// Hash of the last block which received +2/3 prevotes, if any
+    #[serde(deserialize_with = "serializers::parse_non_empty_hash")]
+    pub valid_block_hash: Option<Hash>,
+
+    /// Votes received in each round of the current height
+    pub height_vote_set: Vec<RoundVotes>,
+
+    /// Proposer of the current round
+    pub proposer: Proposer,
+}
+
+/// Height, round and step of the consensus state machine, represented as
+/// `height/round/step` in JSON
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct HeightRoundStep {
+    /// Block height
+    pub height: block::Height,
+
+    /// Round within the height
+    pub round: u32,
+
+    /// Step within the round
+    pub step: RoundStep,
+}
+
+impl FromStr for HeightRoundStep {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let invalid = || Error::parse_error(format!("invalid height/round/step: {}", s));
+        let mut parts = s.split('/');
+
+        let mut next = || parts.next().ok_or_else(invalid);
+        let height = next()?.parse::<u64>().map_err(|_| invalid())?;
+        let round = next()?.parse::<u32>().map_err(|_| invalid())?;
+        let step = next()?.parse::<u8>().map_err(|_| invalid())?;
+
+        Ok(Self {
+            height: height.into(),
+            round,
+            step: RoundStep::try_from(step)?,
+        })
+    }
+}
+
+impl fmt::Display for HeightRoundStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.height, self.round, u8::from(self.step))
+    }
+}
+
+/// Steps of a consensus round
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum RoundStep {
+    /// Waiting for the commit timeout before starting a new height
+    NewHeight,
+
+    /// Setting up a new round
+    NewRound,
+
+    /// Waiting for a proposal
+    Propose,
+
+    /// Prevoting
+    Prevote,
+
+    /// Waiting for +2/3 prevotes for anything
+    PrevoteWait,
+
+    /// Precommitting
+    Precommit,
+
+    /// Waiting for +2/3 precommits for anything
+    PrecommitWait,
+
+    /// Committing a block
+    Commit,
+}
+
+impl TryFrom<u8> for RoundStep {
+    type Error = Error;
+
+    fn try_from(step: u8) -> Result<Self, Error> {
+        Ok(match step {
+            1 => RoundStep::NewHeight,
+            2 => RoundStep::NewRound,
+            3 => RoundStep::Propose,
+            4 => RoundStep::Prevote,
+            5 => RoundStep::PrevoteWait,
+            6 => RoundStep::Precommit,
+            7 => RoundStep::PrecommitWait,
+            8 => RoundStep::Commit,
+            other => return Err(Error::parse_error(format!("invalid round step: {}", other))),
+        })
+    }
+}
+
+impl From<RoundStep> for u8 {
+    fn from(step: RoundStep) -> u8 {
+        match step {
+            RoundStep::NewHeight => 1,
+            RoundStep::NewRound => 2,
+            RoundStep::Propose => 3,
+            RoundStep::Prevote => 4,
+            RoundStep::PrevoteWait => 5,
+            RoundStep::Precommit => 6,
+            RoundStep::PrecommitWait => 7,
+            RoundStep::Commit => 8,
+        }
+    }
+}
+
+impl fmt::Display for RoundStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Votes received in a round
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RoundVotes {
+    /// Round number
+    pub round: u32,
+
+    /// Prevotes, one per validator in the order of the validator set, in
+    /// Tendermint's textual representation (`nil-Vote` if absent)
+    pub prevotes: Vec<String>,
+
+    /// Which validators prevoted, and the fraction of voting power they hold
+    pub prevotes_bit_array: String,
+
+    /// Precommits, in the same representation as the prevotes
+    pub precommits: Vec<String>,
+
+    /// Which validators precommitted, and the fraction of voting power they
+    /// hold
+    pub precommits_bit_array: String,
+}
+
+/// Proposer of a round
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Proposer {
+    /// Address of the proposer
+    pub address: account::Id,
+
+    /// Index of the proposer in the validator set
+    pub index: i32,
+}
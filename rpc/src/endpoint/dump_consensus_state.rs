@@ -0,0 +1,116 @@
+//! `/dump_consensus_state` endpoint JSONRPC wrapper
+
+use serde::{Deserialize, Serialize};
+
+use tendermint::{block, validator, Time};
+
+use super::consensus_state::{RoundStep, RoundVotes};
+
+/// Get the node's full consensus state, including that of its peers
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Request;
+
+impl crate::Request for Request {
+    type Response = Response;
+
+    fn method(&self) -> crate::Method {
+        crate::Method::DumpConsensusState
+    }
+}
+
+/// Full consensus state
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Response {
+    /// State of the current round
+    pub round_state: RoundState,
+
+    /// Consensus state of each peer
+    pub peers: Vec<Peer>,
+}
+
+impl crate::Response for Response {}
+
+/// State of the current consensus round
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RoundState {
+    /// Block height
+    pub height: block::Height,
+
+    /// Round within the height
+    pub round: u32,
+
+    /// Step within the round
+    pub step: RoundStep,
+
+    /// When the round started
+    pub start_time: Time,
+
+    /// When the last block was committed
+    pub commit_time: Time,
+
+    /// Validators of the current height
+    pub validators: validator::Set,
+
+    /// Round the node is locked in (-1 if not locked)
+    pub locked_round: i32,
+
+    /// Last round with +2/3 prevotes for a block (-1 if none)
+    pub valid_round: i32,
+
+    /// Votes received in each round of the current height
+    pub votes: Vec<RoundVotes>,
+
+    /// Round in which the block was committed (-1 if not yet committed)
+    pub commit_round: i32,
+
+    /// Validators of the previous height
+    pub last_validators: validator::Set,
+
+    /// Whether the precommit timeout was triggered
+    pub triggered_timeout_precommit: bool,
+}
+
+/// Consensus state of a peer
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Peer {
+    /// Address of the peer (`id@host:port`)
+    pub node_address: String,
+
+    /// State of the peer, as far as the node knows it
+    pub peer_state: PeerState,
+}
+
+/// State of a peer
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PeerState {
+    /// The peer's round state
+    pub round_state: PeerRoundState,
+}
+
+/// Round state of a peer
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PeerRoundState {
+    /// Block height
+    pub height: block::Height,
+
+    /// Round within the height (-1 if unknown)
+    pub round: i32,
+
+    /// Step within the round
+    pub step: RoundStep,
+
+    /// When the round started
+    pub start_time: Time,
+
+    /// Whether the peer has the proposal
+    pub proposal: bool,
+
+    /// Proof-of-lock round of the proposal (-1 if none)
+    pub proposal_pol_round: i32,
+
+    /// Round of the peer's last commit (-1 if none)
+    pub last_commit_round: i32,
+
+    /// Round the peer is catching up a commit for (-1 if none)
+    pub catchup_commit_round: i32,
+}
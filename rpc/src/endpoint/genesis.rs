@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use tendermint::Genesis;
+use tendermint::{chain, Genesis};
 
 /// Get the genesis state for the current chain
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -23,4 +23,8 @@ pub struct Response {
     pub genesis: Genesis,
 }
 
-impl crate::Response for Response {}
+impl crate::Response for Response {
+    fn chain_ids(&self) -> Vec<&chain::Id> {
+        vec![&self.genesis.chain_id]
+    }
+}
@@ -0,0 +1,47 @@
+//! `/genesis_chunked` endpoint JSONRPC wrapper
+
+use serde::{Deserialize, Serialize};
+
+use tendermint::serializers;
+
+/// Get a chunk of the genesis file, for genesis files too large to be
+/// returned by `/genesis` at once
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Request {
+    /// Index of the chunk to request (starting at 0)
+    #[serde(with = "serializers::from_str")]
+    chunk: u64,
+}
+
+impl Request {
+    /// Create a new request for the chunk with the given index
+    pub fn new(chunk: u64) -> Self {
+        Self { chunk }
+    }
+}
+
+impl crate::Request for Request {
+    type Response = Response;
+
+    fn method(&self) -> crate::Method {
+        crate::Method::GenesisChunked
+    }
+}
+
+/// Genesis file chunk
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Response {
+    /// Index of this chunk
+    #[serde(with = "serializers::from_str")]
+    pub chunk: u64,
+
+    /// Total number of chunks
+    #[serde(with = "serializers::from_str")]
+    pub total: u64,
+
+    /// Contents of this chunk of the JSON-encoded genesis file
+    #[serde(with = "serializers::bytes::base64string")]
+    pub data: Vec<u8>,
+}
+
+impl crate::Response for Response {}
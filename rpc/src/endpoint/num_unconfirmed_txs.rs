@@ -0,0 +1,21 @@
+//! `/num_unconfirmed_txs` endpoint JSONRPC wrapper
+
+use serde::{Deserialize, Serialize};
+
+use super::unconfirmed_txs;
+
+/// Get the number and total size of transactions waiting in the mempool
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Request;
+
+impl crate::Request for Request {
+    type Response = Response;
+
+    fn method(&self) -> crate::Method {
+        crate::Method::NumUnconfirmedTxs
+    }
+}
+
+/// Mempool size, in the same format as `/unconfirmed_txs` but without the
+/// transactions themselves
+pub type Response = unconfirmed_txs::Response;
@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use tendermint::{block, node, serializers, validator, Hash, Time};
+use tendermint::{block, chain, node, serializers, validator, Hash, Time};
 
 /// Node status request
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -29,7 +29,11 @@ pub struct Response {
     pub validator_info: validator::Info,
 }
 
-impl crate::Response for Response {}
+impl crate::Response for Response {
+    fn chain_ids(&self) -> Vec<&chain::Id> {
+        vec![&self.node_info.network]
+    }
+}
 
 /// Sync information
 #[derive(Clone, Debug, Deserialize, Serialize)]
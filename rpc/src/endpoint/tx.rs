@@ -0,0 +1,63 @@
+//! `/tx` endpoint JSONRPC wrapper
+
+use serde::{Deserialize, Serialize};
+
+use tendermint::abci::{transaction, DeliverTx, Transaction};
+use tendermint::{block, serializers};
+
+use super::tx_search::TxProof;
+
+/// Get a committed transaction by its hash
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Request {
+    /// Hash of the transaction (base64-encoded in JSON)
+    #[serde(with = "serializers::bytes::base64string")]
+    pub hash: Vec<u8>,
+
+    /// Include a proof of the transaction's inclusion in its block
+    pub prove: bool,
+}
+
+impl Request {
+    /// Create a new request for the committed transaction with the given
+    /// hash
+    pub fn new(hash: transaction::Hash, prove: bool) -> Self {
+        Self {
+            hash: hash.as_bytes().to_vec(),
+            prove,
+        }
+    }
+}
+
+impl crate::Request for Request {
+    type Response = Response;
+
+    fn method(&self) -> crate::Method {
+        crate::Method::Tx
+    }
+}
+
+/// Committed transaction
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Response {
+    /// Hash of the transaction
+    pub hash: transaction::Hash,
+
+    /// Height of the block containing the transaction
+    pub height: block::Height,
+
+    /// Index of the transaction within its block
+    pub index: u32,
+
+    /// Result of executing the transaction
+    pub tx_result: DeliverTx,
+
+    /// The transaction itself
+    pub tx: Transaction,
+
+    /// Proof of inclusion in the block (only if requested)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof: Option<TxProof>,
+}
+
+impl crate::Response for Response {}
@@ -0,0 +1,119 @@
+//! `/tx_search` endpoint JSONRPC wrapper
+
+use serde::{Deserialize, Serialize};
+
+use tendermint::abci::{transaction, DeliverTx, Transaction};
+use tendermint::{block, serializers, Hash};
+
+use crate::{query::Query, Order};
+
+/// Search for transactions by their events
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Request {
+    /// Query selecting the transactions, e.g. `tx.height = 5`
+    pub query: String,
+
+    /// Include proofs of the transactions' inclusion in their blocks
+    pub prove: bool,
+
+    /// Page of results to return (starting at 1)
+    #[serde(with = "serializers::from_str")]
+    pub page: u32,
+
+    /// Number of results per page (at most 100)
+    #[serde(with = "serializers::from_str")]
+    pub per_page: u8,
+
+    /// Order of the results by height
+    pub order_by: Order,
+}
+
+impl Request {
+    /// Create a new request for the given page of transactions matching the
+    /// given query
+    pub fn new(query: &Query, prove: bool, page: u32, per_page: u8, order_by: Order) -> Self {
+        Self {
+            query: query.to_string(),
+            prove,
+            page,
+            per_page,
+            order_by,
+        }
+    }
+}
+
+impl crate::Request for Request {
+    type Response = Response;
+
+    fn method(&self) -> crate::Method {
+        crate::Method::TxSearch
+    }
+}
+
+/// Transaction search results
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Response {
+    /// Transactions on the requested page
+    pub txs: Vec<ResultTx>,
+
+    /// Total number of transactions matching the query, on all pages
+    #[serde(with = "serializers::from_str")]
+    pub total_count: u32,
+}
+
+impl crate::Response for Response {}
+
+/// A transaction found by a search
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ResultTx {
+    /// Hash of the transaction
+    pub hash: transaction::Hash,
+
+    /// Height of the block containing the transaction
+    pub height: block::Height,
+
+    /// Index of the transaction within its block
+    pub index: u32,
+
+    /// Result of executing the transaction
+    pub tx_result: DeliverTx,
+
+    /// The transaction itself
+    pub tx: Transaction,
+
+    /// Proof of inclusion in the block (only if requested)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof: Option<TxProof>,
+}
+
+/// Proof of a transaction's inclusion in a block
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TxProof {
+    /// Merkle root of the block's transactions
+    pub root_hash: Hash,
+
+    /// The transaction
+    pub data: Transaction,
+
+    /// Merkle proof for the transaction
+    pub proof: SimpleProof,
+}
+
+/// Merkle proof of a leaf in a simple Merkle tree
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SimpleProof {
+    /// Number of leaves
+    #[serde(with = "serializers::from_str")]
+    pub total: u64,
+
+    /// Index of the leaf
+    #[serde(with = "serializers::from_str")]
+    pub index: u64,
+
+    /// Hash of the leaf
+    #[serde(with = "serializers::bytes::base64string")]
+    pub leaf_hash: Vec<u8>,
+
+    /// Base64-encoded hashes of the sibling nodes on the path to the root
+    pub aunts: Vec<String>,
+}
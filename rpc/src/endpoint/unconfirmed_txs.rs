@@ -0,0 +1,53 @@
+//! `/unconfirmed_txs` endpoint JSONRPC wrapper
+
+use serde::{Deserialize, Serialize};
+
+use tendermint::abci::Transaction;
+use tendermint::serializers;
+
+/// Get transactions waiting in the mempool
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Request {
+    /// Maximum number of transactions to return (30 by default, at most 100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    limit: Option<String>,
+}
+
+impl Request {
+    /// Create a new request for at most the given number of transactions
+    pub fn new(limit: Option<u32>) -> Self {
+        Self {
+            limit: limit.map(|limit| limit.to_string()),
+        }
+    }
+}
+
+impl crate::Request for Request {
+    type Response = Response;
+
+    fn method(&self) -> crate::Method {
+        crate::Method::UnconfirmedTxs
+    }
+}
+
+/// Mempool contents
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Response {
+    /// Number of transactions returned
+    #[serde(with = "serializers::from_str")]
+    pub n_txs: u64,
+
+    /// Total number of transactions in the mempool
+    #[serde(with = "serializers::from_str")]
+    pub total: u64,
+
+    /// Total size of the transactions in the mempool in bytes
+    #[serde(with = "serializers::from_str")]
+    pub total_bytes: u64,
+
+    /// The transactions (always empty for `/num_unconfirmed_txs`)
+    #[serde(default, deserialize_with = "serializers::null_as_default")]
+    pub txs: Vec<Transaction>,
+}
+
+impl crate::Response for Response {}
@@ -0,0 +1,31 @@
+//! `/unsubscribe` endpoint JSONRPC wrapper
+
+use serde::{Deserialize, Serialize};
+
+/// Unsubscribe request for events on websocket
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Request {
+    query: String,
+}
+
+impl Request {
+    /// Stop streaming the events selected by the given query, which must be
+    /// the query of a previous subscription
+    pub fn new(query: String) -> Self {
+        Self { query }
+    }
+}
+
+impl crate::Request for Request {
+    type Response = Response;
+
+    fn method(&self) -> crate::Method {
+        crate::Method::Unsubscribe
+    }
+}
+
+/// Unsubscribe responses
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Response {}
+
+impl crate::Response for Response {}
@@ -2,18 +2,39 @@
 
 use serde::{Deserialize, Serialize};
 
-use tendermint::{block, validator};
+use tendermint::{block, serializers, validator};
 
 /// List validators for a specific block
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Request {
     height: block::Height,
+
+    /// Page of validators to return (starting at 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    page: Option<String>,
+
+    /// Number of validators per page (30 by default, at most 100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    per_page: Option<String>,
 }
 
 impl Request {
     /// List validators for a specific block
     pub fn new(height: block::Height) -> Self {
-        Self { height }
+        Self {
+            height,
+            page: None,
+            per_page: None,
+        }
+    }
+
+    /// List the given page of validators for a specific block
+    pub fn new_with_page(height: block::Height, page: u32, per_page: u8) -> Self {
+        Self {
+            height,
+            page: Some(page.to_string()),
+            per_page: Some(per_page.to_string()),
+        }
     }
 }
 
@@ -33,6 +54,11 @@ pub struct Response {
 
     /// Validator list
     pub validators: Vec<validator::Info>,
+
+    /// Total number of validators, on all pages (0 if the node does not
+    /// page validators, as before Tendermint v0.33)
+    #[serde(default, with = "serializers::from_str")]
+    pub total: u32,
 }
 
 impl crate::Response for Response {}
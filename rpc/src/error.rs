@@ -5,6 +5,7 @@ use async_tungstenite::tungstenite::Error as WSError;
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::{self, Display};
+use tendermint::chain;
 use thiserror::Error;
 
 /// Tendermint RPC errors
@@ -18,6 +19,10 @@ pub struct Error {
 
     /// Additional data about the error
     data: Option<String>,
+
+    /// Status of the HTTP response which caused the error, if any
+    #[serde(skip)]
+    http_status: Option<u16>,
 }
 impl std::error::Error for Error {}
 
@@ -30,6 +35,7 @@ impl Error {
             code,
             message,
             data,
+            http_status: None,
         }
     }
 
@@ -39,9 +45,44 @@ impl Error {
             code: Code::HttpError,
             message: message.into(),
             data: None,
+            http_status: None,
+        }
+    }
+
+    /// Create a low-level HTTP error for a response with the given status
+    /// which carries no JSONRPC payload, e.g. the error page of a gateway
+    pub fn http_status_error(status: u16) -> Error {
+        Error {
+            http_status: Some(status),
+            ..Error::http_error(format!("server responded with HTTP status {}", status))
         }
     }
 
+    /// Create a new timeout error
+    pub fn timeout_error(cause: impl Into<String>) -> Error {
+        Error::new(Code::TimeoutError, Some(cause.into()))
+    }
+
+    /// Create a new error for a response from a chain other than the
+    /// expected one
+    pub fn chain_id_mismatch(expected: &chain::Id, actual: &chain::Id) -> Error {
+        Error::new(
+            Code::ChainIdMismatch,
+            Some(format!("expected chain ID {}, got {}", expected, actual)),
+        )
+    }
+
+    /// Create a new error for a response exceeding the given maximum size
+    pub fn response_too_large(max_size: usize) -> Error {
+        Error::new(
+            Code::ResponseTooLarge,
+            Some(format!(
+                "response exceeds the maximum of {} bytes",
+                max_size
+            )),
+        )
+    }
+
     /// Create a new invalid parameter error
     pub fn invalid_params(data: &str) -> Error {
         Error::new(Code::InvalidParams, Some(data.to_string()))
@@ -87,6 +128,22 @@ impl Error {
     pub fn data(&self) -> Option<&str> {
         self.data.as_ref().map(AsRef::as_ref)
     }
+
+    /// Status of the HTTP response which caused this error, if any
+    pub fn http_status(&self) -> Option<u16> {
+        self.http_status
+    }
+
+    /// Cause of this error, as far as it can be told from the well-known
+    /// messages Tendermint reports along with internal errors
+    pub fn reason(&self) -> Reason {
+        match (self.code, self.data()) {
+            (Code::InternalError, Some(data)) | (Code::ServerError, Some(data)) => {
+                Reason::from_message(data)
+            }
+            _ => Reason::Other,
+        }
+    }
 }
 
 impl Display for Error {
@@ -114,8 +171,36 @@ impl From<http::Error> for Error {
 #[cfg(feature = "client")]
 impl From<hyper::Error> for Error {
     fn from(hyper_error: hyper::Error) -> Error {
-        Error::http_error(hyper_error.to_string())
+        if is_timed_out(&hyper_error) {
+            Error::timeout_error(hyper_error.to_string())
+        } else {
+            Error::http_error(hyper_error.to_string())
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl From<tokio::time::Elapsed> for Error {
+    fn from(elapsed: tokio::time::Elapsed) -> Error {
+        Error::timeout_error(elapsed.to_string())
+    }
+}
+
+/// Walk the source chain of the given error looking for an I/O timeout
+#[cfg(feature = "client")]
+fn is_timed_out(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(error);
+
+    while let Some(err) = source {
+        if let Some(io_error) = err.downcast_ref::<std::io::Error>() {
+            if io_error.kind() == std::io::ErrorKind::TimedOut {
+                return true;
+            }
+        }
+        source = err.source();
     }
+
+    false
 }
 
 #[cfg(feature = "client")]
@@ -139,6 +224,19 @@ pub enum Code {
     #[error("Websocket Error")]
     WebSocketError,
 
+    /// The request did not complete in time
+    #[error("Request timed out")]
+    TimeoutError,
+
+    /// The response body exceeded the configured maximum size
+    #[error("Response too large")]
+    ResponseTooLarge,
+
+    /// The response is from a chain other than the one the client is pinned
+    /// to
+    #[error("Chain ID mismatch")]
+    ChainIdMismatch,
+
     /// Parse error i.e. invalid JSON (-32700)
     #[error("Parse error. Invalid JSON")]
     ParseError,
@@ -180,6 +278,9 @@ impl From<i32> for Code {
         match value {
             0 => Code::HttpError,
             1 => Code::WebSocketError,
+            2 => Code::TimeoutError,
+            3 => Code::ResponseTooLarge,
+            4 => Code::ChainIdMismatch,
             -32700 => Code::ParseError,
             -32600 => Code::InvalidRequest,
             -32601 => Code::MethodNotFound,
@@ -196,6 +297,9 @@ impl From<Code> for i32 {
         match code {
             Code::HttpError => 0,
             Code::WebSocketError => 1,
+            Code::TimeoutError => 2,
+            Code::ResponseTooLarge => 3,
+            Code::ChainIdMismatch => 4,
             Code::ParseError => -32700,
             Code::InvalidRequest => -32600,
             Code::MethodNotFound => -32601,
@@ -207,6 +311,64 @@ impl From<Code> for i32 {
     }
 }
 
+/// Well-known causes of errors reported by Tendermint, which only tells them
+/// apart by their message
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Reason {
+    /// The requested height is above the latest one, or below the lowest one
+    /// still stored by the node
+    HeightNotAvailable,
+
+    /// The requested page of search results does not exist
+    PageOutOfRange,
+
+    /// The transaction is in the mempool cache already
+    TxAlreadyInCache,
+
+    /// No transaction with the requested hash has been indexed
+    TxNotFound,
+
+    /// The mempool has no room for the transaction
+    MempoolFull,
+
+    /// The transaction exceeds the maximum size
+    TxTooLarge,
+
+    /// The transaction was not committed before `broadcast_tx_commit` timed
+    /// out
+    TxCommitTimeout,
+
+    /// Any other cause
+    Other,
+}
+
+impl Reason {
+    /// Tell the reason from an error message
+    fn from_message(message: &str) -> Self {
+        let message = message.to_lowercase();
+
+        if message.contains("must be less than or equal to the current blockchain height")
+            || (message.starts_with("height") && message.contains("is not available"))
+        {
+            Reason::HeightNotAvailable
+        } else if message.starts_with("page should be within") {
+            Reason::PageOutOfRange
+        } else if message.contains("tx already exists in cache") {
+            Reason::TxAlreadyInCache
+        } else if message.starts_with("tx (") && message.ends_with("not found") {
+            Reason::TxNotFound
+        } else if message.starts_with("mempool is full") {
+            Reason::MempoolFull
+        } else if message.starts_with("tx too large") {
+            Reason::TxTooLarge
+        } else if message.contains("timed out waiting for tx to be included in a block") {
+            Reason::TxCommitTimeout
+        } else {
+            Reason::Other
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for Code {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         Ok(Code::from(i32::deserialize(deserializer)?))
@@ -223,6 +385,7 @@ impl Serialize for Code {
 mod tests {
     use super::Code;
     use super::Error;
+    use super::Reason;
 
     #[test]
     fn test_serialize() {
@@ -236,4 +399,32 @@ mod tests {
         assert_eq!(res.code.value(), -32700);
         assert_eq!(res.data, Some("hello world".to_string()));
     }
+
+    #[test]
+    fn reasons() {
+        let reason = |data: &str| Error::new(Code::InternalError, Some(data.to_owned())).reason();
+
+        assert_eq!(
+            reason("height 20 must be less than or equal to the current blockchain height 10"),
+            Reason::HeightNotAvailable
+        );
+        assert_eq!(
+            reason("height 1 is not available, lowest height is 100"),
+            Reason::HeightNotAvailable
+        );
+        assert_eq!(
+            reason("page should be within [1, 3] range, given 4"),
+            Reason::PageOutOfRange
+        );
+        assert_eq!(
+            reason("tx already exists in cache"),
+            Reason::TxAlreadyInCache
+        );
+        assert_eq!(reason("tx (0A0B) not found"), Reason::TxNotFound);
+        assert_eq!(reason("something else"), Reason::Other);
+        assert_eq!(
+            Error::parse_error("tx already exists in cache").reason(),
+            Reason::Other
+        );
+    }
 }
@@ -2,6 +2,8 @@
 
 use getrandom::getrandom;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicI64, Ordering};
 
 /// JSONRPC ID: request-specific identifier
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
@@ -30,6 +32,70 @@ impl Id {
     }
 }
 
+/// Strategy for identifying the JSONRPC requests sent by a client
+pub trait IdGenerator: fmt::Debug + Send + Sync {
+    /// ID for the next request
+    fn next_id(&self) -> Id;
+}
+
+/// Random UUID v4 request IDs (the default)
+#[derive(Clone, Debug, Default)]
+pub struct UuidIds;
+
+impl IdGenerator for UuidIds {
+    fn next_id(&self) -> Id {
+        Id::uuid_v4()
+    }
+}
+
+/// Sequential numeric request IDs starting at 1, e.g. for deterministic
+/// requests in tests
+#[derive(Debug, Default)]
+pub struct SequentialIds {
+    last: AtomicI64,
+}
+
+impl SequentialIds {
+    /// Number requests starting at 1
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IdGenerator for SequentialIds {
+    fn next_id(&self) -> Id {
+        Id::Num(self.last.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+}
+
+/// Request IDs made of a fixed prefix and a sequence number, e.g.
+/// `indexer-7`, to correlate requests with the logs of nodes and proxies
+#[derive(Debug)]
+pub struct PrefixedIds {
+    prefix: String,
+    sequence: SequentialIds,
+}
+
+impl PrefixedIds {
+    /// Number requests starting at 1, prefixed with the given prefix and a
+    /// dash
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            sequence: SequentialIds::new(),
+        }
+    }
+}
+
+impl IdGenerator for PrefixedIds {
+    fn next_id(&self) -> Id {
+        match self.sequence.next_id() {
+            Id::Num(n) => Id::Str(format!("{}-{}", self.prefix, n)),
+            _ => unreachable!("sequential IDs are numeric"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde::{de::DeserializeOwned, Serialize};
@@ -55,6 +121,19 @@ mod tests {
         serialization_roundtrip::<Id>(null);
     }
 
+    #[test]
+    fn generated_ids() {
+        let sequential = SequentialIds::new();
+        assert_eq!(sequential.next_id(), Id::Num(1));
+        assert_eq!(sequential.next_id(), Id::Num(2));
+
+        let prefixed = PrefixedIds::new("indexer");
+        assert_eq!(prefixed.next_id(), Id::Str("indexer-1".to_owned()));
+        assert_eq!(prefixed.next_id(), Id::Str("indexer-2".to_owned()));
+
+        assert_ne!(UuidIds.next_id(), UuidIds.next_id());
+    }
+
     fn serialization_roundtrip<T>(json_data: &str)
     where
         T: Debug + PartialEq + Serialize + DeserializeOwned,
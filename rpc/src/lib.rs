@@ -2,17 +2,39 @@
 
 #[cfg(feature = "client")]
 mod client;
+#[cfg(feature = "metrics-prometheus")]
+pub use client::PrometheusMetrics;
 #[cfg(feature = "client")]
-pub use client::{event_listener, Client};
+pub use client::{
+    event_listener, mock, subscription, BalancedClient, CacheStore, CachingClient, CircuitBreaker,
+    Client, ClientBuilder, CommittedTx, CompatMode, DiskCache, FailoverClient, LagPolicy, Layer,
+    MemoryCache, MetricsRecorder, MonitoredBlock, RateLimit, RequestMetrics, RetryPolicy, Strategy,
+    Transport, Url, WebSocketClient,
+};
+
+#[cfg(feature = "wasm-client")]
+mod wasm_client;
+#[cfg(feature = "wasm-client")]
+pub use wasm_client::WasmClient;
 
 pub mod endpoint;
 pub mod error;
 mod id;
 mod method;
+mod order;
+pub mod query;
 pub mod request;
 pub mod response;
+#[cfg(feature = "server")]
+pub mod server;
 mod version;
 
 pub use self::{
-    error::Error, id::Id, method::Method, request::Request, response::Response, version::Version,
+    error::Error,
+    id::{Id, IdGenerator, PrefixedIds, SequentialIds, UuidIds},
+    method::Method,
+    order::Order,
+    request::Request,
+    response::Response,
+    version::Version,
 };
@@ -21,12 +21,18 @@ pub enum Method {
     /// Get block info
     Block,
 
+    /// Get block info by hash
+    BlockByHash,
+
     /// Get ABCI results for a particular block
     BlockResults,
 
     /// Get blockchain info
     Blockchain,
 
+    /// Search for blocks
+    BlockSearch,
+
     /// Broadcast transaction asynchronously
     BroadcastTxAsync,
 
@@ -36,29 +42,56 @@ pub enum Method {
     /// Broadcast transaction commit
     BroadcastTxCommit,
 
+    /// Check a transaction without broadcasting it
+    CheckTx,
+
     /// Get commit info for a block
     Commit,
 
+    /// Get a summary of the consensus state
+    ConsensusState,
+
+    /// Get the full consensus state
+    DumpConsensusState,
+
     /// Get genesis file
     Genesis,
 
+    /// Get a chunk of the genesis file
+    GenesisChunked,
+
     /// Get health info
     Health,
 
     /// Get network info
     NetInfo,
 
+    /// Get the number of transactions in the mempool
+    NumUnconfirmedTxs,
+
     /// Get node status
     Status,
 
+    /// Get transactions in the mempool
+    UnconfirmedTxs,
+
     /// Get validator info for a block
     Validators,
 
     /// Subscribe to events over the websocket
     Subscribe,
 
+    /// Unsubscribe from events over the websocket
+    Unsubscribe,
+
     /// Broadcast evidence
     BroadcastEvidence,
+
+    /// Get a committed transaction by its hash
+    Tx,
+
+    /// Search for transactions
+    TxSearch,
 }
 
 impl Method {
@@ -68,19 +101,47 @@ impl Method {
             Method::AbciInfo => "abci_info",
             Method::AbciQuery => "abci_query",
             Method::Block => "block",
+            Method::BlockByHash => "block_by_hash",
             Method::BlockResults => "block_results",
             Method::Blockchain => "blockchain",
+            Method::BlockSearch => "block_search",
             Method::BroadcastTxAsync => "broadcast_tx_async",
             Method::BroadcastTxSync => "broadcast_tx_sync",
             Method::BroadcastTxCommit => "broadcast_tx_commit",
+            Method::CheckTx => "check_tx",
             Method::Commit => "commit",
+            Method::ConsensusState => "consensus_state",
+            Method::DumpConsensusState => "dump_consensus_state",
             Method::Genesis => "genesis",
+            Method::GenesisChunked => "genesis_chunked",
             Method::Health => "health",
             Method::NetInfo => "net_info",
+            Method::NumUnconfirmedTxs => "num_unconfirmed_txs",
             Method::Status => "status",
+            Method::UnconfirmedTxs => "unconfirmed_txs",
             Method::Validators => "validators",
             Method::Subscribe => "subscribe",
+            Method::Unsubscribe => "unsubscribe",
             Method::BroadcastEvidence => "broadcast_evidence",
+            Method::Tx => "tx",
+            Method::TxSearch => "tx_search",
+        }
+    }
+
+    /// Can a request for this method safely be sent more than once?
+    ///
+    /// This is the case for every method which only reads node state.
+    /// Broadcasting transactions or evidence, subscribing and unsubscribing
+    /// are not.
+    pub fn is_idempotent(self) -> bool {
+        match self {
+            Method::BroadcastTxAsync
+            | Method::BroadcastTxSync
+            | Method::BroadcastTxCommit
+            | Method::BroadcastEvidence
+            | Method::Subscribe
+            | Method::Unsubscribe => false,
+            _ => true,
         }
     }
 }
@@ -93,19 +154,30 @@ impl FromStr for Method {
             "abci_info" => Method::AbciInfo,
             "abci_query" => Method::AbciQuery,
             "block" => Method::Block,
+            "block_by_hash" => Method::BlockByHash,
             "block_results" => Method::BlockResults,
             "blockchain" => Method::Blockchain,
+            "block_search" => Method::BlockSearch,
             "broadcast_tx_async" => Method::BroadcastTxAsync,
             "broadcast_tx_sync" => Method::BroadcastTxSync,
             "broadcast_tx_commit" => Method::BroadcastTxCommit,
+            "check_tx" => Method::CheckTx,
             "commit" => Method::Commit,
+            "consensus_state" => Method::ConsensusState,
+            "dump_consensus_state" => Method::DumpConsensusState,
             "genesis" => Method::Genesis,
+            "genesis_chunked" => Method::GenesisChunked,
             "health" => Method::Health,
             "net_info" => Method::NetInfo,
+            "num_unconfirmed_txs" => Method::NumUnconfirmedTxs,
             "status" => Method::Status,
+            "unconfirmed_txs" => Method::UnconfirmedTxs,
             "validators" => Method::Validators,
             "subscribe" => Method::Subscribe,
+            "unsubscribe" => Method::Unsubscribe,
             "broadcast_evidence" => Method::BroadcastEvidence,
+            "tx" => Method::Tx,
+            "tx_search" => Method::TxSearch,
             other => return Err(Error::method_not_found(other)),
         })
     }
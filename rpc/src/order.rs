@@ -0,0 +1,21 @@
+//! Ordering of search results
+
+use serde::{Deserialize, Serialize};
+
+/// Order in which search results are returned
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum Order {
+    /// Lowest height first
+    #[serde(rename = "asc")]
+    Ascending,
+
+    /// Highest height first
+    #[serde(rename = "desc")]
+    Descending,
+}
+
+impl Default for Order {
+    fn default() -> Self {
+        Order::Ascending
+    }
+}
@@ -0,0 +1,468 @@
+//! Structured queries for subscriptions and searches.
+//!
+//! Tendermint selects events and transactions with queries such as
+//! `tm.event = 'Tx' AND tx.height >= 5`. [`Query`] builds them from typed
+//! conditions, so that they are well-formed by construction:
+//!
+//! ```
+//! use tendermint_rpc::query::{EventType, Query};
+//!
+//! let query = Query::from(EventType::Tx)
+//!     .and_height_range(5u64..10)
+//!     .and_message_sender("cosmos1abc")
+//!     .and_exists("transfer.amount");
+//!
+//! assert_eq!(
+//!     query.to_string(),
+//!     "tm.event = 'Tx' AND tx.height >= 5 AND tx.height < 10 \
+//!      AND message.sender = 'cosmos1abc' AND transfer.amount EXISTS"
+//! );
+//! ```
+
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+
+use tendermint::abci::transaction;
+use tendermint::block::Height;
+use tendermint::Time;
+
+use crate::Error;
+
+/// Query selecting events or transactions by a conjunction of conditions.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Query {
+    /// Type of event to select (the `tm.event` key), if any
+    event_type: Option<EventType>,
+
+    /// Further conditions, all of which must hold
+    conditions: Vec<Condition>,
+}
+
+impl Query {
+    /// Query matching everything which satisfies `key = value`
+    pub fn eq(key: impl Into<String>, value: impl Into<Operand>) -> Self {
+        Self::default().and_eq(key, value)
+    }
+
+    /// Query matching everything which satisfies `key < value`
+    pub fn lt(key: impl Into<String>, value: impl Into<Operand>) -> Self {
+        Self::default().and_lt(key, value)
+    }
+
+    /// Query matching everything which satisfies `key <= value`
+    pub fn lte(key: impl Into<String>, value: impl Into<Operand>) -> Self {
+        Self::default().and_lte(key, value)
+    }
+
+    /// Query matching everything which satisfies `key > value`
+    pub fn gt(key: impl Into<String>, value: impl Into<Operand>) -> Self {
+        Self::default().and_gt(key, value)
+    }
+
+    /// Query matching everything which satisfies `key >= value`
+    pub fn gte(key: impl Into<String>, value: impl Into<Operand>) -> Self {
+        Self::default().and_gte(key, value)
+    }
+
+    /// Query matching everything whose value for `key` contains `value`
+    pub fn contains(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::default().and_contains(key, value)
+    }
+
+    /// Query matching everything which has a value for `key`
+    pub fn exists(key: impl Into<String>) -> Self {
+        Self::default().and_exists(key)
+    }
+
+    /// Additionally require `key = value`
+    pub fn and_eq(self, key: impl Into<String>, value: impl Into<Operand>) -> Self {
+        self.and(key, Operation::Eq(value.into()))
+    }
+
+    /// Additionally require `key < value`
+    pub fn and_lt(self, key: impl Into<String>, value: impl Into<Operand>) -> Self {
+        self.and(key, Operation::Lt(value.into()))
+    }
+
+    /// Additionally require `key <= value`
+    pub fn and_lte(self, key: impl Into<String>, value: impl Into<Operand>) -> Self {
+        self.and(key, Operation::Lte(value.into()))
+    }
+
+    /// Additionally require `key > value`
+    pub fn and_gt(self, key: impl Into<String>, value: impl Into<Operand>) -> Self {
+        self.and(key, Operation::Gt(value.into()))
+    }
+
+    /// Additionally require `key >= value`
+    pub fn and_gte(self, key: impl Into<String>, value: impl Into<Operand>) -> Self {
+        self.and(key, Operation::Gte(value.into()))
+    }
+
+    /// Additionally require the value for `key` to contain `value`
+    pub fn and_contains(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.and(key, Operation::Contains(value.into()))
+    }
+
+    /// Additionally require a value for `key` to exist
+    pub fn and_exists(self, key: impl Into<String>) -> Self {
+        self.and(key, Operation::Exists)
+    }
+
+    /// Additionally require the transaction height (`tx.height`) to lie in
+    /// the given range
+    pub fn and_height_range<H>(self, range: impl RangeBounds<H>) -> Self
+    where
+        H: Into<Height> + Copy,
+    {
+        let height = |h: &H| -> Height { (*h).into() };
+
+        let query = match range.start_bound() {
+            Bound::Included(h) => self.and_gte("tx.height", height(h)),
+            Bound::Excluded(h) => self.and_gt("tx.height", height(h)),
+            Bound::Unbounded => self,
+        };
+
+        match range.end_bound() {
+            Bound::Included(h) => query.and_lte("tx.height", height(h)),
+            Bound::Excluded(h) => query.and_lt("tx.height", height(h)),
+            Bound::Unbounded => query,
+        }
+    }
+
+    /// Additionally require the sender of a message (`message.sender`) to be
+    /// the given address
+    pub fn and_message_sender(self, sender: impl Into<String>) -> Self {
+        self.and_eq("message.sender", Operand::String(sender.into()))
+    }
+
+    /// Additionally require the transaction height (`tx.height`) to be the
+    /// given height
+    pub fn and_height(self, height: impl Into<Height>) -> Self {
+        self.and_eq("tx.height", height.into())
+    }
+
+    /// Additionally require the transaction hash (`tx.hash`) to be the given
+    /// hash
+    pub fn and_tx_hash(self, hash: transaction::Hash) -> Self {
+        self.and_eq("tx.hash", hash.to_string())
+    }
+
+    /// Additionally require an event of the given type to have an attribute
+    /// with the given key and value, e.g. `transfer.recipient = 'cosmos1abc'`
+    pub fn and_event_attribute(
+        self,
+        event_type: &str,
+        attribute_key: &str,
+        value: impl Into<Operand>,
+    ) -> Self {
+        self.and_eq(format!("{}.{}", event_type, attribute_key), value)
+    }
+
+    /// Check that Tendermint can parse this query.
+    ///
+    /// Its query language has no escape sequences: keys must not contain
+    /// whitespace, quotes, parentheses, backslashes or comparison operators,
+    /// and strings must not contain single quotes.
+    pub fn validate(&self) -> Result<(), Error> {
+        for condition in &self.conditions {
+            let key = &condition.key;
+            if key.is_empty() || key.chars().any(is_reserved_in_key) {
+                return Err(Error::invalid_params(&format!(
+                    "invalid query key: {:?}",
+                    key
+                )));
+            }
+
+            let string = match &condition.operation {
+                Operation::Contains(s) => Some(s),
+                Operation::Eq(Operand::String(s))
+                | Operation::Lt(Operand::String(s))
+                | Operation::Lte(Operand::String(s))
+                | Operation::Gt(Operand::String(s))
+                | Operation::Gte(Operand::String(s)) => Some(s),
+                _ => None,
+            };
+            if let Some(s) = string.filter(|s| s.contains('\'')) {
+                return Err(Error::invalid_params(&format!(
+                    "query string for {} contains a single quote: {:?}",
+                    key, s
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Type of event this query selects, if restricted to one
+    pub fn event_type(&self) -> Option<EventType> {
+        self.event_type
+    }
+
+    /// Conditions of this query besides the event type
+    pub fn conditions(&self) -> &[Condition] {
+        &self.conditions
+    }
+
+    fn and(mut self, key: impl Into<String>, operation: Operation) -> Self {
+        self.conditions.push(Condition {
+            key: key.into(),
+            operation,
+        });
+        self
+    }
+}
+
+impl From<EventType> for Query {
+    fn from(event_type: EventType) -> Self {
+        Self {
+            event_type: Some(event_type),
+            conditions: vec![],
+        }
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut separator = "";
+
+        if let Some(event_type) = self.event_type {
+            write!(f, "tm.event = '{}'", event_type)?;
+            separator = " AND ";
+        }
+
+        for condition in &self.conditions {
+            write!(f, "{}{}", separator, condition)?;
+            separator = " AND ";
+        }
+
+        Ok(())
+    }
+}
+
+/// Characters which cannot appear in the keys of conditions
+fn is_reserved_in_key(c: char) -> bool {
+    c.is_whitespace() || "\\()\"'=<>".contains(c)
+}
+
+/// Types of events emitted by Tendermint
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum EventType {
+    /// A new block was committed
+    NewBlock,
+
+    /// A transaction was included in a block
+    Tx,
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventType::NewBlock => write!(f, "NewBlock"),
+            EventType::Tx => write!(f, "Tx"),
+        }
+    }
+}
+
+/// A single condition of a [`Query`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Condition {
+    /// Composite key the condition applies to, e.g. `transfer.recipient`
+    pub key: String,
+
+    /// Operation applied to the value for the key
+    pub operation: Operation,
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.operation {
+            Operation::Eq(value) => write!(f, "{} = {}", self.key, value),
+            Operation::Lt(value) => write!(f, "{} < {}", self.key, value),
+            Operation::Lte(value) => write!(f, "{} <= {}", self.key, value),
+            Operation::Gt(value) => write!(f, "{} > {}", self.key, value),
+            Operation::Gte(value) => write!(f, "{} >= {}", self.key, value),
+            Operation::Contains(value) => write!(f, "{} CONTAINS '{}'", self.key, value),
+            Operation::Exists => write!(f, "{} EXISTS", self.key),
+        }
+    }
+}
+
+/// Operations a [`Condition`] can apply
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    /// Value equals the operand
+    Eq(Operand),
+
+    /// Value is less than the operand
+    Lt(Operand),
+
+    /// Value is less than or equal to the operand
+    Lte(Operand),
+
+    /// Value is greater than the operand
+    Gt(Operand),
+
+    /// Value is greater than or equal to the operand
+    Gte(Operand),
+
+    /// Value contains the given string
+    Contains(String),
+
+    /// A value exists
+    Exists,
+}
+
+/// Values conditions compare against
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operand {
+    /// String, quoted in queries
+    String(String),
+
+    /// Signed integer
+    Signed(i64),
+
+    /// Unsigned integer
+    Unsigned(u64),
+
+    /// Floating point number
+    Float(f64),
+
+    /// Point in time
+    Time(Time),
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::String(s) => write!(f, "'{}'", s),
+            Operand::Signed(i) => write!(f, "{}", i),
+            Operand::Unsigned(u) => write!(f, "{}", u),
+            Operand::Float(x) => write!(f, "{}", x),
+            Operand::Time(t) => write!(f, "TIME {}", t.to_rfc3339()),
+        }
+    }
+}
+
+impl From<String> for Operand {
+    fn from(s: String) -> Self {
+        Operand::String(s)
+    }
+}
+
+impl From<&str> for Operand {
+    fn from(s: &str) -> Self {
+        Operand::String(s.to_owned())
+    }
+}
+
+impl From<i64> for Operand {
+    fn from(i: i64) -> Self {
+        Operand::Signed(i)
+    }
+}
+
+impl From<i32> for Operand {
+    fn from(i: i32) -> Self {
+        Operand::Signed(i.into())
+    }
+}
+
+impl From<u64> for Operand {
+    fn from(u: u64) -> Self {
+        Operand::Unsigned(u)
+    }
+}
+
+impl From<u32> for Operand {
+    fn from(u: u32) -> Self {
+        Operand::Unsigned(u.into())
+    }
+}
+
+impl From<f64> for Operand {
+    fn from(x: f64) -> Self {
+        Operand::Float(x)
+    }
+}
+
+impl From<Height> for Operand {
+    fn from(height: Height) -> Self {
+        Operand::Unsigned(height.value())
+    }
+}
+
+impl From<Time> for Operand {
+    fn from(time: Time) -> Self {
+        Operand::Time(time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operators() {
+        let query = Query::gt("tx.height", 3u64)
+            .and_lte("transfer.amount", -2)
+            .and_contains("message.action", "send")
+            .and_eq("transfer.recipient", "abc");
+
+        assert_eq!(
+            query.to_string(),
+            "tx.height > 3 AND transfer.amount <= -2 AND message.action CONTAINS 'send' \
+             AND transfer.recipient = 'abc'"
+        );
+    }
+
+    #[test]
+    fn height_ranges() {
+        let query = Query::from(EventType::Tx).and_height_range(5u64..=10);
+        assert_eq!(
+            query.to_string(),
+            "tm.event = 'Tx' AND tx.height >= 5 AND tx.height <= 10"
+        );
+
+        let query = Query::from(EventType::Tx).and_height_range(..7u64);
+        assert_eq!(query.to_string(), "tm.event = 'Tx' AND tx.height < 7");
+    }
+
+    #[test]
+    fn transaction_conditions() {
+        let hash = "0D33F2F03A5234F38706E43004489E061AC40A2E".parse().unwrap();
+        let query = Query::from(EventType::Tx)
+            .and_height(5u64)
+            .and_tx_hash(hash)
+            .and_event_attribute("transfer", "recipient", "cosmos1abc");
+
+        assert_eq!(
+            query.to_string(),
+            "tm.event = 'Tx' AND tx.height = 5 \
+             AND tx.hash = '0D33F2F03A5234F38706E43004489E061AC40A2E' \
+             AND transfer.recipient = 'cosmos1abc'"
+        );
+        assert!(query.validate().is_ok());
+    }
+
+    #[test]
+    fn validation() {
+        assert!(Query::eq("transfer.memo", "it's").validate().is_err());
+        assert!(Query::contains("transfer.memo", "'").validate().is_err());
+        assert!(Query::eq("transfer.amount >", 5u64).validate().is_err());
+        assert!(Query::exists("").validate().is_err());
+        assert!(Query::eq("transfer.memo", "\"quoted\"").validate().is_ok());
+    }
+
+    #[test]
+    fn times() {
+        let time = Time::parse_from_rfc3339("2020-09-01T12:00:00Z").unwrap();
+        let query = Query::gte("tx.time", time);
+        assert_eq!(
+            query.to_string(),
+            "tx.time >= TIME 2020-09-01T12:00:00.000000000Z"
+        );
+    }
+}
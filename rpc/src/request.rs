@@ -16,11 +16,16 @@ pub trait Request: Debug + DeserializeOwned + Serialize + Sized {
     fn into_json(self) -> String {
         serde_json::to_string_pretty(&Wrapper::new(self)).unwrap()
     }
+
+    /// Serialize this request as JSON, identified by the given ID
+    fn into_json_with_id(self, id: Id) -> String {
+        serde_json::to_string_pretty(&Wrapper::new_with_id(self, id)).unwrap()
+    }
 }
 
 /// JSONRPC request wrapper (i.e. message envelope)
 #[derive(Debug, Deserialize, Serialize)]
-struct Wrapper<R> {
+pub(crate) struct Wrapper<R> {
     /// JSONRPC version
     jsonrpc: Version,
 
@@ -40,9 +45,14 @@ where
 {
     /// Create a new request wrapper from the given request
     pub fn new(request: R) -> Self {
+        Self::new_with_id(request, Id::uuid_v4())
+    }
+
+    /// Create a new request wrapper from the given request and ID
+    pub(crate) fn new_with_id(request: R, id: Id) -> Self {
         Self {
             jsonrpc: Version::current(),
-            id: Id::uuid_v4(),
+            id,
             method: request.method(),
             params: request,
         }
@@ -3,6 +3,7 @@
 use super::{Error, Id, Version};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::io::Read;
+use tendermint::chain;
 
 /// JSONRPC responses
 pub trait Response: Serialize + DeserializeOwned + Sized {
@@ -18,6 +19,12 @@ pub trait Response: Serialize + DeserializeOwned + Sized {
         let wrapper: Wrapper<Self> = serde_json::from_reader(reader).map_err(Error::parse_error)?;
         wrapper.into_result()
     }
+
+    /// Chain IDs of the headers or node information carried by this response,
+    /// which clients pinned to a chain ID check
+    fn chain_ids(&self) -> Vec<&chain::Id> {
+        vec![]
+    }
 }
 
 /// JSONRPC response wrapper (i.e. message envelope)
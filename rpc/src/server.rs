@@ -0,0 +1,334 @@
+//! JSONRPC server speaking the Tendermint RPC protocol, e.g. for proxies,
+//! caches and mock nodes
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use async_tungstenite::tungstenite::Message;
+use futures::future::{self, BoxFuture};
+use futures::prelude::*;
+use hyper::header::{HeaderValue, CONTENT_TYPE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+
+use crate::client::Transport;
+use crate::error::Code;
+use crate::{Error, Method, Request, Version};
+
+/// Handler of requests for a single method, working on their JSON
+/// parameters and results
+type Handler = Arc<dyn Fn(Value) -> BoxFuture<'static, Result<Value, Error>> + Send + Sync>;
+
+/// Router dispatching JSONRPC requests to handlers by their method.
+///
+/// Handlers take the typed request of their method and return its typed
+/// response, so the router answers exactly like a Tendermint node would.
+/// Requests for methods without a handler fail with a method-not-found
+/// error. Batches are handled concurrently.
+///
+/// ```ignore
+/// let router = Router::new()
+///     .route(Method::Status, |_: status::Request| async { Ok(status.clone()) })
+///     .route(Method::Block, move |request: block::Request| fetch_block(request.height));
+/// router.serve(&"127.0.0.1:26657".parse()?).await?;
+/// ```
+///
+/// A router is a [`Transport`] too, which lets a [`Client`](crate::Client)
+/// talk to it in-process.
+#[derive(Clone, Default)]
+pub struct Router {
+    handlers: HashMap<Method, Handler>,
+}
+
+impl Router {
+    /// Create a router without any handlers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle requests for the given method with the given handler,
+    /// replacing any previous handler for it
+    pub fn route<R, F, Fut>(mut self, method: Method, handler: F) -> Self
+    where
+        R: Request + Send + 'static,
+        F: Fn(R) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R::Response, Error>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        self.handlers.insert(
+            method,
+            Arc::new(move |params| {
+                let handler = handler.clone();
+                async move {
+                    let response = handler(parse_params::<R>(params)?).await?;
+                    serde_json::to_value(response).map_err(Error::server_error)
+                }
+                .boxed()
+            }),
+        );
+        self
+    }
+
+    /// Methods requests are handled for
+    pub fn methods(&self) -> impl Iterator<Item = Method> + '_ {
+        self.handlers.keys().copied()
+    }
+
+    /// Handle the given JSON request body (a single JSONRPC request or a
+    /// batch of them), returning the JSON response body
+    pub async fn handle(&self, request_body: &[u8]) -> Vec<u8> {
+        let response = match serde_json::from_slice::<Value>(request_body) {
+            Ok(Value::Array(requests)) if requests.is_empty() => {
+                error_response(Value::Null, Error::new(Code::InvalidRequest, None))
+            }
+            Ok(Value::Array(requests)) => Value::Array(
+                future::join_all(requests.into_iter().map(|request| self.handle_one(request)))
+                    .await,
+            ),
+            Ok(request) => self.handle_one(request).await,
+            Err(e) => error_response(Value::Null, Error::parse_error(e)),
+        };
+
+        serde_json::to_vec(&response).expect("JSON values serialize")
+    }
+
+    /// Handle a single request, answering with a response object
+    async fn handle_one(&self, mut request: Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+
+        match self.dispatch(&mut request).await {
+            Ok(result) => json!({ "jsonrpc": Version::current(), "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        }
+    }
+
+    async fn dispatch(&self, request: &mut Value) -> Result<Value, Error> {
+        let request = request
+            .as_object_mut()
+            .ok_or_else(|| Error::new(Code::InvalidRequest, Some("not an object".to_owned())))?;
+
+        let version = request
+            .get("jsonrpc")
+            .and_then(Value::as_str)
+            .and_then(|version| Version::from_str(version).ok())
+            .ok_or_else(|| Error::new(Code::InvalidRequest, Some("no version".to_owned())))?;
+        if !version.is_supported() {
+            return Err(Error::new(
+                Code::InvalidRequest,
+                Some(format!("unsupported version: {}", version)),
+            ));
+        }
+
+        let method = request
+            .get("method")
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::new(Code::InvalidRequest, Some("no method".to_owned())))?;
+        let handler = Method::from_str(method)
+            .ok()
+            .and_then(|method| self.handlers.get(&method))
+            .ok_or_else(|| Error::method_not_found(method))?
+            .clone();
+
+        handler(request.remove("params").unwrap_or(Value::Null)).await
+    }
+
+    /// Serve JSONRPC requests POSTed over HTTP on the given address until
+    /// the server fails
+    pub async fn serve(self, addr: &SocketAddr) -> Result<(), Error> {
+        let router = Arc::new(self);
+
+        let make_service = make_service_fn(move |_| {
+            let router = router.clone();
+            future::ok::<_, Infallible>(service_fn(move |request| {
+                let router = router.clone();
+                async move { Ok::<_, Infallible>(router.handle_http(request).await) }
+            }))
+        });
+
+        hyper::Server::try_bind(addr)?
+            .serve(make_service)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Answer the given HTTP request
+    async fn handle_http(&self, request: hyper::Request<Body>) -> hyper::Response<Body> {
+        if request.method() != hyper::Method::POST {
+            let mut response = hyper::Response::new(Body::empty());
+            *response.status_mut() = StatusCode::METHOD_NOT_ALLOWED;
+            return response;
+        }
+
+        let response_body = match hyper::body::to_bytes(request.into_body()).await {
+            Ok(request_body) => self.handle(&request_body).await,
+            Err(e) => {
+                let response = error_response(Value::Null, Error::from(e));
+                serde_json::to_vec(&response).expect("JSON values serialize")
+            }
+        };
+
+        let mut response = hyper::Response::new(Body::from(response_body));
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        response
+    }
+
+    /// Serve JSONRPC requests sent over WebSocket connections on the given
+    /// address until accepting connections fails.
+    ///
+    /// Each text message is handled as a JSON request body and answered
+    /// with a text message. Subscriptions are not supported.
+    pub async fn serve_websocket(self, addr: &SocketAddr) -> Result<(), Error> {
+        let router = Arc::new(self);
+        let mut listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| Error::websocket_error(e.to_string()))?;
+
+        loop {
+            let (stream, _) = listener
+                .accept()
+                .await
+                .map_err(|e| Error::websocket_error(e.to_string()))?;
+            let router = router.clone();
+
+            tokio::spawn(async move {
+                // Connections failing is up to the peer
+                let _ = router.handle_websocket(stream).await;
+            });
+        }
+    }
+
+    /// Answer the requests sent over the given connection until it is closed
+    async fn handle_websocket(&self, stream: tokio::net::TcpStream) -> Result<(), Error> {
+        let mut socket = async_tungstenite::tokio::accept_async(stream).await?;
+
+        while let Some(message) = socket.next().await {
+            let request_body = match message? {
+                Message::Text(text) => text.into_bytes(),
+                Message::Binary(bytes) => bytes,
+                Message::Close(_) => break,
+                // Pings are answered by the socket itself
+                Message::Ping(_) | Message::Pong(_) => continue,
+            };
+
+            let response_body = self.handle(&request_body).await;
+            socket
+                .send(Message::Text(String::from_utf8(response_body).unwrap()))
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Router {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Router")
+            .field("methods", &self.handlers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Transport for Router {
+    async fn send(&self, request_body: String) -> Result<Vec<u8>, Error> {
+        Ok(self.handle(request_body.as_bytes()).await)
+    }
+}
+
+/// Parse the parameters of a request
+fn parse_params<R: DeserializeOwned>(params: Value) -> Result<R, Error> {
+    serde_json::from_value(params.clone()).or_else(|e| {
+        // Clients send the parameters of parameterless requests as empty
+        // objects or arrays as well as null
+        let is_empty = match &params {
+            Value::Object(params) => params.is_empty(),
+            Value::Array(params) => params.is_empty(),
+            _ => false,
+        };
+
+        if is_empty {
+            if let Ok(request) = serde_json::from_value(Value::Null) {
+                return Ok(request);
+            }
+        }
+
+        Err(Error::invalid_params(&e.to_string()))
+    })
+}
+
+fn error_response(id: Value, error: Error) -> Value {
+    json!({ "jsonrpc": Version::current(), "id": id, "error": error })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint::{block, status};
+    use crate::{Client, Response};
+    use std::fs;
+
+    fn status() -> status::Response {
+        status::Response::from_string(fs::read_to_string("tests/support/status.json").unwrap())
+            .unwrap()
+    }
+
+    fn client(router: Router) -> Client {
+        Client::builder("http://example.invalid".parse().unwrap())
+            .transport(router)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn routes_typed_requests() {
+        let router = Router::new()
+            .route(Method::Status, |_: status::Request| future::ok(status()))
+            .route(Method::Block, |_: block::Request| {
+                future::err(Error::server_error("no such block"))
+            });
+        let client = client(router);
+
+        assert_eq!(
+            client.status().await.unwrap().node_info.network,
+            status().node_info.network
+        );
+
+        let error = client.block(10u64).await.unwrap_err();
+        assert_eq!(error.data(), Some("no such block"));
+
+        let error = client.validators(10u64).await.unwrap_err();
+        assert_eq!(error.code(), Code::MethodNotFound);
+    }
+
+    #[tokio::test]
+    async fn handles_batches_and_malformed_requests() {
+        let router = Router::new().route(Method::Status, |_: status::Request| future::ok(status()));
+
+        let response = router
+            .handle(
+                br#"[{"jsonrpc":"2.0","id":1,"method":"status","params":{}},
+                     {"jsonrpc":"2.0","id":2,"method":"health"}]"#,
+            )
+            .await;
+        let response: Value = serde_json::from_slice(&response).unwrap();
+        assert_eq!(response[0]["id"], json!(1));
+        assert!(response[0]["result"].is_object());
+        assert_eq!(response[1]["id"], json!(2));
+        assert_eq!(response[1]["error"]["code"], json!(-32601));
+
+        let response = router.handle(b"{").await;
+        let response: Value = serde_json::from_slice(&response).unwrap();
+        assert_eq!(response["error"]["code"], json!(-32700));
+    }
+}
@@ -0,0 +1,158 @@
+//! Tendermint RPC client for `wasm32-unknown-unknown`, e.g. for browser
+//! applications, which sends requests with the Fetch API.
+
+use js_sys::Promise;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{RequestInit, Response as FetchResponse, Window, WorkerGlobalScope};
+
+use tendermint::abci::{self, Transaction};
+use tendermint::block::Height;
+
+use crate::{endpoint::*, Error, Request, Response};
+
+/// Tendermint RPC client sending requests with the Fetch API of the browser
+/// (or web worker) it runs in.
+///
+/// Offers the same typed requests as the hyper-based client, which does not
+/// compile to WebAssembly. Timeouts, retries and other transport settings
+/// are left to the browser.
+#[derive(Clone, Debug)]
+pub struct WasmClient {
+    url: String,
+}
+
+impl WasmClient {
+    /// Create a new client sending requests to the given URL, e.g.
+    /// `https://rpc.example.com:26657`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+
+    /// `/abci_info`: get information about the ABCI application.
+    pub async fn abci_info(&self) -> Result<abci_info::AbciInfo, Error> {
+        Ok(self.perform(abci_info::Request).await?.response)
+    }
+
+    /// `/abci_query`: query the ABCI application
+    pub async fn abci_query(
+        &self,
+        path: Option<abci::Path>,
+        data: impl Into<Vec<u8>>,
+        height: Option<Height>,
+        prove: bool,
+    ) -> Result<abci_query::AbciQuery, Error> {
+        Ok(self
+            .perform(abci_query::Request::new(path, data, height, prove))
+            .await?
+            .response)
+    }
+
+    /// `/block`: get block at a given height.
+    pub async fn block(&self, height: impl Into<Height>) -> Result<block::Response, Error> {
+        self.perform(block::Request::new(height.into())).await
+    }
+
+    /// `/block`: get the latest block.
+    pub async fn latest_block(&self) -> Result<block::Response, Error> {
+        self.perform(block::Request::default()).await
+    }
+
+    /// `/broadcast_tx_sync`: broadcast a transaction, returning the response
+    /// from `CheckTx`.
+    pub async fn broadcast_tx_sync(
+        &self,
+        tx: Transaction,
+    ) -> Result<broadcast::tx_sync::Response, Error> {
+        self.perform(broadcast::tx_sync::Request::new(tx)).await
+    }
+
+    /// `/commit`: get block commit at a given height.
+    pub async fn commit(&self, height: impl Into<Height>) -> Result<commit::Response, Error> {
+        self.perform(commit::Request::new(height.into())).await
+    }
+
+    /// `/commit`: get the latest block commit
+    pub async fn latest_commit(&self) -> Result<commit::Response, Error> {
+        self.perform(commit::Request::default()).await
+    }
+
+    /// `/status`: get Tendermint status including node info, pubkey, latest
+    /// block hash, app hash, block height and time.
+    pub async fn status(&self) -> Result<status::Response, Error> {
+        self.perform(status::Request).await
+    }
+
+    /// `/validators`: get validators a given height.
+    pub async fn validators(
+        &self,
+        height: impl Into<Height>,
+    ) -> Result<validators::Response, Error> {
+        self.perform(validators::Request::new(height.into())).await
+    }
+
+    /// Perform a request against the RPC endpoint
+    pub async fn perform<R>(&self, request: R) -> Result<R::Response, Error>
+    where
+        R: Request,
+    {
+        let mut init = RequestInit::new();
+        init.method("POST");
+        init.body(Some(&JsValue::from_str(&request.into_json())));
+
+        let request = web_sys::Request::new_with_str_and_init(&self.url, &init)
+            .map_err(|e| Error::invalid_params(&js_error_message(e)))?;
+        request
+            .headers()
+            .set("Content-Type", "application/json")
+            .map_err(http_error)?;
+
+        let response: FetchResponse = JsFuture::from(fetch(&request)?)
+            .await
+            .map_err(http_error)?
+            .dyn_into()
+            .map_err(http_error)?;
+
+        let body = JsFuture::from(response.text().map_err(http_error)?)
+            .await
+            .map_err(http_error)?
+            .as_string()
+            .ok_or_else(|| Error::http_error("response body is not text"))?;
+
+        match R::Response::from_string(&body) {
+            // Gateways and proxies answer with error pages which are not
+            // JSONRPC
+            Err(_) if !response.ok() => Err(Error::http_status_error(response.status())),
+            result => result,
+        }
+    }
+}
+
+/// Start fetching the given request with the Fetch API of the global scope,
+/// which is either a window or a web worker
+fn fetch(request: &web_sys::Request) -> Result<Promise, Error> {
+    let global = js_sys::global();
+
+    if let Some(window) = global.dyn_ref::<Window>() {
+        Ok(window.fetch_with_request(request))
+    } else if let Some(worker) = global.dyn_ref::<WorkerGlobalScope>() {
+        Ok(worker.fetch_with_request(request))
+    } else {
+        Err(Error::http_error(
+            "the Fetch API is only available in windows and web workers",
+        ))
+    }
+}
+
+/// HTTP error for the given JavaScript exception
+fn http_error(error: JsValue) -> Error {
+    Error::http_error(js_error_message(error))
+}
+
+fn js_error_message(error: JsValue) -> String {
+    error
+        .dyn_ref::<js_sys::Error>()
+        .map(|error| String::from(error.message()))
+        .or_else(|| error.as_string())
+        .unwrap_or_else(|| format!("{:?}", error))
+}
@@ -53,6 +53,24 @@ mod endpoints {
         assert_eq!(last_commit.unwrap().signatures.len(), 1);
     }
 
+    #[test]
+    fn block_by_hash_not_found() {
+        let response = endpoint::block_by_hash::Response::from_string(
+            r#"{
+                "jsonrpc": "2.0",
+                "id": "",
+                "result": {
+                    "block_id": { "hash": "", "parts": { "total": "0", "hash": "" } },
+                    "block": null
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert!(response.block_id.is_none());
+        assert!(response.block.is_none());
+    }
+
     #[test]
     fn block_with_evidences() {
         let response =
@@ -127,6 +145,104 @@ mod endpoints {
         assert_eq!(validator_updates[0].power.value(), 1_233_243);
     }
 
+    #[test]
+    fn tx_search() {
+        let response =
+            endpoint::tx_search::Response::from_string(&read_json_fixture("tx_search")).unwrap();
+
+        assert_eq!(response.total_count, 2);
+        assert_eq!(response.txs.len(), 2);
+        assert_eq!(response.txs[0].height.value(), 12);
+        assert_eq!(response.txs[0].tx_result.events[0].type_str, "app");
+        assert_eq!(response.txs[0].proof.as_ref().unwrap().proof.total, 1);
+        assert!(response.txs[1].proof.is_none());
+    }
+
+    #[test]
+    fn tx() {
+        let response = endpoint::tx::Response::from_string(&read_json_fixture("tx")).unwrap();
+
+        assert_eq!(response.height.value(), 12);
+        assert_eq!(response.tx_result.events[0].type_str, "app");
+        assert_eq!(
+            response.hash.to_string(),
+            "9F28904F9C0F3AB74A81CBA48E39124DA1C680B47FBFCBA0126870DB722BCC30"
+        );
+        assert!(response.proof.is_none());
+    }
+
+    #[test]
+    fn check_tx() {
+        let response = endpoint::check_tx::Response::from_string(
+            r#"{
+                "jsonrpc": "2.0",
+                "id": -1,
+                "result": {
+                    "code": 0,
+                    "data": null,
+                    "log": "",
+                    "info": "",
+                    "gas_wanted": "1",
+                    "gas_used": "0",
+                    "events": [],
+                    "codespace": ""
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(response.code, Code::Ok);
+        assert_eq!(response.gas_wanted.value(), 1);
+    }
+
+    #[test]
+    fn unconfirmed_txs() {
+        let response = endpoint::unconfirmed_txs::Response::from_string(
+            r#"{
+                "jsonrpc": "2.0",
+                "id": -1,
+                "result": {
+                    "n_txs": "1",
+                    "total": "3",
+                    "total_bytes": "48",
+                    "txs": ["a2V5MT12YWx1ZTE="]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(response.total, 3);
+        assert_eq!(response.txs[0].as_bytes(), b"key1=value1");
+
+        let response = endpoint::num_unconfirmed_txs::Response::from_string(
+            r#"{
+                "jsonrpc": "2.0",
+                "id": -1,
+                "result": { "n_txs": "3", "total": "3", "total_bytes": "48", "txs": null }
+            }"#,
+        )
+        .unwrap();
+
+        assert!(response.txs.is_empty());
+    }
+
+    #[test]
+    fn consensus_state() {
+        let response =
+            endpoint::consensus_state::Response::from_string(&read_json_fixture("consensus_state"))
+                .unwrap();
+
+        let round_state = response.round_state;
+        assert_eq!(round_state.height_round_step.height.value(), 1_262_197);
+        assert_eq!(
+            round_state.height_round_step.step,
+            endpoint::consensus_state::RoundStep::Commit
+        );
+        assert!(round_state.proposal_block_hash.is_some());
+        assert!(round_state.locked_block_hash.is_none());
+        assert_eq!(round_state.height_vote_set[0].prevotes.len(), 2);
+    }
+
     #[test]
     fn blockchain() {
         let response =
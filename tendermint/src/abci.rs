@@ -9,6 +9,7 @@
 
 mod code;
 mod data;
+pub mod event;
 mod gas;
 mod info;
 mod log;
@@ -24,6 +25,6 @@ pub use self::{
     info::Info,
     log::Log,
     path::Path,
-    responses::{DeliverTx, Event, Responses},
+    responses::{CheckTx, DeliverTx, Event, Responses},
     transaction::Transaction,
 };
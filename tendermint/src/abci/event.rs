@@ -0,0 +1,213 @@
+//! Decoding ABCI events into application types
+
+use super::responses::Event;
+use crate::error::{Error, Kind};
+use std::str::FromStr;
+use subtle_encoding::base64;
+
+/// Application types decoded from ABCI events of a specific type.
+///
+/// Implementations only look up attributes by key, regardless of how the
+/// node encoded them:
+///
+/// ```
+/// use tendermint::abci::event::{Attributes, FromAbciEvent};
+/// use tendermint::Error;
+///
+/// struct Transfer {
+///     recipient: String,
+///     amount: u64,
+/// }
+///
+/// impl FromAbciEvent for Transfer {
+///     const EVENT_TYPE: &'static str = "transfer";
+///
+///     fn from_attributes(attributes: &Attributes) -> Result<Self, Error> {
+///         Ok(Transfer {
+///             recipient: attributes.require("recipient")?.to_owned(),
+///             amount: attributes.parse("amount")?,
+///         })
+///     }
+/// }
+/// ```
+pub trait FromAbciEvent: Sized {
+    /// Type of the events this is decoded from, e.g. `transfer`
+    const EVENT_TYPE: &'static str;
+
+    /// Decode from the attributes of an event of type
+    /// [`EVENT_TYPE`](Self::EVENT_TYPE)
+    fn from_attributes(attributes: &Attributes) -> Result<Self, Error>;
+
+    /// Decode from the given event, failing if it is of another type
+    fn from_abci_event(event: &Event) -> Result<Self, Error> {
+        if event.type_str != Self::EVENT_TYPE {
+            fail!(
+                Kind::Parse,
+                "expected {} event, got {}",
+                Self::EVENT_TYPE,
+                event.type_str
+            );
+        }
+
+        Self::from_attributes(&Attributes::from_event(event))
+    }
+
+    /// Decode all events of type [`EVENT_TYPE`](Self::EVENT_TYPE) among the
+    /// given ones, skipping the others
+    fn from_abci_events(events: &[Event]) -> Result<Vec<Self>, Error> {
+        events
+            .iter()
+            .filter(|event| event.type_str == Self::EVENT_TYPE)
+            .map(Self::from_abci_event)
+            .collect()
+    }
+}
+
+/// Attributes of an event as text.
+///
+/// Tendermint releases before v0.34 base64-encode attribute keys and values
+/// in RPC responses, later releases don't. The attributes of an event are
+/// taken to be base64-encoded if all of their keys and values are valid
+/// base64 encodings of UTF-8 text, which plain keys practically never are.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Attributes(Vec<(String, String)>);
+
+impl Attributes {
+    /// Attributes of the given event
+    pub fn from_event(event: &Event) -> Self {
+        let plain = event
+            .attributes
+            .iter()
+            .map(|tag| (tag.key.as_ref(), tag.value.as_ref()));
+
+        let decoded = plain
+            .clone()
+            .map(|(key, value)| Some((decode_base64(key)?, decode_base64(value)?)))
+            .collect::<Option<Vec<_>>>();
+
+        match decoded {
+            Some(decoded) if !decoded.is_empty() => Attributes(decoded),
+            _ => Attributes(
+                plain
+                    .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Value of the first attribute with the given key, if any
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Values of all attributes with the given key
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> {
+        self.0
+            .iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Value of the first attribute with the given key, failing if there is
+    /// none
+    pub fn require(&self, key: &str) -> Result<&str, Error> {
+        self.get(key)
+            .ok_or_else(|| format_err!(Kind::Parse, "missing event attribute: {}", key).into())
+    }
+
+    /// Parse the value of the first attribute with the given key, failing if
+    /// there is none
+    pub fn parse<T>(&self, key: &str) -> Result<T, Error>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.require(key)?
+            .parse()
+            .map_err(|e| format_err!(Kind::Parse, "invalid event attribute {}: {}", key, e).into())
+    }
+
+    /// Keys and values of all attributes, in order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+}
+
+/// Decode the given base64-encoded UTF-8 text
+fn decode_base64(encoded: &str) -> Option<String> {
+    base64::decode(encoded)
+        .ok()
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abci::tag::Tag;
+
+    #[derive(Debug, PartialEq)]
+    struct Transfer {
+        recipient: String,
+        amount: u64,
+    }
+
+    impl FromAbciEvent for Transfer {
+        const EVENT_TYPE: &'static str = "transfer";
+
+        fn from_attributes(attributes: &Attributes) -> Result<Self, Error> {
+            Ok(Transfer {
+                recipient: attributes.require("recipient")?.to_owned(),
+                amount: attributes.parse("amount")?,
+            })
+        }
+    }
+
+    fn event(type_str: &str, attributes: &[(&str, &str)]) -> Event {
+        Event {
+            type_str: type_str.to_owned(),
+            attributes: attributes
+                .iter()
+                .map(|(key, value)| Tag {
+                    key: key.parse().unwrap(),
+                    value: value.parse().unwrap(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn decodes_plain_and_base64_attributes() {
+        let expected = Transfer {
+            recipient: "cosmos1abc".to_owned(),
+            amount: 10,
+        };
+
+        let plain = event("transfer", &[("recipient", "cosmos1abc"), ("amount", "10")]);
+        assert_eq!(Transfer::from_abci_event(&plain).unwrap(), expected);
+
+        let encoded = event(
+            "transfer",
+            &[("cmVjaXBpZW50", "Y29zbW9zMWFiYw=="), ("YW1vdW50", "MTA=")],
+        );
+        assert_eq!(Transfer::from_abci_event(&encoded).unwrap(), expected);
+    }
+
+    #[test]
+    fn decodes_events_of_its_type() {
+        let events = vec![
+            event("message", &[("sender", "cosmos1abc")]),
+            event("transfer", &[("recipient", "cosmos1abc"), ("amount", "10")]),
+            event("transfer", &[("recipient", "cosmos1def")]),
+        ];
+
+        assert!(Transfer::from_abci_event(&events[0]).is_err());
+        // The second transfer lacks an amount
+        assert!(Transfer::from_abci_events(&events).is_err());
+        assert_eq!(Transfer::from_abci_events(&events[..2]).unwrap().len(), 1);
+    }
+}
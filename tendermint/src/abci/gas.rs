@@ -5,7 +5,7 @@
 //!
 //! <https://tendermint.com/docs/spec/abci/apps.html#gas>
 
-use crate::{Error, Kind};
+use crate::{serializers, Error, Kind};
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     fmt::{self, Display},
@@ -51,8 +51,10 @@ impl FromStr for Gas {
 
 impl<'de> Deserialize<'de> for Gas {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        Ok(Self::from_str(&String::deserialize(deserializer)?)
-            .map_err(|e| D::Error::custom(format!("{}", e)))?)
+        Ok(
+            Self::from_str(&serializers::from_str::deserialize_string(deserializer)?)
+                .map_err(|e| D::Error::custom(format!("{}", e)))?,
+        )
     }
 }
 
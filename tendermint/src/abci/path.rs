@@ -11,6 +11,32 @@ use std::{
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct Path(String);
 
+impl Path {
+    /// Path of queries for the value stored under a key in the given store
+    /// of a Cosmos SDK application, e.g. `/store/bank/key`
+    pub fn store_key(store: &str) -> Self {
+        Path(format!("/store/{}/key", store))
+    }
+
+    /// Path of queries for all values stored under keys starting with a
+    /// prefix in the given store of a Cosmos SDK application, e.g.
+    /// `/store/bank/subspace`
+    pub fn store_subspace(store: &str) -> Self {
+        Path(format!("/store/{}/subspace", store))
+    }
+
+    /// Path of queries handled by application-specific code, made of the
+    /// given segments, e.g. `/custom/staking/validators`
+    pub fn custom(segments: &[&str]) -> Self {
+        Path(format!("/custom/{}", segments.join("/")))
+    }
+
+    /// This path as a string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 impl Display for Path {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", &self.0)
@@ -24,3 +50,18 @@ impl FromStr for Path {
         Ok(Path(s.to_owned()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_paths() {
+        assert_eq!(Path::store_key("bank").as_str(), "/store/bank/key");
+        assert_eq!(Path::store_subspace("acc").as_str(), "/store/acc/subspace");
+        assert_eq!(
+            Path::custom(&["staking", "validators"]).as_str(),
+            "/custom/staking/validators"
+        );
+    }
+}
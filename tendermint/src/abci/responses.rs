@@ -61,10 +61,12 @@ pub struct DeliverTx {
 
     /// Amount of gas wanted
     #[serde(rename = "gasWanted")]
+    #[cfg_attr(feature = "compat", serde(alias = "gas_wanted"))]
     pub gas_wanted: Gas,
 
     /// Amount of gas used
     #[serde(rename = "gasUsed")]
+    #[cfg_attr(feature = "compat", serde(alias = "gas_used"))]
     pub gas_used: Gas,
 
     /// Events
@@ -74,6 +76,42 @@ pub struct DeliverTx {
     pub codespace: Codespace,
 }
 
+/// Check TX response.
+///
+/// This type corresponds to the `ResponseCheckTx` proto from:
+///
+/// <https://github.com/tendermint/tendermint/blob/master/abci/types/types.proto>
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CheckTx {
+    /// ABCI application response code
+    pub code: Code,
+
+    /// ABCI application data
+    #[serde(deserialize_with = "serializers::null_as_default")]
+    pub data: Data,
+
+    /// ABCI log data (nondeterministic)
+    pub log: Log,
+
+    /// ABCI info (nondeterministic)
+    pub info: Info,
+
+    /// Amount of gas wanted
+    #[serde(rename = "gasWanted", alias = "gas_wanted")]
+    pub gas_wanted: Gas,
+
+    /// Amount of gas used
+    #[serde(rename = "gasUsed", alias = "gas_used")]
+    pub gas_used: Gas,
+
+    /// Events
+    #[serde(default, deserialize_with = "serializers::null_as_default")]
+    pub events: Vec<Event>,
+
+    /// Codespace
+    pub codespace: Codespace,
+}
+
 /// Event
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Event {
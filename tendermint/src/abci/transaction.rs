@@ -3,6 +3,8 @@
 mod hash;
 
 pub use self::hash::Hash;
+use crate::merkle::simple_hash_from_byte_vectors;
+use sha2::{Digest, Sha256};
 use std::slice;
 use {
     serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer},
@@ -32,6 +34,13 @@ impl Transaction {
     pub fn as_bytes(&self) -> &[u8] {
         self.0.as_slice()
     }
+
+    /// Hash of this transaction, which identifies it e.g. to `/tx`
+    pub fn hash(&self) -> Hash {
+        let mut bytes = [0; hash::LENGTH];
+        bytes.copy_from_slice(&Sha256::digest(&self.0));
+        Hash::new(bytes)
+    }
 }
 
 impl AsRef<[u8]> for Transaction {
@@ -86,6 +95,23 @@ impl Data {
     pub fn iter(&self) -> slice::Iter<'_, Transaction> {
         self.as_ref().iter()
     }
+
+    /// Merkle root of the hashes of the transactions, which block headers
+    /// commit to as `data_hash` (`None` if there are no transactions)
+    pub fn hash(&self) -> Option<crate::Hash> {
+        if self.as_ref().is_empty() {
+            return None;
+        }
+
+        let tx_hashes = self
+            .iter()
+            .map(|tx| Sha256::digest(tx.as_bytes()).to_vec())
+            .collect();
+
+        Some(crate::Hash::Sha256(simple_hash_from_byte_vectors(
+            tx_hashes,
+        )))
+    }
 }
 
 impl AsRef<[Transaction]> for Data {
@@ -1,4 +1,5 @@
 use crate::error::{Error, Kind};
+use crate::serializers;
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     convert::TryFrom,
@@ -83,8 +84,10 @@ impl FromStr for Height {
 
 impl<'de> Deserialize<'de> for Height {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        Ok(Self::from_str(&String::deserialize(deserializer)?)
-            .map_err(|e| D::Error::custom(format!("{}", e)))?)
+        Ok(
+            Self::from_str(&serializers::from_str::deserialize_string(deserializer)?)
+                .map_err(|e| D::Error::custom(format!("{}", e)))?,
+        )
     }
 }
 
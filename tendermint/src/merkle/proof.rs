@@ -1,6 +1,10 @@
 //! Merkle proofs
 
+mod ops;
+
+use crate::error::{Error, Kind};
 use crate::serializers;
+use anomaly::fail;
 use serde::{Deserialize, Serialize};
 
 /// Proof is Merkle proof defined by the list of ProofOps
@@ -11,6 +15,44 @@ pub struct Proof {
     pub ops: Vec<ProofOp>,
 }
 
+impl Proof {
+    /// Verify that the given value is stored under the given keys in a Merkle
+    /// tree with the given root, e.g. under `[store, key]` in the state of a
+    /// Cosmos SDK application with the given app hash.
+    ///
+    /// The operations are run in order, each on the output of the previous
+    /// one. Operations with a key consume the keys from last to first.
+    pub fn verify_value(&self, root: &[u8], keys: &[&[u8]], value: &[u8]) -> Result<(), Error> {
+        let mut keys = keys.to_vec();
+        let mut hash = value.to_vec();
+
+        for op in &self.ops {
+            if !op.key.is_empty() {
+                match keys.pop() {
+                    Some(key) if key == op.key.as_slice() => (),
+                    _ => fail!(
+                        Kind::Crypto,
+                        "key mismatch in proof operation {}",
+                        op.field_type
+                    ),
+                }
+            }
+
+            hash = ops::run(op, &hash)?;
+        }
+
+        if !keys.is_empty() {
+            fail!(Kind::Crypto, "proof does not cover all keys");
+        }
+
+        if hash != root {
+            fail!(Kind::Crypto, "proof does not lead to the expected root");
+        }
+
+        Ok(())
+    }
+}
+
 /// ProofOp defines an operation used for calculating Merkle root
 /// The data could be arbitrary format, providing necessary data
 /// for example neighbouring node hash
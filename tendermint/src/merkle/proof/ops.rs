@@ -0,0 +1,359 @@
+//! Proof operations, each proving its input (a value or the root of an inner
+//! tree) to be part of a Merkle tree with a certain root.
+//!
+//! Supported are Tendermint's `simple:v`, IAVL's `iavl:v` and the Cosmos
+//! SDK's `multistore` operations, whose data is amino encoded.
+
+use anomaly::fail;
+use prost_amino::encoding::encode_varint;
+use prost_amino::Message;
+use prost_amino_derive::Message;
+use sha2::{Digest, Sha256};
+
+use super::ProofOp;
+use crate::error::{Error, Kind};
+use crate::merkle::{get_split_point, inner_hash, leaf_hash, simple_hash_from_byte_vectors, Hash};
+
+/// Run the given proof operation, returning the root of the Merkle tree the
+/// input has been proven to be part of
+pub(super) fn run(op: &ProofOp, input: &[u8]) -> Result<Vec<u8>, Error> {
+    match op.field_type.as_str() {
+        "simple:v" => SimpleValueOp::decode_length_delimited(op.data.as_slice())
+            .map_err(|e| Kind::Parse.context(e))?
+            .run(&op.key, input),
+        "iavl:v" => IavlValueOp::decode_length_delimited(op.data.as_slice())
+            .map_err(|e| Kind::Parse.context(e))?
+            .run(&op.key, input),
+        "multistore" => MultiStoreProofOp::decode(op.data.as_slice())
+            .map_err(|e| Kind::Parse.context(e))?
+            .run(&op.key, input),
+        other => fail!(Kind::Crypto, "unsupported proof operation: {}", other),
+    }
+}
+
+/// Proof of a value in a simple Merkle map (`simple:v`)
+#[derive(Clone, PartialEq, Message)]
+struct SimpleValueOp {
+    #[prost_amino(message, tag = "1")]
+    proof: Option<SimpleProof>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct SimpleProof {
+    #[prost_amino(int64, tag = "1")]
+    total: i64,
+    #[prost_amino(int64, tag = "2")]
+    index: i64,
+    #[prost_amino(bytes, tag = "3")]
+    leaf_hash: Vec<u8>,
+    #[prost_amino(bytes, repeated, tag = "4")]
+    aunts: Vec<Vec<u8>>,
+}
+
+impl SimpleValueOp {
+    fn run(&self, key: &[u8], value: &[u8]) -> Result<Vec<u8>, Error> {
+        let proof = match &self.proof {
+            Some(proof) => proof,
+            None => fail!(Kind::Crypto, "simple value proof is missing"),
+        };
+
+        let leaf = kv_leaf_hash(key, value);
+        if leaf[..] != proof.leaf_hash[..] {
+            fail!(Kind::Crypto, "leaf hash mismatch in simple value proof");
+        }
+
+        match hash_from_aunts(proof.index, proof.total, leaf, &proof.aunts) {
+            Some(root) => Ok(root.to_vec()),
+            None => fail!(Kind::Crypto, "invalid simple value proof"),
+        }
+    }
+}
+
+/// Compute the root of a simple Merkle tree from the hash of a leaf and the
+/// hashes of its aunts, from the bottom up
+fn hash_from_aunts(index: i64, total: i64, leaf: Hash, aunts: &[Vec<u8>]) -> Option<Hash> {
+    if index < 0 || index >= total {
+        return None;
+    }
+
+    match (total, aunts.split_last()) {
+        (1, None) => Some(leaf),
+        (1, Some(_)) | (_, None) => None,
+        (_, Some((aunt, aunts))) => {
+            let split = get_split_point(total as usize) as i64;
+            if index < split {
+                let left = hash_from_aunts(index, split, leaf, aunts)?;
+                Some(inner_hash(&left, aunt))
+            } else {
+                let right = hash_from_aunts(index - split, total - split, leaf, aunts)?;
+                Some(inner_hash(aunt, &right))
+            }
+        }
+    }
+}
+
+/// Proof of a value in an IAVL tree (`iavl:v`)
+#[derive(Clone, PartialEq, Message)]
+struct IavlValueOp {
+    #[prost_amino(message, tag = "1")]
+    proof: Option<RangeProof>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct RangeProof {
+    #[prost_amino(message, repeated, tag = "1")]
+    left_path: Vec<ProofInnerNode>,
+    #[prost_amino(message, repeated, tag = "2")]
+    inner_nodes: Vec<PathToLeaf>,
+    #[prost_amino(message, repeated, tag = "3")]
+    leaves: Vec<ProofLeafNode>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct PathToLeaf {
+    #[prost_amino(message, repeated, tag = "1")]
+    nodes: Vec<ProofInnerNode>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ProofInnerNode {
+    #[prost_amino(sint32, tag = "1")]
+    height: i32,
+    #[prost_amino(int64, tag = "2")]
+    size: i64,
+    #[prost_amino(int64, tag = "3")]
+    version: i64,
+    #[prost_amino(bytes, tag = "4")]
+    left: Vec<u8>,
+    #[prost_amino(bytes, tag = "5")]
+    right: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ProofLeafNode {
+    #[prost_amino(bytes, tag = "1")]
+    key: Vec<u8>,
+    #[prost_amino(bytes, tag = "2")]
+    value_hash: Vec<u8>,
+    #[prost_amino(int64, tag = "3")]
+    version: i64,
+}
+
+impl IavlValueOp {
+    fn run(&self, key: &[u8], value: &[u8]) -> Result<Vec<u8>, Error> {
+        let proof = match &self.proof {
+            Some(proof) => proof,
+            None => fail!(Kind::Crypto, "IAVL value proof is missing"),
+        };
+
+        let leaf = proof.leaf()?;
+        if leaf.key[..] != key[..] {
+            fail!(Kind::Crypto, "IAVL value proof is for a different key");
+        }
+        if leaf.value_hash[..] != Sha256::digest(value)[..] {
+            fail!(Kind::Crypto, "value hash mismatch in IAVL value proof");
+        }
+
+        Ok(proof.root_hash()?.to_vec())
+    }
+}
+
+impl RangeProof {
+    /// The single leaf proven. Proofs of ranges of several leaves are not
+    /// supported.
+    fn leaf(&self) -> Result<&ProofLeafNode, Error> {
+        match self.leaves.as_slice() {
+            [leaf] if self.inner_nodes.is_empty() => Ok(leaf),
+            _ => fail!(Kind::Crypto, "IAVL range proofs are not supported"),
+        }
+    }
+
+    /// Root of the IAVL tree, computed from the leaf up along the left path
+    fn root_hash(&self) -> Result<Hash, Error> {
+        let leaf = self.leaf()?;
+
+        let mut bytes = vec![];
+        encode_zigzag(0, &mut bytes);
+        encode_zigzag(1, &mut bytes);
+        encode_zigzag(leaf.version, &mut bytes);
+        encode_bytes(&leaf.key, &mut bytes);
+        encode_bytes(&leaf.value_hash, &mut bytes);
+        let mut hash = sha256(&bytes);
+
+        for node in self.left_path.iter().rev() {
+            let mut bytes = vec![];
+            encode_zigzag(node.height.into(), &mut bytes);
+            encode_zigzag(node.size, &mut bytes);
+            encode_zigzag(node.version, &mut bytes);
+            if node.left.is_empty() {
+                encode_bytes(&hash, &mut bytes);
+                encode_bytes(&node.right, &mut bytes);
+            } else {
+                encode_bytes(&node.left, &mut bytes);
+                encode_bytes(&hash, &mut bytes);
+            }
+            hash = sha256(&bytes);
+        }
+
+        Ok(hash)
+    }
+}
+
+/// Proof of the root of a store in a Cosmos SDK multistore (`multistore`)
+#[derive(Clone, PartialEq, Message)]
+struct MultiStoreProofOp {
+    #[prost_amino(message, tag = "1")]
+    proof: Option<MultiStoreProof>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct MultiStoreProof {
+    #[prost_amino(message, repeated, tag = "1")]
+    store_infos: Vec<StoreInfo>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct StoreInfo {
+    #[prost_amino(string, tag = "1")]
+    name: String,
+    #[prost_amino(message, tag = "2")]
+    core: Option<StoreCore>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct StoreCore {
+    #[prost_amino(message, tag = "1")]
+    commit_id: Option<CommitId>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct CommitId {
+    #[prost_amino(int64, tag = "1")]
+    version: i64,
+    #[prost_amino(bytes, tag = "2")]
+    hash: Vec<u8>,
+}
+
+impl StoreInfo {
+    fn commit_hash(&self) -> &[u8] {
+        self.core
+            .as_ref()
+            .and_then(|core| core.commit_id.as_ref())
+            .map(|commit_id| commit_id.hash.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+impl MultiStoreProofOp {
+    fn run(&self, key: &[u8], root: &[u8]) -> Result<Vec<u8>, Error> {
+        let store_infos = match &self.proof {
+            Some(proof) => &proof.store_infos,
+            None => fail!(Kind::Crypto, "multistore proof is missing"),
+        };
+
+        match store_infos.iter().find(|info| info.name.as_bytes() == key) {
+            Some(info) if info.commit_hash() == root => (),
+            Some(_) => fail!(Kind::Crypto, "store root mismatch in multistore proof"),
+            None => fail!(Kind::Crypto, "store not found in multistore proof"),
+        }
+
+        // The root of the multistore is the root of a simple Merkle map from
+        // store names to the hashes of their commit hashes
+        let mut store_infos = store_infos.iter().collect::<Vec<_>>();
+        store_infos.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let leaves = store_infos
+            .into_iter()
+            .map(|info| kv_leaf(info.name.as_bytes(), &sha256(info.commit_hash())))
+            .collect();
+
+        Ok(simple_hash_from_byte_vectors(leaves).to_vec())
+    }
+}
+
+/// Leaf of a simple Merkle map for the given key and value
+fn kv_leaf(key: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![];
+    encode_bytes(key, &mut bytes);
+    encode_bytes(&sha256(value), &mut bytes);
+    bytes
+}
+
+fn kv_leaf_hash(key: &[u8], value: &[u8]) -> Hash {
+    leaf_hash(&kv_leaf(key, value))
+}
+
+fn sha256(bytes: &[u8]) -> Hash {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&Sha256::digest(bytes));
+    hash
+}
+
+// amino encoding of a length-prefixed byte slice
+fn encode_bytes(bytes: &[u8], buf: &mut Vec<u8>) {
+    encode_varint(bytes.len() as u64, buf);
+    buf.extend_from_slice(bytes);
+}
+
+// amino encoding of a signed integer, as zigzag varint
+fn encode_zigzag(value: i64, buf: &mut Vec<u8>) {
+    encode_varint(((value << 1) ^ (value >> 63)) as u64, buf);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use subtle_encoding::base64;
+
+    // Taken from the proof of an `ibc` store query against a Cosmos SDK node
+    const IAVL_OP: &str = "8QEK7gEKKAgIEAwYHCIgG9RAkJgHlxNjmyzOW6bUAidhiRSja0x6+GXCVENPG1oKKAgGEAUYFyIgwRns+dJvjf1Zk2BaFrXz8inPbvYHB7xx2HCy9ima5f8KKAgEEAMYFyogOr8EGajEV6fG5fzJ2fAAvVMgRLhdMJTzCPlogl9rxlIKKAgCEAIYFyIgcjzX/a+2bFbnNldpawQqZ+kYhIwz5r4wCUzuu1IFW04aRAoeY29uc2Vuc3VzU3RhdGUvaWJjb25lY2xpZW50LzIyEiAZ1uuG60K4NHJZZMuS9QX6o4eEhica5jIHYwflRiYkDBgX";
+    const MULTISTORE_OP: &str = "CvEECjAKBGJhbmsSKAomCIjYAxIg2MEyyonbZButYnvSRkf2bPQg+nqA+Am1MeDxG6F4p1UKLwoDYWNjEigKJgiI2AMSIN2YHczeuXNvyetrSFQpkCcJzfB6PXVCw0i/XShMgPnIChEKB3VwZ3JhZGUSBgoECIjYAwovCgNnb3YSKAomCIjYAxIgYM0TfBli7KxhY4nWgDSDPykhUJwtKFql9RU5l86WinQKLwoDaWJjEigKJgiI2AMSIFp6aJASeInQKF8y824zjmgcFORN6M+ECbgFfJkobKs8CjAKBG1haW4SKAomCIjYAxIgsZzwmLQ7PH1UeZ/vCUSqlQmfgt3CGfoMgJLkUqKCv0EKMwoHc3Rha2luZxIoCiYIiNgDEiCiBZoBLyDGj5euy3n33ik+SpqYK9eB5xbI+iY8ycYVbwo0CghzbGFzaGluZxIoCiYIiNgDEiAJz3gEYuIhdensHU3b5qH5ons2quepd6EaRgCHXab6PQoyCgZzdXBwbHkSKAomCIjYAxIglWLA5/THPTiTxAlaLHOBYFIzEJTmKPznItUwAc8zD+AKEgoIZXZpZGVuY2USBgoECIjYAwowCgRtaW50EigKJgiI2AMSIMS8dZ1j8F6JVVv+hB1rHBZC+gIFJxHan2hM8qDC64n/CjIKBnBhcmFtcxIoCiYIiNgDEiB8VIzExUHX+SvHZFz/P9NM9THnw/gTDDLVReuZX8htLgo4CgxkaXN0cmlidXRpb24SKAomCIjYAxIg3u/Nd4L+8LT8OXJCh14o8PHIJ/GLQwsmE7KYIl1GdSYKEgoIdHJhbnNmZXISBgoECIjYAw==";
+
+    #[test]
+    fn iavl_root_is_store_root() {
+        let iavl =
+            IavlValueOp::decode_length_delimited(base64::decode(IAVL_OP).unwrap().as_slice())
+                .unwrap();
+        let root = iavl.proof.unwrap().root_hash().unwrap();
+
+        let multistore =
+            MultiStoreProofOp::decode(base64::decode(MULTISTORE_OP).unwrap().as_slice()).unwrap();
+        assert!(multistore.run(b"ibc", &root).is_ok());
+        assert!(multistore.run(b"bank", &root).is_err());
+        assert!(multistore.run(b"ibc", &[0; 32]).is_err());
+    }
+
+    #[test]
+    fn iavl_rejects_wrong_value() {
+        let iavl =
+            IavlValueOp::decode_length_delimited(base64::decode(IAVL_OP).unwrap().as_slice())
+                .unwrap();
+        let key = b"consensusState/ibconeclient/22";
+        assert!(iavl.run(key, b"wrong value").is_err());
+    }
+
+    #[test]
+    fn simple_value() {
+        let leaves = vec![
+            kv_leaf(b"a", b"1"),
+            kv_leaf(b"b", b"2"),
+            kv_leaf(b"c", b"3"),
+        ];
+        let root = simple_hash_from_byte_vectors(leaves.clone());
+
+        let op = SimpleValueOp {
+            proof: Some(SimpleProof {
+                total: 3,
+                index: 1,
+                leaf_hash: leaf_hash(&leaves[1]).to_vec(),
+                aunts: vec![
+                    leaf_hash(&leaves[0]).to_vec(),
+                    leaf_hash(&leaves[2]).to_vec(),
+                ],
+            }),
+        };
+
+        assert_eq!(op.run(b"b", b"2").unwrap(), root.to_vec());
+        assert!(op.run(b"b", b"3").is_err());
+    }
+}
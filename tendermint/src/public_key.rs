@@ -91,6 +91,58 @@ impl PublicKey {
         }
     }
 
+    /// Verify the given [`Signature`]s of the given messages, each using the
+    /// given public key.
+    ///
+    /// With the `batch-verify` feature, Ed25519 signatures are verified as a
+    /// batch, which is significantly faster than verifying them one by one,
+    /// but does not tell which of the signatures is invalid, if any.
+    ///
+    /// Note that batch verification checks the cofactored verification
+    /// equation, whereas [`PublicKey::verify`] checks the cofactorless one:
+    /// a batch may thus pass even though some of its signatures, whose keys
+    /// or commitments have a small-order component, are rejected one by one.
+    /// Signatures produced by honest signers are accepted either way.
+    pub fn verify_batch(items: &[(&PublicKey, &[u8], &Signature)]) -> Result<(), Error> {
+        #[cfg(feature = "batch-verify")]
+        {
+            let mut messages = Vec::with_capacity(items.len());
+            let mut signatures = Vec::with_capacity(items.len());
+            let mut public_keys = Vec::with_capacity(items.len());
+
+            for (public_key, msg, signature) in items {
+                match public_key {
+                    PublicKey::Ed25519(pk) => match signature {
+                        Signature::Ed25519(sig) => {
+                            messages.push(*msg);
+                            signatures.push(*sig);
+                            public_keys.push(*pk);
+                        }
+                    },
+                    #[cfg(feature = "secp256k1")]
+                    PublicKey::Secp256k1(_) => public_key.verify(msg, signature)?,
+                }
+            }
+
+            if public_keys.is_empty() {
+                return Ok(());
+            }
+
+            ed25519_dalek::verify_batch(&messages, &signatures, &public_keys).map_err(|_| {
+                format_err!(
+                    error::Kind::SignatureInvalid,
+                    "Ed25519 batch signature verification failed"
+                )
+                .into()
+            })
+        }
+
+        #[cfg(not(feature = "batch-verify"))]
+        items
+            .iter()
+            .try_for_each(|(public_key, msg, signature)| public_key.verify(msg, signature))
+    }
+
     /// View this key as a byte slice
     pub fn as_bytes(&self) -> &[u8] {
         match self {
@@ -352,6 +404,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn verify_batch_rejects_any_invalid_signature() {
+        use crate::signature::{Signature, Signer};
+        use ed25519_dalek::{Keypair, PublicKey as Ed25519, SecretKey};
+
+        let keypairs = (1..=3_u8)
+            .map(|i| {
+                let secret = SecretKey::from_bytes(&[i; 32]).unwrap();
+                let public = Ed25519::from(&secret);
+                Keypair { secret, public }
+            })
+            .collect::<Vec<_>>();
+
+        let public_keys = keypairs
+            .iter()
+            .map(|keypair| PublicKey::Ed25519(keypair.public))
+            .collect::<Vec<_>>();
+        let messages = [b"one".as_ref(), b"two".as_ref(), b"three".as_ref()];
+        let mut signatures = keypairs
+            .iter()
+            .zip(messages.iter())
+            .map(|(keypair, msg)| Signature::Ed25519(keypair.sign(msg)))
+            .collect::<Vec<_>>();
+
+        let items = |signatures: &[Signature]| {
+            (0..3)
+                .map(|i| (&public_keys[i], messages[i], &signatures[i]))
+                .collect::<Vec<_>>()
+        };
+
+        assert!(PublicKey::verify_batch(&items(&signatures)).is_ok());
+
+        // Swap the signatures of the last two messages
+        signatures.swap(1, 2);
+
+        assert!(PublicKey::verify_batch(&items(&signatures)).is_err());
+        assert!(public_keys[0].verify(messages[0], &signatures[0]).is_ok());
+        assert!(public_keys[1].verify(messages[1], &signatures[1]).is_err());
+    }
+
     #[test]
     fn json_parsing() {
         let json_string = "{\"type\":\"tendermint/PubKeyEd25519\",\"value\":\"RblzMO4is5L1hZz6wo4kPbptzOyue6LTk4+lPhD1FRk=\"}";
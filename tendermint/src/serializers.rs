@@ -41,6 +41,8 @@
 //! * Any type that has the "FromStr" trait can be serialized into a string with
 //!   serializers::primitives::string.
 //! * serializers::bytes::* deserializes a null value into an empty vec![].
+//! * With the `compat` feature, numbers encoded as strings are also accepted as
+//!   JSON numbers, for compatibility with nodes of other Tendermint releases.
 
 pub mod bytes;
 pub mod from_str;
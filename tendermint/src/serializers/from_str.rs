@@ -1,7 +1,7 @@
 //! Serialize and deserialize any `T` that implements [[std::str::FromStr]]
 //! and [[std::fmt::Display]] from or into string. Note this be used for
 //! all primitive data types (e.g. .
-use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use serde::{de::Error as _, Deserializer, Serialize, Serializer};
 
 /// Deserialize string into T
 pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
@@ -10,11 +10,63 @@ where
     T: std::str::FromStr,
     <T as std::str::FromStr>::Err: std::fmt::Display,
 {
-    String::deserialize(deserializer)?
+    deserialize_string(deserializer)?
         .parse::<T>()
         .map_err(|e| D::Error::custom(format!("{}", e)))
 }
 
+/// Deserialize the string representation of a number.
+///
+/// With the `compat` feature, JSON numbers are accepted as well, as some
+/// Tendermint releases encode the same field either way.
+pub fn deserialize_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[cfg(feature = "compat")]
+    {
+        deserializer.deserialize_any(compat::NumberVisitor)
+    }
+
+    #[cfg(not(feature = "compat"))]
+    {
+        serde::Deserialize::deserialize(deserializer)
+    }
+}
+
+#[cfg(feature = "compat")]
+mod compat {
+    use serde::de::{Error, Visitor};
+    use std::fmt;
+
+    /// Visitor of numbers given either as strings or as JSON numbers
+    pub(super) struct NumberVisitor;
+
+    impl<'de> Visitor<'de> for NumberVisitor {
+        type Value = String;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a number or a string containing a number")
+        }
+
+        fn visit_str<E: Error>(self, value: &str) -> Result<String, E> {
+            Ok(value.to_owned())
+        }
+
+        fn visit_string<E: Error>(self, value: String) -> Result<String, E> {
+            Ok(value)
+        }
+
+        fn visit_u64<E: Error>(self, value: u64) -> Result<String, E> {
+            Ok(value.to_string())
+        }
+
+        fn visit_i64<E: Error>(self, value: i64) -> Result<String, E> {
+            Ok(value.to_string())
+        }
+    }
+}
+
 /// Serialize from T into string
 pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
 where
@@ -34,6 +34,32 @@ fn serde_integer_string() {
     );
 }
 
+#[cfg(feature = "compat")]
+#[test]
+fn serde_integer_number() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct IntegerTests {
+        #[serde(with = "super::from_str")]
+        unsigned: u64,
+
+        #[serde(with = "super::from_str")]
+        signed: i64,
+
+        height: crate::block::Height,
+    }
+
+    let parsed: IntegerTests =
+        serde_json::from_str(r#"{ "unsigned": 42, "signed": "-7", "height": 10 }"#).unwrap();
+    assert_eq!(
+        parsed,
+        IntegerTests {
+            unsigned: 42,
+            signed: -7,
+            height: crate::block::Height(10),
+        }
+    );
+}
+
 #[test]
 fn serde_duration_string() {
     #[derive(Serialize, Deserialize, Debug, PartialEq)]
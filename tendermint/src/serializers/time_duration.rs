@@ -1,5 +1,5 @@
 //! Serialize/deserialize std::time::Duration type from and into string:
-use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use serde::{de::Error as _, Deserializer, Serialize, Serializer};
 
 use std::time::Duration;
 
@@ -8,7 +8,7 @@ pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let value = String::deserialize(deserializer)?
+    let value = super::from_str::deserialize_string(deserializer)?
         .parse::<u64>()
         .map_err(|e| D::Error::custom(format!("{}", e)))?;
 
@@ -2,8 +2,16 @@
 
 use std::fmt::{self, Debug, Display};
 
-use crate::serializers;
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use crate::{
+    block::Height,
+    error::{Error, Kind},
+    serializers,
+};
+use anomaly::fail;
+use serde::{
+    de::{DeserializeOwned, Error as _},
+    Deserialize, Deserializer, Serialize,
+};
 
 /// TrustThreshold defines how much of the total voting power of a known
 /// and trusted validator set is sufficient for a commit to be
@@ -32,7 +40,22 @@ pub struct TrustThresholdFraction {
 }
 
 impl TrustThresholdFraction {
-    /// Constant for a trust threshold of 2/3.
+    /// Constant for a trust threshold of 1/3.
+    pub const ONE_THIRD: Self = Self {
+        numerator: 1,
+        denominator: 3,
+    };
+
+    /// Constant for a trust threshold of 1/2, the share of the voting power
+    /// committing blocks on chains whose consensus requires more than half
+    /// of the votes.
+    pub const ONE_HALF: Self = Self {
+        numerator: 1,
+        denominator: 2,
+    };
+
+    /// Constant for a trust threshold of 2/3, the share of the voting power
+    /// committing blocks on Tendermint chains.
     pub const TWO_THIRDS: Self = Self {
         numerator: 2,
         denominator: 3,
@@ -53,6 +76,11 @@ impl TrustThresholdFraction {
             None
         }
     }
+
+    /// Whether this threshold is between 1/3 and 1, see [`TrustThresholdFraction::new`].
+    pub fn is_valid(&self) -> bool {
+        Self::new(self.numerator, self.denominator).is_some()
+    }
 }
 
 impl TrustThreshold for TrustThresholdFraction {
@@ -73,3 +101,226 @@ impl Display for TrustThresholdFraction {
         write!(f, "{}/{}", self.numerator, self.denominator)
     }
 }
+
+/// Share of the voting power which must sign a commit for it to be valid, by height,
+/// for chains whose consensus switches from one vote rate to another at known heights,
+/// eg. from 2/3 to 1/2.
+///
+/// The [`Default::default()`] requires 2/3 of the voting power at every height.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ThresholdSchedule {
+    /// Threshold below the height of the first switch
+    initial: TrustThresholdFraction,
+    /// Switches to another threshold, by increasing height
+    switches: Vec<ThresholdSwitch>,
+}
+
+/// Schedule as found in configuration files, before validation
+#[derive(Deserialize)]
+struct RawThresholdSchedule {
+    initial: TrustThresholdFraction,
+    #[serde(default)]
+    switches: Vec<ThresholdSwitch>,
+}
+
+/// Switch to another commit threshold from a given height on.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdSwitch {
+    /// Height of the first block committed with the new threshold
+    pub height: Height,
+    /// Threshold from that height on
+    pub threshold: TrustThresholdFraction,
+}
+
+impl ThresholdSchedule {
+    /// Schedule starting with the given threshold and switching to another at the height
+    /// of each of the given switches, which may come in any order.
+    ///
+    /// Fails if any threshold is not between 1/3 and 1, or if several switches
+    /// share the same height.
+    pub fn new(
+        initial: TrustThresholdFraction,
+        mut switches: Vec<ThresholdSwitch>,
+    ) -> Result<Self, Error> {
+        if !initial.is_valid() {
+            fail!(
+                Kind::OutOfRange,
+                "invalid initial commit threshold: {}",
+                initial
+            );
+        }
+
+        if let Some(switch) = switches.iter().find(|switch| !switch.threshold.is_valid()) {
+            fail!(
+                Kind::OutOfRange,
+                "invalid commit threshold at height {}: {}",
+                switch.height,
+                switch.threshold
+            );
+        }
+
+        switches.sort_by_key(|switch| switch.height);
+
+        if let Some(pair) = switches
+            .windows(2)
+            .find(|pair| pair[0].height == pair[1].height)
+        {
+            fail!(
+                Kind::Parse,
+                "several commit threshold switches at height {}",
+                pair[0].height
+            );
+        }
+
+        Ok(Self { initial, switches })
+    }
+
+    /// Schedule of the given threshold at every height
+    ///
+    /// Fails if the threshold is not between 1/3 and 1.
+    pub fn constant(threshold: TrustThresholdFraction) -> Result<Self, Error> {
+        Self::new(threshold, Vec::new())
+    }
+
+    /// Switch to the given threshold from the given height on
+    ///
+    /// Fails under the same conditions as [`ThresholdSchedule::new`].
+    pub fn switching_at(
+        mut self,
+        height: Height,
+        threshold: TrustThresholdFraction,
+    ) -> Result<Self, Error> {
+        self.switches.push(ThresholdSwitch { height, threshold });
+        Self::new(self.initial, self.switches)
+    }
+
+    /// Threshold below the height of the first switch
+    pub fn initial(&self) -> TrustThresholdFraction {
+        self.initial
+    }
+
+    /// Switches to another threshold, by increasing height
+    pub fn switches(&self) -> &[ThresholdSwitch] {
+        &self.switches
+    }
+
+    /// Threshold of the commit of the block at the given height
+    pub fn threshold_at(&self, height: Height) -> TrustThresholdFraction {
+        self.switches
+            .iter()
+            .rev()
+            .find(|switch| switch.height <= height)
+            .map_or(self.initial, |switch| switch.threshold)
+    }
+}
+
+impl Default for ThresholdSchedule {
+    fn default() -> Self {
+        Self {
+            initial: TrustThresholdFraction::TWO_THIRDS,
+            switches: Vec::new(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ThresholdSchedule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = RawThresholdSchedule::deserialize(deserializer)?;
+        Self::new(raw.initial, raw.switches).map_err(|e| D::Error::custom(format!("{}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn threshold_schedule() {
+        let schedule = ThresholdSchedule::default()
+            .switching_at(Height::from(100_u64), TrustThresholdFraction::ONE_HALF)
+            .unwrap();
+
+        let threshold_at = |height: u64| schedule.threshold_at(Height::from(height));
+
+        assert_eq!(threshold_at(1), TrustThresholdFraction::TWO_THIRDS);
+        assert_eq!(threshold_at(99), TrustThresholdFraction::TWO_THIRDS);
+        assert_eq!(threshold_at(100), TrustThresholdFraction::ONE_HALF);
+        assert_eq!(threshold_at(1_000), TrustThresholdFraction::ONE_HALF);
+    }
+
+    #[test]
+    fn threshold_schedule_sorts_switches() {
+        let switch = |height: u64, threshold| ThresholdSwitch {
+            height: Height::from(height),
+            threshold,
+        };
+
+        let schedule = ThresholdSchedule::new(
+            TrustThresholdFraction::TWO_THIRDS,
+            vec![
+                switch(200, TrustThresholdFraction::TWO_THIRDS),
+                switch(100, TrustThresholdFraction::ONE_HALF),
+            ],
+        )
+        .unwrap();
+
+        let threshold_at = |height: u64| schedule.threshold_at(Height::from(height));
+
+        assert_eq!(threshold_at(99), TrustThresholdFraction::TWO_THIRDS);
+        assert_eq!(threshold_at(150), TrustThresholdFraction::ONE_HALF);
+        assert_eq!(threshold_at(200), TrustThresholdFraction::TWO_THIRDS);
+
+        let duplicate = vec![
+            switch(100, TrustThresholdFraction::ONE_HALF),
+            switch(100, TrustThresholdFraction::TWO_THIRDS),
+        ];
+        assert!(ThresholdSchedule::new(TrustThresholdFraction::TWO_THIRDS, duplicate).is_err());
+
+        let too_low = TrustThresholdFraction {
+            numerator: 1,
+            denominator: 4,
+        };
+        assert!(ThresholdSchedule::new(too_low, vec![]).is_err());
+        assert!(ThresholdSchedule::constant(too_low).is_err());
+        assert!(ThresholdSchedule::default()
+            .switching_at(Height::from(100_u64), too_low)
+            .is_err());
+    }
+
+    #[test]
+    fn deserialize_threshold_schedule() {
+        let schedule: ThresholdSchedule = serde_json::from_str(
+            r#"{
+                "initial": { "numerator": "2", "denominator": "3" },
+                "switches": [
+                    { "height": "200", "threshold": { "numerator": "2", "denominator": "3" } },
+                    { "height": "100", "threshold": { "numerator": "1", "denominator": "2" } }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let heights: Vec<u64> = schedule
+            .switches()
+            .iter()
+            .map(|switch| switch.height.value())
+            .collect();
+        assert_eq!(heights, vec![100, 200]);
+
+        assert!(serde_json::from_str::<ThresholdSchedule>(
+            r#"{
+                "initial": { "numerator": "2", "denominator": "3" },
+                "switches": [
+                    { "height": "100", "threshold": { "numerator": "1", "denominator": "2" } },
+                    { "height": "100", "threshold": { "numerator": "2", "denominator": "3" } }
+                ]
+            }"#,
+        )
+        .is_err());
+
+        assert!(serde_json::from_str::<ThresholdSchedule>(
+            r#"{ "initial": { "numerator": "3", "denominator": "2" } }"#,
+        )
+        .is_err());
+    }
+}
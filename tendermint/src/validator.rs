@@ -5,7 +5,7 @@ use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use subtle_encoding::base64;
 
 use crate::amino_types::message::AminoMessage;
-use crate::{account, hash::Hash, merkle, vote, Error, PublicKey, Signature};
+use crate::{account, hash::Hash, merkle, serializers, vote, Error, PublicKey, Signature};
 
 /// Validator set contains a vector of validators
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -181,7 +181,7 @@ impl From<ProposerPriority> for i64 {
 impl<'de> Deserialize<'de> for ProposerPriority {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         Ok(ProposerPriority(
-            String::deserialize(deserializer)?
+            serializers::from_str::deserialize_string(deserializer)?
                 .parse()
                 .map_err(|e| D::Error::custom(format!("{}", e)))?,
         ))
@@ -1,5 +1,6 @@
 //! Votes
 
+use crate::serializers;
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 
 /// Voting power
@@ -32,7 +33,7 @@ impl From<Power> for u64 {
 impl<'de> Deserialize<'de> for Power {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         Ok(Power(
-            String::deserialize(deserializer)?
+            serializers::from_str::deserialize_string(deserializer)?
                 .parse()
                 .map_err(|e| D::Error::custom(format!("{}", e)))?,
         ))